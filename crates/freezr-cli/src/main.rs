@@ -1,6 +1,13 @@
 use clap::{Parser, Subcommand};
 use colored::*;
-use freezr_core::{ProcessScanner, SystemdService, VERSION};
+use freezr_core::{
+    frozen_registry::DEFAULT_REGISTRY_PATH, profile_request::DEFAULT_PROFILE_REQUEST_PATH,
+    FrozenRegistry, LeftoverState, ProcessScanner, ProfileRequests, SystemdService, VERSION,
+};
+use std::path::{Path, PathBuf};
+
+/// Stats exported by process-monitor every check
+const STATS_FILE: &str = "/tmp/freezr-stats.json";
 
 #[derive(Parser)]
 #[command(name = "freezr")]
@@ -23,17 +30,50 @@ enum Commands {
     /// Force restart KESL service
     ForceRestart,
 
+    /// List processes left frozen by the daemon
+    Frozen {
+        /// Resume (SIGCONT) every leftover process that is still stopped
+        #[arg(long)]
+        thaw: bool,
+
+        /// Frozen process registry written by the daemon
+        #[arg(long, default_value = DEFAULT_REGISTRY_PATH)]
+        registry: PathBuf,
+    },
+
+    /// Switch the daemon's policy profile
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+
+        /// Request file read by the daemon
+        #[arg(long, default_value = DEFAULT_PROFILE_REQUEST_PATH)]
+        request: PathBuf,
+    },
+
     /// Show version information
     Version,
 }
 
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// Switch to a profile of the daemon config ([profiles.<name>])
+    Set { name: String },
+
+    /// Back to the profile chosen by power source (or the base config)
+    Auto,
+
+    /// Show the requested and the active profile
+    Show,
+}
+
 fn main() {
     let cli = Cli::parse();
 
     match cli.command {
         Some(Commands::Monitor) => {
             println!("{}", "=== FreezR Monitor ===".green().bold());
-            show_status();
+            show_status(&ProcessScanner::new());
         }
         Some(Commands::Watch) => {
             println!("{}", "=== FreezR Watch Mode ===".green().bold());
@@ -44,6 +84,19 @@ fn main() {
             println!("{}", "=== FreezR Force Restart ===".yellow().bold());
             force_restart();
         }
+        Some(Commands::Frozen { thaw, registry }) => {
+            println!("{}", "=== FreezR Frozen Processes ===".cyan().bold());
+            show_frozen(&FrozenRegistry::new(registry), thaw);
+        }
+        Some(Commands::Profile { action, request }) => {
+            println!("{}", "=== FreezR Profile ===".cyan().bold());
+            let requests = ProfileRequests::new(request);
+            match action {
+                ProfileAction::Set { name } => request_profile(&requests, Some(&name)),
+                ProfileAction::Auto => request_profile(&requests, None),
+                ProfileAction::Show => show_profile(&requests, Path::new(STATS_FILE)),
+            }
+        }
         Some(Commands::Version) => {
             println!("FreezR v{}", VERSION);
             println!("Intelligent system resource guardian");
@@ -51,54 +104,49 @@ fn main() {
         None => {
             // Default: show status
             println!("{}", "=== FreezR Status ===".green().bold());
-            show_status();
+            show_status(&ProcessScanner::new());
         }
     }
 }
 
-fn show_status() {
-    let scanner = ProcessScanner::new();
+fn show_status(scanner: &ProcessScanner) {
+    // One scan for both sections: CPU is sampled once per refresh
+    let scan = match scanner.scan() {
+        Ok(scan) => scan,
+        Err(e) => {
+            println!("\n{}", format!("❌ Error scanning processes: {}", e).red());
+            return;
+        }
+    };
 
-    // Scan KESL
-    match scanner.scan_kesl() {
-        Ok(Some(process)) => {
+    // KESL
+    match scan.kesl() {
+        Some(process) => {
             println!("\n{}", "✅ KESL Process Found:".green());
             println!("  PID:     {}", process.pid);
             println!("  CPU:     {:.1}%", process.cpu_percent);
             println!("  Memory:  {} MB", process.memory_mb);
         }
-        Ok(None) => {
+        None => {
             println!("\n{}", "⚠️  KESL process not found".yellow());
         }
-        Err(e) => {
-            println!("\n{}", format!("❌ Error scanning KESL: {}", e).red());
-        }
     }
 
-    // Scan Node processes
-    match scanner.scan_node_processes() {
-        Ok(processes) => {
-            if processes.is_empty() {
-                println!("\n{}", "✅ No Node.js processes found".green());
+    // Node processes
+    let processes = scan.node_processes();
+    if processes.is_empty() {
+        println!("\n{}", "✅ No Node.js processes found".green());
+    } else {
+        println!("\n{} {}", "🔍 Node.js Processes:".cyan(), processes.len());
+        for proc in processes {
+            let status = if proc.cpu_percent > 80.0 {
+                "🔥 HIGH CPU".red()
             } else {
-                println!("\n{} {}", "🔍 Node.js Processes:".cyan(), processes.len());
-                for proc in processes {
-                    let status = if proc.cpu_percent > 80.0 {
-                        "🔥 HIGH CPU".red()
-                    } else {
-                        "✅ OK".green()
-                    };
-                    println!(
-                        "  {} - PID: {}, CPU: {:.1}%, Memory: {} MB",
-                        status, proc.pid, proc.cpu_percent, proc.memory_mb
-                    );
-                }
-            }
-        }
-        Err(e) => {
+                "✅ OK".green()
+            };
             println!(
-                "\n{}",
-                format!("❌ Error scanning Node processes: {}", e).red()
+                "  {} - PID: {}, CPU: {:.1}%, Memory: {} MB",
+                status, proc.pid, proc.cpu_percent, proc.memory_mb
             );
         }
     }
@@ -107,17 +155,146 @@ fn show_status() {
 }
 
 fn watch_mode() {
+    // One scanner for the whole loop: CPU is computed from the delta between refreshes
+    let scanner = ProcessScanner::new();
+
     loop {
         // Clear screen
         print!("\x1B[2J\x1B[1;1H");
 
-        show_status();
+        show_status(&scanner);
 
         // Sleep 2 seconds
         std::thread::sleep(std::time::Duration::from_secs(2));
     }
 }
 
+fn show_frozen(registry: &FrozenRegistry, thaw: bool) {
+    let leftovers = match registry.inspect() {
+        Ok(leftovers) => leftovers,
+        Err(e) => {
+            println!(
+                "{}",
+                format!("❌ Failed to read {}: {}", registry.path().display(), e).red()
+            );
+            std::process::exit(1);
+        }
+    };
+
+    if leftovers.is_empty() {
+        println!("\n{}", "✅ No frozen processes recorded".green());
+        return;
+    }
+
+    println!();
+    for (entry, state) in &leftovers {
+        let status = match state {
+            LeftoverState::Stopped => "❄️  STOPPED".red(),
+            LeftoverState::Running => "✅ RUNNING".green(),
+            LeftoverState::Gone => "⚪ GONE".dimmed(),
+        };
+        println!(
+            "  {} - PID: {}, Name: {}, Frozen: {}, Planned thaw: {}",
+            status,
+            entry.pid,
+            entry.name,
+            entry.frozen_at.format("%Y-%m-%d %H:%M:%S"),
+            entry.thaw_at.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+
+    if !thaw {
+        let stopped = leftovers
+            .iter()
+            .filter(|(_, state)| *state == LeftoverState::Stopped)
+            .count();
+        if stopped > 0 {
+            println!("\nRun with --thaw to resume {} stopped processes", stopped);
+        }
+        println!();
+        return;
+    }
+
+    match registry.thaw_leftovers() {
+        Ok(thawed) => {
+            for entry in &thawed {
+                println!("  ▶️  Thawed PID {} ({})", entry.pid, entry.name);
+            }
+            println!(
+                "\n{}",
+                format!("✅ Thawed {} processes", thawed.len()).green()
+            );
+
+            let remaining = registry.load().map(|entries| entries.len()).unwrap_or(0);
+            if remaining > 0 {
+                println!(
+                    "{}",
+                    format!(
+                        "⚠️  {} processes could not be thawed, kept in the registry",
+                        remaining
+                    )
+                    .yellow()
+                );
+            }
+        }
+        Err(e) => {
+            println!("{}", format!("❌ Failed to thaw processes: {}", e).red());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn request_profile(requests: &ProfileRequests, name: Option<&str>) {
+    if let Err(e) = requests.request(name) {
+        println!(
+            "{}",
+            format!("❌ Failed to write {}: {}", requests.path().display(), e).red()
+        );
+        std::process::exit(1);
+    }
+
+    match name {
+        Some(name) => println!("{}", format!("✅ Requested profile '{}'", name).green()),
+        None => println!("{}", "✅ Requested automatic profile selection".green()),
+    }
+    println!("The daemon switches before its next check; unknown profiles are logged and ignored");
+}
+
+fn show_profile(requests: &ProfileRequests, stats_file: &Path) {
+    match requests.load() {
+        Ok(Some(request)) => println!(
+            "  Requested: {} (at {})",
+            request.profile.as_deref().unwrap_or("automatic"),
+            request.requested_at.format("%Y-%m-%d %H:%M:%S")
+        ),
+        Ok(None) => println!("  Requested: automatic"),
+        Err(e) => println!(
+            "{}",
+            format!("⚠️  Failed to read {}: {}", requests.path().display(), e).yellow()
+        ),
+    }
+
+    let stats: Option<serde_json::Value> = std::fs::read_to_string(stats_file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    match stats {
+        Some(stats) => {
+            let health = &stats["system_health"];
+            println!(
+                "  Active:    {}",
+                health["active_profile"].as_str().unwrap_or("base")
+            );
+            if let Some(source) = health["power_source"].as_str() {
+                println!("  Power:     {}", source);
+            }
+        }
+        None => println!(
+            "  Active:    unknown (no stats in {}, is process-monitor running?)",
+            stats_file.display()
+        ),
+    }
+}
+
 fn force_restart() {
     let mut service = SystemdService::new("kesl");
 
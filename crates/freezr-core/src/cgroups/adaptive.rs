@@ -0,0 +1,279 @@
+//! Adaptive limits of throttle cgroups
+//!
+//! Fixed limits either hold an offender too tight (it starves on an idle
+//! system) or too loose (it still drags the system down). In adaptive mode
+//! the limits of throttle cgroups follow a feedback loop on every check:
+//!
+//! - `cpu.max` is tightened while the group runs at its quota and the
+//!   system load stays above the target, and loosened step by step while
+//!   the group starves (most periods throttled, `nr_throttled`) and the
+//!   system is idle.
+//! - `memory.high` is tightened while the group holds its memory and the
+//!   system stalls on memory (PSI), and loosened while the group itself
+//!   stalls (cgroup memory PSI) and the system does not.
+//!
+//! Between the two thresholds the limit is held, so it does not oscillate.
+
+use serde::{Deserialize, Serialize};
+
+use super::error::{CgroupError, Result};
+
+/// Share of memory.high the group must use to count as holding its memory
+const MEMORY_HELD: f64 = 0.9;
+
+/// Adaptive mode settings of throttle cgroups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveSettings {
+    /// Adjust the limits of throttle cgroups on every check
+    pub enabled: bool,
+
+    /// Load average (1 min) per CPU above which cpu.max is tightened
+    pub target_load: f64,
+
+    /// Load average per CPU below which the system counts as idle
+    pub idle_load: f64,
+
+    /// Share of cpu.max periods throttled (%) at which the group starves
+    pub starving_throttle_percent: f64,
+
+    /// Bounds of cpu.max (% of one core)
+    pub min_cpu_limit: f64,
+    pub max_cpu_limit: f64,
+
+    /// System memory PSI `some avg10` (%) above which memory.high is tightened
+    pub memory_pressure_target: f64,
+
+    /// System memory PSI `some avg10` (%) below which memory is plentiful
+    pub idle_memory_pressure: f64,
+
+    /// Cgroup memory PSI `some avg10` (%) at which the group starves
+    pub starving_memory_pressure: f64,
+
+    /// Bounds of memory.high (MB)
+    pub min_memory_high_mb: u64,
+    pub max_memory_high_mb: u64,
+
+    /// Change of a limit per adjustment (% of its current value)
+    pub step_percent: f64,
+}
+
+impl Default for AdaptiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_load: 1.0,
+            idle_load: 0.5,
+            starving_throttle_percent: 50.0,
+            min_cpu_limit: 10.0,
+            max_cpu_limit: 200.0,
+            memory_pressure_target: 10.0,
+            idle_memory_pressure: 2.0,
+            starving_memory_pressure: 20.0,
+            min_memory_high_mb: 256,
+            max_memory_high_mb: 4096,
+            step_percent: 20.0,
+        }
+    }
+}
+
+/// Direction of one adjustment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Adjustment {
+    Tighten,
+    Loosen,
+    Hold,
+}
+
+impl AdaptiveSettings {
+    /// Check thresholds and bounds
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(CgroupError::ValidationError(msg));
+
+        if self.idle_load < 0.0 || self.idle_load >= self.target_load {
+            return invalid(format!(
+                "Adaptive idle_load ({}) must be >= 0 and below target_load ({})",
+                self.idle_load, self.target_load
+            ));
+        }
+        if self.idle_memory_pressure < 0.0
+            || self.idle_memory_pressure >= self.memory_pressure_target
+        {
+            return invalid(format!(
+                "Adaptive idle_memory_pressure ({}) must be >= 0 and below memory_pressure_target ({})",
+                self.idle_memory_pressure, self.memory_pressure_target
+            ));
+        }
+        if !(0.0..=100.0).contains(&self.starving_throttle_percent)
+            || !(0.0..=100.0).contains(&self.starving_memory_pressure)
+        {
+            return invalid("Adaptive starving thresholds must be 0-100%".to_string());
+        }
+        if self.min_cpu_limit <= 0.0 || self.min_cpu_limit > self.max_cpu_limit {
+            return invalid(format!(
+                "Adaptive CPU bounds must satisfy 0 < min ({}) <= max ({})",
+                self.min_cpu_limit, self.max_cpu_limit
+            ));
+        }
+        if self.min_memory_high_mb == 0 || self.min_memory_high_mb > self.max_memory_high_mb {
+            return invalid(format!(
+                "Adaptive memory bounds must satisfy 0 < min ({} MB) <= max ({} MB)",
+                self.min_memory_high_mb, self.max_memory_high_mb
+            ));
+        }
+        if self.step_percent <= 0.0 || self.step_percent >= 100.0 {
+            return invalid(format!(
+                "Adaptive step must be between 0 and 100%, got {}%",
+                self.step_percent
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Next cpu.max of a throttle cgroup (None = keep `current`)
+    ///
+    /// # Arguments
+    /// * `current` - Current limit (% of one core)
+    /// * `system_load` - Load average (1 min) per CPU
+    /// * `throttle_percent` - Share of periods throttled since the last check
+    pub fn next_cpu_limit(
+        &self,
+        current: f64,
+        system_load: f64,
+        throttle_percent: f64,
+    ) -> Option<f64> {
+        // Only a group running at its quota pushes the load
+        let adjustment = if system_load > self.target_load && throttle_percent > 0.0 {
+            Adjustment::Tighten
+        } else if system_load < self.idle_load && throttle_percent >= self.starving_throttle_percent
+        {
+            Adjustment::Loosen
+        } else {
+            Adjustment::Hold
+        };
+
+        self.step(current, adjustment, self.min_cpu_limit, self.max_cpu_limit)
+    }
+
+    /// Next memory.high of a throttle cgroup (None = keep `current`)
+    ///
+    /// # Arguments
+    /// * `current` - Current memory.high (bytes)
+    /// * `usage` - memory.current of the group (bytes)
+    /// * `system_pressure` - System memory PSI `some avg10` (%)
+    /// * `cgroup_pressure` - Memory PSI `some avg10` of the group (%)
+    pub fn next_memory_high(
+        &self,
+        current: u64,
+        usage: u64,
+        system_pressure: f64,
+        cgroup_pressure: f64,
+    ) -> Option<u64> {
+        let held = usage as f64 >= current as f64 * MEMORY_HELD;
+        let adjustment = if system_pressure > self.memory_pressure_target && held {
+            Adjustment::Tighten
+        } else if system_pressure < self.idle_memory_pressure
+            && cgroup_pressure >= self.starving_memory_pressure
+        {
+            Adjustment::Loosen
+        } else {
+            Adjustment::Hold
+        };
+
+        let mb = 1024.0 * 1024.0;
+        let next = self.step(
+            current as f64 / mb,
+            adjustment,
+            self.min_memory_high_mb as f64,
+            self.max_memory_high_mb as f64,
+        )?;
+        Some((next * mb) as u64)
+    }
+
+    /// Apply one step within `[min, max]`, None if the value stays the same
+    fn step(&self, current: f64, adjustment: Adjustment, min: f64, max: f64) -> Option<f64> {
+        let factor = self.step_percent / 100.0;
+        let next = match adjustment {
+            Adjustment::Tighten => current * (1.0 - factor),
+            Adjustment::Loosen => current * (1.0 + factor),
+            Adjustment::Hold => return None,
+        }
+        .clamp(min, max);
+
+        ((next - current).abs() > f64::EPSILON).then_some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_default_settings_are_valid() {
+        let settings = AdaptiveSettings::default();
+        assert!(!settings.enabled);
+        assert!(settings.validate().is_ok());
+
+        let invalid = AdaptiveSettings {
+            idle_load: 2.0,
+            ..AdaptiveSettings::default()
+        };
+        assert!(invalid.validate().is_err());
+
+        let invalid = AdaptiveSettings {
+            step_percent: 0.0,
+            ..AdaptiveSettings::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_cpu_limit_feedback() {
+        let settings = AdaptiveSettings::default();
+
+        // Busy system, group at its quota: tighten by 20%
+        assert_eq!(settings.next_cpu_limit(50.0, 1.5, 10.0), Some(40.0));
+        // Busy system, group below its quota: not the culprit
+        assert_eq!(settings.next_cpu_limit(50.0, 1.5, 0.0), None);
+        // Idle system, group starving: loosen
+        assert_eq!(settings.next_cpu_limit(50.0, 0.2, 80.0), Some(60.0));
+        // Idle system, group fine
+        assert_eq!(settings.next_cpu_limit(50.0, 0.2, 10.0), None);
+        // Dead band between idle and target load
+        assert_eq!(settings.next_cpu_limit(50.0, 0.7, 80.0), None);
+
+        // Bounds
+        assert_eq!(settings.next_cpu_limit(11.0, 1.5, 10.0), Some(10.0));
+        assert_eq!(settings.next_cpu_limit(10.0, 1.5, 10.0), None);
+        assert_eq!(settings.next_cpu_limit(200.0, 0.2, 80.0), None);
+    }
+
+    #[test]
+    fn test_memory_high_feedback() {
+        let settings = AdaptiveSettings::default();
+        let high = 1000 * MB;
+
+        // System stalls on memory, group holds its memory: tighten
+        assert_eq!(
+            settings.next_memory_high(high, 950 * MB, 15.0, 0.0),
+            Some(800 * MB)
+        );
+        // Group uses little memory: leave it alone
+        assert_eq!(settings.next_memory_high(high, 100 * MB, 15.0, 0.0), None);
+        // Plenty of memory, group stalls on reclaim: loosen
+        assert_eq!(
+            settings.next_memory_high(high, 1000 * MB, 0.5, 40.0),
+            Some(1200 * MB)
+        );
+        // Group stalls, but so does the system: hold
+        assert_eq!(settings.next_memory_high(high, 1000 * MB, 5.0, 40.0), None);
+
+        assert_eq!(
+            settings.next_memory_high(300 * MB, 300 * MB, 15.0, 0.0),
+            Some(256 * MB)
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Access to the cgroup filesystem
+//!
+//! Every read, write, mkdir and rmdir of the cgroup module goes through
+//! this module. Paths are served by the real filesystem unless a backend
+//! is mounted at one of their ancestors: tests mount a `FakeCgroupFs` at a
+//! made-up root and point [`CgroupConfig::cgroupfs_root`](super::CgroupConfig)
+//! and `root_path` under it, so the whole cgroup subsystem runs without root.
+//!
+//! Mounting is only available in tests and with the `fake-cgroupfs`
+//! feature; other builds go straight to the real filesystem.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+use std::sync::Mutex;
+
+/// Backend of a cgroup filesystem tree
+///
+/// Errors follow the kernel: `NotFound` for missing files, `InvalidInput`
+/// for rejected values, `EBUSY` for removing a cgroup that is still in use.
+pub trait CgroupFs: Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    fn write(&self, path: &Path, content: &str) -> io::Result<()>;
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+    fn exists(&self, path: &Path) -> bool;
+    /// Child cgroups (subdirectories) of `path`
+    fn subdirs(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+}
+
+/// Backends mounted over the real filesystem (root -> backend)
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+static MOUNTS: Mutex<Vec<(PathBuf, Arc<dyn CgroupFs>)>> = Mutex::new(Vec::new());
+
+/// A mounted backend; unmounted when dropped
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+#[must_use = "the backend is unmounted when the guard is dropped"]
+pub struct CgroupFsMount {
+    root: PathBuf,
+}
+
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+impl Drop for CgroupFsMount {
+    fn drop(&mut self) {
+        let mut mounts = MOUNTS.lock().unwrap_or_else(|p| p.into_inner());
+        mounts.retain(|(root, _)| root != &self.root);
+    }
+}
+
+/// Serve every path under `root` from `backend`
+///
+/// Mounting over an existing mount point replaces it.
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+pub fn mount(root: impl Into<PathBuf>, backend: Arc<dyn CgroupFs>) -> CgroupFsMount {
+    let root = root.into();
+    let mut mounts = MOUNTS.lock().unwrap_or_else(|p| p.into_inner());
+    mounts.retain(|(existing, _)| existing != &root);
+    mounts.push((root.clone(), backend));
+    CgroupFsMount { root }
+}
+
+/// Backend of the innermost mount containing `path`
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+fn backend(path: &Path) -> Option<Arc<dyn CgroupFs>> {
+    let mounts = MOUNTS.lock().unwrap_or_else(|p| p.into_inner());
+    mounts
+        .iter()
+        .filter(|(root, _)| path.starts_with(root))
+        .max_by_key(|(root, _)| root.components().count())
+        .map(|(_, backend)| backend.clone())
+}
+
+/// Nothing can be mounted: always the real filesystem
+#[cfg(not(any(test, feature = "fake-cgroupfs")))]
+fn backend(_path: &Path) -> Option<Arc<dyn CgroupFs>> {
+    None
+}
+
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    match backend(path) {
+        Some(backend) => backend.read_to_string(path),
+        None => fs::read_to_string(path),
+    }
+}
+
+pub fn write(path: &Path, content: &str) -> io::Result<()> {
+    match backend(path) {
+        Some(backend) => backend.write(path, content),
+        None => fs::write(path, content),
+    }
+}
+
+pub fn create_dir(path: &Path) -> io::Result<()> {
+    match backend(path) {
+        Some(backend) => backend.create_dir(path),
+        None => fs::create_dir(path),
+    }
+}
+
+pub fn remove_dir(path: &Path) -> io::Result<()> {
+    match backend(path) {
+        Some(backend) => backend.remove_dir(path),
+        None => fs::remove_dir(path),
+    }
+}
+
+pub fn exists(path: &Path) -> bool {
+    match backend(path) {
+        Some(backend) => backend.exists(path),
+        None => path.exists(),
+    }
+}
+
+pub fn subdirs(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if let Some(backend) = backend(path) {
+        return backend.subdirs(path);
+    }
+
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backend answering every read with its own name
+    struct Named(&'static str);
+
+    impl CgroupFs for Named {
+        fn read_to_string(&self, _path: &Path) -> io::Result<String> {
+            Ok(self.0.to_string())
+        }
+        fn write(&self, _path: &Path, _content: &str) -> io::Result<()> {
+            Ok(())
+        }
+        fn create_dir(&self, _path: &Path) -> io::Result<()> {
+            Ok(())
+        }
+        fn remove_dir(&self, _path: &Path) -> io::Result<()> {
+            Ok(())
+        }
+        fn exists(&self, _path: &Path) -> bool {
+            true
+        }
+        fn subdirs(&self, _path: &Path) -> io::Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_innermost_mount_wins() {
+        let outer = mount("/fake-mounts/outer", Arc::new(Named("outer")));
+        let inner = mount("/fake-mounts/outer/inner", Arc::new(Named("inner")));
+
+        let read = |path: &str| read_to_string(Path::new(path)).unwrap();
+        assert_eq!(read("/fake-mounts/outer/a"), "outer");
+        assert_eq!(read("/fake-mounts/outer/inner/b"), "inner");
+        assert!(!exists(Path::new("/fake-mounts/outerx")));
+
+        drop(inner);
+        assert_eq!(read("/fake-mounts/outer/inner/b"), "outer");
+        drop(outer);
+        assert!(!exists(Path::new("/fake-mounts/outer/a")));
+    }
+}
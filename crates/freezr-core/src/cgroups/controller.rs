@@ -1,12 +1,19 @@
-//! Controller-specific operations for CPU and Memory
+//! Controller-specific operations for CPU, Cpuset, Memory, I/O, PIDs and Freezer
 
+use std::fmt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
 use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use super::error::Result;
+use super::cgroupfs;
+use super::error::{CgroupError, Result};
 use super::utils::{
     convert_percent_to_quota, convert_quota_to_percent, parse_cpu_stat, parse_memory_stat,
     read_cgroup_file, write_cgroup_file,
 };
+use crate::psi::PressureStall;
 
 /// CPU controller operations
 pub struct CpuController;
@@ -141,6 +148,153 @@ impl CpuStats {
     pub fn is_throttled(&self) -> bool {
         self.nr_throttled > 0
     }
+
+    /// Counters accumulated since an earlier sample
+    ///
+    /// `throttle_percentage` of the result covers only the interval
+    /// between the two samples.
+    pub fn since(&self, earlier: &CpuStats) -> CpuStats {
+        CpuStats {
+            usage_usec: self.usage_usec.saturating_sub(earlier.usage_usec),
+            user_usec: self.user_usec.saturating_sub(earlier.user_usec),
+            system_usec: self.system_usec.saturating_sub(earlier.system_usec),
+            nr_periods: self.nr_periods.saturating_sub(earlier.nr_periods),
+            nr_throttled: self.nr_throttled.saturating_sub(earlier.nr_throttled),
+            throttled_usec: self.throttled_usec.saturating_sub(earlier.throttled_usec),
+        }
+    }
+}
+
+/// Cpuset controller operations (cgroup v2 `cpuset.cpus`, `cpuset.mems`)
+///
+/// Pins a cgroup to a subset of CPUs and memory nodes, e.g. to keep a
+/// scanner or a build on cores 6-7 and leave the rest for interactive work.
+pub struct CpusetController;
+
+impl CpusetController {
+    /// Restrict the cgroup to `cpus` (empty list = inherit from parent)
+    pub fn set_cpus(cgroup_path: &Path, cpus: &CpuList) -> Result<()> {
+        write_cgroup_file(&cgroup_path.join("cpuset.cpus"), &cpus.to_string())
+    }
+
+    /// Configured CPUs (empty = inherited from parent)
+    pub fn get_cpus(cgroup_path: &Path) -> Result<CpuList> {
+        read_cgroup_file(&cgroup_path.join("cpuset.cpus"))?.parse()
+    }
+
+    /// CPUs the cgroup actually runs on
+    pub fn get_effective_cpus(cgroup_path: &Path) -> Result<CpuList> {
+        read_cgroup_file(&cgroup_path.join("cpuset.cpus.effective"))?.parse()
+    }
+
+    /// Restrict the cgroup to memory nodes `mems` (empty list = inherit)
+    pub fn set_mems(cgroup_path: &Path, mems: &CpuList) -> Result<()> {
+        write_cgroup_file(&cgroup_path.join("cpuset.mems"), &mems.to_string())
+    }
+
+    /// Configured memory nodes (empty = inherited from parent)
+    pub fn get_mems(cgroup_path: &Path) -> Result<CpuList> {
+        read_cgroup_file(&cgroup_path.join("cpuset.mems"))?.parse()
+    }
+
+    /// CPUs currently online (/sys/devices/system/cpu/online)
+    pub fn online_cpus() -> Result<CpuList> {
+        read_cgroup_file(Path::new("/sys/devices/system/cpu/online"))?.parse()
+    }
+
+    /// Memory nodes currently online (/sys/devices/system/node/online)
+    pub fn online_mems() -> Result<CpuList> {
+        read_cgroup_file(Path::new("/sys/devices/system/node/online"))?.parse()
+    }
+}
+
+/// List of CPU or memory node IDs in the kernel list format (`0-3,6`)
+///
+/// Sorted and without duplicates; the empty list means "inherit".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuList(Vec<u32>);
+
+impl CpuList {
+    /// Highest ID accepted when parsing (guards against `0-4294967295`)
+    const MAX_ID: u32 = 8191;
+
+    pub fn ids(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// IDs of `self` missing from `other`
+    pub fn missing_from(&self, other: &CpuList) -> CpuList {
+        CpuList(
+            self.0
+                .iter()
+                .copied()
+                .filter(|id| other.0.binary_search(id).is_err())
+                .collect(),
+        )
+    }
+}
+
+impl FromStr for CpuList {
+    type Err = CgroupError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || CgroupError::ParseError(format!("Invalid CPU list: {}", s));
+        let parse_id = |id: &str| {
+            id.trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|&id| id <= Self::MAX_ID)
+                .ok_or_else(invalid)
+        };
+
+        let mut ids = Vec::new();
+        for part in s.trim().split(',').filter(|part| !part.trim().is_empty()) {
+            match part.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse_id(start)?, parse_id(end)?);
+                    if start > end {
+                        return Err(invalid());
+                    }
+                    ids.extend(start..=end);
+                }
+                None => ids.push(parse_id(part)?),
+            }
+        }
+
+        ids.sort_unstable();
+        ids.dedup();
+        Ok(Self(ids))
+    }
+}
+
+/// Compact kernel format: consecutive IDs become ranges (`0-3,6`)
+impl fmt::Display for CpuList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ranges = Vec::new();
+        let mut iter = self.0.iter().copied().peekable();
+
+        while let Some(start) = iter.next() {
+            let mut end = start;
+            while iter.peek() == Some(&(end + 1)) {
+                end = iter.next().unwrap_or(end);
+            }
+            ranges.push(if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            });
+        }
+
+        write!(f, "{}", ranges.join(","))
+    }
 }
 
 /// Memory controller operations
@@ -189,6 +343,21 @@ impl MemoryController {
         Ok(())
     }
 
+    /// Get soft memory limit (None = unlimited)
+    pub fn get_high(cgroup_path: &Path) -> Result<Option<u64>> {
+        let content = read_cgroup_file(&cgroup_path.join("memory.high"))?;
+
+        if content.trim() == "max" {
+            return Ok(None);
+        }
+
+        let bytes: u64 = content.trim().parse().map_err(|_| {
+            super::error::CgroupError::ParseError(format!("Invalid memory.high: {}", content))
+        })?;
+
+        Ok(Some(bytes))
+    }
+
     /// Get current memory usage
     pub fn get_current(cgroup_path: &Path) -> Result<u64> {
         let memory_current_file = cgroup_path.join("memory.current");
@@ -236,14 +405,39 @@ impl MemoryController {
     }
 
     /// Get memory pressure (PSI)
-    pub fn get_pressure(cgroup_path: &Path) -> Result<MemoryPressure> {
+    pub fn get_pressure(cgroup_path: &Path) -> Result<PressureStall> {
         let memory_pressure_file = cgroup_path.join("memory.pressure");
         let content = read_cgroup_file(&memory_pressure_file)?;
 
-        parse_memory_pressure(&content)
+        PressureStall::parse(&content).map_err(|e| CgroupError::ParseError(e.to_string()))
+    }
+
+    /// Read memory.events (counters since the cgroup was created)
+    pub fn get_events(cgroup_path: &Path) -> Result<MemoryEvents> {
+        let content = read_cgroup_file(&cgroup_path.join("memory.events"))?;
+        parse_memory_events(&content)
     }
 }
 
+/// Counters from memory.events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryEvents {
+    /// Reclaimed despite being below memory.low
+    pub low: u64,
+
+    /// Throttled and reclaimed for exceeding memory.high
+    pub high: u64,
+
+    /// Usage was about to exceed memory.max
+    pub max: u64,
+
+    /// Allocation failed at memory.max, OOM killer invoked
+    pub oom: u64,
+
+    /// Processes killed by the OOM killer
+    pub oom_kill: u64,
+}
+
 /// Memory statistics
 #[derive(Debug, Clone, Default)]
 pub struct MemoryStats {
@@ -278,82 +472,615 @@ impl MemoryStats {
     }
 }
 
-/// Memory pressure information (PSI)
-#[derive(Debug, Clone, Default)]
-pub struct MemoryPressure {
-    /// Some: avg10, avg60, avg300
-    pub some_avg10: f64,
-    pub some_avg60: f64,
-    pub some_avg300: f64,
+/// I/O controller operations (cgroup v2 `io.max`, `io.weight`, `io.stat`)
+///
+/// Limits are set per block device. io.max only accepts whole disks, so
+/// partitions are resolved to their parent disk (see
+/// [`IoController::resolve_device`]).
+pub struct IoController;
 
-    /// Full: avg10, avg60, avg300
-    pub full_avg10: f64,
-    pub full_avg60: f64,
-    pub full_avg300: f64,
+impl IoController {
+    /// Set bandwidth/IOPS limits of one device
+    ///
+    /// Limits that are `None` are reset to unlimited.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// // Cap writes to the root disk at 20 MB/s
+    /// let device = IoController::resolve_device("/")?;
+    /// IoController::set_max(path, &IoMax::new(device).with_wbps(20 * 1024 * 1024))?;
+    /// ```
+    pub fn set_max(cgroup_path: &Path, max: &IoMax) -> Result<()> {
+        write_cgroup_file(&cgroup_path.join("io.max"), &max.to_string())
+    }
+
+    /// Get limits of all limited devices
+    pub fn get_max(cgroup_path: &Path) -> Result<Vec<IoMax>> {
+        let content = read_cgroup_file(&cgroup_path.join("io.max"))?;
+        parse_io_max(&content)
+    }
+
+    /// Remove all limits of one device
+    pub fn remove_max(cgroup_path: &Path, device: DeviceNumber) -> Result<()> {
+        Self::set_max(cgroup_path, &IoMax::new(device))
+    }
+
+    /// Set default I/O weight (relative share, 1-10000)
+    ///
+    /// Higher weight = more disk time when there's contention
+    /// Default weight = 100
+    pub fn set_weight(cgroup_path: &Path, weight: u32) -> Result<()> {
+        if !(1..=10000).contains(&weight) {
+            return Err(CgroupError::InvalidLimit(format!(
+                "I/O weight must be 1-10000, got {}",
+                weight
+            )));
+        }
+
+        let io_weight_file = cgroup_path.join("io.weight");
+        write_cgroup_file(&io_weight_file, &format!("default {}", weight))
+    }
+
+    /// Get default I/O weight
+    pub fn get_weight(cgroup_path: &Path) -> Result<u32> {
+        let content = read_cgroup_file(&cgroup_path.join("io.weight"))?;
+        parse_io_weight(&content)
+    }
+
+    /// Get per-device I/O statistics
+    pub fn get_stats(cgroup_path: &Path) -> Result<Vec<IoStat>> {
+        let content = read_cgroup_file(&cgroup_path.join("io.stat"))?;
+        parse_io_stat(&content)
+    }
+
+    /// Resolve the block device to limit
+    ///
+    /// # Arguments
+    /// * `target` - `MAJ:MIN`, a block device node (`/dev/sda`) or any path
+    ///   on a mounted filesystem (`/home`): the disk holding that filesystem
+    pub fn resolve_device(target: &str) -> Result<DeviceNumber> {
+        if let Ok(device) = target.parse::<DeviceNumber>() {
+            return Ok(device);
+        }
+
+        let metadata = std::fs::metadata(target).map_err(|e| {
+            CgroupError::NotFound(format!("Cannot resolve block device of {}: {}", target, e))
+        })?;
+
+        let device = if metadata.file_type().is_block_device() {
+            DeviceNumber::from_dev(metadata.rdev())
+        } else {
+            let device = DeviceNumber::from_dev(metadata.dev());
+            if device.major == 0 {
+                // Anonymous device (btrfs, overlay): use the mount source
+                Self::mount_source_device(target)?
+            } else {
+                device
+            }
+        };
+
+        Ok(whole_disk(device))
+    }
+
+    /// Block device mounted at the mount point holding `path`
+    fn mount_source_device(path: &str) -> Result<DeviceNumber> {
+        let path = std::fs::canonicalize(path)?;
+        let mountinfo = read_cgroup_file(Path::new("/proc/self/mountinfo"))?;
+
+        let source = mount_source(&mountinfo, &path).ok_or_else(|| {
+            CgroupError::NotFound(format!("No block device mounted at {}", path.display()))
+        })?;
+        if !source.starts_with("/dev/") {
+            return Err(CgroupError::NotFound(format!(
+                "{} is not on a block device (mounted from {})",
+                path.display(),
+                source
+            )));
+        }
+
+        let metadata = std::fs::metadata(&source)?;
+        Ok(DeviceNumber::from_dev(metadata.rdev()))
+    }
 }
 
-impl MemoryPressure {
-    /// Check if under pressure (some avg10 > threshold)
-    pub fn is_under_pressure(&self, threshold: f64) -> bool {
-        self.some_avg10 > threshold
+/// Block device number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceNumber {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl DeviceNumber {
+    pub fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
     }
 
-    /// Check if critical pressure (full avg10 > threshold)
-    pub fn is_critical(&self, threshold: f64) -> bool {
-        self.full_avg10 > threshold
+    /// Split a `dev_t` (glibc encoding)
+    pub fn from_dev(dev: u64) -> Self {
+        Self {
+            major: (((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0000_0fff)) as u32,
+            minor: (((dev >> 12) & 0xffff_ff00) | (dev & 0x0000_00ff)) as u32,
+        }
+    }
+}
+
+impl fmt::Display for DeviceNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.major, self.minor)
     }
 }
 
-/// Parse memory.pressure file
+impl FromStr for DeviceNumber {
+    type Err = CgroupError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || CgroupError::ParseError(format!("Invalid device number: {}", s));
+        let (major, minor) = s.trim().split_once(':').ok_or_else(invalid)?;
+
+        Ok(Self {
+            major: major.parse().map_err(|_| invalid())?,
+            minor: minor.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Limits of one device in io.max (None = unlimited)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoMax {
+    pub device: DeviceNumber,
+
+    /// Read bytes per second
+    pub rbps: Option<u64>,
+
+    /// Write bytes per second
+    pub wbps: Option<u64>,
+
+    /// Read operations per second
+    pub riops: Option<u64>,
+
+    /// Write operations per second
+    pub wiops: Option<u64>,
+}
+
+impl IoMax {
+    /// No limits on `device`
+    pub fn new(device: DeviceNumber) -> Self {
+        Self {
+            device,
+            rbps: None,
+            wbps: None,
+            riops: None,
+            wiops: None,
+        }
+    }
+
+    pub fn with_rbps(mut self, bytes: u64) -> Self {
+        self.rbps = Some(bytes);
+        self
+    }
+
+    pub fn with_wbps(mut self, bytes: u64) -> Self {
+        self.wbps = Some(bytes);
+        self
+    }
+
+    pub fn with_riops(mut self, ops: u64) -> Self {
+        self.riops = Some(ops);
+        self
+    }
+
+    pub fn with_wiops(mut self, ops: u64) -> Self {
+        self.wiops = Some(ops);
+        self
+    }
+
+    /// Is any limit set?
+    pub fn is_limited(&self) -> bool {
+        self.rbps.is_some() || self.wbps.is_some() || self.riops.is_some() || self.wiops.is_some()
+    }
+}
+
+/// Format as an io.max line: `8:0 rbps=max wbps=20971520 riops=max wiops=max`
+impl fmt::Display for IoMax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |limit: Option<u64>| limit.map_or("max".to_string(), |v| v.to_string());
+        write!(
+            f,
+            "{} rbps={} wbps={} riops={} wiops={}",
+            self.device,
+            value(self.rbps),
+            value(self.wbps),
+            value(self.riops),
+            value(self.wiops)
+        )
+    }
+}
+
+/// I/O statistics of one device (io.stat)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoStat {
+    pub device: DeviceNumber,
+
+    /// Bytes read
+    pub rbytes: u64,
+
+    /// Bytes written
+    pub wbytes: u64,
+
+    /// Read operations
+    pub rios: u64,
+
+    /// Write operations
+    pub wios: u64,
+
+    /// Bytes discarded
+    pub dbytes: u64,
+
+    /// Discard operations
+    pub dios: u64,
+}
+
+/// PIDs controller operations (cgroup v2 `pids.max`)
+///
+/// Caps the number of tasks (processes and threads) in a cgroup: fork() and
+/// clone() fail with EAGAIN at the limit, which stops fork bombs without
+/// killing anything. Moving processes in is never refused, so
+/// `pids.current` may exceed `pids.max`.
+pub struct PidsController;
+
+impl PidsController {
+    /// Set maximum number of tasks
+    pub fn set_max(cgroup_path: &Path, max: u64) -> Result<()> {
+        if max == 0 {
+            return Err(CgroupError::InvalidLimit(
+                "pids.max cannot be 0".to_string(),
+            ));
+        }
+        write_cgroup_file(&cgroup_path.join("pids.max"), &max.to_string())
+    }
+
+    /// Get maximum number of tasks (None = unlimited)
+    pub fn get_max(cgroup_path: &Path) -> Result<Option<u64>> {
+        let content = read_cgroup_file(&cgroup_path.join("pids.max"))?;
+        if content.trim() == "max" {
+            return Ok(None); // Unlimited
+        }
+
+        let max = content
+            .trim()
+            .parse()
+            .map_err(|_| CgroupError::ParseError(format!("Invalid pids.max: {}", content)))?;
+        Ok(Some(max))
+    }
+
+    /// Remove task limit (set to unlimited)
+    pub fn remove_max(cgroup_path: &Path) -> Result<()> {
+        write_cgroup_file(&cgroup_path.join("pids.max"), "max")
+    }
+
+    /// Get current number of tasks
+    pub fn get_current(cgroup_path: &Path) -> Result<u64> {
+        let content = read_cgroup_file(&cgroup_path.join("pids.current"))?;
+        content
+            .trim()
+            .parse()
+            .map_err(|_| CgroupError::ParseError(format!("Invalid pids.current: {}", content)))
+    }
+
+    /// Read pids.events
+    pub fn get_events(cgroup_path: &Path) -> Result<PidsEvents> {
+        let content = read_cgroup_file(&cgroup_path.join("pids.events"))?;
+        parse_pids_events(&content)
+    }
+}
+
+/// Counters from pids.events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PidsEvents {
+    /// Forks refused because the cgroup (or an ancestor) hit pids.max
+    pub max: u64,
+}
+
+/// Freezer operations (cgroup v2 `cgroup.freeze`)
+///
+/// Freezing a cgroup stops every process in it, including processes forked
+/// while the cgroup is frozen.
+pub struct FreezerController;
+
+impl FreezerController {
+    /// Does this cgroup support freezing? (cgroup v2, non-root cgroup)
+    pub fn is_supported(cgroup_path: &Path) -> bool {
+        cgroupfs::exists(&cgroup_path.join("cgroup.freeze"))
+    }
+
+    /// Request freeze of all processes in the cgroup
+    ///
+    /// Asynchronous: the cgroup is frozen once `cgroup.events` reports
+    /// `frozen 1` (see [`FreezerController::wait`]).
+    pub fn freeze(cgroup_path: &Path) -> Result<()> {
+        write_cgroup_file(&cgroup_path.join("cgroup.freeze"), "1")
+    }
+
+    /// Thaw all processes in the cgroup
+    pub fn thaw(cgroup_path: &Path) -> Result<()> {
+        write_cgroup_file(&cgroup_path.join("cgroup.freeze"), "0")
+    }
+
+    /// Read cgroup.events
+    pub fn get_events(cgroup_path: &Path) -> Result<CgroupEvents> {
+        let content = read_cgroup_file(&cgroup_path.join("cgroup.events"))?;
+        parse_cgroup_events(&content)
+    }
+
+    /// Is the cgroup fully frozen?
+    pub fn is_frozen(cgroup_path: &Path) -> Result<bool> {
+        Ok(Self::get_events(cgroup_path)?.frozen)
+    }
+
+    /// Wait until `cgroup.events` reports the requested frozen state
+    ///
+    /// # Arguments
+    /// * `cgroup_path` - Path to cgroup directory
+    /// * `frozen` - Expected state (true = `frozen 1`)
+    /// * `timeout` - Maximum time to wait
+    pub fn wait(cgroup_path: &Path, frozen: bool, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+
+        while Self::is_frozen(cgroup_path)? != frozen {
+            if Instant::now() >= deadline {
+                return Err(CgroupError::Timeout(format!(
+                    "cgroup {:?} did not report frozen {} within {:?}",
+                    cgroup_path, frozen as u8, timeout
+                )));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(())
+    }
+}
+
+/// State from cgroup.events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CgroupEvents {
+    /// Cgroup (or a descendant) has live processes
+    pub populated: bool,
+
+    /// Cgroup is fully frozen
+    pub frozen: bool,
+}
+
+/// Parse cgroup.events file
 ///
 /// Format:
 /// ```text
-/// some avg10=0.00 avg60=0.00 avg300=0.00 total=0
-/// full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+/// populated 1
+/// frozen 0
 /// ```
-fn parse_memory_pressure(content: &str) -> Result<MemoryPressure> {
-    let mut pressure = MemoryPressure::default();
+fn parse_cgroup_events(content: &str) -> Result<CgroupEvents> {
+    let mut events = CgroupEvents::default();
 
     for line in content.lines() {
-        if line.starts_with("some ") {
-            pressure.some_avg10 = extract_avg_value(line, "avg10")?;
-            pressure.some_avg60 = extract_avg_value(line, "avg60")?;
-            pressure.some_avg300 = extract_avg_value(line, "avg300")?;
-        } else if line.starts_with("full ") {
-            pressure.full_avg10 = extract_avg_value(line, "avg10")?;
-            pressure.full_avg60 = extract_avg_value(line, "avg60")?;
-            pressure.full_avg300 = extract_avg_value(line, "avg300")?;
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+
+        let value = match value {
+            "0" => false,
+            "1" => true,
+            other => {
+                return Err(CgroupError::ParseError(format!(
+                    "Invalid cgroup.events value for {}: {}",
+                    key, other
+                )))
+            }
+        };
+
+        match key {
+            "populated" => events.populated = value,
+            "frozen" => events.frozen = value,
+            _ => {} // Ignore unknown keys
         }
     }
 
-    Ok(pressure)
+    Ok(events)
 }
 
-/// Extract average value from PSI line
-fn extract_avg_value(line: &str, key: &str) -> Result<f64> {
-    let search = format!("{}=", key);
-    if let Some(start) = line.find(&search) {
-        let start = start + search.len();
-        if let Some(end) = line[start..].find(char::is_whitespace) {
-            let value_str = &line[start..start + end];
-            return value_str.parse::<f64>().map_err(|_| {
-                super::error::CgroupError::ParseError(format!(
-                    "Failed to parse {}: {}",
-                    key, value_str
-                ))
-            });
+/// Parse memory.events file
+///
+/// Format:
+/// ```text
+/// low 0
+/// high 12
+/// max 0
+/// oom 0
+/// oom_kill 0
+/// ```
+fn parse_memory_events(content: &str) -> Result<MemoryEvents> {
+    let mut events = MemoryEvents::default();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+
+        let counter = match key {
+            "low" => &mut events.low,
+            "high" => &mut events.high,
+            "max" => &mut events.max,
+            "oom" => &mut events.oom,
+            "oom_kill" => &mut events.oom_kill,
+            _ => continue, // oom_group_kill and newer keys
+        };
+        *counter = value.parse().map_err(|_| {
+            CgroupError::ParseError(format!("Invalid memory.events {}: {}", key, value))
+        })?;
+    }
+
+    Ok(events)
+}
+
+/// Parse pids.events file
+///
+/// Format:
+/// ```text
+/// max 42
+/// ```
+fn parse_pids_events(content: &str) -> Result<PidsEvents> {
+    let mut events = PidsEvents::default();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        if let (Some("max"), Some(value)) = (parts.next(), parts.next()) {
+            events.max = value.parse().map_err(|_| {
+                CgroupError::ParseError(format!("Invalid pids.events max: {}", value))
+            })?;
+        }
+    }
+
+    Ok(events)
+}
+
+/// Parse io.max file
+///
+/// Format (one line per limited device):
+/// ```text
+/// 8:0 rbps=max wbps=20971520 riops=max wiops=max
+/// ```
+fn parse_io_max(content: &str) -> Result<Vec<IoMax>> {
+    let mut limits = Vec::new();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(device) = parts.next() else {
+            continue;
+        };
+
+        let mut max = IoMax::new(device.parse()?);
+        for (key, value) in parts.filter_map(|part| part.split_once('=')) {
+            let value = match value {
+                "max" => None,
+                value => Some(value.parse::<u64>().map_err(|_| {
+                    CgroupError::ParseError(format!("Invalid io.max value {}={}", key, value))
+                })?),
+            };
+
+            match key {
+                "rbps" => max.rbps = value,
+                "wbps" => max.wbps = value,
+                "riops" => max.riops = value,
+                "wiops" => max.wiops = value,
+                _ => {} // Ignore unknown keys
+            }
+        }
+        limits.push(max);
+    }
+
+    Ok(limits)
+}
+
+/// Parse io.weight file (only the default weight)
+///
+/// Format:
+/// ```text
+/// default 100
+/// 8:0 200
+/// ```
+fn parse_io_weight(content: &str) -> Result<u32> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("default "))
+        .and_then(|weight| weight.trim().parse().ok())
+        .ok_or_else(|| CgroupError::ParseError(format!("Invalid io.weight: {}", content)))
+}
+
+/// Parse io.stat file
+///
+/// Format (one line per device):
+/// ```text
+/// 8:0 rbytes=1459200 wbytes=314773504 rios=192 wios=353 dbytes=0 dios=0
+/// ```
+fn parse_io_stat(content: &str) -> Result<Vec<IoStat>> {
+    let mut stats = Vec::new();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(device) = parts.next() else {
+            continue;
+        };
+
+        let mut stat = IoStat {
+            device: device.parse()?,
+            rbytes: 0,
+            wbytes: 0,
+            rios: 0,
+            wios: 0,
+            dbytes: 0,
+            dios: 0,
+        };
+        for (key, value) in parts.filter_map(|part| part.split_once('=')) {
+            let Ok(value) = value.parse::<u64>() else {
+                continue;
+            };
+            match key {
+                "rbytes" => stat.rbytes = value,
+                "wbytes" => stat.wbytes = value,
+                "rios" => stat.rios = value,
+                "wios" => stat.wios = value,
+                "dbytes" => stat.dbytes = value,
+                "dios" => stat.dios = value,
+                _ => {} // Ignore unknown keys
+            }
         }
+        stats.push(stat);
     }
 
-    Err(super::error::CgroupError::ParseError(format!(
-        "Could not find {} in line: {}",
-        key, line
-    )))
+    Ok(stats)
+}
+
+/// Source of the mount holding `path`, from /proc/self/mountinfo
+///
+/// Format (fields after " - ": fstype, source, options):
+/// ```text
+/// 29 1 0:26 / / rw,relatime shared:1 - btrfs /dev/nvme0n1p2 rw,ssd
+/// ```
+fn mount_source(mountinfo: &str, path: &Path) -> Option<String> {
+    mountinfo
+        .lines()
+        .filter_map(|line| {
+            let (mount, fs) = line.split_once(" - ")?;
+            let mount_point = mount.split_whitespace().nth(4)?;
+            let source = fs.split_whitespace().nth(1)?;
+            Some((mount_point, source))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        // Longest mount point wins; later mounts shadow earlier ones
+        .fold(None, |best: Option<(&str, &str)>, candidate| match best {
+            Some(best) if best.0.len() > candidate.0.len() => Some(best),
+            _ => Some(candidate),
+        })
+        .map(|(_, source)| source.to_string())
+}
+
+/// Parent disk of a partition (io.max rejects partitions)
+fn whole_disk(device: DeviceNumber) -> DeviceNumber {
+    let sysfs = Path::new("/sys/dev/block").join(device.to_string());
+    if !sysfs.join("partition").exists() {
+        return device;
+    }
+
+    read_cgroup_file(&sysfs.join("..").join("dev"))
+        .ok()
+        .and_then(|dev| dev.parse().ok())
+        .unwrap_or(device)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cgroups::FakeCgroupFs;
     use std::path::PathBuf;
 
     #[test]
@@ -385,43 +1112,231 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_memory_pressure() {
-        let content = r#"some avg10=12.50 avg60=8.33 avg300=3.14 total=123456
-full avg10=5.00 avg60=2.50 avg300=1.00 total=654321"#;
-
-        let pressure = parse_memory_pressure(content).unwrap();
+    fn test_get_memory_pressure() {
+        let fake = FakeCgroupFs::new("/fake/controller-pressure");
+        let _mount = fake.mount();
+        let path = fake.root().join("group");
+        cgroupfs::create_dir(&path).unwrap();
+        fake.set(
+            &path.join("memory.pressure"),
+            "some avg10=12.50 avg60=8.33 avg300=3.25 total=123456\n\
+             full avg10=5.00 avg60=2.50 avg300=1.00 total=654321\n",
+        )
+        .unwrap();
+
+        let pressure = MemoryController::get_pressure(&path).unwrap();
         assert_eq!(pressure.some_avg10, 12.50);
-        assert_eq!(pressure.some_avg60, 8.33);
-        assert_eq!(pressure.some_avg300, 3.14);
-        assert_eq!(pressure.full_avg10, 5.00);
-        assert_eq!(pressure.full_avg60, 2.50);
         assert_eq!(pressure.full_avg300, 1.00);
+        assert_eq!(pressure.full_total, 654321);
+
+        fake.set(&path.join("memory.pressure"), "garbage").unwrap();
+        assert!(matches!(
+            MemoryController::get_pressure(&path),
+            Err(CgroupError::ParseError(_))
+        ));
     }
 
     #[test]
-    fn test_memory_pressure_thresholds() {
-        let pressure = MemoryPressure {
-            some_avg10: 15.0,
-            full_avg10: 8.0,
-            ..Default::default()
+    fn test_parse_cgroup_events() {
+        let events = parse_cgroup_events("populated 1\nfrozen 0\n").unwrap();
+        assert!(events.populated);
+        assert!(!events.frozen);
+
+        let events = parse_cgroup_events("populated 1\nfrozen 1\n").unwrap();
+        assert!(events.frozen);
+
+        assert!(parse_cgroup_events("frozen maybe\n").is_err());
+    }
+
+    #[test]
+    fn test_cpu_stats_since() {
+        let earlier = CpuStats {
+            nr_periods: 100,
+            nr_throttled: 10,
+            ..CpuStats::default()
         };
+        let now = CpuStats {
+            nr_periods: 120,
+            nr_throttled: 25,
+            ..CpuStats::default()
+        };
+
+        let interval = now.since(&earlier);
+        assert_eq!(interval.nr_periods, 20);
+        assert_eq!(interval.throttle_percentage(), 75.0);
+        assert!(!earlier.since(&now).is_throttled());
+    }
+
+    #[test]
+    fn test_parse_memory_events() {
+        let events =
+            parse_memory_events("low 0\nhigh 12\nmax 3\noom 1\noom_kill 1\noom_group_kill 0\n")
+                .unwrap();
+        assert_eq!(
+            events,
+            MemoryEvents {
+                low: 0,
+                high: 12,
+                max: 3,
+                oom: 1,
+                oom_kill: 1,
+            }
+        );
+
+        assert!(parse_memory_events("high lots\n").is_err());
+    }
+
+    #[test]
+    fn test_freezer_unsupported_path() {
+        let path = PathBuf::from("/nonexistent/cgroup");
+        assert!(!FreezerController::is_supported(&path));
+        assert!(FreezerController::is_frozen(&path).is_err());
+    }
+
+    #[test]
+    fn test_cpu_list_parse() {
+        let list: CpuList = "0-3,6, 8-9".parse().unwrap();
+        assert_eq!(list.ids(), &[0, 1, 2, 3, 6, 8, 9]);
+        assert_eq!(list.len(), 7);
+        assert_eq!(list.to_string(), "0-3,6,8-9");
+
+        // Unsorted with duplicates: normalized
+        let list: CpuList = "7,6,6-7\n".parse().unwrap();
+        assert_eq!(list.to_string(), "6-7");
+
+        assert!("".parse::<CpuList>().unwrap().is_empty());
+        assert!("3-1".parse::<CpuList>().is_err());
+        assert!("a-b".parse::<CpuList>().is_err());
+        assert!("0-4294967295".parse::<CpuList>().is_err());
+    }
+
+    #[test]
+    fn test_cpu_list_missing_from() {
+        let online: CpuList = "0-7".parse().unwrap();
+        let wanted: CpuList = "6-9".parse().unwrap();
+
+        assert_eq!(wanted.missing_from(&online).to_string(), "8-9");
+        assert!(online.missing_from(&online).is_empty());
+    }
+
+    #[test]
+    fn test_online_cpus() {
+        // Always at least the boot CPU
+        let online = CpusetController::online_cpus().unwrap();
+        assert!(online.ids().contains(&0));
+    }
 
-        assert!(pressure.is_under_pressure(10.0));
-        assert!(!pressure.is_under_pressure(20.0));
+    #[test]
+    fn test_parse_pids_events() {
+        assert_eq!(parse_pids_events("max 42\n").unwrap().max, 42);
+        // Newer kernels add max.imposed
+        assert_eq!(parse_pids_events("max 3\nmax.imposed 5\n").unwrap().max, 3);
+        assert_eq!(parse_pids_events("").unwrap(), PidsEvents::default());
+        assert!(parse_pids_events("max lots\n").is_err());
+    }
+
+    #[test]
+    fn test_pids_max_zero() {
+        let path = PathBuf::from("/nonexistent/cgroup");
+        assert!(matches!(
+            PidsController::set_max(&path, 0),
+            Err(CgroupError::InvalidLimit(_))
+        ));
+        assert!(PidsController::get_current(&path).is_err());
+    }
 
-        assert!(pressure.is_critical(5.0));
-        assert!(!pressure.is_critical(10.0));
+    #[test]
+    fn test_device_number() {
+        let device: DeviceNumber = "259:3".parse().unwrap();
+        assert_eq!(device, DeviceNumber::new(259, 3));
+        assert_eq!(device.to_string(), "259:3");
+
+        assert!("sda".parse::<DeviceNumber>().is_err());
+        assert!("8:".parse::<DeviceNumber>().is_err());
+
+        // makedev(8, 1) and makedev(259, 65536)
+        assert_eq!(DeviceNumber::from_dev(0x801), DeviceNumber::new(8, 1));
+        assert_eq!(
+            DeviceNumber::from_dev(0x1001_0300),
+            DeviceNumber::new(259, 65536)
+        );
     }
 
     #[test]
-    fn test_extract_avg_value() {
-        let line = "some avg10=12.50 avg60=8.33 avg300=3.14 total=123456";
+    fn test_io_max_roundtrip() {
+        let max = IoMax::new(DeviceNumber::new(8, 0)).with_wbps(20 * 1024 * 1024);
+        assert!(max.is_limited());
+        assert_eq!(
+            max.to_string(),
+            "8:0 rbps=max wbps=20971520 riops=max wiops=max"
+        );
+
+        let content = format!("{}\n253:0 rbps=1000 wbps=max riops=max wiops=50\n", max);
+        let parsed = parse_io_max(&content).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0], max);
+        assert_eq!(parsed[1].rbps, Some(1000));
+        assert_eq!(parsed[1].wiops, Some(50));
+
+        assert!(!IoMax::new(DeviceNumber::new(8, 0)).is_limited());
+        assert!(parse_io_max("8:0 wbps=fast").is_err());
+        assert!(parse_io_max("").unwrap().is_empty());
+    }
 
-        assert_eq!(extract_avg_value(line, "avg10").unwrap(), 12.50);
-        assert_eq!(extract_avg_value(line, "avg60").unwrap(), 8.33);
-        assert_eq!(extract_avg_value(line, "avg300").unwrap(), 3.14);
+    #[test]
+    fn test_parse_io_weight() {
+        assert_eq!(parse_io_weight("default 100\n8:0 200\n").unwrap(), 100);
+        assert!(parse_io_weight("8:0 200\n").is_err());
+    }
 
-        // Missing key
-        assert!(extract_avg_value(line, "avg999").is_err());
+    #[test]
+    fn test_parse_io_stat() {
+        let content = "8:0 rbytes=1459200 wbytes=314773504 rios=192 wios=353 dbytes=0 dios=0\n\
+                       253:0 rbytes=4096 wbytes=0 rios=1 wios=0 dbytes=0 dios=0\n";
+
+        let stats = parse_io_stat(content).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].device, DeviceNumber::new(8, 0));
+        assert_eq!(stats[0].wbytes, 314773504);
+        assert_eq!(stats[0].wios, 353);
+        assert_eq!(stats[1].rbytes, 4096);
+    }
+
+    #[test]
+    fn test_mount_source() {
+        let mountinfo = "\
+29 1 8:2 / / rw,relatime shared:1 - ext4 /dev/sda2 rw
+30 29 0:27 / /home rw,relatime shared:2 - btrfs /dev/nvme0n1p3 rw,ssd
+31 29 0:28 / /tmp rw shared:3 - tmpfs tmpfs rw
+";
+
+        let source = |path: &str| mount_source(mountinfo, Path::new(path));
+        assert_eq!(source("/home/user/dl").as_deref(), Some("/dev/nvme0n1p3"));
+        assert_eq!(source("/var/backup").as_deref(), Some("/dev/sda2"));
+        assert_eq!(source("/tmp").as_deref(), Some("tmpfs"));
+        // Component-wise: /homework is not under /home
+        assert_eq!(source("/homework").as_deref(), Some("/dev/sda2"));
+    }
+
+    #[test]
+    fn test_resolve_device() {
+        assert_eq!(
+            IoController::resolve_device("8:16").unwrap(),
+            DeviceNumber::new(8, 16)
+        );
+        assert!(IoController::resolve_device("/nonexistent/mount").is_err());
+    }
+
+    #[test]
+    fn test_io_weight_range() {
+        let path = PathBuf::from("/nonexistent/cgroup");
+        assert!(matches!(
+            IoController::set_weight(&path, 0),
+            Err(CgroupError::InvalidLimit(_))
+        ));
+        assert!(matches!(
+            IoController::set_weight(&path, 10001),
+            Err(CgroupError::InvalidLimit(_))
+        ));
     }
 }
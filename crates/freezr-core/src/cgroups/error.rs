@@ -41,6 +41,9 @@ pub enum CgroupError {
 
     #[error("Insufficient privileges (need root or CAP_SYS_ADMIN)")]
     InsufficientPrivileges,
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
 }
 
 pub type Result<T> = std::result::Result<T, CgroupError>;
@@ -0,0 +1,438 @@
+//! Event-driven monitoring of cgroup state
+//!
+//! The kernel signals every change of `memory.events` and `cgroup.events`
+//! as a file modification, so an inotify watch replaces polling. On each
+//! change the file is re-read and compared with the previous snapshot; the
+//! difference is emitted as typed [`CgroupEvent`]s (e.g. two new `oom_kill`
+//! or `populated 1 -> 0`).
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::controller::{CgroupEvents, FreezerController, MemoryController, MemoryEvents};
+use super::error::{CgroupError, Result};
+
+/// Files watched in every cgroup
+const MEMORY_EVENTS: &str = "memory.events";
+const CGROUP_EVENTS: &str = "cgroup.events";
+
+/// What changed in a cgroup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgroupEventKind {
+    /// New memory.high breaches (the group was throttled and reclaimed)
+    MemoryHigh(u64),
+
+    /// New times usage hit memory.max
+    MemoryMax(u64),
+
+    /// New OOM situations at memory.max
+    Oom(u64),
+
+    /// Processes killed by the OOM killer since the last event
+    OomKill(u64),
+
+    /// The cgroup got its first process (true) or lost its last one
+    Populated(bool),
+
+    /// The cgroup finished freezing (true) or was thawed
+    Frozen(bool),
+}
+
+/// Change of one watched cgroup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CgroupEvent {
+    /// Cgroup directory
+    pub cgroup: PathBuf,
+    pub kind: CgroupEventKind,
+}
+
+/// Last seen state of a watched cgroup
+struct Watched {
+    memory: Option<MemoryEvents>,
+    state: Option<CgroupEvents>,
+    /// Watch descriptors of the cgroup's files
+    descriptors: Vec<i32>,
+}
+
+/// inotify watch over memory.events and cgroup.events of cgroups
+///
+/// # Examples
+/// ```ignore
+/// let mut watcher = CgroupWatcher::new()?;
+/// watcher.watch(Path::new("/sys/fs/cgroup/freezr.slice/kesl"))?;
+/// for event in watcher.poll(Duration::from_secs(1))? {
+///     println!("{:?}: {:?}", event.cgroup, event.kind);
+/// }
+/// ```
+pub struct CgroupWatcher {
+    inotify: OwnedFd,
+    /// Watched cgroups (path -> last state)
+    cgroups: HashMap<PathBuf, Watched>,
+    /// Watch descriptor -> (cgroup, file name)
+    descriptors: HashMap<i32, (PathBuf, &'static str)>,
+}
+
+impl CgroupWatcher {
+    pub fn new() -> Result<Self> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd == -1 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        Ok(Self {
+            inotify: unsafe { OwnedFd::from_raw_fd(fd) },
+            cgroups: HashMap::new(),
+            descriptors: HashMap::new(),
+        })
+    }
+
+    /// Start watching a cgroup
+    ///
+    /// memory.events is optional (memory controller not enabled); at least
+    /// one of the two files must exist.
+    pub fn watch(&mut self, cgroup_path: &Path) -> Result<()> {
+        if self.cgroups.contains_key(cgroup_path) {
+            return Ok(());
+        }
+
+        let mut watched = Watched {
+            memory: MemoryController::get_events(cgroup_path).ok(),
+            state: FreezerController::get_events(cgroup_path).ok(),
+            descriptors: Vec::new(),
+        };
+
+        for file in [MEMORY_EVENTS, CGROUP_EVENTS] {
+            let path = cgroup_path.join(file);
+            if !path.exists() {
+                continue;
+            }
+            let wd = self.add_watch(&path)?;
+            watched.descriptors.push(wd);
+            self.descriptors
+                .insert(wd, (cgroup_path.to_path_buf(), file));
+        }
+
+        if watched.descriptors.is_empty() {
+            return Err(CgroupError::NotFound(format!(
+                "{:?} has neither {} nor {}",
+                cgroup_path, MEMORY_EVENTS, CGROUP_EVENTS
+            )));
+        }
+
+        self.cgroups.insert(cgroup_path.to_path_buf(), watched);
+        Ok(())
+    }
+
+    /// Stop watching a cgroup (no-op if it is not watched)
+    pub fn unwatch(&mut self, cgroup_path: &Path) {
+        let Some(watched) = self.cgroups.remove(cgroup_path) else {
+            return;
+        };
+
+        for wd in watched.descriptors {
+            self.descriptors.remove(&wd);
+            // Fails with EINVAL if the cgroup was already removed
+            unsafe { libc::inotify_rm_watch(self.inotify.as_raw_fd(), wd) };
+        }
+    }
+
+    /// Watch exactly `paths`: new cgroups are added, the rest dropped
+    ///
+    /// Returns the number of cgroups that could not be watched.
+    pub fn sync(&mut self, paths: &[PathBuf]) -> usize {
+        let stale: Vec<PathBuf> = self
+            .cgroups
+            .keys()
+            .filter(|path| !paths.contains(path))
+            .cloned()
+            .collect();
+        for path in stale {
+            self.unwatch(&path);
+        }
+
+        paths
+            .iter()
+            .filter(|path| self.watch(path).is_err())
+            .count()
+    }
+
+    /// Is the cgroup watched?
+    pub fn is_watched(&self, cgroup_path: &Path) -> bool {
+        self.cgroups.contains_key(cgroup_path)
+    }
+
+    /// Number of watched cgroups
+    pub fn watched_count(&self) -> usize {
+        self.cgroups.len()
+    }
+
+    /// Wait up to `timeout` for changes and return them
+    ///
+    /// `Duration::ZERO` only collects changes that already happened.
+    pub fn poll(&mut self, timeout: Duration) -> Result<Vec<CgroupEvent>> {
+        if !self.wait(timeout)? {
+            return Ok(Vec::new());
+        }
+
+        // Several writes of one file collapse into a single re-read
+        let mut changed: Vec<(PathBuf, &'static str)> = Vec::new();
+        for wd in self.read_descriptors()? {
+            if let Some(target) = self.descriptors.get(&wd) {
+                if !changed.contains(target) {
+                    changed.push(target.clone());
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        for (cgroup, file) in changed {
+            let Some(watched) = self.cgroups.get_mut(&cgroup) else {
+                continue;
+            };
+
+            let kinds = match file {
+                MEMORY_EVENTS => match MemoryController::get_events(&cgroup) {
+                    Ok(current) => {
+                        let previous = watched.memory.replace(current).unwrap_or_default();
+                        memory_changes(&previous, &current)
+                    }
+                    Err(_) => continue, // cgroup removed meanwhile
+                },
+                _ => match FreezerController::get_events(&cgroup) {
+                    Ok(current) => {
+                        let previous = watched.state.replace(current);
+                        state_changes(previous.as_ref(), &current)
+                    }
+                    Err(_) => continue,
+                },
+            };
+
+            events.extend(kinds.into_iter().map(|kind| CgroupEvent {
+                cgroup: cgroup.clone(),
+                kind,
+            }));
+        }
+
+        Ok(events)
+    }
+
+    fn add_watch(&self, path: &Path) -> Result<i32> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| CgroupError::ValidationError(format!("Invalid path: {:?}", path)))?;
+
+        let wd = unsafe {
+            libc::inotify_add_watch(self.inotify.as_raw_fd(), c_path.as_ptr(), libc::IN_MODIFY)
+        };
+        if wd == -1 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(wd)
+    }
+
+    /// Wait for the inotify descriptor to become readable
+    fn wait(&self, timeout: Duration) -> Result<bool> {
+        let mut pfd = libc::pollfd {
+            fd: self.inotify.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+
+        let ret = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+        match ret {
+            -1 => {
+                let err = std::io::Error::last_os_error();
+                if err.raw_os_error() == Some(libc::EINTR) {
+                    Ok(false)
+                } else {
+                    Err(err.into())
+                }
+            }
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Drain pending inotify events, returning their watch descriptors
+    fn read_descriptors(&mut self) -> Result<Vec<i32>> {
+        let header = std::mem::size_of::<libc::inotify_event>();
+        let mut buf = [0u8; 4096];
+        let mut descriptors = Vec::new();
+
+        loop {
+            let len = unsafe {
+                libc::read(
+                    self.inotify.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                )
+            };
+            if len == -1 {
+                let err = std::io::Error::last_os_error();
+                if err.kind() == std::io::ErrorKind::WouldBlock {
+                    break;
+                }
+                return Err(err.into());
+            }
+
+            let mut offset = 0;
+            while offset + header <= len as usize {
+                // Events are not aligned in the buffer
+                let event = unsafe {
+                    std::ptr::read_unaligned(buf.as_ptr().add(offset) as *const libc::inotify_event)
+                };
+                if event.mask & libc::IN_IGNORED != 0 {
+                    // Watch gone: file removed together with its cgroup
+                    self.descriptors.remove(&event.wd);
+                } else {
+                    descriptors.push(event.wd);
+                }
+                offset += header + event.len as usize;
+            }
+        }
+
+        Ok(descriptors)
+    }
+}
+
+/// Events for counters of memory.events that grew
+fn memory_changes(previous: &MemoryEvents, current: &MemoryEvents) -> Vec<CgroupEventKind> {
+    let grown = |before: u64, after: u64| after.checked_sub(before).filter(|&n| n > 0);
+
+    [
+        grown(previous.high, current.high).map(CgroupEventKind::MemoryHigh),
+        grown(previous.max, current.max).map(CgroupEventKind::MemoryMax),
+        grown(previous.oom, current.oom).map(CgroupEventKind::Oom),
+        grown(previous.oom_kill, current.oom_kill).map(CgroupEventKind::OomKill),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Events for flags of cgroup.events that flipped
+///
+/// Without a previous snapshot every flag is reported.
+fn state_changes(previous: Option<&CgroupEvents>, current: &CgroupEvents) -> Vec<CgroupEventKind> {
+    let mut kinds = Vec::new();
+    if previous.map_or(true, |p| p.populated != current.populated) {
+        kinds.push(CgroupEventKind::Populated(current.populated));
+    }
+    if previous.map_or(true, |p| p.frozen != current.frozen) {
+        kinds.push(CgroupEventKind::Frozen(current.frozen));
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_cgroup(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("freezr-events-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(MEMORY_EVENTS),
+            "low 0\nhigh 0\nmax 0\noom 0\noom_kill 0\n",
+        )
+        .unwrap();
+        fs::write(dir.join(CGROUP_EVENTS), "populated 1\nfrozen 0\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_memory_changes() {
+        let previous = MemoryEvents {
+            high: 5,
+            ..MemoryEvents::default()
+        };
+        let current = MemoryEvents {
+            high: 8,
+            oom: 1,
+            oom_kill: 2,
+            ..MemoryEvents::default()
+        };
+        assert_eq!(
+            memory_changes(&previous, &current),
+            vec![
+                CgroupEventKind::MemoryHigh(3),
+                CgroupEventKind::Oom(1),
+                CgroupEventKind::OomKill(2),
+            ]
+        );
+        assert!(memory_changes(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_state_changes() {
+        let running = CgroupEvents {
+            populated: true,
+            frozen: false,
+        };
+        let empty = CgroupEvents::default();
+        assert_eq!(
+            state_changes(Some(&running), &empty),
+            vec![CgroupEventKind::Populated(false)]
+        );
+        assert!(state_changes(Some(&running), &running).is_empty());
+        assert_eq!(state_changes(None, &empty).len(), 2);
+    }
+
+    #[test]
+    fn test_watch_emits_events() {
+        let dir = temp_cgroup("watch");
+        let mut watcher = CgroupWatcher::new().unwrap();
+        watcher.watch(&dir).unwrap();
+        assert!(watcher.poll(Duration::ZERO).unwrap().is_empty());
+
+        fs::write(
+            dir.join(MEMORY_EVENTS),
+            "low 0\nhigh 0\nmax 1\noom 1\noom_kill 1\n",
+        )
+        .unwrap();
+        fs::write(dir.join(CGROUP_EVENTS), "populated 0\nfrozen 0\n").unwrap();
+
+        let events = watcher.poll(Duration::from_secs(1)).unwrap();
+        let kinds: Vec<CgroupEventKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CgroupEventKind::MemoryMax(1),
+                CgroupEventKind::Oom(1),
+                CgroupEventKind::OomKill(1),
+                CgroupEventKind::Populated(false),
+            ]
+        );
+        assert!(events.iter().all(|e| e.cgroup == dir));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_watches() {
+        let kept = temp_cgroup("kept");
+        let dropped = temp_cgroup("dropped");
+        let missing = std::env::temp_dir().join("freezr-events-missing");
+
+        let mut watcher = CgroupWatcher::new().unwrap();
+        watcher.watch(&dropped).unwrap();
+        assert_eq!(watcher.sync(&[kept.clone(), missing]), 1);
+        assert!(watcher.is_watched(&kept));
+        assert!(!watcher.is_watched(&dropped));
+        assert_eq!(watcher.watched_count(), 1);
+
+        // Changes of unwatched cgroups are not reported
+        fs::write(dropped.join(CGROUP_EVENTS), "populated 0\nfrozen 0\n").unwrap();
+        assert!(watcher.poll(Duration::from_millis(50)).unwrap().is_empty());
+
+        fs::remove_dir_all(&kept).unwrap();
+        fs::remove_dir_all(&dropped).unwrap();
+    }
+}
@@ -0,0 +1,603 @@
+//! In-memory cgroup filesystem for tests
+//!
+//! [`FakeCgroupFs`] simulates what the kernel does behind the cgroupfs
+//! files FreezR uses, so `create_cgroup`, `apply_limits`, freezing,
+//! throttling and `on_service_stop` can be tested without root:
+//!
+//! - `mkdir` creates the control files (`cgroup.procs`, `cpu.max`,
+//!   `memory.*`, `pids.*`, `io.*`, `cpuset.*`, `cgroup.freeze`, ...)
+//! - writing a PID to `cgroup.procs` moves it out of every other cgroup;
+//!   processes cannot join a cgroup that has controllers enabled for its
+//!   children ("no internal processes")
+//! - `cgroup.events`, `cgroup.controllers`, `pids.current` and
+//!   `cpuset.cpus.effective` are computed from the tree
+//! - limit files reject malformed values, `rmdir` fails with `EBUSY` while
+//!   the cgroup has processes or children
+//!
+//! Counters the kernel maintains (`memory.current`, `cpu.stat`, ...) stay
+//! at zero unless a test sets them with [`FakeCgroupFs::set`].
+//!
+//! # Examples
+//! ```
+//! use freezr_core::cgroups::{CgroupConfig, CgroupManager, FakeCgroupFs};
+//!
+//! let fake = FakeCgroupFs::new("/fake/doc-example");
+//! let _mount = fake.mount();
+//!
+//! let config = CgroupConfig {
+//!     enabled: true,
+//!     cgroupfs_root: fake.root().to_path_buf(),
+//!     root_path: fake.root().join("freezr.slice"),
+//!     ..CgroupConfig::default()
+//! };
+//! let mut manager = CgroupManager::new(config).unwrap();
+//! manager.initialize().unwrap();
+//! let cgroup = manager.create_cgroup("throttle-1").unwrap();
+//! assert!(cgroup.exists());
+//! assert!(fake.procs(&cgroup.path).is_empty());
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::cgroupfs::{self, CgroupFs, CgroupFsMount};
+
+/// Controllers available at the root of the fake tree
+const CONTROLLERS: &[&str] = &["cpuset", "cpu", "io", "memory", "pids"];
+
+/// CPUs of the fake machine
+const ONLINE_CPUS: &str = "0-3";
+
+/// Files computed from the tree on every read
+const COMPUTED: &[&str] = &[
+    "cgroup.events",
+    "cgroup.controllers",
+    "pids.current",
+    "cpuset.cpus.effective",
+];
+
+/// PSI file content of a cgroup nothing stalls in
+const NO_PRESSURE: &str =
+    "some avg10=0.00 avg60=0.00 avg300=0.00 total=0\nfull avg10=0.00 avg60=0.00 avg300=0.00 total=0\n";
+
+/// Control files of a new (non-root) cgroup and their initial content
+const CGROUP_FILES: &[(&str, &str)] = &[
+    ("cgroup.procs", ""),
+    ("cgroup.freeze", "0\n"),
+    ("cgroup.subtree_control", "\n"),
+    ("cgroup.type", "domain\n"),
+    ("cpu.max", "max 100000\n"),
+    ("cpu.weight", "100\n"),
+    (
+        "cpu.stat",
+        "usage_usec 0\nuser_usec 0\nsystem_usec 0\nnr_periods 0\nnr_throttled 0\nthrottled_usec 0\n",
+    ),
+    ("memory.current", "0\n"),
+    ("memory.peak", "0\n"),
+    ("memory.min", "0\n"),
+    ("memory.low", "0\n"),
+    ("memory.high", "max\n"),
+    ("memory.max", "max\n"),
+    ("memory.stat", "anon 0\nfile 0\nkernel_stack 0\nslab 0\n"),
+    (
+        "memory.events",
+        "low 0\nhigh 0\nmax 0\noom 0\noom_kill 0\noom_group_kill 0\n",
+    ),
+    ("memory.pressure", NO_PRESSURE),
+    ("pids.max", "max\n"),
+    ("pids.events", "max 0\n"),
+    ("io.max", ""),
+    ("io.weight", "default 100\n"),
+    ("io.stat", ""),
+    ("cpuset.cpus", "\n"),
+    ("cpuset.mems", "\n"),
+    ("cpu.pressure", NO_PRESSURE),
+    ("io.pressure", NO_PRESSURE),
+];
+
+/// Files of one cgroup (name -> content)
+type Files = BTreeMap<String, String>;
+
+/// In-memory cgroup v2 tree, see the module docs
+pub struct FakeCgroupFs {
+    root: PathBuf,
+    /// Cgroup directories (path -> files)
+    cgroups: Mutex<BTreeMap<PathBuf, Files>>,
+}
+
+impl FakeCgroupFs {
+    /// Empty tree with only the root cgroup at `root`
+    ///
+    /// All controllers are enabled for the children of the root, as systemd
+    /// does on boot.
+    pub fn new(root: impl Into<PathBuf>) -> Arc<Self> {
+        let root = root.into();
+        let files = Files::from([
+            ("cgroup.procs".to_string(), String::new()),
+            (
+                "cgroup.subtree_control".to_string(),
+                format!("{}\n", CONTROLLERS.join(" ")),
+            ),
+            ("cgroup.controllers".to_string(), String::new()),
+            ("cgroup.events".to_string(), String::new()),
+            ("cpuset.cpus.effective".to_string(), String::new()),
+            ("cpu.stat".to_string(), CGROUP_FILES[6].1.to_string()),
+            ("memory.stat".to_string(), CGROUP_FILES[13].1.to_string()),
+            ("cpu.pressure".to_string(), NO_PRESSURE.to_string()),
+            ("io.pressure".to_string(), NO_PRESSURE.to_string()),
+            ("memory.pressure".to_string(), NO_PRESSURE.to_string()),
+        ]);
+
+        Arc::new(Self {
+            cgroups: Mutex::new(BTreeMap::from([(root.clone(), files)])),
+            root,
+        })
+    }
+
+    /// Serve paths under the root from this tree until the guard is dropped
+    pub fn mount(self: &Arc<Self>) -> CgroupFsMount {
+        cgroupfs::mount(self.root.clone(), self.clone())
+    }
+
+    /// Mount point (plays the role of /sys/fs/cgroup)
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Set a file the way the kernel would (e.g. `memory.current`)
+    ///
+    /// Unlike [`CgroupFs::write`] the content is not checked.
+    pub fn set(&self, path: &Path, content: &str) -> io::Result<()> {
+        let (dir, name) = split(path)?;
+        let mut cgroups = self.lock();
+        let files = cgroups.get_mut(dir).ok_or_else(|| not_found(path))?;
+        files.insert(name.to_string(), content.to_string());
+        Ok(())
+    }
+
+    /// Processes directly in a cgroup
+    pub fn procs(&self, cgroup: &Path) -> Vec<u32> {
+        self.lock().get(cgroup).map(procs_of).unwrap_or_default()
+    }
+
+    /// Existing cgroup directories, sorted
+    pub fn cgroups(&self) -> Vec<PathBuf> {
+        self.lock().keys().cloned().collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Files>> {
+        self.cgroups.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+impl CgroupFs for FakeCgroupFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let (dir, name) = split(path)?;
+        let cgroups = self.lock();
+        let files = cgroups.get(dir).ok_or_else(|| not_found(path))?;
+        let content = files.get(name).ok_or_else(|| not_found(path))?;
+
+        if !COMPUTED.contains(&name) {
+            return Ok(content.clone());
+        }
+
+        let subtree = || {
+            cgroups
+                .iter()
+                .filter(move |(p, _)| p.starts_with(dir))
+                .map(|(_, files)| files)
+        };
+        Ok(match name {
+            "cgroup.events" => {
+                let populated = subtree().any(|files| !procs_of(files).is_empty());
+                let frozen = dir
+                    .ancestors()
+                    .filter_map(|p| cgroups.get(p))
+                    .any(|files| files.get("cgroup.freeze").map(|f| f.trim()) == Some("1"));
+                format!("populated {}\nfrozen {}\n", populated as u8, frozen as u8)
+            }
+            "cgroup.controllers" => match dir.parent().and_then(|p| cgroups.get(p)) {
+                Some(parent) if dir != self.root => parent["cgroup.subtree_control"].clone(),
+                _ => format!("{}\n", CONTROLLERS.join(" ")),
+            },
+            "pids.current" => {
+                let tasks: usize = subtree().map(|files| procs_of(files).len()).sum();
+                format!("{}\n", tasks)
+            }
+            // cpuset.cpus.effective: own list or the nearest ancestor's
+            _ => dir
+                .ancestors()
+                .filter_map(|p| cgroups.get(p))
+                .filter_map(|files| files.get("cpuset.cpus"))
+                .find(|cpus| !cpus.trim().is_empty())
+                .map_or_else(|| format!("{}\n", ONLINE_CPUS), |cpus| cpus.clone()),
+        })
+    }
+
+    fn write(&self, path: &Path, content: &str) -> io::Result<()> {
+        let (dir, name) = split(path)?;
+        let mut cgroups = self.lock();
+        let files = cgroups.get(dir).ok_or_else(|| not_found(path))?;
+        let current = files.get(name).ok_or_else(|| not_found(path))?;
+        if COMPUTED.contains(&name) {
+            return Err(io::Error::from_raw_os_error(libc::EACCES));
+        }
+
+        let value = content.trim();
+        let stored = match name {
+            "cgroup.procs" => {
+                let pid: u32 = value.parse().map_err(|_| invalid(path, value))?;
+                let internal =
+                    dir != self.root && !files["cgroup.subtree_control"].trim().is_empty();
+                if internal {
+                    return Err(io::Error::from_raw_os_error(libc::EBUSY));
+                }
+
+                for files in cgroups.values_mut() {
+                    let procs = procs_of(files);
+                    if procs.contains(&pid) {
+                        files.insert("cgroup.procs".to_string(), join_procs(&procs, pid, None));
+                    }
+                }
+                let files = cgroups.get_mut(dir).expect("checked above");
+                let procs = procs_of(files);
+                files.insert(name.to_string(), join_procs(&procs, pid, Some(pid)));
+                return Ok(());
+            }
+            "cgroup.freeze" => match value {
+                "0" | "1" => format!("{}\n", value),
+                _ => return Err(invalid(path, value)),
+            },
+            "cgroup.subtree_control" => {
+                let available: Vec<String> = match dir.parent().and_then(|p| cgroups.get(p)) {
+                    Some(parent) if dir != self.root => parent["cgroup.subtree_control"]
+                        .split_whitespace()
+                        .map(str::to_string)
+                        .collect(),
+                    _ => CONTROLLERS.iter().map(|c| c.to_string()).collect(),
+                };
+                let mut enabled: BTreeSet<&str> = current.split_whitespace().collect();
+                for token in value.split_whitespace() {
+                    let (op, controller) = token.split_at(1);
+                    if !available.iter().any(|c| c == controller) {
+                        return Err(invalid(path, token));
+                    }
+                    match op {
+                        "+" => enabled.insert(controller),
+                        "-" => enabled.remove(controller),
+                        _ => return Err(invalid(path, token)),
+                    };
+                }
+                // Kernel order, not alphabetical
+                let ordered: Vec<&str> = CONTROLLERS
+                    .iter()
+                    .copied()
+                    .filter(|c| enabled.contains(c))
+                    .collect();
+                format!("{}\n", ordered.join(" "))
+            }
+            "cpu.max" => {
+                let mut parts = value.split_whitespace();
+                let quota = parts.next().ok_or_else(|| invalid(path, value))?;
+                let period = match parts.next() {
+                    Some(period) => period.to_string(),
+                    None => current
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap_or("100000")
+                        .to_string(),
+                };
+                let valid_quota = quota == "max" || quota.parse::<u64>().is_ok_and(|q| q > 0);
+                if !valid_quota || period.parse::<u64>().is_err() || parts.next().is_some() {
+                    return Err(invalid(path, value));
+                }
+                format!("{} {}\n", quota, period)
+            }
+            "cpu.weight" => match value.parse::<u32>() {
+                Ok(weight) if (1..=10000).contains(&weight) => format!("{}\n", weight),
+                _ => return Err(invalid(path, value)),
+            },
+            "memory.max" | "memory.high" | "memory.low" | "memory.min" | "pids.max" => {
+                if value != "max" && value.parse::<u64>().is_err() {
+                    return Err(invalid(path, value));
+                }
+                format!("{}\n", value)
+            }
+            "io.max" => merge_io_max(current, value).ok_or_else(|| invalid(path, value))?,
+            "io.weight" => {
+                let weight = value.strip_prefix("default ").unwrap_or(value);
+                match weight.trim().parse::<u32>() {
+                    Ok(weight) if (1..=10000).contains(&weight) => {
+                        format!("default {}\n", weight)
+                    }
+                    _ => return Err(invalid(path, value)),
+                }
+            }
+            _ => format!("{}\n", value),
+        };
+
+        cgroups
+            .get_mut(dir)
+            .expect("checked above")
+            .insert(name.to_string(), stored);
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let mut cgroups = self.lock();
+        if cgroups.contains_key(path) {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+        let parent = path.parent().ok_or_else(|| not_found(path))?;
+        if !cgroups.contains_key(parent) {
+            return Err(not_found(path));
+        }
+
+        let mut files: Files = CGROUP_FILES
+            .iter()
+            .map(|(name, content)| (name.to_string(), content.to_string()))
+            .collect();
+        for name in COMPUTED {
+            files.insert(name.to_string(), String::new());
+        }
+        cgroups.insert(path.to_path_buf(), files);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let mut cgroups = self.lock();
+        let files = cgroups.get(path).ok_or_else(|| not_found(path))?;
+
+        let has_children = cgroups.keys().any(|p| p != path && p.starts_with(path));
+        if path == self.root || has_children || !procs_of(files).is_empty() {
+            return Err(io::Error::from_raw_os_error(libc::EBUSY));
+        }
+
+        cgroups.remove(path);
+        Ok(())
+    }
+
+    fn subdirs(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let cgroups = self.lock();
+        if !cgroups.contains_key(path) {
+            return Err(not_found(path));
+        }
+        Ok(cgroups
+            .keys()
+            .filter(|p| p.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let cgroups = self.lock();
+        if cgroups.contains_key(path) {
+            return true;
+        }
+        split(path).is_ok_and(|(dir, name)| cgroups.get(dir).is_some_and(|f| f.contains_key(name)))
+    }
+}
+
+/// Directory and file name of a path
+fn split(path: &Path) -> io::Result<(&Path, &str)> {
+    match (path.parent(), path.file_name().and_then(|n| n.to_str())) {
+        (Some(dir), Some(name)) => Ok((dir, name)),
+        _ => Err(not_found(path)),
+    }
+}
+
+fn procs_of(files: &Files) -> Vec<u32> {
+    files
+        .get("cgroup.procs")
+        .map(|procs| {
+            procs
+                .lines()
+                .filter_map(|l| l.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// cgroup.procs content without `removed`, plus `added`
+fn join_procs(procs: &[u32], removed: u32, added: Option<u32>) -> String {
+    procs
+        .iter()
+        .copied()
+        .filter(|&p| p != removed)
+        .chain(added)
+        .map(|p| format!("{}\n", p))
+        .collect()
+}
+
+/// Apply one io.max line to the current content
+///
+/// Keys not given keep their value; a device with all keys at "max" is
+/// dropped, like the kernel does.
+fn merge_io_max(current: &str, line: &str) -> Option<String> {
+    const KEYS: [&str; 4] = ["rbps", "wbps", "riops", "wiops"];
+
+    let mut parts = line.split_whitespace();
+    let device = parts.next()?;
+    let (major, minor) = device.split_once(':')?;
+    major.parse::<u32>().ok()?;
+    minor.parse::<u32>().ok()?;
+
+    let mut devices: BTreeMap<String, [String; 4]> = BTreeMap::new();
+    for existing in current.lines() {
+        let mut fields = existing.split_whitespace();
+        let Some(dev) = fields.next() else {
+            continue;
+        };
+        let mut values: [String; 4] = Default::default();
+        for (slot, field) in values.iter_mut().zip(fields) {
+            *slot = field.split_once('=').map_or("max", |(_, v)| v).to_string();
+        }
+        devices.insert(dev.to_string(), values);
+    }
+
+    let values = devices
+        .entry(device.to_string())
+        .or_insert_with(|| ["max", "max", "max", "max"].map(String::from));
+    for part in parts {
+        let (key, value) = part.split_once('=')?;
+        let slot = KEYS.iter().position(|k| *k == key)?;
+        if value != "max" {
+            value.parse::<u64>().ok()?;
+        }
+        values[slot] = value.to_string();
+    }
+
+    Some(
+        devices
+            .iter()
+            .filter(|(_, values)| values.iter().any(|v| v != "max"))
+            .map(|(dev, values)| {
+                let fields: Vec<String> = KEYS
+                    .iter()
+                    .zip(values)
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect();
+                format!("{} {}\n", dev, fields.join(" "))
+            })
+            .collect(),
+    )
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{:?} not found", path))
+}
+
+fn invalid(path: &Path, value: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{:?} rejects {:?}", path, value),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgroups::{CpuController, FreezerController, MemoryController, PidsController};
+
+    fn fake(name: &str) -> (Arc<FakeCgroupFs>, CgroupFsMount) {
+        let fake = FakeCgroupFs::new(format!("/fake/unit-{}", name));
+        let mount = fake.mount();
+        (fake, mount)
+    }
+
+    #[test]
+    fn test_mkdir_creates_control_files() {
+        let (fake, _mount) = fake("mkdir");
+        let group = fake.root().join("group");
+        cgroupfs::create_dir(&group).unwrap();
+
+        assert!(cgroupfs::exists(&group.join("cpu.max")));
+        assert!(cgroupfs::exists(&group.join("cgroup.events")));
+        assert_eq!(
+            cgroupfs::create_dir(&group).unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert!(cgroupfs::create_dir(&fake.root().join("missing/child")).is_err());
+        assert_eq!(CpuController::get_quota(&group).unwrap(), None);
+    }
+
+    #[test]
+    fn test_limits_are_checked() {
+        let (fake, _mount) = fake("limits");
+        let group = fake.root().join("group");
+        cgroupfs::create_dir(&group).unwrap();
+
+        CpuController::set_quota(&group, 30.0).unwrap();
+        assert_eq!(
+            cgroupfs::read_to_string(&group.join("cpu.max")).unwrap(),
+            "30000 100000\n"
+        );
+        MemoryController::set_max(&group, 512 * 1024 * 1024).unwrap();
+        assert_eq!(
+            MemoryController::get_max(&group).unwrap(),
+            Some(512 * 1024 * 1024)
+        );
+
+        assert!(cgroupfs::write(&group.join("memory.max"), "lots").is_err());
+        assert!(cgroupfs::write(&group.join("cpu.max"), "0 100000").is_err());
+        assert!(cgroupfs::write(&group.join("no.such.file"), "1").is_err());
+    }
+
+    #[test]
+    fn test_procs_move_between_cgroups() {
+        let (fake, _mount) = fake("procs");
+        let a = fake.root().join("a");
+        let b = fake.root().join("b");
+        cgroupfs::create_dir(&a).unwrap();
+        cgroupfs::create_dir(&b).unwrap();
+
+        cgroupfs::write(&a.join("cgroup.procs"), "100").unwrap();
+        cgroupfs::write(&a.join("cgroup.procs"), "200").unwrap();
+        cgroupfs::write(&b.join("cgroup.procs"), "100").unwrap();
+        assert_eq!(fake.procs(&a), vec![200]);
+        assert_eq!(fake.procs(&b), vec![100]);
+        assert_eq!(PidsController::get_current(&a).unwrap(), 1);
+
+        assert!(FreezerController::get_events(&a).unwrap().populated);
+        let err = cgroupfs::remove_dir(&a).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+
+        cgroupfs::write(&fake.root().join("cgroup.procs"), "200").unwrap();
+        assert!(!FreezerController::get_events(&a).unwrap().populated);
+        assert_eq!(cgroupfs::subdirs(fake.root()).unwrap(), vec![a.clone(), b]);
+        cgroupfs::remove_dir(&a).unwrap();
+        assert!(!cgroupfs::exists(&a));
+    }
+
+    #[test]
+    fn test_no_internal_processes() {
+        let (fake, _mount) = fake("internal");
+        let parent = fake.root().join("parent");
+        cgroupfs::create_dir(&parent).unwrap();
+        cgroupfs::create_dir(&parent.join("child")).unwrap();
+
+        // Only controllers enabled at the parent's parent are available
+        cgroupfs::write(&fake.root().join("cgroup.subtree_control"), "-io -pids").unwrap();
+        assert!(cgroupfs::write(&parent.join("cgroup.subtree_control"), "+io").is_err());
+        assert_eq!(
+            cgroupfs::read_to_string(&parent.join("cgroup.controllers")).unwrap(),
+            "cpuset cpu memory\n"
+        );
+        cgroupfs::write(&parent.join("cgroup.subtree_control"), "+cpu").unwrap();
+
+        let err = cgroupfs::write(&parent.join("cgroup.procs"), "100").unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+    }
+
+    #[test]
+    fn test_freeze_is_inherited() {
+        let (fake, _mount) = fake("freeze");
+        let parent = fake.root().join("parent");
+        let child = parent.join("child");
+        cgroupfs::create_dir(&parent).unwrap();
+        cgroupfs::create_dir(&child).unwrap();
+
+        FreezerController::freeze(&parent).unwrap();
+        assert!(FreezerController::is_frozen(&child).unwrap());
+        FreezerController::thaw(&parent).unwrap();
+        assert!(!FreezerController::is_frozen(&child).unwrap());
+        assert!(FreezerController::is_supported(&child));
+    }
+
+    #[test]
+    fn test_merge_io_max() {
+        let merged = merge_io_max("", "8:0 wbps=1048576").unwrap();
+        assert_eq!(merged, "8:0 rbps=max wbps=1048576 riops=max wiops=max\n");
+
+        let merged = merge_io_max(&merged, "8:0 riops=100").unwrap();
+        assert_eq!(merged, "8:0 rbps=max wbps=1048576 riops=100 wiops=max\n");
+
+        let cleared = merge_io_max(&merged, "8:0 wbps=max riops=max").unwrap();
+        assert_eq!(cleared, "");
+
+        assert!(merge_io_max("", "sda wbps=1").is_none());
+        assert!(merge_io_max("", "8:0 speed=1").is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! Persistent journal of original cgroups
+//!
+//! The cgroups under freezr.slice outlive the daemon, but the original
+//! cgroup of each moved process only lives in [`CgroupManager`] memory.
+//! Every origin is therefore also written to a state file, keyed by PID and
+//! start time. After a crash the next start adopts the leftover cgroups and
+//! uses the journal to send their processes back where they came from.
+//!
+//! [`CgroupManager`]: super::CgroupManager
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::error::{CgroupError, Result};
+use super::utils::read_start_ticks;
+
+/// Default location of the journal (tmpfs, cleared on reboot with the cgroups)
+pub const DEFAULT_JOURNAL_PATH: &str = "/run/freezr/cgroups.json";
+
+/// Original cgroup of one process moved by FreezR
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OriginEntry {
+    pub pid: u32,
+    /// Start time from /proc/[pid]/stat (detects PID reuse)
+    pub start_ticks: u64,
+    /// Cgroup path from /proc/[pid]/cgroup before the first move
+    pub origin: String,
+}
+
+/// State file with the original cgroups of moved processes
+#[derive(Debug, Clone)]
+pub struct OriginJournal {
+    path: PathBuf,
+}
+
+impl OriginJournal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read all journaled entries (missing file = no entries)
+    pub fn load(&self) -> Result<Vec<OriginEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        serde_json::from_str(&content).map_err(|e| {
+            CgroupError::ParseError(format!(
+                "Invalid cgroup journal {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Journaled entries of processes that are still alive
+    ///
+    /// Entries of exited processes (or of PIDs reused by another process)
+    /// are skipped.
+    pub fn load_alive(&self) -> Result<Vec<OriginEntry>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .filter(|entry| read_start_ticks(entry.pid) == Some(entry.start_ticks))
+            .collect())
+    }
+
+    /// Replace the journal with `entries`
+    ///
+    /// The start time of each entry is the one recorded when the process
+    /// was moved. Written to a temporary file and renamed, so a crash never
+    /// leaves a truncated journal behind.
+    pub fn save(&self, entries: &[OriginEntry]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let json = serde_json::to_string_pretty(entries).map_err(|e| {
+            CgroupError::ParseError(format!("Failed to serialize cgroup journal: {}", e))
+        })?;
+
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn temp_journal(name: &str) -> OriginJournal {
+        let dir =
+            std::env::temp_dir().join(format!("freezr-journal-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        OriginJournal::new(dir.join("cgroups.json"))
+    }
+
+    fn entry(pid: u32, start_ticks: u64) -> OriginEntry {
+        OriginEntry {
+            pid,
+            start_ticks,
+            origin: "/user.slice/shell".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let journal = temp_journal("save");
+        assert!(journal.load().unwrap().is_empty());
+
+        let own = std::process::id();
+        let entries = vec![entry(own, read_start_ticks(own).unwrap())];
+        journal.save(&entries).unwrap();
+
+        assert_eq!(journal.load().unwrap(), entries);
+        assert_eq!(journal.load_alive().unwrap(), entries);
+
+        let _ = fs::remove_dir_all(journal.path().parent().unwrap());
+    }
+
+    #[test]
+    fn test_exited_and_reused_pids_are_dropped() {
+        let journal = temp_journal("exited");
+        let mut child = Command::new("true").stdout(Stdio::null()).spawn().unwrap();
+        let exited = child.id();
+        let exited_ticks = read_start_ticks(exited).unwrap_or_default();
+        child.wait().unwrap();
+
+        // Same PID, other start time: another process
+        let own = std::process::id();
+        let stale = vec![
+            entry(exited, exited_ticks),
+            entry(own, read_start_ticks(own).unwrap() + 1),
+        ];
+        journal.save(&stale).unwrap();
+
+        assert_eq!(journal.load().unwrap().len(), 2);
+        assert!(journal.load_alive().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(journal.path().parent().unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_file_is_error() {
+        let journal = temp_journal("corrupt");
+        fs::create_dir_all(journal.path().parent().unwrap()).unwrap();
+        fs::write(journal.path(), "{ not json").unwrap();
+
+        assert!(journal.load().is_err());
+
+        let _ = fs::remove_dir_all(journal.path().parent().unwrap());
+    }
+}
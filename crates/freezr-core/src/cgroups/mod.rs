@@ -1,24 +1,50 @@
 //! Cgroup v2 integration module
 //!
 //! Provides high-level API for managing Linux cgroup v2 resources.
-//! Supports CPU and memory limits with systemd integration.
+//! Supports CPU, memory, I/O and task limits with systemd integration, and
+//! watches memory.events/cgroup.events of managed cgroups via inotify.
+//! All filesystem access goes through [`cgroupfs`], so with the
+//! `fake-cgroupfs` feature the subsystem can run against an in-memory tree
+//! (`FakeCgroupFs`) in tests.
 //!
 //! # Safety
 //! - Only works when systemd service is active
 //! - Automatically cleans up on service stop
 //! - Restores all processes to original cgroups on shutdown
+//! - Journals original cgroups, so cgroups left by a crash are adopted and
+//!   their processes restored on the next start
 
+pub mod adaptive;
+pub mod cgroupfs;
 pub mod controller;
 pub mod error;
+pub mod events;
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+pub mod fake;
+pub mod journal;
 pub mod types;
 mod utils;
 
-pub use controller::{CpuController, CpuStats, MemoryController, MemoryPressure, MemoryStats};
+pub use adaptive::AdaptiveSettings;
+pub use cgroupfs::CgroupFs;
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+pub use cgroupfs::CgroupFsMount;
+pub use controller::{
+    CgroupEvents, CpuController, CpuList, CpuStats, CpusetController, DeviceNumber,
+    FreezerController, IoController, IoMax, IoStat, MemoryController, MemoryEvents, MemoryStats,
+    PidsController, PidsEvents,
+};
 pub use error::{CgroupError, Result};
+pub use events::{CgroupEvent, CgroupEventKind, CgroupWatcher};
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+pub use fake::FakeCgroupFs;
+pub use journal::{OriginEntry, OriginJournal};
 pub use types::{
     Cgroup, CgroupConfig, CgroupManager, CgroupStrategy, CgroupType, DynamicCgroupSettings,
-    HealthStatus, ResourceLimits, StaticCgroupConfig,
+    FrozenTree, HealthReport, HealthStatus, IoLimit, ResourceLimits, StaticCgroupConfig,
+    ThrottledTree,
 };
+pub use utils::{list_processes, ProcEntry};
 
 #[cfg(test)]
 mod tests {
@@ -2,9 +2,20 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
-
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::adaptive::AdaptiveSettings;
+use super::cgroupfs;
+use super::controller::{
+    CpuList, CpusetController, FreezerController, IoController, IoMax, PidsController,
+};
 use super::error::{CgroupError, Result};
+use super::journal::{OriginEntry, OriginJournal, DEFAULT_JOURNAL_PATH};
+use super::utils::{
+    descendants, get_process_cgroup, list_processes, process_exists, process_tree, read_ppid,
+    read_start_ticks,
+};
 
 /// Cgroup management strategy
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -38,6 +49,115 @@ pub struct ResourceLimits {
 
     /// Soft memory limit (bytes)
     pub memory_high: Option<u64>,
+
+    /// Per-device I/O bandwidth/IOPS limits (io.max)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub io_limits: Vec<IoLimit>,
+
+    /// I/O weight (1-10000, default 100)
+    #[serde(default)]
+    pub io_weight: Option<u32>,
+
+    /// Maximum number of tasks, processes and threads (pids.max)
+    #[serde(default)]
+    pub pids_max: Option<u64>,
+
+    /// CPUs the cgroup may run on, kernel list format: "6-7" (cpuset.cpus)
+    #[serde(default)]
+    pub cpuset_cpus: Option<String>,
+
+    /// Memory nodes the cgroup may allocate from: "0" (cpuset.mems)
+    #[serde(default)]
+    pub cpuset_mems: Option<String>,
+}
+
+/// I/O limits of one block device
+///
+/// ```toml
+/// # Cap writes to the disk holding /home at 20 MB/s
+/// [[io_limits]]
+/// device = "/home"
+/// write_bps = 20971520
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IoLimit {
+    /// Mount point (or any path on it), device node, or `MAJ:MIN`
+    pub device: String,
+
+    /// Read bytes per second
+    pub read_bps: Option<u64>,
+
+    /// Write bytes per second
+    pub write_bps: Option<u64>,
+
+    /// Read operations per second
+    pub read_iops: Option<u64>,
+
+    /// Write operations per second
+    pub write_iops: Option<u64>,
+}
+
+impl IoLimit {
+    /// No limits yet on `device`
+    pub fn new(device: impl Into<String>) -> Self {
+        Self {
+            device: device.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_read_bps(mut self, bytes: u64) -> Self {
+        self.read_bps = Some(bytes);
+        self
+    }
+
+    pub fn with_write_bps(mut self, bytes: u64) -> Self {
+        self.write_bps = Some(bytes);
+        self
+    }
+
+    /// Resolve the device and build the io.max entry
+    pub fn resolve(&self) -> Result<IoMax> {
+        let device = IoController::resolve_device(&self.device)?;
+
+        Ok(IoMax {
+            device,
+            rbps: self.read_bps,
+            wbps: self.write_bps,
+            riops: self.read_iops,
+            wiops: self.write_iops,
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.device.is_empty() {
+            return Err(CgroupError::InvalidLimit(
+                "I/O limit needs a device".to_string(),
+            ));
+        }
+
+        let values = [
+            self.read_bps,
+            self.write_bps,
+            self.read_iops,
+            self.write_iops,
+        ];
+        if values.iter().all(Option::is_none) {
+            return Err(CgroupError::InvalidLimit(format!(
+                "I/O limit for {} sets no limit",
+                self.device
+            )));
+        }
+        if values.contains(&Some(0)) {
+            return Err(CgroupError::InvalidLimit(format!(
+                "I/O limit for {} cannot be 0",
+                self.device
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 impl ResourceLimits {
@@ -60,6 +180,49 @@ impl ResourceLimits {
         self
     }
 
+    pub fn with_io_limit(mut self, limit: IoLimit) -> Self {
+        self.io_limits.push(limit);
+        self
+    }
+
+    pub fn with_io_weight(mut self, weight: u32) -> Self {
+        self.io_weight = Some(weight);
+        self
+    }
+
+    pub fn with_pids_max(mut self, max: u64) -> Self {
+        self.pids_max = Some(max);
+        self
+    }
+
+    pub fn with_cpuset_cpus(mut self, cpus: impl Into<String>) -> Self {
+        self.cpuset_cpus = Some(cpus.into());
+        self
+    }
+
+    pub fn with_cpuset_mems(mut self, mems: impl Into<String>) -> Self {
+        self.cpuset_mems = Some(mems.into());
+        self
+    }
+
+    /// These limits, with every limit set in `other` taking precedence
+    pub fn overlay(self, other: ResourceLimits) -> Self {
+        Self {
+            cpu_limit_percent: other.cpu_limit_percent.or(self.cpu_limit_percent),
+            memory_max: other.memory_max.or(self.memory_max),
+            memory_high: other.memory_high.or(self.memory_high),
+            io_limits: if other.io_limits.is_empty() {
+                self.io_limits
+            } else {
+                other.io_limits
+            },
+            io_weight: other.io_weight.or(self.io_weight),
+            pids_max: other.pids_max.or(self.pids_max),
+            cpuset_cpus: other.cpuset_cpus.or(self.cpuset_cpus),
+            cpuset_mems: other.cpuset_mems.or(self.cpuset_mems),
+        }
+    }
+
     /// Validate limits are reasonable
     pub fn validate(&self) -> Result<()> {
         if let Some(cpu) = self.cpu_limit_percent {
@@ -87,10 +250,64 @@ impl ResourceLimits {
             }
         }
 
+        if let Some(weight) = self.io_weight {
+            if !(1..=10000).contains(&weight) {
+                return Err(CgroupError::InvalidLimit(format!(
+                    "I/O weight must be 1-10000, got {}",
+                    weight
+                )));
+            }
+        }
+
+        for limit in &self.io_limits {
+            limit.validate()?;
+        }
+
+        if self.pids_max == Some(0) {
+            return Err(CgroupError::InvalidLimit(
+                "pids max limit cannot be 0".to_string(),
+            ));
+        }
+
+        if let Some(cpus) = &self.cpuset_cpus {
+            validate_cpuset("CPUs", cpus, CpusetController::online_cpus())?;
+        }
+
+        if let Some(mems) = &self.cpuset_mems {
+            validate_cpuset("memory nodes", mems, CpusetController::online_mems())?;
+        }
+
         Ok(())
     }
 }
 
+/// Check a cpuset list: valid syntax, not empty, every ID online
+///
+/// The online check is skipped if the online list cannot be read.
+fn validate_cpuset(kind: &str, list: &str, online: Result<CpuList>) -> Result<()> {
+    let ids: CpuList = list.parse().map_err(|_| {
+        CgroupError::InvalidLimit(format!("Invalid cpuset {} list: {:?}", kind, list))
+    })?;
+    if ids.is_empty() {
+        return Err(CgroupError::InvalidLimit(format!(
+            "Cpuset {} list cannot be empty",
+            kind
+        )));
+    }
+
+    if let Ok(online) = online {
+        let offline = ids.missing_from(&online);
+        if !offline.is_empty() {
+            return Err(CgroupError::InvalidLimit(format!(
+                "Cpuset {} {} not online (online: {})",
+                kind, offline, online
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Configuration for static cgroup
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StaticCgroupConfig {
@@ -98,6 +315,7 @@ pub struct StaticCgroupConfig {
     pub name: String,
 
     /// Process name patterns to assign to this cgroup
+    /// (substring of the process name, descendants follow)
     pub process_patterns: Vec<String>,
 
     /// Resource limits
@@ -118,6 +336,13 @@ pub struct StaticCgroupConfig {
 }
 
 impl StaticCgroupConfig {
+    /// Does a process with this name belong to the group?
+    pub fn matches(&self, process_name: &str) -> bool {
+        self.process_patterns
+            .iter()
+            .any(|pattern| !pattern.is_empty() && process_name.contains(pattern.as_str()))
+    }
+
     /// Get resource limits (handles TOML convenience fields)
     pub fn get_limits(&self) -> ResourceLimits {
         let mut limits = self.limits.clone();
@@ -151,6 +376,36 @@ pub struct DynamicCgroupSettings {
 
     /// Default memory limit for dynamic cgroups (MB)
     pub default_memory_limit_mb: u64,
+
+    /// Release a throttled process after it behaved this long (seconds)
+    #[serde(default = "default_release_cooldown_secs")]
+    pub release_cooldown_secs: u64,
+
+    /// When the limit is reached, evict the least recently used dynamic
+    /// cgroup instead of refusing to create a new one (frozen cgroups are
+    /// never evicted)
+    #[serde(default)]
+    pub evict_when_full: bool,
+
+    /// Feedback control of throttle cgroup limits (off by default)
+    #[serde(default)]
+    pub adaptive: AdaptiveSettings,
+}
+
+fn default_release_cooldown_secs() -> u64 {
+    60
+}
+
+impl DynamicCgroupSettings {
+    /// Limits for a throttle cgroup
+    ///
+    /// Memory goes to memory.high, not memory.max: above it the kernel
+    /// reclaims and slows the process down instead of OOM-killing it.
+    pub fn default_limits(&self) -> ResourceLimits {
+        ResourceLimits::new()
+            .with_cpu_limit(self.default_cpu_limit)
+            .with_memory_high(self.default_memory_limit_mb * 1024 * 1024)
+    }
 }
 
 impl Default for DynamicCgroupSettings {
@@ -160,6 +415,9 @@ impl Default for DynamicCgroupSettings {
             cleanup_timeout_secs: 300,
             default_cpu_limit: 50.0,
             default_memory_limit_mb: 1024,
+            release_cooldown_secs: default_release_cooldown_secs(),
+            evict_when_full: false,
+            adaptive: AdaptiveSettings::default(),
         }
     }
 }
@@ -171,6 +429,10 @@ pub struct CgroupConfig {
     #[serde(default)]
     pub enabled: bool,
 
+    /// Mount point of the cgroup v2 hierarchy
+    #[serde(default = "default_cgroupfs_root")]
+    pub cgroupfs_root: PathBuf,
+
     /// Root path for FreezR cgroups
     #[serde(default = "default_root_path")]
     pub root_path: PathBuf,
@@ -194,12 +456,24 @@ pub struct CgroupConfig {
     /// Restore processes to original cgroups on stop
     #[serde(default = "default_true")]
     pub restore_processes_on_stop: bool,
+
+    /// Journal of original cgroups, survives a daemon crash (None = memory only)
+    #[serde(default = "default_journal_path")]
+    pub journal_path: Option<PathBuf>,
+}
+
+fn default_cgroupfs_root() -> PathBuf {
+    PathBuf::from("/sys/fs/cgroup")
 }
 
 fn default_root_path() -> PathBuf {
     PathBuf::from("/sys/fs/cgroup/freezr.slice")
 }
 
+fn default_journal_path() -> Option<PathBuf> {
+    Some(PathBuf::from(DEFAULT_JOURNAL_PATH))
+}
+
 fn default_strategy() -> CgroupStrategy {
     CgroupStrategy::Hybrid
 }
@@ -212,12 +486,14 @@ impl Default for CgroupConfig {
     fn default() -> Self {
         Self {
             enabled: false,
+            cgroupfs_root: default_cgroupfs_root(),
             root_path: default_root_path(),
             strategy: default_strategy(),
             static_groups: Vec::new(),
             dynamic_settings: DynamicCgroupSettings::default(),
             auto_cleanup_on_stop: true,
             restore_processes_on_stop: true,
+            journal_path: default_journal_path(),
         }
     }
 }
@@ -254,15 +530,13 @@ impl Cgroup {
 
     /// Check if cgroup directory exists
     pub fn exists(&self) -> bool {
-        self.path.exists()
+        cgroupfs::exists(&self.path)
     }
 
     /// Get list of processes in this cgroup
     pub fn get_processes(&self) -> Result<Vec<u32>> {
-        use std::fs;
-
         let procs_file = self.path.join("cgroup.procs");
-        let content = fs::read_to_string(&procs_file)?;
+        let content = cgroupfs::read_to_string(&procs_file)?;
 
         let pids = content
             .lines()
@@ -279,6 +553,56 @@ impl Cgroup {
     }
 }
 
+/// Process tree frozen through `cgroup.freeze`
+#[derive(Debug, Clone)]
+pub struct FrozenTree {
+    /// Process whose tree was frozen
+    pub root_pid: u32,
+
+    /// Dynamic cgroup holding the tree
+    pub cgroup: Cgroup,
+
+    /// Original cgroup of each moved process (path from /proc/[pid]/cgroup)
+    pub origins: HashMap<u32, String>,
+}
+
+/// Process tree throttled in a dynamic cgroup with CPU/memory limits
+#[derive(Debug, Clone)]
+pub struct ThrottledTree {
+    /// Process whose tree was throttled
+    pub root_pid: u32,
+
+    /// Dynamic cgroup holding the tree (with the applied limits)
+    pub cgroup: Cgroup,
+
+    /// Original cgroup of each moved process (path from /proc/[pid]/cgroup)
+    pub origins: HashMap<u32, String>,
+}
+
+/// Usage of a dynamic cgroup, for garbage collection
+#[derive(Debug, Clone, Copy)]
+struct DynamicActivity {
+    /// Last time the cgroup was created or seen with processes
+    last_used: Instant,
+    /// Since when the cgroup has had no processes
+    empty_since: Option<Instant>,
+}
+
+impl DynamicActivity {
+    fn new(now: Instant) -> Self {
+        Self {
+            last_used: now,
+            empty_since: None,
+        }
+    }
+}
+
+/// Controllers enabled when available, besides the required cpu and memory
+const OPTIONAL_CONTROLLERS: &[&str] = &["io", "pids", "cpuset"];
+
+/// How many reaped cgroup names `health_check` reports
+const REAPED_HISTORY: usize = 20;
+
 /// Cgroup manager
 pub struct CgroupManager {
     /// Root path for FreezR cgroups
@@ -296,6 +620,27 @@ pub struct CgroupManager {
     /// Dynamic cgroup settings
     dynamic_settings: DynamicCgroupSettings,
 
+    /// Original cgroup (outside the FreezR root) of each moved process
+    origins: HashMap<u32, OriginEntry>,
+
+    /// Persistent copy of `origins`
+    journal: Option<OriginJournal>,
+
+    /// Cgroups left by a previous run, not released yet
+    adopted: Vec<String>,
+
+    /// Usage of dynamic cgroups (name -> activity)
+    activity: HashMap<String, DynamicActivity>,
+
+    /// Recently reaped or evicted dynamic cgroups (newest last)
+    reaped: Vec<String>,
+
+    /// Dynamic cgroups removed because they stayed empty
+    total_reaped: u64,
+
+    /// Dynamic cgroups evicted because the limit was reached
+    total_evicted: u64,
+
     /// Config
     config: CgroupConfig,
 }
@@ -305,7 +650,7 @@ impl CgroupManager {
     pub fn new(config: CgroupConfig) -> Result<Self> {
         // Validate configuration
         if config.enabled {
-            Self::validate_system()?;
+            Self::validate_system(&config.cgroupfs_root)?;
         }
 
         Ok(Self {
@@ -314,29 +659,33 @@ impl CgroupManager {
             cgroups: HashMap::new(),
             static_configs: config.static_groups.clone(),
             dynamic_settings: config.dynamic_settings.clone(),
+            origins: HashMap::new(),
+            journal: config.journal_path.clone().map(OriginJournal::new),
+            adopted: Vec::new(),
+            activity: HashMap::new(),
+            reaped: Vec::new(),
+            total_reaped: 0,
+            total_evicted: 0,
             config,
         })
     }
 
-    /// Validate system supports cgroup v2
-    fn validate_system() -> Result<()> {
-        use std::fs;
-
+    /// Validate system supports cgroup v2 at `cgroup_mount`
+    fn validate_system(cgroup_mount: &Path) -> Result<()> {
         // Check if cgroup v2 is mounted
-        let cgroup_mount = PathBuf::from("/sys/fs/cgroup");
-        if !cgroup_mount.exists() {
+        if !cgroupfs::exists(cgroup_mount) {
             return Err(CgroupError::CgroupV2NotAvailable);
         }
 
         // Check if cgroup.controllers exists (v2 indicator)
         let controllers_file = cgroup_mount.join("cgroup.controllers");
-        if !controllers_file.exists() {
+        if !cgroupfs::exists(&controllers_file) {
             return Err(CgroupError::CgroupV2NotAvailable);
         }
 
         // Check if we have write permission
         let test_path = cgroup_mount.join("cgroup.subtree_control");
-        if let Err(_) = fs::metadata(&test_path) {
+        if !cgroupfs::exists(&test_path) {
             return Err(CgroupError::InsufficientPrivileges);
         }
 
@@ -344,12 +693,14 @@ impl CgroupManager {
     }
 
     /// Initialize: create root slice, apply static configs (called on service start)
+    ///
+    /// Cgroups left under the root by a previous run (e.g. after a crash) are
+    /// adopted, and the original cgroups of their processes are read back
+    /// from the journal. See [`release_adopted`](Self::release_adopted).
     pub fn initialize(&mut self) -> Result<()> {
-        use std::fs;
-
         // Create root slice directory
-        if !self.root_path.exists() {
-            fs::create_dir(&self.root_path)?;
+        if !cgroupfs::exists(&self.root_path) {
+            cgroupfs::create_dir(&self.root_path)?;
             println!("Created cgroup root: {:?}", self.root_path);
         }
 
@@ -366,34 +717,127 @@ impl CgroupManager {
             }
         }
 
+        // Origins of processes moved by a previous run
+        if let Some(journal) = &self.journal {
+            match journal.load_alive() {
+                Ok(entries) => {
+                    for entry in entries {
+                        self.origins.entry(entry.pid).or_insert(entry);
+                    }
+                }
+                Err(e) => println!("Failed to read cgroup journal {:?}: {}", journal.path(), e),
+            }
+        }
+        self.adopt_existing()?;
+
         println!("Cgroup integration initialized");
         Ok(())
     }
 
-    /// Enable CPU and memory controllers
-    fn enable_controllers(&self, path: &PathBuf) -> Result<()> {
-        use std::fs;
+    /// Adopt the cgroups a previous run left under the root
+    ///
+    /// Children of the root that are not static groups become dynamic
+    /// cgroups again, with their limits read back from the cgroup files.
+    fn adopt_existing(&mut self) -> Result<()> {
+        use super::controller::{CpuController, MemoryController};
 
+        for path in cgroupfs::subdirs(&self.root_path)? {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let name = name.to_string();
+            if self.cgroups.contains_key(&name) {
+                continue;
+            }
+
+            let mut cgroup = Cgroup::new(name.clone(), path, CgroupType::Dynamic);
+            cgroup.limits = ResourceLimits {
+                cpu_limit_percent: CpuController::get_quota(&cgroup.path).ok().flatten(),
+                memory_max: MemoryController::get_max(&cgroup.path).ok().flatten(),
+                memory_high: MemoryController::get_high(&cgroup.path).ok().flatten(),
+                pids_max: PidsController::get_max(&cgroup.path).ok().flatten(),
+                ..ResourceLimits::default()
+            };
+            let _ = cgroup.reload_processes();
+
+            println!(
+                "Adopted cgroup {} from a previous run ({} processes)",
+                name,
+                cgroup.pids.len()
+            );
+            self.cgroups.insert(name.clone(), cgroup);
+            self.activity
+                .insert(name.clone(), DynamicActivity::new(Instant::now()));
+            self.adopted.push(name);
+        }
+
+        Ok(())
+    }
+
+    /// Cgroups adopted from a previous run and not released yet
+    pub fn adopted_cgroups(&self) -> &[String] {
+        &self.adopted
+    }
+
+    /// Move the processes of adopted cgroups back and remove the cgroups
+    ///
+    /// Processes return to their journaled original cgroups (children follow
+    /// their parent), or to the root cgroup if the journal has no entry.
+    /// Frozen cgroups are kept: leaving one would thaw its processes.
+    /// Returns the names of the released cgroups.
+    pub fn release_adopted(&mut self) -> Vec<String> {
+        let mut released = Vec::new();
+        for name in std::mem::take(&mut self.adopted) {
+            let Some(cgroup) = self.cgroups.get(&name) else {
+                continue; // reaped meanwhile
+            };
+            if FreezerController::is_frozen(&cgroup.path).unwrap_or(false) {
+                println!("Adopted cgroup {} is frozen, not releasing it", name);
+                self.adopted.push(name);
+                continue;
+            }
+
+            match self.remove_cgroup(&name) {
+                Ok(()) => released.push(name),
+                Err(e) => {
+                    println!("Failed to release adopted cgroup {}: {}", name, e);
+                    self.adopted.push(name);
+                }
+            }
+        }
+        released
+    }
+
+    /// Enable CPU and memory controllers, plus io, pids and cpuset if available
+    fn enable_controllers(&self, path: &PathBuf) -> Result<()> {
         let subtree_control = path.join("cgroup.subtree_control");
-        fs::write(&subtree_control, "+cpu +memory").map_err(|e| {
+        cgroupfs::write(&subtree_control, "+cpu +memory").map_err(|e| {
             CgroupError::PermissionDenied(format!(
                 "Failed to enable controllers at {:?}: {}",
                 subtree_control, e
             ))
         })?;
 
+        // Optional controllers: limits using them fail when they are missing
+        for controller in OPTIONAL_CONTROLLERS {
+            if let Err(e) = cgroupfs::write(&subtree_control, &format!("+{}", controller)) {
+                println!(
+                    "Controller {} not available at {:?}: {}",
+                    controller, subtree_control, e
+                );
+            }
+        }
+
         Ok(())
     }
 
     /// Create static cgroup from config
     fn create_static_cgroup(&mut self, config: &StaticCgroupConfig) -> Result<()> {
-        use std::fs;
-
         let cgroup_path = self.root_path.join(&config.name);
 
         // Create directory
-        if !cgroup_path.exists() {
-            fs::create_dir(&cgroup_path)?;
+        if !cgroupfs::exists(&cgroup_path) {
+            cgroupfs::create_dir(&cgroup_path)?;
         }
 
         // Create Cgroup object
@@ -412,8 +856,6 @@ impl CgroupManager {
 
     /// Create dynamic cgroup
     pub fn create_cgroup(&mut self, name: &str) -> Result<Cgroup> {
-        use std::fs;
-
         // Check if already exists
         if self.cgroups.contains_key(name) {
             return Err(CgroupError::AlreadyExists(name.to_string()));
@@ -421,21 +863,26 @@ impl CgroupManager {
 
         // Check dynamic cgroup limit
         if self.count_dynamic_cgroups() >= self.dynamic_settings.max_dynamic_cgroups {
-            return Err(CgroupError::MaxCgroupsReached(
-                self.dynamic_settings.max_dynamic_cgroups,
-            ));
+            let evicted = self.dynamic_settings.evict_when_full && self.evict_lru();
+            if !evicted {
+                return Err(CgroupError::MaxCgroupsReached(
+                    self.dynamic_settings.max_dynamic_cgroups,
+                ));
+            }
         }
 
         let cgroup_path = self.root_path.join(name);
 
         // Create directory
-        fs::create_dir(&cgroup_path)?;
+        cgroupfs::create_dir(&cgroup_path)?;
 
         // Create Cgroup object
         let cgroup = Cgroup::new(name.to_string(), cgroup_path, CgroupType::Dynamic);
 
         // Store in map
         self.cgroups.insert(name.to_string(), cgroup.clone());
+        self.activity
+            .insert(name.to_string(), DynamicActivity::new(Instant::now()));
 
         println!("Created dynamic cgroup: {}", name);
         Ok(cgroup)
@@ -472,13 +919,41 @@ impl CgroupManager {
             MemoryController::set_high(&cgroup.path, mem_high)?;
         }
 
+        // Apply I/O limits
+        if let Some(weight) = cgroup.limits.io_weight {
+            IoController::set_weight(&cgroup.path, weight)?;
+        }
+
+        for limit in &cgroup.limits.io_limits {
+            let max = limit.resolve()?;
+            IoController::set_max(&cgroup.path, &max)?;
+            println!(
+                "Applied I/O limit {} ({}) to cgroup {}",
+                max, limit.device, cgroup.name
+            );
+        }
+
+        // Apply CPU/memory node pinning (mems first: cpus alone is enough to pin)
+        if let Some(mems) = &cgroup.limits.cpuset_mems {
+            CpusetController::set_mems(&cgroup.path, &mems.parse()?)?;
+        }
+
+        if let Some(cpus) = &cgroup.limits.cpuset_cpus {
+            CpusetController::set_cpus(&cgroup.path, &cpus.parse()?)?;
+            println!("Pinned cgroup {} to CPUs {}", cgroup.name, cpus);
+        }
+
+        // Apply task limit
+        if let Some(pids_max) = cgroup.limits.pids_max {
+            PidsController::set_max(&cgroup.path, pids_max)?;
+            println!("Applied pids max {} to cgroup {}", pids_max, cgroup.name);
+        }
+
         Ok(())
     }
 
     /// Assign process to cgroup
     pub fn assign_process(&self, cgroup: &Cgroup, pid: u32) -> Result<()> {
-        use std::fs;
-
         // Check process exists
         if !PathBuf::from(format!("/proc/{}", pid)).exists() {
             return Err(CgroupError::ProcessNotFound(pid));
@@ -486,7 +961,7 @@ impl CgroupManager {
 
         // Write PID to cgroup.procs
         let procs_file = cgroup.path.join("cgroup.procs");
-        fs::write(&procs_file, pid.to_string())?;
+        cgroupfs::write(&procs_file, &pid.to_string())?;
 
         println!("Assigned process {} to cgroup {}", pid, cgroup.name);
         Ok(())
@@ -494,30 +969,456 @@ impl CgroupManager {
 
     /// Remove cgroup
     pub fn remove_cgroup(&mut self, name: &str) -> Result<()> {
-        use std::fs;
-
         let cgroup = self
             .cgroups
             .remove(name)
             .ok_or_else(|| CgroupError::NotFound(name.to_string()))?;
-
-        // Move processes out first
-        let pids = cgroup.get_processes()?;
-        for pid in pids {
-            // Move back to root cgroup
-            let root_procs = PathBuf::from("/sys/fs/cgroup/cgroup.procs");
-            if let Ok(_) = fs::write(&root_procs, pid.to_string()) {
-                println!("Moved process {} back to root cgroup", pid);
-            }
-        }
+        self.activity.remove(name);
+
+        // Move processes out first, back to where they came from
+        restore_origins(
+            &self.config.cgroupfs_root,
+            &cgroup,
+            &self.origin_paths(),
+            None,
+        );
 
         // Remove directory
-        fs::remove_dir(&cgroup.path)?;
+        cgroupfs::remove_dir(&cgroup.path)?;
 
         println!("Removed cgroup: {}", name);
         Ok(())
     }
 
+    /// Freeze a process together with all its descendants
+    ///
+    /// Moves the tree into the dynamic cgroup `freeze-<pid>`, writes
+    /// `cgroup.freeze` and waits until `cgroup.events` reports `frozen 1`.
+    /// Processes forked while the cgroup is frozen start in it and are frozen too.
+    ///
+    /// # Arguments
+    /// * `root_pid` - Process whose tree is frozen
+    /// * `timeout` - Maximum time to wait for the freeze to complete
+    pub fn freeze_tree(&mut self, root_pid: u32, timeout: Duration) -> Result<FrozenTree> {
+        if !process_exists(root_pid) {
+            return Err(CgroupError::ProcessNotFound(root_pid));
+        }
+
+        let name = format!("freeze-{}", root_pid);
+        let cgroup = self.create_cgroup(&name)?;
+
+        if !FreezerController::is_supported(&cgroup.path) {
+            self.remove_cgroup(&name)?;
+            return Err(CgroupError::CgroupV2NotAvailable);
+        }
+
+        let origins = self.move_tree(&cgroup, root_pid);
+        let mut tree = FrozenTree {
+            root_pid,
+            cgroup,
+            origins,
+        };
+
+        if tree.origins.is_empty() {
+            self.remove_cgroup(&name)?;
+            return Err(CgroupError::ProcessNotFound(root_pid));
+        }
+
+        let frozen = FreezerController::freeze(&tree.cgroup.path)
+            .and_then(|_| FreezerController::wait(&tree.cgroup.path, true, timeout));
+        if let Err(e) = frozen {
+            let _ = self.thaw_tree(&tree, timeout);
+            return Err(e);
+        }
+
+        tree.cgroup.reload_processes()?;
+        if let Some(stored) = self.cgroups.get_mut(&name) {
+            stored.pids = tree.cgroup.pids.clone();
+        }
+
+        println!(
+            "Froze process tree of {} ({} processes) in cgroup {}",
+            root_pid,
+            tree.cgroup.pids.len(),
+            name
+        );
+        Ok(tree)
+    }
+
+    /// Thaw a frozen tree atomically and move its processes back
+    ///
+    /// All processes resume at once when `cgroup.freeze` is cleared. Each one
+    /// then returns to its original cgroup (children forked during the freeze
+    /// follow the root process) and the dynamic cgroup is removed.
+    pub fn thaw_tree(&mut self, tree: &FrozenTree, timeout: Duration) -> Result<()> {
+        let path = &tree.cgroup.path;
+
+        FreezerController::thaw(path)?;
+        let thawed = FreezerController::wait(path, false, timeout);
+
+        // Restore processes even if the thaw was not confirmed: leaving a
+        // frozen cgroup thaws a process anyway
+        restore_origins(
+            &self.config.cgroupfs_root,
+            &tree.cgroup,
+            &tree.origins,
+            tree.origins.get(&tree.root_pid),
+        );
+
+        // Already reaped if the whole tree exited meanwhile
+        if self.cgroups.contains_key(&tree.cgroup.name) {
+            self.remove_cgroup(&tree.cgroup.name)?;
+        }
+        thawed
+    }
+
+    /// Put a process and its descendants under CPU/memory limits
+    ///
+    /// Moves the tree into the dynamic cgroup `throttle-<pid>` with `limits`
+    /// applied (usually [`DynamicCgroupSettings::default_limits`]). Children
+    /// forked later start in the same cgroup and share the limits.
+    ///
+    /// # Arguments
+    /// * `root_pid` - Process whose tree is throttled
+    /// * `limits` - Limits of the throttle cgroup
+    pub fn throttle_tree(
+        &mut self,
+        root_pid: u32,
+        limits: ResourceLimits,
+    ) -> Result<ThrottledTree> {
+        limits.validate()?;
+
+        let current = get_process_cgroup(root_pid)?;
+
+        // Leaving a frozen cgroup would thaw the process
+        let current = self
+            .config
+            .cgroupfs_root
+            .join(current.trim_start_matches('/'));
+        if FreezerController::is_frozen(&current).unwrap_or(false) {
+            return Err(CgroupError::ValidationError(format!(
+                "Process {} is frozen, not throttling it",
+                root_pid
+            )));
+        }
+
+        let name = format!("throttle-{}", root_pid);
+        let mut cgroup = self.create_cgroup(&name)?;
+        cgroup.limits = limits;
+
+        if let Err(e) = self.apply_limits(&cgroup) {
+            self.remove_cgroup(&name)?;
+            return Err(e);
+        }
+
+        let origins = self.move_tree(&cgroup, root_pid);
+        if origins.is_empty() {
+            self.remove_cgroup(&name)?;
+            return Err(CgroupError::ProcessNotFound(root_pid));
+        }
+
+        cgroup.reload_processes()?;
+        if let Some(stored) = self.cgroups.get_mut(&name) {
+            stored.limits = cgroup.limits.clone();
+            stored.pids = cgroup.pids.clone();
+        }
+
+        println!(
+            "Throttled process tree of {} ({} processes) in cgroup {}",
+            root_pid,
+            cgroup.pids.len(),
+            name
+        );
+        Ok(ThrottledTree {
+            root_pid,
+            cgroup,
+            origins,
+        })
+    }
+
+    /// Lift the limits of a throttled tree
+    ///
+    /// Every process still in the cgroup returns to its original cgroup
+    /// (children forked meanwhile follow the root process), then the dynamic
+    /// cgroup is removed.
+    pub fn release_tree(&mut self, tree: &ThrottledTree) -> Result<()> {
+        restore_origins(
+            &self.config.cgroupfs_root,
+            &tree.cgroup,
+            &tree.origins,
+            tree.origins.get(&tree.root_pid),
+        );
+
+        // Already reaped or evicted: the processes were restored then. A
+        // directory left by a failed removal is removed again.
+        if !self.cgroups.contains_key(&tree.cgroup.name) {
+            if tree.cgroup.exists() {
+                cgroupfs::remove_dir(&tree.cgroup.path)?;
+            }
+            return Ok(());
+        }
+        self.remove_cgroup(&tree.cgroup.name)
+    }
+
+    /// Remove dynamic cgroups that stayed empty for `cleanup_timeout_secs`
+    ///
+    /// Call periodically (the daemon does it on every monitoring cycle).
+    /// A cgroup is empty once all its processes exited; a cgroup whose
+    /// directory disappeared is dropped right away. Returns the names of
+    /// the removed cgroups, which `health_check` also reports.
+    pub fn reap_dynamic_cgroups(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        let timeout = Duration::from_secs(self.dynamic_settings.cleanup_timeout_secs);
+
+        let mut expired = Vec::new();
+        for (name, cgroup) in &self.cgroups {
+            if cgroup.cgroup_type != CgroupType::Dynamic {
+                continue;
+            }
+
+            let activity = self
+                .activity
+                .entry(name.clone())
+                .or_insert_with(|| DynamicActivity::new(now));
+
+            let populated = cgroup
+                .get_processes()
+                .map(|pids| !pids.is_empty())
+                .unwrap_or(false);
+            if populated {
+                activity.last_used = now;
+                activity.empty_since = None;
+                continue;
+            }
+
+            let empty_since = *activity.empty_since.get_or_insert(now);
+            if !cgroup.exists() || now.duration_since(empty_since) >= timeout {
+                expired.push(name.clone());
+            }
+        }
+
+        let mut reaped = Vec::new();
+        for name in expired {
+            let gone = self.cgroups.get(&name).is_some_and(|c| !c.exists());
+            let removed = if gone {
+                self.cgroups.remove(&name);
+                self.activity.remove(&name);
+                Ok(())
+            } else {
+                self.remove_cgroup(&name)
+            };
+
+            match removed {
+                Ok(()) => {
+                    println!("Reaped empty dynamic cgroup: {}", name);
+                    self.total_reaped += 1;
+                    self.remember_reaped(&name);
+                    reaped.push(name);
+                }
+                Err(e) => println!("Failed to reap dynamic cgroup {}: {}", name, e),
+            }
+        }
+
+        reaped
+    }
+
+    /// Remove the least recently used dynamic cgroup to make room
+    ///
+    /// Frozen cgroups are skipped: removing one would thaw its processes
+    /// behind the freezer's back. Processes of the evicted cgroup go back
+    /// to their original cgroups. Returns false if nothing could be evicted.
+    fn evict_lru(&mut self) -> bool {
+        let mut candidates: Vec<(Instant, String)> = self
+            .cgroups
+            .values()
+            .filter(|c| c.cgroup_type == CgroupType::Dynamic)
+            .filter(|c| !FreezerController::is_frozen(&c.path).unwrap_or(false))
+            .map(|c| {
+                let last_used = self.activity.get(&c.name).map(|a| a.last_used);
+                (last_used.unwrap_or_else(Instant::now), c.name.clone())
+            })
+            .collect();
+        candidates.sort();
+
+        for (_, name) in candidates {
+            match self.remove_cgroup(&name) {
+                Ok(()) => {
+                    println!("Evicted least recently used dynamic cgroup: {}", name);
+                    self.total_evicted += 1;
+                    self.remember_reaped(&name);
+                    return true;
+                }
+                Err(e) => println!("Failed to evict dynamic cgroup {}: {}", name, e),
+            }
+        }
+        false
+    }
+
+    fn remember_reaped(&mut self, name: &str) {
+        if self.reaped.len() >= REAPED_HISTORY {
+            self.reaped.remove(0);
+        }
+        self.reaped.push(name.to_string());
+    }
+
+    /// Move processes matching `process_patterns` into their static cgroups
+    ///
+    /// Called on every monitoring cycle, so processes started later are
+    /// picked up too. Descendants of a matching process follow it (e.g. KESL
+    /// workers with other names). Processes already under the FreezR root
+    /// (members, frozen or throttled trees) are left where they are.
+    /// Returns the number of migrated processes.
+    pub fn sync_static_groups(&mut self) -> usize {
+        if !matches!(
+            self.strategy,
+            CgroupStrategy::Static | CgroupStrategy::Hybrid
+        ) {
+            return 0;
+        }
+
+        let table = list_processes();
+        let mut changed = self.prune_origins();
+
+        let own_pid = std::process::id();
+        let mut migrated = 0;
+        for config in &self.static_configs.clone() {
+            let Some(cgroup) = self.cgroups.get(&config.name).cloned() else {
+                continue;
+            };
+
+            let roots: Vec<u32> = table
+                .iter()
+                .filter(|p| p.pid != 1 && p.pid != own_pid && config.matches(&p.name))
+                .map(|p| p.pid)
+                .collect();
+
+            for pid in descendants(&table, &roots) {
+                let Ok(origin) = get_process_cgroup(pid) else {
+                    continue; // exited meanwhile
+                };
+                if self.is_managed(&origin) {
+                    continue;
+                }
+                if self.assign_process(&cgroup, pid).is_ok() {
+                    self.remember_origin(pid, &origin);
+                    migrated += 1;
+                    changed = true;
+                }
+            }
+        }
+
+        if changed {
+            self.save_journal();
+        }
+
+        for cgroup in self.cgroups.values_mut() {
+            if cgroup.cgroup_type == CgroupType::Static {
+                let _ = cgroup.reload_processes();
+            }
+        }
+
+        migrated
+    }
+
+    /// Static cgroups with their current members
+    pub fn static_cgroups(&self) -> Vec<&Cgroup> {
+        let mut groups: Vec<&Cgroup> = self
+            .cgroups
+            .values()
+            .filter(|c| c.cgroup_type == CgroupType::Static)
+            .collect();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+
+    /// Is a cgroup path (from /proc/[pid]/cgroup) under the FreezR root?
+    fn is_managed(&self, cgroup: &str) -> bool {
+        self.config
+            .cgroupfs_root
+            .join(cgroup.trim_start_matches('/'))
+            .starts_with(&self.root_path)
+    }
+
+    /// Move a process tree into `cgroup`, returning the original cgroup of
+    /// each moved process
+    fn move_tree(&mut self, cgroup: &Cgroup, root_pid: u32) -> HashMap<u32, String> {
+        // Root first: children forked after it moved are born in the new cgroup
+        let mut origins = HashMap::new();
+        for pid in process_tree(root_pid) {
+            let Ok(origin) = get_process_cgroup(pid) else {
+                continue; // exited meanwhile
+            };
+            if self.assign_process(cgroup, pid).is_ok() {
+                self.remember_origin(pid, &origin);
+                origins.insert(pid, origin);
+            }
+        }
+        self.save_journal();
+        origins
+    }
+
+    /// Record where a process lived before FreezR first moved it
+    ///
+    /// Moves between FreezR cgroups (e.g. freezing a throttled process) keep
+    /// the original, so removing any FreezR cgroup restores the process to
+    /// where it came from.
+    ///
+    /// The start time is read right away: by the time the journal is
+    /// written the PID may already belong to another process.
+    fn remember_origin(&mut self, pid: u32, origin: &str) {
+        if self.is_managed(origin) {
+            return;
+        }
+        let Some(start_ticks) = read_start_ticks(pid) else {
+            return; // exited meanwhile
+        };
+        self.origins.insert(
+            pid,
+            OriginEntry {
+                pid,
+                start_ticks,
+                origin: origin.to_string(),
+            },
+        );
+    }
+
+    /// Forget the origins of exited processes and of reused PIDs
+    ///
+    /// Returns true if any origin was dropped.
+    fn prune_origins(&mut self) -> bool {
+        let known = self.origins.len();
+        self.origins
+            .retain(|&pid, entry| read_start_ticks(pid) == Some(entry.start_ticks));
+        self.origins.len() != known
+    }
+
+    /// Original cgroup path of each moved process
+    fn origin_paths(&self) -> HashMap<u32, String> {
+        self.origins
+            .iter()
+            .map(|(&pid, entry)| (pid, entry.origin.clone()))
+            .collect()
+    }
+
+    /// Write the origins of live processes to the journal (failures are
+    /// logged, not fatal)
+    fn save_journal(&mut self) {
+        self.prune_origins();
+
+        if let Some(journal) = &self.journal {
+            let mut entries: Vec<OriginEntry> = self.origins.values().cloned().collect();
+            entries.sort_by_key(|entry| entry.pid);
+            if let Err(e) = journal.save(&entries) {
+                println!("Failed to write cgroup journal {:?}: {}", journal.path(), e);
+            }
+        }
+    }
+
+    /// Settings of dynamic cgroups (default limits, cooldown)
+    pub fn dynamic_settings(&self) -> &DynamicCgroupSettings {
+        &self.dynamic_settings
+    }
+
     /// Called when systemd service stops (CRITICAL)
     pub fn on_service_stop(&mut self) -> Result<()> {
         println!("Cleaning up cgroups on service stop...");
@@ -525,6 +1426,12 @@ impl CgroupManager {
         // Restore all processes
         if self.config.restore_processes_on_stop {
             self.restore_all_processes()?;
+
+            // Nothing left to restore after a restart
+            if !self.origins.is_empty() {
+                self.origins.clear();
+                self.save_journal();
+            }
         }
 
         // Remove all cgroups
@@ -538,17 +1445,13 @@ impl CgroupManager {
     }
 
     /// Restore all processes to their original cgroups
+    ///
+    /// Processes without a recorded origin go to the root cgroup.
     fn restore_all_processes(&self) -> Result<()> {
-        use std::fs;
-
+        let origins = self.origin_paths();
         for cgroup in self.cgroups.values() {
-            let pids = cgroup.get_processes()?;
-            for pid in pids {
-                let root_procs = PathBuf::from("/sys/fs/cgroup/cgroup.procs");
-                if let Ok(_) = fs::write(&root_procs, pid.to_string()) {
-                    println!("Restored process {} to root cgroup", pid);
-                }
-            }
+            restore_origins(&self.config.cgroupfs_root, cgroup, &origins, None);
+            println!("Restored processes of cgroup {}", cgroup.name);
         }
 
         Ok(())
@@ -556,14 +1459,12 @@ impl CgroupManager {
 
     /// Remove all FreezR cgroups (static + dynamic)
     fn remove_all_cgroups(&mut self) -> Result<()> {
-        use std::fs;
-
         let cgroup_names: Vec<String> = self.cgroups.keys().cloned().collect();
 
         for name in cgroup_names {
             if let Some(cgroup) = self.cgroups.remove(&name) {
-                if cgroup.path.exists() {
-                    let _ = fs::remove_dir(&cgroup.path);
+                if cgroupfs::exists(&cgroup.path) {
+                    let _ = cgroupfs::remove_dir(&cgroup.path);
                 }
             }
         }
@@ -573,10 +1474,8 @@ impl CgroupManager {
 
     /// Remove root slice
     fn cleanup_root_slice(&self) -> Result<()> {
-        use std::fs;
-
-        if self.root_path.exists() {
-            fs::remove_dir(&self.root_path)?;
+        if cgroupfs::exists(&self.root_path) {
+            cgroupfs::remove_dir(&self.root_path)?;
             println!("Removed root cgroup: {:?}", self.root_path);
         }
 
@@ -584,27 +1483,48 @@ impl CgroupManager {
     }
 
     /// Health check
-    pub fn health_check(&self) -> Result<HealthStatus> {
+    ///
+    /// Also reports dynamic cgroup usage and the cgroups removed by the
+    /// reaper or evicted at the limit.
+    pub fn health_check(&self) -> Result<HealthReport> {
         let mut issues = Vec::new();
 
         // Check root slice exists
-        if !self.root_path.exists() {
+        if !cgroupfs::exists(&self.root_path) {
             issues.push("Root cgroup slice missing".to_string());
         }
 
         // Check static cgroups
         for config in &self.static_configs {
             let path = self.root_path.join(&config.name);
-            if !path.exists() {
+            if !cgroupfs::exists(&path) {
                 issues.push(format!("Static cgroup missing: {}", config.name));
             }
         }
 
-        if issues.is_empty() {
-            Ok(HealthStatus::Healthy)
-        } else {
-            Ok(HealthStatus::Degraded(issues))
+        let dynamic_cgroups = self.count_dynamic_cgroups();
+        let max_dynamic_cgroups = self.dynamic_settings.max_dynamic_cgroups;
+        if dynamic_cgroups >= max_dynamic_cgroups {
+            issues.push(format!(
+                "Dynamic cgroup limit reached ({}/{})",
+                dynamic_cgroups, max_dynamic_cgroups
+            ));
         }
+
+        let status = if issues.is_empty() {
+            HealthStatus::Healthy
+        } else {
+            HealthStatus::Degraded(issues)
+        };
+
+        Ok(HealthReport {
+            status,
+            dynamic_cgroups,
+            max_dynamic_cgroups,
+            total_reaped: self.total_reaped,
+            total_evicted: self.total_evicted,
+            recently_reaped: self.reaped.clone(),
+        })
     }
 
     /// Get cgroup by name
@@ -612,6 +1532,13 @@ impl CgroupManager {
         self.cgroups.get(name)
     }
 
+    /// Paths of all managed cgroups, sorted
+    pub fn cgroup_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.cgroups.values().map(|c| c.path.clone()).collect();
+        paths.sort();
+        paths
+    }
+
     /// Get mutable cgroup by name
     pub fn get_cgroup_mut(&mut self, name: &str) -> Option<&mut Cgroup> {
         self.cgroups.get_mut(name)
@@ -631,6 +1558,43 @@ impl CgroupManager {
     }
 }
 
+/// Move every process of `cgroup` back to its original cgroup
+///
+/// Processes without a recorded origin (forked after the move) follow their
+/// nearest recorded ancestor, then `fallback`. If the original cgroup is gone
+/// (or is not a leaf anymore), the root cgroup at `mount` is used.
+fn restore_origins(
+    mount: &Path,
+    cgroup: &Cgroup,
+    origins: &HashMap<u32, String>,
+    fallback: Option<&String>,
+) {
+    for pid in cgroup.get_processes().unwrap_or_default() {
+        let origin = inherited_origin(pid, origins)
+            .or(fallback)
+            .map(|origin| origin.trim_start_matches('/'))
+            .unwrap_or_default();
+
+        let procs = mount.join(origin).join("cgroup.procs");
+        if cgroupfs::write(&procs, &pid.to_string()).is_err() {
+            let _ = cgroupfs::write(&mount.join("cgroup.procs"), &pid.to_string());
+        }
+    }
+}
+
+/// Origin of `pid` or of its nearest ancestor with a recorded origin
+fn inherited_origin(pid: u32, origins: &HashMap<u32, String>) -> Option<&String> {
+    let mut current = pid;
+    // Bounded walk: the parent chain may change while we follow it
+    for _ in 0..64 {
+        if let Some(origin) = origins.get(&current) {
+            return Some(origin);
+        }
+        current = read_ppid(current).filter(|&ppid| ppid > 1)?;
+    }
+    None
+}
+
 /// Health status
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HealthStatus {
@@ -639,6 +1603,27 @@ pub enum HealthStatus {
     Failed(String),
 }
 
+/// Result of `CgroupManager::health_check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+
+    /// Dynamic cgroups currently alive
+    pub dynamic_cgroups: usize,
+
+    /// Limit from `max_dynamic_cgroups`
+    pub max_dynamic_cgroups: usize,
+
+    /// Empty dynamic cgroups removed after `cleanup_timeout_secs`
+    pub total_reaped: u64,
+
+    /// Dynamic cgroups evicted (LRU) at the limit
+    pub total_evicted: u64,
+
+    /// Names of the most recently reaped or evicted cgroups (newest last)
+    pub recently_reaped: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -674,6 +1659,118 @@ mod tests {
         assert!(limits.validate().is_err());
     }
 
+    #[test]
+    fn test_io_limits_validation() {
+        let mb = 1024 * 1024;
+        let limits = ResourceLimits::new()
+            .with_io_limit(IoLimit::new("/").with_write_bps(20 * mb))
+            .with_io_weight(50);
+        assert!(limits.validate().is_ok());
+
+        // Device without any limit
+        let limits = ResourceLimits::new().with_io_limit(IoLimit::new("/"));
+        assert!(limits.validate().is_err());
+
+        let limits = ResourceLimits::new().with_io_limit(IoLimit::new("").with_read_bps(mb));
+        assert!(limits.validate().is_err());
+
+        let limits = ResourceLimits::new().with_io_limit(IoLimit::new("/").with_write_bps(0));
+        assert!(limits.validate().is_err());
+
+        let limits = ResourceLimits::new().with_io_weight(0);
+        assert!(limits.validate().is_err());
+    }
+
+    #[test]
+    fn test_cpuset_limits_validation() {
+        // CPU 0 and memory node 0 are always online
+        let limits = ResourceLimits::new()
+            .with_cpuset_cpus("0")
+            .with_cpuset_mems("0");
+        assert!(limits.validate().is_ok());
+
+        let cpus = |list: &str| ResourceLimits::new().with_cpuset_cpus(list).validate();
+        assert!(cpus("").is_err());
+        assert!(cpus("7-6").is_err());
+        assert!(cpus("8191").is_err()); // valid syntax, not online
+    }
+
+    #[test]
+    fn test_validate_cpuset_against_online() {
+        let online = || "0-7".parse::<CpuList>();
+
+        assert!(validate_cpuset("CPUs", "6-7", online()).is_ok());
+        let err = validate_cpuset("CPUs", "6-9", online()).unwrap_err();
+        assert!(err.to_string().contains("8-9 not online"));
+
+        // Online list unreadable: syntax check only
+        let unreadable = Err(CgroupError::NotFound("online".to_string()));
+        assert!(validate_cpuset("CPUs", "6-9", unreadable).is_ok());
+    }
+
+    #[test]
+    fn test_limits_overlay() {
+        let base = ResourceLimits::new()
+            .with_cpu_limit(50.0)
+            .with_memory_high(1024);
+        let extra = ResourceLimits::new()
+            .with_cpuset_cpus("6-7")
+            .with_cpu_limit(25.0);
+
+        let limits = base.overlay(extra);
+        assert_eq!(limits.cpu_limit_percent, Some(25.0));
+        assert_eq!(limits.memory_high, Some(1024));
+        assert_eq!(limits.cpuset_cpus.as_deref(), Some("6-7"));
+        assert!(limits.io_limits.is_empty());
+    }
+
+    #[test]
+    fn test_pids_limit() {
+        assert!(ResourceLimits::new().with_pids_max(512).validate().is_ok());
+        assert!(ResourceLimits::new().with_pids_max(0).validate().is_err());
+
+        let config: StaticCgroupConfig = toml::from_str(
+            r#"
+            name = "builds"
+            process_patterns = ["make"]
+            pids_max = 256
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.get_limits().pids_max, Some(256));
+    }
+
+    #[test]
+    fn test_io_limit_resolve() {
+        let max = IoLimit::new("8:0").with_write_bps(1000).resolve().unwrap();
+        assert_eq!(
+            max.to_string(),
+            "8:0 rbps=max wbps=1000 riops=max wiops=max"
+        );
+    }
+
+    #[test]
+    fn test_static_config_io_limits_toml() {
+        let config: StaticCgroupConfig = toml::from_str(
+            r#"
+            name = "backup"
+            process_patterns = ["rsync"]
+            io_weight = 50
+
+            [[io_limits]]
+            device = "/home"
+            write_bps = 20971520
+            "#,
+        )
+        .unwrap();
+
+        let limits = config.get_limits();
+        assert_eq!(limits.io_weight, Some(50));
+        assert_eq!(limits.io_limits[0].device, "/home");
+        assert_eq!(limits.io_limits[0].write_bps, Some(20 * 1024 * 1024));
+        assert_eq!(limits.io_limits[0].read_bps, None);
+    }
+
     #[test]
     fn test_cgroup_strategy_serde() {
         let strategy = CgroupStrategy::Hybrid;
@@ -703,6 +1800,158 @@ mod tests {
         assert!(cgroup.pids.is_empty());
     }
 
+    fn static_config(name: &str, patterns: &[&str]) -> StaticCgroupConfig {
+        StaticCgroupConfig {
+            name: name.to_string(),
+            process_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            limits: ResourceLimits::default(),
+            cpu_limit_percent_compat: Some(30.0),
+            memory_max_mb: None,
+            memory_high_mb: None,
+        }
+    }
+
+    #[test]
+    fn test_static_config_matches() {
+        let config = static_config("kesl", &["kesl", ""]);
+
+        assert!(config.matches("kesl"));
+        assert!(config.matches("kesl-gui"));
+        assert!(!config.matches("firefox"));
+        assert!(!static_config("none", &[]).matches("kesl"));
+    }
+
+    #[test]
+    fn test_inherited_origin_follows_parent() {
+        let parent = read_ppid(std::process::id()).unwrap();
+        let origins = HashMap::from([(parent, "/user.slice/shell".to_string())]);
+
+        assert_eq!(
+            inherited_origin(std::process::id(), &origins),
+            Some(&"/user.slice/shell".to_string())
+        );
+        assert_eq!(inherited_origin(std::process::id(), &HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_is_managed() {
+        let manager = CgroupManager::new(CgroupConfig::default()).unwrap();
+
+        assert!(manager.is_managed("/freezr.slice/kesl"));
+        assert!(manager.is_managed("/freezr.slice"));
+        assert!(!manager.is_managed("/system.slice/kesl.service"));
+        assert!(!manager.is_managed("/freezr.slice.old"));
+    }
+
+    #[test]
+    fn test_sync_without_static_cgroups() {
+        let config = CgroupConfig {
+            static_groups: vec![static_config("kesl", &["kesl"])],
+            ..CgroupConfig::default()
+        };
+        let mut manager = CgroupManager::new(config).unwrap();
+
+        // Group configured but not created (initialize not called): nothing moves
+        assert_eq!(manager.sync_static_groups(), 0);
+        assert!(manager.static_cgroups().is_empty());
+    }
+
+    /// Manager over a plain directory: empty subdirectories act as empty cgroups
+    fn temp_manager(name: &str, settings: DynamicCgroupSettings) -> CgroupManager {
+        let root = std::env::temp_dir().join(format!(
+            "freezr-cgroups-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let config = CgroupConfig {
+            root_path: root,
+            dynamic_settings: settings,
+            journal_path: None,
+            ..CgroupConfig::default()
+        };
+        CgroupManager::new(config).unwrap()
+    }
+
+    fn gc_settings(max: usize, timeout_secs: u64, evict: bool) -> DynamicCgroupSettings {
+        DynamicCgroupSettings {
+            max_dynamic_cgroups: max,
+            cleanup_timeout_secs: timeout_secs,
+            evict_when_full: evict,
+            ..DynamicCgroupSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_reap_empty_dynamic_cgroups() {
+        let mut manager = temp_manager("reap", gc_settings(10, 0, false));
+        let cgroup = manager.create_cgroup("throttle-1").unwrap();
+
+        assert_eq!(manager.reap_dynamic_cgroups(), vec!["throttle-1".to_string()]);
+        assert!(!cgroup.path.exists());
+        assert_eq!(manager.count_dynamic_cgroups(), 0);
+
+        let report = manager.health_check().unwrap();
+        assert_eq!(report.total_reaped, 1);
+        assert_eq!(report.recently_reaped, vec!["throttle-1".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&manager.root_path);
+    }
+
+    #[test]
+    fn test_reap_waits_for_timeout() {
+        let mut manager = temp_manager("reap-timeout", gc_settings(10, 300, false));
+        let cgroup = manager.create_cgroup("throttle-1").unwrap();
+
+        assert!(manager.reap_dynamic_cgroups().is_empty());
+        assert!(cgroup.path.exists());
+
+        // Directory removed behind our back: dropped without waiting
+        std::fs::remove_dir(&cgroup.path).unwrap();
+        assert_eq!(manager.reap_dynamic_cgroups().len(), 1);
+        assert_eq!(manager.count_dynamic_cgroups(), 0);
+
+        let _ = std::fs::remove_dir_all(&manager.root_path);
+    }
+
+    #[test]
+    fn test_limit_refuses_new_cgroup() {
+        let mut manager = temp_manager("refuse", gc_settings(1, 300, false));
+        manager.create_cgroup("throttle-1").unwrap();
+
+        assert!(matches!(
+            manager.create_cgroup("throttle-2"),
+            Err(CgroupError::MaxCgroupsReached(1))
+        ));
+
+        let report = manager.health_check().unwrap();
+        assert_eq!(report.dynamic_cgroups, 1);
+        assert!(matches!(report.status, HealthStatus::Degraded(_)));
+
+        let _ = std::fs::remove_dir_all(&manager.root_path);
+    }
+
+    #[test]
+    fn test_limit_evicts_least_recently_used() {
+        let mut manager = temp_manager("evict", gc_settings(2, 300, true));
+        let oldest = manager.create_cgroup("throttle-1").unwrap();
+        manager.create_cgroup("throttle-2").unwrap();
+
+        manager.create_cgroup("throttle-3").unwrap();
+
+        assert!(!oldest.path.exists());
+        assert_eq!(manager.count_dynamic_cgroups(), 2);
+        assert!(manager.get_cgroup("throttle-2").is_some());
+
+        let report = manager.health_check().unwrap();
+        assert_eq!(report.total_evicted, 1);
+        assert_eq!(report.recently_reaped, vec!["throttle-1".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&manager.root_path);
+    }
+
     #[test]
     fn test_default_config() {
         let config = CgroupConfig::default();
@@ -712,6 +1961,28 @@ mod tests {
         assert!(config.auto_cleanup_on_stop);
     }
 
+    #[test]
+    fn test_dynamic_default_limits() {
+        let settings = DynamicCgroupSettings::default();
+        let limits = settings.default_limits();
+
+        assert_eq!(limits.cpu_limit_percent, Some(50.0));
+        assert_eq!(limits.memory_high, Some(1024 * 1024 * 1024));
+        assert_eq!(limits.memory_max, None);
+        assert_eq!(settings.release_cooldown_secs, 60);
+    }
+
+    #[test]
+    fn test_dynamic_settings_without_cooldown() {
+        let settings: DynamicCgroupSettings = serde_json::from_str(
+            r#"{"max_dynamic_cgroups": 10, "cleanup_timeout_secs": 60,
+                "default_cpu_limit": 25.0, "default_memory_limit_mb": 512}"#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.release_cooldown_secs, 60);
+    }
+
     #[test]
     fn test_health_status() {
         let healthy = HealthStatus::Healthy;
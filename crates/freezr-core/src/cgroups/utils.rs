@@ -1,8 +1,10 @@
 //! Utility functions for cgroup operations
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+use super::cgroupfs;
 use super::error::{CgroupError, Result};
 
 /// Convert CPU percentage to cgroup quota/period microseconds
@@ -176,9 +178,91 @@ pub fn get_process_cgroup(pid: u32) -> Result<String> {
     )))
 }
 
+/// Process from the /proc table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcEntry {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Process name (comm)
+    pub name: String,
+}
+
+/// All processes in /proc with their parent PID and name
+pub fn list_processes() -> Vec<ProcEntry> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| {
+            let (name, ppid) = read_stat_fields(pid)?;
+            Some(ProcEntry { pid, ppid, name })
+        })
+        .collect()
+}
+
+/// PIDs of a process and all its descendants (root first)
+///
+/// Built from the parent PID of every process in /proc.
+pub fn process_tree(root: u32) -> Vec<u32> {
+    descendants(&list_processes(), &[root])
+}
+
+/// PIDs of `roots` and all their descendants in `table` (roots first)
+pub fn descendants(table: &[ProcEntry], roots: &[u32]) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for entry in table {
+        children.entry(entry.ppid).or_default().push(entry.pid);
+    }
+
+    let mut tree = roots.to_vec();
+    let mut i = 0;
+    while i < tree.len() {
+        if let Some(kids) = children.get(&tree[i]) {
+            for &kid in kids {
+                // A matching child is also a root: keep each PID once
+                if !tree.contains(&kid) {
+                    tree.push(kid);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    tree
+}
+
+/// Parent PID from /proc/[pid]/stat
+pub fn read_ppid(pid: u32) -> Option<u32> {
+    read_stat_fields(pid).map(|(_, ppid)| ppid)
+}
+
+/// Start time (clock ticks after boot) from /proc/[pid]/stat
+///
+/// Together with the PID it identifies a process across PID reuse.
+pub fn read_start_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let close = stat.rfind(')')?;
+    // Field 22 of stat, the 20th after the comm
+    stat[close + 1..].split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Name (comm) and parent PID from /proc/[pid]/stat
+fn read_stat_fields(pid: u32) -> Option<(String, u32)> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // comm may contain spaces and parentheses: fields start after the last ')'
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let ppid = stat[close + 1..].split_whitespace().nth(1)?.parse().ok()?;
+    Some((name, ppid))
+}
+
 /// Safe read file to string
 pub fn read_cgroup_file(path: &Path) -> Result<String> {
-    fs::read_to_string(path).map_err(|e| {
+    cgroupfs::read_to_string(path).map_err(|e| {
         if e.kind() == std::io::ErrorKind::NotFound {
             CgroupError::NotFound(format!("{:?}", path))
         } else if e.kind() == std::io::ErrorKind::PermissionDenied {
@@ -191,7 +275,7 @@ pub fn read_cgroup_file(path: &Path) -> Result<String> {
 
 /// Safe write file
 pub fn write_cgroup_file(path: &Path, content: &str) -> Result<()> {
-    fs::write(path, content).map_err(|e| {
+    cgroupfs::write(path, content).map_err(|e| {
         if e.kind() == std::io::ErrorKind::PermissionDenied {
             CgroupError::PermissionDenied(format!("{:?}", path))
         } else {
@@ -283,6 +367,55 @@ file_writeback 512"#;
         assert_eq!(values.kernel_stack, 65536);
     }
 
+    #[test]
+    fn test_process_tree() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+
+        let tree = process_tree(std::process::id());
+        assert_eq!(tree[0], std::process::id());
+        assert!(tree.contains(&child.id()));
+
+        // Leaf process: only itself
+        assert_eq!(process_tree(child.id()), vec![child.id()]);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_descendants() {
+        let entry = |pid, ppid| ProcEntry {
+            pid,
+            ppid,
+            name: "test".to_string(),
+        };
+        let table = vec![entry(10, 1), entry(11, 10), entry(12, 11), entry(20, 1)];
+
+        assert_eq!(descendants(&table, &[10]), vec![10, 11, 12]);
+        // Root that is also a descendant of another root appears once
+        assert_eq!(descendants(&table, &[10, 11, 20]), vec![10, 11, 20, 12]);
+    }
+
+    #[test]
+    fn test_list_processes_contains_self() {
+        let pid = std::process::id();
+        let me = list_processes().into_iter().find(|p| p.pid == pid).unwrap();
+
+        assert_eq!(Some(me.ppid), read_ppid(pid));
+        assert!(!me.name.is_empty());
+    }
+
+    #[test]
+    fn test_read_start_ticks() {
+        let own = read_start_ticks(std::process::id()).unwrap();
+        assert!(own > 0);
+        assert_eq!(read_start_ticks(std::process::id()), Some(own));
+        assert!(read_start_ticks(u32::MAX).is_none());
+    }
+
     #[test]
     fn test_process_exists() {
         // PID 1 should always exist (init/systemd)
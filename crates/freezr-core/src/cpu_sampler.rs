@@ -0,0 +1,339 @@
+//! Stateful CPU sampler
+//!
+//! Computes per-process CPU usage from the delta of utime+stime between
+//! consecutive scans, relative to the delta of total system jiffies from
+//! /proc/stat. No sleeping is required: the interval between two monitoring
+//! cycles is the measurement window.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+
+/// Minimum window (jiffies per CPU) for a new measurement
+///
+/// Scans closer together than this (e.g. stats export right after a check)
+/// return the last smoothed value instead of a noisy near-zero delta.
+const MIN_INTERVAL_JIFFIES_PER_CPU: u64 = 10;
+
+/// Smoothing applied to consecutive CPU samples
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CpuSmoothing {
+    /// Raw delta of the last interval
+    None,
+    /// Exponentially weighted moving average (alpha in 0.0-1.0, higher = more reactive)
+    Ewma { alpha: f64 },
+    /// Mean of the last N samples
+    Mean { samples: usize },
+}
+
+impl Default for CpuSmoothing {
+    fn default() -> Self {
+        // Matches the previous 3-sample averaging used for KESL
+        CpuSmoothing::Mean { samples: 3 }
+    }
+}
+
+impl CpuSmoothing {
+    /// Validate smoothing parameters
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            CpuSmoothing::None => Ok(()),
+            CpuSmoothing::Ewma { alpha } if alpha > 0.0 && alpha <= 1.0 => Ok(()),
+            CpuSmoothing::Ewma { alpha } => Err(Error::Other(format!(
+                "EWMA alpha must be in (0, 1], got {}",
+                alpha
+            ))),
+            CpuSmoothing::Mean { samples } if samples > 0 => Ok(()),
+            CpuSmoothing::Mean { .. } => {
+                Err(Error::Other("Mean window must be > 0 samples".to_string()))
+            }
+        }
+    }
+}
+
+/// Total CPU time of the system from /proc/stat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemCpuTicks {
+    /// Sum of all jiffies over all CPUs
+    pub total: u64,
+    /// Number of CPUs listed in /proc/stat
+    pub num_cpus: u32,
+}
+
+impl SystemCpuTicks {
+    /// Read /proc/stat
+    pub fn read() -> Result<Self> {
+        let content = fs::read_to_string("/proc/stat")
+            .map_err(|e| Error::Scanner(format!("Failed to read /proc/stat: {}", e)))?;
+
+        Self::parse(&content)
+    }
+
+    /// Parse /proc/stat
+    ///
+    /// Format:
+    /// ```text
+    /// cpu  user nice system idle iowait irq softirq steal guest guest_nice
+    /// cpu0 ...
+    /// ```
+    fn parse(content: &str) -> Result<Self> {
+        let mut total = None;
+        let mut num_cpus = 0;
+
+        for line in content.lines() {
+            if let Some(rest) = line.strip_prefix("cpu ") {
+                // guest and guest_nice are already included in user/nice
+                let sum = rest
+                    .split_whitespace()
+                    .take(8)
+                    .map(|v| v.parse::<u64>())
+                    .sum::<std::result::Result<u64, _>>()
+                    .map_err(|e| Error::Parse(format!("Invalid /proc/stat cpu line: {}", e)))?;
+                total = Some(sum);
+            } else if line.starts_with("cpu") {
+                num_cpus += 1;
+            }
+        }
+
+        let total =
+            total.ok_or_else(|| Error::Parse("Missing cpu line in /proc/stat".to_string()))?;
+
+        Ok(Self {
+            total,
+            num_cpus: num_cpus.max(1),
+        })
+    }
+}
+
+/// Per-PID state remembered between scans
+#[derive(Debug, Clone)]
+struct PidState {
+    start_ticks: u64,
+    cpu_ticks: u64,
+    system_ticks: u64,
+    history: VecDeque<f64>,
+    smoothed: Option<f64>,
+}
+
+/// Delta-based CPU sampler
+///
+/// CPU percent is relative to one core (100% = one core fully used), like top.
+#[derive(Debug, Clone)]
+pub struct CpuSampler {
+    smoothing: CpuSmoothing,
+    states: HashMap<u32, PidState>,
+}
+
+impl Default for CpuSampler {
+    fn default() -> Self {
+        Self::new(CpuSmoothing::default())
+    }
+}
+
+impl CpuSampler {
+    pub fn new(smoothing: CpuSmoothing) -> Self {
+        Self {
+            smoothing,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Current smoothing mode
+    pub fn smoothing(&self) -> CpuSmoothing {
+        self.smoothing
+    }
+
+    /// Record a new observation of a process
+    ///
+    /// Returns `None` on the first observation of a PID (no baseline yet).
+    ///
+    /// # Arguments
+    /// * `pid` - Process ID
+    /// * `start_ticks` - Process start time (detects PID reuse)
+    /// * `cpu_ticks` - utime + stime of the process
+    /// * `system` - Current system-wide jiffies
+    pub fn sample(
+        &mut self,
+        pid: u32,
+        start_ticks: u64,
+        cpu_ticks: u64,
+        system: SystemCpuTicks,
+    ) -> Option<f64> {
+        let state = match self.states.get_mut(&pid) {
+            Some(state) if state.start_ticks == start_ticks => state,
+            _ => {
+                // New process (or PID reused): remember baseline only
+                self.states.insert(
+                    pid,
+                    PidState {
+                        start_ticks,
+                        cpu_ticks,
+                        system_ticks: system.total,
+                        history: VecDeque::new(),
+                        smoothed: None,
+                    },
+                );
+                return None;
+            }
+        };
+
+        let system_delta = system.total.saturating_sub(state.system_ticks);
+        if system_delta < MIN_INTERVAL_JIFFIES_PER_CPU * system.num_cpus as u64 {
+            return state.smoothed;
+        }
+
+        let cpu_delta = cpu_ticks.saturating_sub(state.cpu_ticks);
+        let raw = cpu_delta as f64 / system_delta as f64 * system.num_cpus as f64 * 100.0;
+
+        state.cpu_ticks = cpu_ticks;
+        state.system_ticks = system.total;
+
+        let smoothed = match self.smoothing {
+            CpuSmoothing::None => raw,
+            CpuSmoothing::Ewma { alpha } => match state.smoothed {
+                Some(prev) => alpha * raw + (1.0 - alpha) * prev,
+                None => raw,
+            },
+            CpuSmoothing::Mean { samples } => {
+                state.history.push_back(raw);
+                while state.history.len() > samples.max(1) {
+                    state.history.pop_front();
+                }
+                state.history.iter().sum::<f64>() / state.history.len() as f64
+            }
+        };
+
+        state.smoothed = Some(smoothed);
+        Some(smoothed)
+    }
+
+    /// Forget PIDs that are no longer running
+    pub fn retain_pids(&mut self, alive: &HashSet<u32>) {
+        self.states.retain(|pid, _| alive.contains(pid));
+    }
+
+    /// Number of tracked PIDs
+    pub fn tracked_count(&self) -> usize {
+        self.states.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(total: u64) -> SystemCpuTicks {
+        SystemCpuTicks { total, num_cpus: 4 }
+    }
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let content = "cpu  100 20 30 800 10 5 5 0 50 0\n\
+                       cpu0 50 10 15 400 5 2 3 0 25 0\n\
+                       cpu1 50 10 15 400 5 3 2 0 25 0\n\
+                       intr 12345\n";
+
+        let ticks = SystemCpuTicks::parse(content).unwrap();
+        assert_eq!(ticks.total, 970); // guest columns excluded
+        assert_eq!(ticks.num_cpus, 2);
+    }
+
+    #[test]
+    fn test_parse_proc_stat_missing_cpu_line() {
+        assert!(SystemCpuTicks::parse("intr 12345\n").is_err());
+    }
+
+    #[test]
+    fn test_first_sample_has_no_baseline() {
+        let mut sampler = CpuSampler::new(CpuSmoothing::None);
+        assert_eq!(sampler.sample(42, 1000, 500, system(10_000)), None);
+        assert_eq!(sampler.tracked_count(), 1);
+    }
+
+    #[test]
+    fn test_delta_cpu_percent() {
+        let mut sampler = CpuSampler::new(CpuSmoothing::None);
+        sampler.sample(42, 1000, 500, system(10_000));
+
+        // 400 jiffies over 4 CPUs = 100 jiffies of wall time; process used 50 -> 50%
+        let cpu = sampler.sample(42, 1000, 550, system(10_400)).unwrap();
+        assert!((cpu - 50.0).abs() < 0.001);
+
+        // Multi-core usage can exceed 100%
+        let cpu = sampler.sample(42, 1000, 750, system(10_800)).unwrap();
+        assert!((cpu - 200.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_short_interval_returns_previous_value() {
+        let mut sampler = CpuSampler::new(CpuSmoothing::None);
+        sampler.sample(42, 1000, 500, system(10_000));
+        let cpu = sampler.sample(42, 1000, 550, system(10_400)).unwrap();
+
+        // Only 4 jiffies later: too short to measure
+        assert_eq!(sampler.sample(42, 1000, 551, system(10_404)), Some(cpu));
+    }
+
+    #[test]
+    fn test_pid_reuse_resets_baseline() {
+        let mut sampler = CpuSampler::new(CpuSmoothing::None);
+        sampler.sample(42, 1000, 500, system(10_000));
+        sampler.sample(42, 1000, 550, system(10_400));
+
+        // Same PID, different start time -> different process
+        assert_eq!(sampler.sample(42, 2000, 10, system(10_800)), None);
+    }
+
+    #[test]
+    fn test_mean_smoothing() {
+        let mut sampler = CpuSampler::new(CpuSmoothing::Mean { samples: 2 });
+        sampler.sample(42, 1000, 0, system(0));
+
+        assert!((sampler.sample(42, 1000, 100, system(400)).unwrap() - 100.0).abs() < 0.001);
+        assert!((sampler.sample(42, 1000, 100, system(800)).unwrap() - 50.0).abs() < 0.001);
+        // Oldest sample (100%) dropped from the window
+        assert!((sampler.sample(42, 1000, 100, system(1200)).unwrap() - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_ewma_smoothing() {
+        let mut sampler = CpuSampler::new(CpuSmoothing::Ewma { alpha: 0.5 });
+        sampler.sample(42, 1000, 0, system(0));
+
+        assert!((sampler.sample(42, 1000, 100, system(400)).unwrap() - 100.0).abs() < 0.001);
+        assert!((sampler.sample(42, 1000, 100, system(800)).unwrap() - 50.0).abs() < 0.001);
+        assert!((sampler.sample(42, 1000, 100, system(1200)).unwrap() - 25.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_retain_pids() {
+        let mut sampler = CpuSampler::default();
+        sampler.sample(1, 0, 0, system(0));
+        sampler.sample(2, 0, 0, system(0));
+
+        let alive: HashSet<u32> = [2].into_iter().collect();
+        sampler.retain_pids(&alive);
+        assert_eq!(sampler.tracked_count(), 1);
+    }
+
+    #[test]
+    fn test_smoothing_validation() {
+        assert!(CpuSmoothing::None.validate().is_ok());
+        assert!(CpuSmoothing::Ewma { alpha: 0.3 }.validate().is_ok());
+        assert!(CpuSmoothing::Ewma { alpha: 0.0 }.validate().is_err());
+        assert!(CpuSmoothing::Ewma { alpha: 1.5 }.validate().is_err());
+        assert!(CpuSmoothing::Mean { samples: 3 }.validate().is_ok());
+        assert!(CpuSmoothing::Mean { samples: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_smoothing_serde() {
+        let smoothing: CpuSmoothing = toml::from_str("type = \"ewma\"\nalpha = 0.3").unwrap();
+        assert_eq!(smoothing, CpuSmoothing::Ewma { alpha: 0.3 });
+
+        let smoothing: CpuSmoothing = toml::from_str("type = \"mean\"\nsamples = 5").unwrap();
+        assert_eq!(smoothing, CpuSmoothing::Mean { samples: 5 });
+    }
+}
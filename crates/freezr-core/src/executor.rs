@@ -1,10 +1,11 @@
-use crate::{Error, Result};
-use nix::sys::signal::{kill, Signal};
-use nix::unistd::Pid;
-use std::thread;
+use crate::{process_handle::ProcessHandle, Error, Result};
+use nix::sys::signal::Signal;
 use std::time::Duration;
 
 /// Process action executor
+///
+/// All actions take a `ProcessHandle`, so the signal reaches exactly the
+/// scanned process, even if its PID was reused.
 pub struct ProcessExecutor;
 
 impl ProcessExecutor {
@@ -15,44 +16,37 @@ impl ProcessExecutor {
     /// Kill a process (SIGTERM, then SIGKILL if needed)
     ///
     /// Algorithm:
-    /// 1. Check if process exists
+    /// 1. Check if process is alive
     /// 2. Send SIGTERM (graceful termination)
-    /// 3. Wait 2 seconds
+    /// 3. Wait up to 2 seconds for exit (pidfd poll, no PID re-lookup)
     /// 4. If process still alive, send SIGKILL (forced termination)
-    pub fn kill_process(pid: u32) -> Result<()> {
-        let process_pid = Pid::from_raw(pid as i32);
+    pub fn kill_process(process: &ProcessHandle) -> Result<()> {
+        let pid = process.pid();
 
-        // Check if process exists (send signal 0)
-        if !Self::process_exists(pid)? {
-            return Err(Error::Executor(format!(
-                "Process {} does not exist",
-                pid
-            )));
+        if !process.is_alive() {
+            return Err(Error::Executor(format!("Process {} does not exist", pid)));
         }
 
         // Step 1: Try graceful termination (SIGTERM)
-        kill(process_pid, Signal::SIGTERM).map_err(|e| {
-            Error::Executor(format!("Failed to send SIGTERM to process {}: {}", pid, e))
-        })?;
+        process.send_signal(Signal::SIGTERM)?;
 
-        // Wait 2 seconds for termination
-        thread::sleep(Duration::from_secs(2));
-
-        // Check if process terminated
-        if !Self::process_exists(pid)? {
+        // Wait up to 2 seconds for termination
+        if process.wait_exit(Duration::from_secs(2)) {
             // Process successfully terminated after SIGTERM
             return Ok(());
         }
 
         // Step 2: Force termination (SIGKILL)
-        kill(process_pid, Signal::SIGKILL).map_err(|e| {
-            Error::Executor(format!("Failed to send SIGKILL to process {}: {}", pid, e))
-        })?;
+        if let Err(e) = process.send_signal(Signal::SIGKILL) {
+            // Exited between the check and SIGKILL
+            if !process.is_alive() {
+                return Ok(());
+            }
+            return Err(e);
+        }
 
         // Final check
-        thread::sleep(Duration::from_millis(500));
-
-        if Self::process_exists(pid)? {
+        if !process.wait_exit(Duration::from_millis(500)) {
             return Err(Error::Executor(format!(
                 "Failed to kill process {} even with SIGKILL",
                 pid
@@ -62,61 +56,26 @@ impl ProcessExecutor {
         Ok(())
     }
 
-    /// Check if process exists
-    ///
-    /// Uses kill(pid, 0) - doesn't kill the process, only checks existence
-    fn process_exists(pid: u32) -> Result<bool> {
-        let process_pid = Pid::from_raw(pid as i32);
-
-        match kill(process_pid, None) {
-            Ok(_) => Ok(true), // Process exists
-            Err(nix::errno::Errno::ESRCH) => Ok(false), // Process not found
-            Err(nix::errno::Errno::EPERM) => Ok(true), // No permission, but process exists
-            Err(e) => Err(Error::Executor(format!(
-                "Failed to check process {}: {}",
-                pid, e
-            ))),
-        }
-    }
-
     /// Freeze process (SIGSTOP)
     ///
-    /// For future use in FreezR - temporary process suspension
-    pub fn freeze_process(pid: u32) -> Result<()> {
-        let process_pid = Pid::from_raw(pid as i32);
-
-        if !Self::process_exists(pid)? {
-            return Err(Error::Executor(format!(
-                "Process {} does not exist",
-                pid
-            )));
-        }
-
-        kill(process_pid, Signal::SIGSTOP).map_err(|e| {
-            Error::Executor(format!("Failed to freeze process {}: {}", pid, e))
-        })?;
-
-        Ok(())
+    /// Temporary process suspension, keep the handle to unfreeze the same process
+    pub fn freeze_process(process: &ProcessHandle) -> Result<()> {
+        process.send_signal(Signal::SIGSTOP).map_err(|e| {
+            Error::Executor(format!("Failed to freeze process {}: {}", process.pid(), e))
+        })
     }
 
     /// Unfreeze process (SIGCONT)
     ///
-    /// For future use in FreezR - resume suspended process
-    pub fn unfreeze_process(pid: u32) -> Result<()> {
-        let process_pid = Pid::from_raw(pid as i32);
-
-        if !Self::process_exists(pid)? {
-            return Err(Error::Executor(format!(
-                "Process {} does not exist",
-                pid
-            )));
-        }
-
-        kill(process_pid, Signal::SIGCONT).map_err(|e| {
-            Error::Executor(format!("Failed to unfreeze process {}: {}", pid, e))
-        })?;
-
-        Ok(())
+    /// Resume suspended process
+    pub fn unfreeze_process(process: &ProcessHandle) -> Result<()> {
+        process.send_signal(Signal::SIGCONT).map_err(|e| {
+            Error::Executor(format!(
+                "Failed to unfreeze process {}: {}",
+                process.pid(),
+                e
+            ))
+        })
     }
 
     /// Set process nice level (priority)
@@ -126,12 +85,14 @@ impl ProcessExecutor {
     ///
     /// Uses libc setpriority() directly instead of sudo renice command
     /// Requires CAP_SYS_NICE capability (configured in systemd service)
-    pub fn renice_process(pid: u32, nice_level: i32) -> Result<()> {
-        if !Self::process_exists(pid)? {
-            return Err(Error::Executor(format!(
-                "Process {} does not exist",
-                pid
-            )));
+    ///
+    /// setpriority() has no pidfd variant: the handle is checked right before
+    /// the call, which narrows the PID reuse window to a single syscall
+    pub fn renice_process(process: &ProcessHandle, nice_level: i32) -> Result<()> {
+        let pid = process.pid();
+
+        if !process.is_alive() {
+            return Err(Error::Executor(format!("Process {} does not exist", pid)));
         }
 
         // Validate nice level
@@ -145,7 +106,11 @@ impl ProcessExecutor {
         // Use direct libc setpriority() syscall
         // PRIO_PROCESS = 0, pid, nice_level
         let result = unsafe {
-            libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice_level as libc::c_int)
+            libc::setpriority(
+                libc::PRIO_PROCESS,
+                pid as libc::id_t,
+                nice_level as libc::c_int,
+            )
         };
 
         if result == -1 {
@@ -169,78 +134,75 @@ mod tests {
     #[test]
     fn test_executor_creation() {
         let _executor = ProcessExecutor::new();
-        assert!(true);
     }
 
     #[test]
-    fn test_process_exists_invalid_pid() {
+    fn test_open_invalid_pid() {
         // PID 999999 most likely doesn't exist
-        let exists = ProcessExecutor::process_exists(999999).unwrap_or(true);
-        assert!(!exists);
+        assert!(ProcessHandle::open(999999).is_err());
     }
 
     #[test]
-    fn test_process_exists_init_process() {
+    fn test_open_init_process() {
         // PID 1 (init/systemd) always exists
-        let exists = ProcessExecutor::process_exists(1).unwrap_or(false);
-        assert!(exists);
+        let handle = ProcessHandle::open(1).unwrap();
+        assert!(handle.is_alive());
     }
 
     #[test]
-    #[ignore] // Requires spawning test process
     fn test_kill_process_workflow() {
         // Spawn test process (sleep 60)
-        let child = Command::new("sleep")
+        let mut child = Command::new("sleep")
             .arg("60")
             .spawn()
             .expect("Failed to spawn test process");
 
-        let pid = child.id();
-
-        // Check that process exists
-        assert!(ProcessExecutor::process_exists(pid).unwrap());
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        assert!(handle.is_alive());
 
-        // Kill process
-        ProcessExecutor::kill_process(pid).expect("Failed to kill process");
+        // Kill process (exits on SIGTERM, no 2s sleep needed)
+        ProcessExecutor::kill_process(&handle).expect("Failed to kill process");
+        assert!(!handle.is_alive());
 
-        // Check that process no longer exists
-        thread::sleep(Duration::from_millis(100));
-        assert!(!ProcessExecutor::process_exists(pid).unwrap());
+        child.wait().unwrap();
     }
 
     #[test]
-    #[ignore] // Requires spawning test process
     fn test_freeze_unfreeze_workflow() {
         // Spawn test process
-        let child = Command::new("sleep")
+        let mut child = Command::new("sleep")
             .arg("60")
             .spawn()
             .expect("Failed to spawn test process");
 
-        let pid = child.id();
+        let handle = ProcessHandle::open(child.id()).unwrap();
 
         // Freeze process
-        ProcessExecutor::freeze_process(pid).expect("Failed to freeze");
+        ProcessExecutor::freeze_process(&handle).expect("Failed to freeze");
 
         // Process should exist but be stopped
-        assert!(ProcessExecutor::process_exists(pid).unwrap());
+        assert!(handle.is_alive());
 
         // Unfreeze process
-        ProcessExecutor::unfreeze_process(pid).expect("Failed to unfreeze");
+        ProcessExecutor::unfreeze_process(&handle).expect("Failed to unfreeze");
 
         // Kill process for cleanup
-        ProcessExecutor::kill_process(pid).expect("Failed to kill");
+        ProcessExecutor::kill_process(&handle).expect("Failed to kill");
+        child.wait().unwrap();
     }
 
     #[test]
-    fn test_kill_nonexistent_process() {
-        // Try to kill nonexistent process
-        let result = ProcessExecutor::kill_process(999999);
+    fn test_actions_on_exited_process() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        child.wait().unwrap();
+
+        // PID may be reused now - the handle must refuse to act
+        let result = ProcessExecutor::kill_process(&handle);
         assert!(result.is_err());
+        assert!(format!("{}", result.unwrap_err()).contains("does not exist"));
 
-        if let Err(e) = result {
-            let error_msg = format!("{}", e);
-            assert!(error_msg.contains("does not exist"));
-        }
+        assert!(ProcessExecutor::freeze_process(&handle).is_err());
+        assert!(ProcessExecutor::renice_process(&handle, 10).is_err());
     }
 }
@@ -0,0 +1,370 @@
+//! Persistent registry of frozen processes
+//!
+//! Every SIGSTOP sent by the daemon is journaled to a state file together with
+//! the process identity (PID + start time) and the planned thaw time. If the
+//! daemon dies while a process is frozen (release builds use `panic = "abort"`),
+//! the next start - or `freezr frozen --thaw` - resumes every leftover process.
+
+use crate::{
+    cgroups::FreezerController, error::Error, executor::ProcessExecutor,
+    process_handle::ProcessHandle, Result,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Default location of the registry (tmpfs, cleared on reboot)
+pub const DEFAULT_REGISTRY_PATH: &str = "/run/freezr/frozen.json";
+
+/// One process frozen by the daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrozenEntry {
+    pub pid: u32,
+    /// Start time from /proc/[pid]/stat (detects PID reuse)
+    pub start_ticks: u64,
+    /// Process name at freeze time
+    pub name: String,
+    pub frozen_at: DateTime<Utc>,
+    /// When the daemon planned to unfreeze the process
+    pub thaw_at: DateTime<Utc>,
+    /// Dynamic cgroup frozen through `cgroup.freeze` (None = SIGSTOP)
+    #[serde(default)]
+    pub cgroup: Option<PathBuf>,
+}
+
+impl FrozenEntry {
+    /// Current state, with a handle to the process if it is still alive
+    fn state(&self) -> (LeftoverState, Option<ProcessHandle>) {
+        // A frozen cgroup is a leftover even if the root process exited:
+        // its children may still be frozen
+        if let Some(cgroup) = &self.cgroup {
+            if FreezerController::is_frozen(cgroup).unwrap_or(false) {
+                return (LeftoverState::Stopped, None);
+            }
+        }
+
+        // open_expected refuses a process that merely reused the PID
+        match ProcessHandle::open_expected(self.pid, self.start_ticks) {
+            Ok(handle) if self.cgroup.is_none() && handle.is_stopped() => {
+                (LeftoverState::Stopped, Some(handle))
+            }
+            Ok(handle) => (LeftoverState::Running, Some(handle)),
+            Err(_) => (LeftoverState::Gone, None),
+        }
+    }
+}
+
+/// Current state of a journaled process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeftoverState {
+    /// Same process, still SIGSTOPped
+    Stopped,
+    /// Same process, already running again
+    Running,
+    /// Process exited (or PID now belongs to another process)
+    Gone,
+}
+
+/// State file with frozen processes
+#[derive(Debug, Clone)]
+pub struct FrozenRegistry {
+    path: PathBuf,
+}
+
+impl Default for FrozenRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_REGISTRY_PATH)
+    }
+}
+
+impl FrozenRegistry {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read all journaled entries (missing file = no entries)
+    pub fn load(&self) -> Result<Vec<FrozenEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        serde_json::from_str(&content).map_err(|e| {
+            Error::Parse(format!(
+                "Invalid frozen registry {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Replace the journal with `entries`
+    ///
+    /// Written to a temporary file and renamed, so a crash never leaves a
+    /// truncated journal behind.
+    pub fn save(&self, entries: &[FrozenEntry]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let json = serde_json::to_string_pretty(entries)
+            .map_err(|e| Error::Other(format!("Failed to serialize frozen registry: {}", e)))?;
+
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Add or update an entry (keyed by PID)
+    pub fn record(&self, entry: FrozenEntry) -> Result<()> {
+        let mut entries = self.load()?;
+        entries.retain(|e| e.pid != entry.pid);
+        entries.push(entry);
+        self.save(&entries)
+    }
+
+    /// Remove the entry of this exact process
+    pub fn remove(&self, pid: u32, start_ticks: u64) -> Result<()> {
+        let mut entries = self.load()?;
+        let before = entries.len();
+        entries.retain(|e| !(e.pid == pid && e.start_ticks == start_ticks));
+
+        if entries.len() != before {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+
+    /// Journaled entries with the current state of each process
+    pub fn inspect(&self) -> Result<Vec<(FrozenEntry, LeftoverState)>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .map(|entry| {
+                let (state, _) = entry.state();
+                (entry, state)
+            })
+            .collect())
+    }
+
+    /// Resume every journaled process that is still stopped, then clear the journal
+    ///
+    /// Cgroup-frozen entries are thawed by clearing `cgroup.freeze`, which
+    /// resumes the whole tree. Recovery is best-effort: a failing entry is
+    /// logged and stays in the journal (unless its process is gone by now),
+    /// the remaining entries are still thawed. Returns the entries that were
+    /// thawed.
+    pub fn thaw_leftovers(&self) -> Result<Vec<FrozenEntry>> {
+        let entries = self.load()?;
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut thawed = Vec::new();
+        let mut failed = Vec::new();
+        for entry in entries {
+            let result = match (entry.state(), &entry.cgroup) {
+                ((LeftoverState::Stopped, _), Some(cgroup)) => {
+                    FreezerController::thaw(cgroup).map_err(Error::from)
+                }
+                ((LeftoverState::Stopped, Some(handle)), None) => {
+                    ProcessExecutor::unfreeze_process(&handle)
+                }
+                _ => continue,
+            };
+
+            match result {
+                Ok(()) => thawed.push(entry),
+                // Exited in the meantime: nothing left to thaw
+                Err(_) if entry.state().0 == LeftoverState::Gone => {}
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to thaw leftover process {} ({}): {}",
+                        entry.pid,
+                        entry.name,
+                        e
+                    );
+                    failed.push(entry);
+                }
+            }
+        }
+
+        self.save(&failed)?;
+        Ok(thawed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::sys::signal::Signal;
+    use std::process::{Child, Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    fn temp_registry(name: &str) -> FrozenRegistry {
+        let dir =
+            std::env::temp_dir().join(format!("freezr-registry-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        FrozenRegistry::new(dir.join("frozen.json"))
+    }
+
+    fn spawn_sleep() -> Child {
+        Command::new("sleep")
+            .arg("60")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to spawn test process")
+    }
+
+    fn entry_for(handle: &ProcessHandle) -> FrozenEntry {
+        FrozenEntry {
+            pid: handle.pid(),
+            start_ticks: handle.start_ticks(),
+            name: "sleep".to_string(),
+            frozen_at: Utc::now(),
+            thaw_at: Utc::now() + chrono::Duration::seconds(5),
+            cgroup: None,
+        }
+    }
+
+    fn wait_stopped(handle: &ProcessHandle, stopped: bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while handle.is_stopped() != stopped {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        true
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let registry = temp_registry("missing");
+        assert!(registry.load().unwrap().is_empty());
+        assert!(registry.thaw_leftovers().unwrap().is_empty());
+        assert!(!registry.path().exists());
+    }
+
+    #[test]
+    fn test_record_and_remove() {
+        let registry = temp_registry("record");
+        let handle = ProcessHandle::open(std::process::id()).unwrap();
+        let entry = entry_for(&handle);
+
+        registry.record(entry.clone()).unwrap();
+        // Re-recording the same PID replaces the entry (extended freeze)
+        registry.record(entry.clone()).unwrap();
+        assert_eq!(registry.load().unwrap(), vec![entry.clone()]);
+
+        // Different start time: another process, not removed
+        registry.remove(entry.pid, entry.start_ticks + 1).unwrap();
+        assert_eq!(registry.load().unwrap().len(), 1);
+
+        registry.remove(entry.pid, entry.start_ticks).unwrap();
+        assert!(registry.load().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(registry.path().parent().unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_file_is_error() {
+        let registry = temp_registry("corrupt");
+        fs::create_dir_all(registry.path().parent().unwrap()).unwrap();
+        fs::write(registry.path(), "{ not json").unwrap();
+
+        assert!(registry.load().is_err());
+
+        let _ = fs::remove_dir_all(registry.path().parent().unwrap());
+    }
+
+    #[test]
+    fn test_thaw_leftovers() {
+        let registry = temp_registry("thaw");
+        let mut child = spawn_sleep();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+
+        handle.send_signal(Signal::SIGSTOP).unwrap();
+        assert!(wait_stopped(&handle, true));
+        registry.record(entry_for(&handle)).unwrap();
+
+        let leftovers = registry.inspect().unwrap();
+        assert_eq!(leftovers.len(), 1);
+        assert_eq!(leftovers[0].1, LeftoverState::Stopped);
+
+        let thawed = registry.thaw_leftovers().unwrap();
+        assert_eq!(thawed.len(), 1);
+        assert!(wait_stopped(&handle, false));
+        assert!(registry.load().unwrap().is_empty());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let _ = fs::remove_dir_all(registry.path().parent().unwrap());
+    }
+
+    #[test]
+    fn test_thaw_leftovers_continues_after_failure() {
+        let registry = temp_registry("partial");
+        let dir = registry.path().parent().unwrap().to_path_buf();
+
+        // Cgroup reported frozen, but cgroup.freeze cannot be written
+        let cgroup = dir.join("broken.scope");
+        fs::create_dir_all(cgroup.join("cgroup.freeze")).unwrap();
+        fs::write(cgroup.join("cgroup.events"), "populated 1\nfrozen 1\n").unwrap();
+        let self_handle = ProcessHandle::open(std::process::id()).unwrap();
+        let broken = FrozenEntry {
+            cgroup: Some(cgroup),
+            ..entry_for(&self_handle)
+        };
+
+        let mut child = spawn_sleep();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        handle.send_signal(Signal::SIGSTOP).unwrap();
+        assert!(wait_stopped(&handle, true));
+
+        registry.record(broken.clone()).unwrap();
+        registry.record(entry_for(&handle)).unwrap();
+
+        let thawed = registry.thaw_leftovers().unwrap();
+        assert_eq!(thawed.len(), 1);
+        assert_eq!(thawed[0].pid, handle.pid());
+        assert!(wait_stopped(&handle, false));
+
+        // Only the failed entry is kept for the next attempt
+        assert_eq!(registry.load().unwrap(), vec![broken]);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_exited_process_is_gone() {
+        let registry = temp_registry("gone");
+        let mut child = spawn_sleep();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        registry.record(entry_for(&handle)).unwrap();
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let leftovers = registry.inspect().unwrap();
+        assert_eq!(leftovers[0].1, LeftoverState::Gone);
+        assert!(registry.thaw_leftovers().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(registry.path().parent().unwrap());
+    }
+}
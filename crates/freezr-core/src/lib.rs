@@ -4,28 +4,51 @@
 //! Provides process scanning, systemd service management, and resource monitoring.
 
 pub mod cgroups;
+pub mod cpu_sampler;
 pub mod error;
 pub mod executor;
+pub mod frozen_registry;
+pub mod meminfo;
 pub mod memory_pressure;
 pub mod ml_types;
+pub mod oom;
+pub mod power;
+pub mod process_handle;
+pub mod profile_request;
+pub mod psi;
 pub mod scanner;
 pub mod systemd;
+pub mod thermal;
 pub mod types;
 
 pub use cgroups::{
-    Cgroup, CgroupConfig, CgroupError as CgroupErr, CgroupManager, CgroupStrategy, CgroupType,
-    CpuController, CpuStats, DynamicCgroupSettings, HealthStatus, MemoryController,
-    MemoryPressure as CgroupMemoryPressure, MemoryStats, ResourceLimits, StaticCgroupConfig,
+    AdaptiveSettings, Cgroup, CgroupConfig, CgroupError as CgroupErr, CgroupEvent, CgroupEventKind,
+    CgroupEvents, CgroupFs, CgroupManager, CgroupStrategy, CgroupType, CgroupWatcher,
+    CpuController, CpuList, CpuStats, CpusetController, DeviceNumber, DynamicCgroupSettings,
+    FreezerController, FrozenTree, HealthReport, HealthStatus, IoController, IoLimit, IoMax,
+    IoStat, MemoryController, MemoryEvents, MemoryStats, OriginJournal, PidsController, PidsEvents,
+    ResourceLimits, StaticCgroupConfig, ThrottledTree,
 };
+#[cfg(any(test, feature = "fake-cgroupfs"))]
+pub use cgroups::{CgroupFsMount, FakeCgroupFs};
+pub use cpu_sampler::{CpuSampler, CpuSmoothing, SystemCpuTicks};
 pub use error::{Error, Result};
 pub use executor::ProcessExecutor;
+pub use frozen_registry::{FrozenEntry, FrozenRegistry, LeftoverState};
+pub use meminfo::MemInfo;
 pub use memory_pressure::MemoryPressure;
 pub use ml_types::{
     EventDetails, EventType, IOStats, ProcessCategory, ProcessDailySummary, ProcessEvent,
     ProcessSnapshot, ProcessState,
 };
-pub use scanner::ProcessScanner;
+pub use oom::OomInfo;
+pub use power::{PowerReader, PowerSource, PowerState, PowerSupply, SupplyKind};
+pub use process_handle::ProcessHandle;
+pub use profile_request::{ProfileRequest, ProfileRequests};
+pub use psi::{PressureStall, PsiResource, PsiTrigger, StallKind};
+pub use scanner::{ProcessScan, ProcessScanner};
 pub use systemd::SystemdService;
+pub use thermal::{SensorSource, ThermalReader, ThermalSensor, TripPoint};
 pub use types::{MonitorStats, ProcessInfo};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
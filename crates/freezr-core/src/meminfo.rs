@@ -0,0 +1,167 @@
+//! System memory from /proc/meminfo
+//!
+//! earlyoom-style triggers work where PSI does not: with `psi=0`, and on
+//! swapless machines where memory runs out before stall time builds up.
+//!
+//! All values are in KB as the kernel reports them. Fields missing on older
+//! kernels read as zero, except MemAvailable (Linux 3.14+), which is then
+//! estimated as MemFree + Buffers + Cached.
+
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::Path;
+
+/// Snapshot of /proc/meminfo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemInfo {
+    pub mem_total_kb: u64,
+    pub mem_free_kb: u64,
+    /// Memory available for new allocations without swapping
+    pub mem_available_kb: u64,
+    pub buffers_kb: u64,
+    /// Page cache, Shmem included
+    pub cached_kb: u64,
+    pub swap_total_kb: u64,
+    pub swap_free_kb: u64,
+    /// Waiting to be written back to disk
+    pub dirty_kb: u64,
+    /// Being written back to disk
+    pub writeback_kb: u64,
+    pub anon_pages_kb: u64,
+    /// tmpfs and shared memory, not freeable by dropping caches
+    pub shmem_kb: u64,
+    /// Reclaimable kernel slab (dentries, inodes)
+    pub slab_reclaimable_kb: u64,
+}
+
+impl MemInfo {
+    /// Read /proc/meminfo
+    pub fn read() -> Result<Self> {
+        Self::read_from(Path::new("/proc/meminfo"))
+    }
+
+    /// Read a meminfo file (e.g. a fixture in tests)
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("Failed to read {}: {}", path.display(), e)))?;
+        Self::parse(&content)
+    }
+
+    /// Parse meminfo format ("MemTotal:       16314828 kB")
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut info = Self::default();
+        let mut mem_available = None;
+
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let Some(kb) = value.split_whitespace().next().and_then(|v| v.parse().ok()) else {
+                continue;
+            };
+
+            let field = match key {
+                "MemTotal" => &mut info.mem_total_kb,
+                "MemFree" => &mut info.mem_free_kb,
+                "MemAvailable" => {
+                    mem_available = Some(kb);
+                    continue;
+                }
+                "Buffers" => &mut info.buffers_kb,
+                "Cached" => &mut info.cached_kb,
+                "SwapTotal" => &mut info.swap_total_kb,
+                "SwapFree" => &mut info.swap_free_kb,
+                "Dirty" => &mut info.dirty_kb,
+                "Writeback" => &mut info.writeback_kb,
+                "AnonPages" => &mut info.anon_pages_kb,
+                "Shmem" => &mut info.shmem_kb,
+                "SReclaimable" => &mut info.slab_reclaimable_kb,
+                _ => continue,
+            };
+            *field = kb;
+        }
+
+        if info.mem_total_kb == 0 {
+            return Err(Error::Parse("meminfo has no MemTotal".to_string()));
+        }
+
+        info.mem_available_kb = mem_available
+            .unwrap_or(info.mem_free_kb + info.buffers_kb + info.cached_kb)
+            .min(info.mem_total_kb);
+        Ok(info)
+    }
+
+    /// MemAvailable in percent of MemTotal
+    pub fn available_percent(&self) -> f64 {
+        self.mem_available_kb as f64 / self.mem_total_kb as f64 * 100.0
+    }
+
+    /// Memory in use (MemTotal - MemAvailable) in percent
+    pub fn used_percent(&self) -> f64 {
+        100.0 - self.available_percent()
+    }
+
+    /// SwapFree in percent of SwapTotal, None without swap
+    pub fn swap_free_percent(&self) -> Option<f64> {
+        (self.swap_total_kb > 0)
+            .then(|| self.swap_free_kb as f64 / self.swap_total_kb as f64 * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = "MemTotal:       16000000 kB\n\
+                           MemFree:          500000 kB\n\
+                           MemAvailable:    1600000 kB\n\
+                           Buffers:          100000 kB\n\
+                           Cached:          2000000 kB\n\
+                           SwapCached:        10000 kB\n\
+                           SwapTotal:       4000000 kB\n\
+                           SwapFree:        1000000 kB\n\
+                           Dirty:             12345 kB\n\
+                           Writeback:             0 kB\n\
+                           AnonPages:      11000000 kB\n\
+                           Shmem:            300000 kB\n\
+                           SReclaimable:     200000 kB\n\
+                           HugePages_Total:       0\n";
+
+    #[test]
+    fn test_parse() {
+        let info = MemInfo::parse(MEMINFO).unwrap();
+        assert_eq!(info.mem_total_kb, 16_000_000);
+        assert_eq!(info.mem_available_kb, 1_600_000);
+        assert_eq!(info.swap_free_kb, 1_000_000);
+        assert_eq!(info.dirty_kb, 12345);
+        assert_eq!(info.shmem_kb, 300_000);
+        assert_eq!(info.slab_reclaimable_kb, 200_000);
+
+        assert!((info.available_percent() - 10.0).abs() < 1e-9);
+        assert!((info.used_percent() - 90.0).abs() < 1e-9);
+        assert_eq!(info.swap_free_percent(), Some(25.0));
+    }
+
+    #[test]
+    fn test_parse_old_kernel_and_no_swap() {
+        // Before Linux 3.14: no MemAvailable
+        let info = MemInfo::parse(
+            "MemTotal: 1000 kB\nMemFree: 100 kB\nBuffers: 50 kB\nCached: 200 kB\n\
+             SwapTotal: 0 kB\nSwapFree: 0 kB\n",
+        )
+        .unwrap();
+        assert_eq!(info.mem_available_kb, 350);
+        assert_eq!(info.swap_free_percent(), None);
+
+        assert!(MemInfo::parse("").is_err());
+        assert!(MemInfo::parse("MemFree: 100 kB\n").is_err());
+    }
+
+    #[test]
+    fn test_read_system_meminfo() {
+        let info = MemInfo::read().unwrap();
+        assert!(info.mem_total_kb > 0);
+        assert!(info.mem_available_kb <= info.mem_total_kb);
+        assert!(info.swap_free_kb <= info.swap_total_kb);
+    }
+}
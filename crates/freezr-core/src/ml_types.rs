@@ -228,6 +228,9 @@ pub enum EventType {
     CpuViolation { cpu_percent: f64, threshold: f64 },
     MemoryViolation { memory_mb: u64, threshold: u64 },
 
+    // Policy
+    ProfileSwitched { from: Option<String>, to: Option<String> },
+
     // Anomalies (for future ML)
     AnomalyDetected { anomaly_score: f64, description: String },
     UnusualBehavior { reason: String },
@@ -0,0 +1,104 @@
+//! Per-process OOM data from /proc
+//!
+//! What the kernel OOM killer looks at: `oom_score` (badness 0-2000,
+//! `oom_score_adj` included) and `oom_score_adj` itself, plus the
+//! proportional set size from `smaps_rollup`. PSS splits shared pages
+//! between the processes mapping them, so browser renderers sharing a
+//! cache are not each charged the whole cache as with RSS.
+
+use crate::error::{Error, Result};
+use std::fs;
+
+/// `oom_score_adj` that exempts a process from the kernel OOM killer
+pub const OOM_SCORE_ADJ_MIN: i32 = -1000;
+
+/// OOM-related state of one process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OomInfo {
+    /// Kernel badness, higher is killed first (/proc/[pid]/oom_score)
+    pub oom_score: u32,
+    /// User adjustment, -1000 to 1000 (/proc/[pid]/oom_score_adj)
+    pub oom_score_adj: i32,
+    /// PSS in KB, None if smaps_rollup is unreadable (Linux < 4.14, no ptrace access)
+    pub pss_kb: Option<u64>,
+}
+
+impl OomInfo {
+    /// Read /proc/[pid]/{oom_score,oom_score_adj,smaps_rollup}
+    pub fn read(pid: u32) -> Result<Self> {
+        let read = |file: &str| {
+            fs::read_to_string(format!("/proc/{}/{}", pid, file)).map_err(|e| {
+                Error::Scanner(format!("Failed to read /proc/{}/{}: {}", pid, file, e))
+            })
+        };
+        let parse_err = |file: &str, value: &str| {
+            Error::Parse(format!("Invalid /proc/{}/{}: {}", pid, file, value.trim()))
+        };
+
+        let score = read("oom_score")?;
+        let oom_score = score
+            .trim()
+            .parse()
+            .map_err(|_| parse_err("oom_score", &score))?;
+
+        let adj = read("oom_score_adj")?;
+        let oom_score_adj = adj
+            .trim()
+            .parse()
+            .map_err(|_| parse_err("oom_score_adj", &adj))?;
+
+        let pss_kb = read("smaps_rollup").ok().and_then(|s| parse_pss_kb(&s));
+
+        Ok(Self {
+            oom_score,
+            oom_score_adj,
+            pss_kb,
+        })
+    }
+
+    /// Exempted from OOM kills (`oom_score_adj` = -1000)
+    pub fn is_unkillable(&self) -> bool {
+        self.oom_score_adj <= OOM_SCORE_ADJ_MIN
+    }
+}
+
+/// `Pss:` line of smaps_rollup in KB
+pub fn parse_pss_kb(smaps_rollup: &str) -> Option<u64> {
+    smaps_rollup
+        .lines()
+        .find_map(|line| line.strip_prefix("Pss:"))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|kb| kb.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pss() {
+        let content = "55d4c9a2e000-7ffd3b9f1000 ---p 00000000 00:00 0   [rollup]\n\
+                       Rss:              123456 kB\n\
+                       Pss:               98765 kB\n\
+                       Pss_Anon:          80000 kB\n\
+                       Pss_File:          18765 kB\n";
+
+        assert_eq!(parse_pss_kb(content), Some(98765));
+        assert_eq!(parse_pss_kb("Rss: 10 kB\n"), None);
+    }
+
+    #[test]
+    fn test_read_self() {
+        let info = OomInfo::read(std::process::id()).unwrap();
+        assert!(info.oom_score <= 2000);
+        assert!((-1000..=1000).contains(&info.oom_score_adj));
+        if let Some(pss) = info.pss_kb {
+            assert!(pss > 0);
+        }
+    }
+
+    #[test]
+    fn test_missing_process() {
+        assert!(OomInfo::read(u32::MAX).is_err());
+    }
+}
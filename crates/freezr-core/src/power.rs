@@ -0,0 +1,262 @@
+//! Power source from sysfs
+//!
+//! Every entry of `/sys/class/power_supply` has a `type` ("Mains", "USB",
+//! "Battery"...). Adapters report `online`, batteries `capacity` (percent)
+//! and `status` ("Charging", "Discharging", "Full", "Not charging").
+//! Batteries of wireless mice and keyboards (`scope` = "Device") are not
+//! the system's and are ignored.
+//!
+//! The sysfs root is configurable so tests can use a fake tree.
+
+use crate::error::{Error, Result};
+use crate::thermal::DEFAULT_SYSFS_ROOT;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Kind of a power supply (`type` file)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupplyKind {
+    Mains,
+    Usb,
+    Battery,
+    Other,
+}
+
+/// One entry of /sys/class/power_supply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowerSupply {
+    /// Directory name ("AC", "BAT0", "ucsi-source-psy-USBC000:001")
+    pub name: String,
+    pub kind: SupplyKind,
+    /// Adapter plugged in, None if not reported
+    pub online: Option<bool>,
+    /// Battery charge in percent
+    pub capacity: Option<u8>,
+    /// Battery status as reported ("Discharging")
+    pub status: Option<String>,
+}
+
+/// Where the system draws power from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+impl PowerSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ac => "ac",
+            Self::Battery => "battery",
+        }
+    }
+}
+
+impl fmt::Display for PowerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Power source and remaining battery charge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerState {
+    pub source: PowerSource,
+    /// Mean charge of the system batteries, None without battery
+    pub battery_percent: Option<u8>,
+}
+
+impl PowerState {
+    /// Derive the state from the supplies
+    ///
+    /// On AC if any adapter is online. Otherwise on battery if a battery
+    /// discharges or adapters exist but are all offline. Machines without
+    /// any supply (desktops, VMs) count as AC.
+    pub fn from_supplies(supplies: &[PowerSupply]) -> Self {
+        let (batteries, adapters): (Vec<_>, Vec<_>) =
+            supplies.iter().partition(|s| s.kind == SupplyKind::Battery);
+
+        let capacities: Vec<u32> = batteries
+            .iter()
+            .filter_map(|b| b.capacity.map(u32::from))
+            .collect();
+        let battery_percent = (!capacities.is_empty())
+            .then(|| (capacities.iter().sum::<u32>() / capacities.len() as u32) as u8);
+
+        let adapter_online = adapters.iter().any(|a| a.online == Some(true));
+        let discharging = batteries
+            .iter()
+            .any(|b| b.status.as_deref() == Some("Discharging"));
+        let adapters_offline =
+            !batteries.is_empty() && adapters.iter().any(|a| a.online == Some(false));
+
+        let source = if !adapter_online && (discharging || adapters_offline) {
+            PowerSource::Battery
+        } else {
+            PowerSource::Ac
+        };
+
+        Self {
+            source,
+            battery_percent,
+        }
+    }
+}
+
+/// Reads /sys/class/power_supply below a sysfs root
+#[derive(Debug, Clone)]
+pub struct PowerReader {
+    root: PathBuf,
+}
+
+impl Default for PowerReader {
+    fn default() -> Self {
+        Self::new(DEFAULT_SYSFS_ROOT)
+    }
+}
+
+impl PowerReader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// System power supplies, sorted by name
+    pub fn read_supplies(&self) -> Result<Vec<PowerSupply>> {
+        let class_dir = self.root.join("class").join("power_supply");
+        let entries = fs::read_dir(&class_dir)
+            .map_err(|e| Error::Other(format!("Failed to read {}: {}", class_dir.display(), e)))?;
+
+        let mut supplies: Vec<PowerSupply> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let dir = entry.path();
+                let read = |file: &str| {
+                    fs::read_to_string(dir.join(file))
+                        .ok()
+                        .map(|s| s.trim().to_string())
+                };
+
+                if read("scope").as_deref() == Some("Device") {
+                    return None;
+                }
+
+                let kind = match read("type").as_deref() {
+                    Some("Mains") => SupplyKind::Mains,
+                    Some("Battery") => SupplyKind::Battery,
+                    Some(usb) if usb.starts_with("USB") => SupplyKind::Usb,
+                    _ => SupplyKind::Other,
+                };
+
+                Some(PowerSupply {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    kind,
+                    online: read("online").map(|v| v == "1"),
+                    capacity: read("capacity").and_then(|v| v.parse().ok()),
+                    status: read("status").filter(|s| !s.is_empty()),
+                })
+            })
+            .collect();
+        supplies.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(supplies)
+    }
+
+    /// Current power state (AC when power_supply is missing entirely)
+    pub fn read_state(&self) -> Result<PowerState> {
+        Ok(PowerState::from_supplies(&self.read_supplies()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(
+        name: &str,
+        kind: SupplyKind,
+        online: Option<bool>,
+        status: Option<&str>,
+    ) -> PowerSupply {
+        PowerSupply {
+            name: name.to_string(),
+            kind,
+            online,
+            capacity: (kind == SupplyKind::Battery).then_some(50),
+            status: status.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_power_state() {
+        let ac = supply("AC", SupplyKind::Mains, Some(true), None);
+        let ac_off = supply("AC", SupplyKind::Mains, Some(false), None);
+        let charging = supply("BAT0", SupplyKind::Battery, None, Some("Charging"));
+        let discharging = supply("BAT0", SupplyKind::Battery, None, Some("Discharging"));
+        let not_charging = supply("BAT1", SupplyKind::Battery, None, Some("Not charging"));
+
+        let state = |supplies: &[PowerSupply]| PowerState::from_supplies(supplies).source;
+        assert_eq!(state(&[ac.clone(), charging.clone()]), PowerSource::Ac);
+        assert_eq!(
+            state(&[ac_off.clone(), discharging.clone()]),
+            PowerSource::Battery
+        );
+        // Adapter offline, battery idle (charge threshold reached)
+        assert_eq!(state(&[ac_off, not_charging.clone()]), PowerSource::Battery);
+        // No adapter entry, only the battery status tells
+        assert_eq!(
+            state(std::slice::from_ref(&discharging)),
+            PowerSource::Battery
+        );
+        assert_eq!(state(&[charging]), PowerSource::Ac);
+        // Desktop
+        assert_eq!(state(&[]), PowerSource::Ac);
+
+        let mut low = not_charging;
+        low.capacity = Some(21);
+        let both = PowerState::from_supplies(&[ac, discharging, low]);
+        assert_eq!(both.source, PowerSource::Ac);
+        assert_eq!(both.battery_percent, Some(35));
+        assert_eq!(PowerState::from_supplies(&[]).battery_percent, None);
+    }
+
+    #[test]
+    fn test_read_supplies() {
+        let root = std::env::temp_dir().join(format!("freezr-power-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let write = |file: &str, content: &str| {
+            let path = root.join("class/power_supply").join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("AC/type", "Mains\n");
+        write("AC/online", "0\n");
+        write("BAT0/type", "Battery\n");
+        write("BAT0/capacity", "42\n");
+        write("BAT0/status", "Discharging\n");
+        write("BAT0/scope", "System\n");
+        write("hidpp_battery_0/type", "Battery\n");
+        write("hidpp_battery_0/scope", "Device\n");
+        write("hidpp_battery_0/capacity", "5\n");
+        write("ucsi-source-psy-USBC000:001/type", "USB\n");
+        write("ucsi-source-psy-USBC000:001/online", "0\n");
+
+        let reader = PowerReader::new(&root);
+        let supplies = reader.read_supplies().unwrap();
+        let names: Vec<&str> = supplies.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["AC", "BAT0", "ucsi-source-psy-USBC000:001"]);
+        assert_eq!(supplies[0].online, Some(false));
+        assert_eq!(supplies[1].capacity, Some(42));
+        assert_eq!(supplies[2].kind, SupplyKind::Usb);
+
+        let state = reader.read_state().unwrap();
+        assert_eq!(state.source, PowerSource::Battery);
+        assert_eq!(state.battery_percent, Some(42));
+
+        assert!(PowerReader::new(root.join("missing")).read_state().is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
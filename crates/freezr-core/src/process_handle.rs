@@ -0,0 +1,300 @@
+//! Process identity that survives PID reuse
+//!
+//! `ProcessHandle` keeps the PID together with the start time from
+//! /proc/[pid]/stat and a pidfd (pidfd_open, Linux 5.3+). Signals go through
+//! pidfd_send_signal, so they only reach the process that was scanned, even
+//! if its PID now belongs to another process.
+//!
+//! Kernels without pidfd fall back to kill() with the start time checked
+//! again before every signal.
+
+use crate::{types::ProcessInfo, Error, Result};
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use procfs::process::Process;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Process identified by PID and start time
+#[derive(Debug)]
+pub struct ProcessHandle {
+    pid: u32,
+    start_ticks: u64,
+    /// None if the kernel has no pidfd support
+    pidfd: Option<OwnedFd>,
+}
+
+impl ProcessHandle {
+    /// Open the process that currently has this PID
+    pub fn open(pid: u32) -> Result<Self> {
+        let (start_ticks, _) = read_stat(pid)?;
+        Self::open_expected(pid, start_ticks)
+    }
+
+    /// Open the process only if it is the one that was scanned
+    ///
+    /// # Arguments
+    /// * `pid` - Process ID
+    /// * `start_ticks` - Start time read by the scan
+    pub fn open_expected(pid: u32, start_ticks: u64) -> Result<Self> {
+        let pidfd = pidfd_open(pid)?;
+
+        // Checked after opening the pidfd: if the PID was reused before,
+        // the start time differs
+        let (actual, _) = read_stat(pid)?;
+        if actual != start_ticks {
+            return Err(Error::Executor(format!(
+                "Process {} was replaced by another process (start time {} != {})",
+                pid, actual, start_ticks
+            )));
+        }
+
+        Ok(Self {
+            pid,
+            start_ticks,
+            pidfd,
+        })
+    }
+
+    /// Open a scanned process
+    ///
+    /// Fails if the scan recorded no start time (0): without it the process
+    /// cannot be told apart from a later one with the same PID.
+    pub fn from_process(process: &ProcessInfo) -> Result<Self> {
+        if process.start_ticks == 0 {
+            return Err(Error::Executor(format!(
+                "Process {} ({}) has no recorded start time",
+                process.pid, process.name
+            )));
+        }
+        Self::open_expected(process.pid, process.start_ticks)
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn start_ticks(&self) -> u64 {
+        self.start_ticks
+    }
+
+    /// Whether a pidfd is used (otherwise kill() with a start time check)
+    pub fn has_pidfd(&self) -> bool {
+        self.pidfd.is_some()
+    }
+
+    /// Send a signal to exactly this process
+    pub fn send_signal(&self, signal: Signal) -> Result<()> {
+        match &self.pidfd {
+            Some(fd) => {
+                let ret = unsafe {
+                    libc::syscall(
+                        libc::SYS_pidfd_send_signal,
+                        fd.as_raw_fd(),
+                        signal as libc::c_int,
+                        std::ptr::null::<libc::siginfo_t>(),
+                        0,
+                    )
+                };
+                if ret == -1 {
+                    let err = std::io::Error::last_os_error();
+                    return Err(match err.raw_os_error() {
+                        Some(libc::ESRCH) => self.exited_error(),
+                        _ => Error::Executor(format!(
+                            "Failed to send {} to process {}: {}",
+                            signal, self.pid, err
+                        )),
+                    });
+                }
+                Ok(())
+            }
+            None => {
+                if !self.is_alive() {
+                    return Err(self.exited_error());
+                }
+                kill(Pid::from_raw(self.pid as i32), signal).map_err(|e| {
+                    Error::Executor(format!(
+                        "Failed to send {} to process {}: {}",
+                        signal, self.pid, e
+                    ))
+                })
+            }
+        }
+    }
+
+    /// Process still running (neither exited nor a zombie)
+    pub fn is_alive(&self) -> bool {
+        match &self.pidfd {
+            // The pidfd becomes readable when the process exits
+            Some(fd) => !poll_exited(fd, 0),
+            None => matches!(
+                read_stat(self.pid),
+                Ok((start, state)) if start == self.start_ticks && state != 'Z'
+            ),
+        }
+    }
+
+    /// Process stopped (SIGSTOP, state T)
+    pub fn is_stopped(&self) -> bool {
+        matches!(
+            read_stat(self.pid),
+            Ok((start, 'T')) if start == self.start_ticks
+        )
+    }
+
+    /// Wait for the process to exit
+    ///
+    /// Returns true if it exited before `timeout`
+    pub fn wait_exit(&self, timeout: Duration) -> bool {
+        match &self.pidfd {
+            Some(fd) => poll_exited(fd, timeout.as_millis().min(i32::MAX as u128) as i32),
+            None => {
+                let deadline = Instant::now() + timeout;
+                while self.is_alive() {
+                    if Instant::now() >= deadline {
+                        return false;
+                    }
+                    thread::sleep(Duration::from_millis(100));
+                }
+                true
+            }
+        }
+    }
+
+    fn exited_error(&self) -> Error {
+        Error::Executor(format!("Process {} does not exist (exited)", self.pid))
+    }
+}
+
+/// Start time and state (R, S, T, Z...) from /proc/[pid]/stat
+fn read_stat(pid: u32) -> Result<(u64, char)> {
+    let stat = Process::new(pid as i32)
+        .and_then(|p| p.stat())
+        .map_err(|_| Error::Executor(format!("Process {} does not exist", pid)))?;
+
+    Ok((stat.starttime, stat.state))
+}
+
+/// pidfd_open(2), `None` if the kernel has no pidfd support (ENOSYS)
+fn pidfd_open(pid: u32) -> Result<Option<OwnedFd>> {
+    let ret = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+    if ret == -1 {
+        let err = std::io::Error::last_os_error();
+        return match err.raw_os_error() {
+            Some(libc::ENOSYS) => Ok(None),
+            Some(libc::ESRCH) => Err(Error::Executor(format!("Process {} does not exist", pid))),
+            _ => Err(Error::Executor(format!(
+                "pidfd_open failed for process {}: {}",
+                pid, err
+            ))),
+        };
+    }
+
+    // SAFETY: pidfd_open returned a new file descriptor that we own
+    Ok(Some(unsafe { OwnedFd::from_raw_fd(ret as libc::c_int) }))
+}
+
+/// Wait for POLLIN on the pidfd (process exited)
+fn poll_exited(fd: &OwnedFd, timeout_ms: i32) -> bool {
+    let mut pfd = libc::pollfd {
+        fd: fd.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    loop {
+        let ret = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+        if ret == -1 && std::io::Error::last_os_error().raw_os_error() == Some(libc::EINTR) {
+            continue;
+        }
+        return ret > 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+
+    fn spawn_sleep() -> Child {
+        Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .expect("Failed to spawn test process")
+    }
+
+    #[test]
+    fn test_open_self() {
+        let handle = ProcessHandle::open(std::process::id()).unwrap();
+
+        assert_eq!(handle.pid(), std::process::id());
+        assert!(handle.start_ticks() > 0);
+        assert!(handle.is_alive());
+        assert!(!handle.wait_exit(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_open_nonexistent() {
+        let err = ProcessHandle::open(999_999).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_open_expected_rejects_other_start_time() {
+        let pid = std::process::id();
+        let (start, _) = read_stat(pid).unwrap();
+
+        assert!(ProcessHandle::open_expected(pid, start).is_ok());
+
+        let err = ProcessHandle::open_expected(pid, start + 1).unwrap_err();
+        assert!(err.to_string().contains("replaced"));
+    }
+
+    #[test]
+    fn test_from_process_uses_scanned_identity() {
+        let pid = std::process::id();
+        let (start, _) = read_stat(pid).unwrap();
+
+        let info = ProcessInfo::new(pid, "test".into(), "test".into(), 0.0, 0);
+        // No start time: identity unknown, refused
+        assert!(ProcessHandle::from_process(&info).is_err());
+
+        let scanned = info.clone().with_start_ticks(start);
+        assert!(ProcessHandle::from_process(&scanned).is_ok());
+
+        let stale = info.with_start_ticks(start + 100);
+        assert!(ProcessHandle::from_process(&stale).is_err());
+    }
+
+    #[test]
+    fn test_is_stopped() {
+        let mut child = spawn_sleep();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        assert!(!handle.is_stopped());
+
+        handle.send_signal(Signal::SIGSTOP).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while !handle.is_stopped() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(handle.is_stopped());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_signal_and_wait_exit() {
+        let mut child = spawn_sleep();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+
+        handle.send_signal(Signal::SIGKILL).unwrap();
+        assert!(handle.wait_exit(Duration::from_secs(2)));
+        assert!(!handle.is_alive());
+
+        // Once reaped, the PID may be reused: the handle must not signal it
+        child.wait().unwrap();
+        assert!(handle.send_signal(Signal::SIGCONT).is_err());
+    }
+}
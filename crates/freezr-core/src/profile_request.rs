@@ -0,0 +1,118 @@
+//! Profile requests from the CLI to the daemon
+//!
+//! `freezr profile set <name>` writes the wanted policy profile to a state
+//! file; the daemon reads it between two check cycles and switches. A
+//! request without profile (`freezr profile auto`) hands the choice back to
+//! the automatic `[power]` selection.
+
+use crate::{error::Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Default location of the request file (tmpfs, cleared on reboot)
+pub const DEFAULT_PROFILE_REQUEST_PATH: &str = "/run/freezr/profile.json";
+
+/// Profile wanted by the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileRequest {
+    /// Profile name, None for automatic selection
+    pub profile: Option<String>,
+    /// Distinguishes a new request from one already applied
+    pub requested_at: DateTime<Utc>,
+}
+
+/// State file with the last profile request
+#[derive(Debug, Clone)]
+pub struct ProfileRequests {
+    path: PathBuf,
+}
+
+impl Default for ProfileRequests {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROFILE_REQUEST_PATH)
+    }
+}
+
+impl ProfileRequests {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Last request (missing file = none)
+    pub fn load(&self) -> Result<Option<ProfileRequest>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        if content.trim().is_empty() {
+            return Ok(None);
+        }
+
+        serde_json::from_str(&content).map(Some).map_err(|e| {
+            Error::Parse(format!(
+                "Invalid profile request {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Ask the daemon to switch to `profile` (None = automatic)
+    ///
+    /// Written to a temporary file and renamed, so the daemon never reads a
+    /// half-written request.
+    pub fn request(&self, profile: Option<&str>) -> Result<ProfileRequest> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let request = ProfileRequest {
+            profile: profile.map(str::to_string),
+            requested_at: Utc::now(),
+        };
+        let json = serde_json::to_string_pretty(&request)
+            .map_err(|e| Error::Other(format!("Failed to serialize profile request: {}", e)))?;
+
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_roundtrip() {
+        let dir =
+            std::env::temp_dir().join(format!("freezr-profile-request-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let requests = ProfileRequests::new(dir.join("profile.json"));
+
+        assert_eq!(requests.load().unwrap(), None);
+
+        let gaming = requests.request(Some("gaming")).unwrap();
+        assert_eq!(requests.load().unwrap(), Some(gaming));
+
+        let auto = requests.request(None).unwrap();
+        let loaded = requests.load().unwrap().unwrap();
+        assert_eq!(loaded.profile, None);
+        assert_eq!(loaded, auto);
+
+        fs::write(requests.path(), "{").unwrap();
+        assert!(requests.load().is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
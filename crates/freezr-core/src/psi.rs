@@ -0,0 +1,596 @@
+//! Pressure Stall Information (PSI)
+//!
+//! One reader for every PSI file of the kernel: system-wide
+//! `/proc/pressure/{cpu,io,memory,irq}` and the `{cpu,io,memory,irq}.pressure`
+//! files of cgroups. All of them share the format:
+//!
+//! ```text
+//! some avg10=0.00 avg60=0.00 avg300=0.00 total=634678
+//! full avg10=0.00 avg60=0.00 avg300=0.00 total=583219
+//! ```
+//!
+//! Older kernels have no `full` line for CPU, `irq` has only `full`: missing
+//! lines read as zero.
+//!
+//! Instead of polling the averages, a [`PsiTrigger`] lets the kernel signal
+//! a stall threshold (e.g. 150ms of `some` stall within 1s) as `POLLPRI`.
+
+use crate::cgroups::cgroupfs;
+use crate::error::{Error, Result};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Resource a PSI file reports stalls on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PsiResource {
+    Cpu,
+    Io,
+    Memory,
+    /// Interrupt handling (CONFIG_IRQ_TIME_ACCOUNTING, Linux 6.1+)
+    Irq,
+}
+
+impl PsiResource {
+    pub const ALL: [PsiResource; 4] = [Self::Cpu, Self::Io, Self::Memory, Self::Irq];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cpu => "cpu",
+            Self::Io => "io",
+            Self::Memory => "memory",
+            Self::Irq => "irq",
+        }
+    }
+
+    /// Name for log messages
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Cpu => "CPU",
+            Self::Io => "IO",
+            Self::Memory => "Memory",
+            Self::Irq => "IRQ",
+        }
+    }
+
+    /// System-wide file, e.g. /proc/pressure/memory
+    pub fn proc_path(&self) -> PathBuf {
+        PathBuf::from("/proc/pressure").join(self.name())
+    }
+
+    /// File in a cgroup directory, e.g. memory.pressure
+    pub fn cgroup_file(&self) -> String {
+        format!("{}.pressure", self.name())
+    }
+}
+
+impl fmt::Display for PsiResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Stall metrics of one PSI file
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PressureStall {
+    /// "some" metric: percentage of time at least one task is stalled
+    pub some_avg10: f64,
+    pub some_avg60: f64,
+    pub some_avg300: f64,
+    /// Total stall time (µs)
+    pub some_total: u64,
+
+    /// "full" metric: percentage of time ALL non-idle tasks are stalled
+    pub full_avg10: f64,
+    pub full_avg60: f64,
+    pub full_avg300: f64,
+    pub full_total: u64,
+}
+
+impl PressureStall {
+    /// Read system-wide pressure from /proc/pressure/<resource>
+    pub fn read(resource: PsiResource) -> Result<Self> {
+        let path = resource.proc_path();
+        let content = fs::read_to_string(&path)
+            .map_err(|e| Error::Other(format!("Failed to read {}: {}", path.display(), e)))?;
+
+        Self::parse(&content)
+    }
+
+    /// Read pressure of a cgroup from <cgroup>/<resource>.pressure
+    pub fn read_cgroup(cgroup_path: &Path, resource: PsiResource) -> Result<Self> {
+        let path = cgroup_path.join(resource.cgroup_file());
+        let content = cgroupfs::read_to_string(&path)
+            .map_err(|e| Error::Other(format!("Failed to read {}: {}", path.display(), e)))?;
+
+        Self::parse(&content)
+    }
+
+    /// Parse PSI format
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut pressure = Self::default();
+        let mut parsed = 0;
+
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            if line.starts_with("some") {
+                let (avg10, avg60, avg300, total) = Self::parse_line(line, "some")?;
+                pressure.some_avg10 = avg10;
+                pressure.some_avg60 = avg60;
+                pressure.some_avg300 = avg300;
+                pressure.some_total = total;
+            } else if line.starts_with("full") {
+                let (avg10, avg60, avg300, total) = Self::parse_line(line, "full")?;
+                pressure.full_avg10 = avg10;
+                pressure.full_avg60 = avg60;
+                pressure.full_avg300 = avg300;
+                pressure.full_total = total;
+            } else {
+                return Err(Error::Parse(format!("Unknown PSI line: {}", line)));
+            }
+            parsed += 1;
+        }
+
+        if parsed == 0 {
+            return Err(Error::Parse(
+                "Invalid PSI format: expected some/full lines".to_string(),
+            ));
+        }
+
+        Ok(pressure)
+    }
+
+    /// Parse single line: "some avg10=0.00 avg60=0.00 avg300=0.00 total=634678"
+    /// Returns: (avg10, avg60, avg300, total)
+    fn parse_line(line: &str, expected_prefix: &str) -> Result<(f64, f64, f64, u64)> {
+        if !line.starts_with(expected_prefix) {
+            return Err(Error::Parse(format!(
+                "Line should start with '{}', got: {}",
+                expected_prefix, line
+            )));
+        }
+
+        // Split by whitespace and parse key=value pairs
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() != 5 {
+            return Err(Error::Parse(format!(
+                "Expected 5 parts, got {}: {}",
+                parts.len(),
+                line
+            )));
+        }
+
+        let avg10 = Self::parse_value(parts[1], "avg10=")?;
+        let avg60 = Self::parse_value(parts[2], "avg60=")?;
+        let avg300 = Self::parse_value(parts[3], "avg300=")?;
+        let total = Self::parse_int_value(parts[4], "total=")?;
+
+        Ok((avg10, avg60, avg300, total))
+    }
+
+    /// Parse "key=value" to f64
+    fn parse_value(part: &str, expected_key: &str) -> Result<f64> {
+        if !part.starts_with(expected_key) {
+            return Err(Error::Parse(format!(
+                "Expected key '{}', got: {}",
+                expected_key, part
+            )));
+        }
+
+        let value_str = &part[expected_key.len()..];
+        value_str
+            .parse::<f64>()
+            .map_err(|e| Error::Parse(format!("Failed to parse float '{}': {}", value_str, e)))
+    }
+
+    /// Parse "key=value" to u64
+    fn parse_int_value(part: &str, expected_key: &str) -> Result<u64> {
+        if !part.starts_with(expected_key) {
+            return Err(Error::Parse(format!(
+                "Expected key '{}', got: {}",
+                expected_key, part
+            )));
+        }
+
+        let value_str = &part[expected_key.len()..];
+        value_str
+            .parse::<u64>()
+            .map_err(|e| Error::Parse(format!("Failed to parse int '{}': {}", value_str, e)))
+    }
+
+    /// Check if pressure is at warning level
+    pub fn is_warning(&self, some_threshold: f64, full_threshold: f64) -> bool {
+        self.some_avg10 >= some_threshold || self.full_avg10 >= full_threshold
+    }
+
+    /// Check if pressure is at critical level
+    pub fn is_critical(&self, some_threshold: f64, full_threshold: f64) -> bool {
+        self.some_avg10 >= some_threshold || self.full_avg10 >= full_threshold
+    }
+
+    /// Stall (some, full) in percent between an earlier reading and this one
+    ///
+    /// Computed from the `total` counters, so unlike avg10 it reflects only
+    /// the `elapsed` interval, e.g. the last 500ms after a kill.
+    pub fn stall_since(&self, earlier: &PressureStall, elapsed: Duration) -> (f64, f64) {
+        let elapsed_us = elapsed.as_micros() as f64;
+        if elapsed_us == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let percent = |now: u64, before: u64| {
+            (now.saturating_sub(before) as f64 / elapsed_us * 100.0).min(100.0)
+        };
+        (
+            percent(self.some_total, earlier.some_total),
+            percent(self.full_total, earlier.full_total),
+        )
+    }
+
+    /// Get human-readable status
+    pub fn status(&self) -> &'static str {
+        if self.full_avg10 > 0.0 {
+            "CRITICAL" // Full stall = all processes blocked
+        } else if self.some_avg10 > 10.0 {
+            "HIGH"
+        } else if self.some_avg10 > 5.0 {
+            "MEDIUM"
+        } else if self.some_avg10 > 0.0 {
+            "LOW"
+        } else {
+            "NONE"
+        }
+    }
+}
+
+/// Stall metric watched by a trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallKind {
+    /// At least one task stalled
+    Some,
+    /// All non-idle tasks stalled
+    Full,
+}
+
+impl StallKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Some => "some",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// Kernel PSI trigger (Linux 5.2+)
+///
+/// Registered by writing `<some|full> <stall us> <window us>` to a PSI file;
+/// the kernel then raises `POLLPRI` on the open descriptor as soon as the
+/// tasks stalled for `stall` within the last `window`, at most once per
+/// window. The trigger lives as long as the descriptor stays open.
+///
+/// # Examples
+/// ```ignore
+/// // 150ms of memory stall within 1s
+/// let trigger = PsiTrigger::new(
+///     PsiResource::Memory,
+///     StallKind::Some,
+///     Duration::from_millis(150),
+///     Duration::from_secs(1),
+/// )?;
+/// if trigger.wait(Duration::from_secs(1))? {
+///     println!("memory stall: {}", trigger.spec());
+/// }
+/// ```
+pub struct PsiTrigger {
+    file: File,
+    resource: PsiResource,
+    spec: String,
+}
+
+impl PsiTrigger {
+    /// Smallest window accepted by the kernel
+    pub const MIN_WINDOW: Duration = Duration::from_millis(500);
+
+    /// Largest window accepted by the kernel
+    pub const MAX_WINDOW: Duration = Duration::from_secs(10);
+
+    /// Register a trigger on /proc/pressure/<resource>
+    ///
+    /// Fails if PSI is disabled (`psi=0`, CONFIG_PSI_DEFAULT_DISABLED) or the
+    /// kernel rejects the trigger: without CAP_SYS_RESOURCE, Linux 6.5+ only
+    /// accepts windows that are a multiple of 2s.
+    pub fn new(
+        resource: PsiResource,
+        kind: StallKind,
+        stall: Duration,
+        window: Duration,
+    ) -> Result<Self> {
+        Self::register(&resource.proc_path(), resource, kind, stall, window)
+    }
+
+    /// Register a trigger on <cgroup>/<resource>.pressure
+    pub fn for_cgroup(
+        cgroup_path: &Path,
+        resource: PsiResource,
+        kind: StallKind,
+        stall: Duration,
+        window: Duration,
+    ) -> Result<Self> {
+        let path = cgroup_path.join(resource.cgroup_file());
+        Self::register(&path, resource, kind, stall, window)
+    }
+
+    fn register(
+        path: &Path,
+        resource: PsiResource,
+        kind: StallKind,
+        stall: Duration,
+        window: Duration,
+    ) -> Result<Self> {
+        if !(Self::MIN_WINDOW..=Self::MAX_WINDOW).contains(&window) {
+            return Err(Error::Other(format!(
+                "PSI trigger window must be {}ms-{}s, got {}ms",
+                Self::MIN_WINDOW.as_millis(),
+                Self::MAX_WINDOW.as_secs(),
+                window.as_millis()
+            )));
+        }
+        if stall.is_zero() || stall > window {
+            return Err(Error::Other(format!(
+                "PSI trigger stall must be within the window, got {}ms of {}ms",
+                stall.as_millis(),
+                window.as_millis()
+            )));
+        }
+
+        let spec = format!(
+            "{} {} {}",
+            kind.name(),
+            stall.as_micros(),
+            window.as_micros()
+        );
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)
+            .map_err(|e| Error::Other(format!("Failed to open {}: {}", path.display(), e)))?;
+
+        // The kernel parses a NUL-terminated string
+        file.write_all(format!("{}\0", spec).as_bytes())
+            .map_err(|e| {
+                Error::Other(format!(
+                    "Kernel rejected PSI trigger '{}' on {}: {}",
+                    spec,
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        Ok(Self {
+            file,
+            resource,
+            spec,
+        })
+    }
+
+    pub fn resource(&self) -> PsiResource {
+        self.resource
+    }
+
+    /// Trigger as written to the kernel, e.g. "some 150000 1000000"
+    pub fn spec(&self) -> &str {
+        &self.spec
+    }
+
+    /// Wait until the trigger fires
+    ///
+    /// Returns true if the stall threshold was crossed, false on timeout or
+    /// when interrupted by a signal.
+    pub fn wait(&self, timeout: Duration) -> Result<bool> {
+        let mut pfd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLPRI,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+
+        let ret = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+        match ret {
+            -1 => {
+                let err = std::io::Error::last_os_error();
+                if err.raw_os_error() == Some(libc::EINTR) {
+                    Ok(false)
+                } else {
+                    Err(err.into())
+                }
+            }
+            0 => Ok(false),
+            // POLLERR: the trigger is gone (e.g. its cgroup was removed)
+            _ if pfd.revents & libc::POLLERR != 0 => Err(Error::Other(format!(
+                "PSI trigger '{}' on {} is no longer valid",
+                self.spec, self.resource
+            ))),
+            _ => Ok(pfd.revents & libc::POLLPRI != 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgroups::FakeCgroupFs;
+
+    #[test]
+    fn test_parse_all_lines() {
+        let content = "some avg10=12.50 avg60=8.33 avg300=3.25 total=123456\n\
+                       full avg10=5.00 avg60=2.50 avg300=1.00 total=654321\n";
+
+        let pressure = PressureStall::parse(content).unwrap();
+        assert_eq!(pressure.some_avg10, 12.50);
+        assert_eq!(pressure.some_avg300, 3.25);
+        assert_eq!(pressure.some_total, 123456);
+        assert_eq!(pressure.full_avg60, 2.50);
+        assert_eq!(pressure.full_total, 654321);
+    }
+
+    #[test]
+    fn test_parse_missing_lines() {
+        // CPU on kernels before 5.13: no full line
+        let cpu =
+            PressureStall::parse("some avg10=1.00 avg60=0.50 avg300=0.10 total=42\n").unwrap();
+        assert_eq!(cpu.some_total, 42);
+        assert_eq!(cpu.full_avg10, 0.0);
+
+        // irq: only full
+        let irq = PressureStall::parse("full avg10=0.20 avg60=0.10 avg300=0.00 total=7\n").unwrap();
+        assert_eq!(irq.some_avg10, 0.0);
+        assert_eq!(irq.full_total, 7);
+
+        assert!(PressureStall::parse("").is_err());
+        assert!(PressureStall::parse("most avg10=1.00").is_err());
+        assert!(PressureStall::parse("some avg10=x avg60=0 avg300=0 total=0").is_err());
+    }
+
+    #[test]
+    fn test_stall_since() {
+        let before = PressureStall::parse(
+            "some avg10=40.00 avg60=0 avg300=0 total=1000000\n\
+             full avg10=20.00 avg60=0 avg300=0 total=500000\n",
+        )
+        .unwrap();
+        // 100ms of 'some' and 25ms of 'full' stall within 500ms
+        let after = PressureStall::parse(
+            "some avg10=40.00 avg60=0 avg300=0 total=1100000\n\
+             full avg10=20.00 avg60=0 avg300=0 total=525000\n",
+        )
+        .unwrap();
+
+        let (some, full) = after.stall_since(&before, Duration::from_millis(500));
+        assert!((some - 20.0).abs() < 1e-9);
+        assert!((full - 5.0).abs() < 1e-9);
+        assert_eq!(after.stall_since(&before, Duration::ZERO), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_is_warning() {
+        let pressure = PressureStall {
+            some_avg10: 15.0,
+            some_avg60: 10.0,
+            some_avg300: 5.0,
+            some_total: 1000,
+            full_avg10: 2.0,
+            full_avg60: 1.0,
+            full_avg300: 0.5,
+            full_total: 500,
+        };
+
+        assert!(pressure.is_warning(10.0, 5.0)); // some_avg10 (15.0) > 10.0
+        assert!(!pressure.is_warning(20.0, 5.0)); // some_avg10 (15.0) < 20.0
+    }
+
+    #[test]
+    fn test_status() {
+        let no_pressure = PressureStall::default();
+        assert_eq!(no_pressure.status(), "NONE");
+
+        let critical_pressure = PressureStall {
+            some_avg10: 50.0,
+            some_avg60: 40.0,
+            some_avg300: 30.0,
+            some_total: 10000,
+            full_avg10: 10.0, // Full stall!
+            full_avg60: 8.0,
+            full_avg300: 5.0,
+            full_total: 5000,
+        };
+        assert_eq!(critical_pressure.status(), "CRITICAL");
+    }
+
+    #[test]
+    fn test_resource_paths() {
+        assert_eq!(
+            PsiResource::Io.proc_path(),
+            PathBuf::from("/proc/pressure/io")
+        );
+        assert_eq!(PsiResource::Cpu.cgroup_file(), "cpu.pressure");
+        assert_eq!(PsiResource::ALL.len(), 4);
+    }
+
+    #[test]
+    fn test_read_cgroup_pressure() {
+        let fake = FakeCgroupFs::new("/fake/psi");
+        let _mount = fake.mount();
+        let cgroup = fake.root().join("group");
+        cgroupfs::create_dir(&cgroup).unwrap();
+        fake.set(
+            &cgroup.join("io.pressure"),
+            "some avg10=30.00 avg60=20.00 avg300=10.00 total=999\n\
+             full avg10=25.00 avg60=15.00 avg300=5.00 total=888\n",
+        )
+        .unwrap();
+
+        let io = PressureStall::read_cgroup(&cgroup, PsiResource::Io).unwrap();
+        assert_eq!(io.full_avg10, 25.0);
+        assert_eq!(io.some_total, 999);
+
+        let memory = PressureStall::read_cgroup(&cgroup, PsiResource::Memory).unwrap();
+        assert_eq!(memory, PressureStall::default());
+
+        let root = PressureStall::read_cgroup(fake.root(), PsiResource::Cpu).unwrap();
+        assert_eq!(root, PressureStall::default());
+        assert!(PressureStall::read_cgroup(&cgroup, PsiResource::Irq).is_err());
+    }
+
+    #[test]
+    fn test_trigger_rejects_bad_window() {
+        let second = Duration::from_secs(1);
+        assert!(PsiTrigger::new(
+            PsiResource::Memory,
+            StallKind::Some,
+            second,
+            Duration::from_millis(100)
+        )
+        .is_err());
+        assert!(PsiTrigger::new(
+            PsiResource::Memory,
+            StallKind::Some,
+            Duration::from_secs(2),
+            second
+        )
+        .is_err());
+        assert!(
+            PsiTrigger::new(PsiResource::Memory, StallKind::Full, Duration::ZERO, second).is_err()
+        );
+    }
+
+    #[test]
+    fn test_trigger_register_and_wait() {
+        // PSI may be disabled, unprivileged triggers need a 2s window
+        let Ok(trigger) = PsiTrigger::new(
+            PsiResource::Memory,
+            StallKind::Some,
+            Duration::from_millis(500),
+            Duration::from_secs(2),
+        ) else {
+            return;
+        };
+
+        assert_eq!(trigger.spec(), "some 500000 2000000");
+        assert_eq!(trigger.resource(), PsiResource::Memory);
+        // Nothing stalls this test for 500ms
+        assert!(!trigger.wait(Duration::from_millis(10)).unwrap());
+    }
+
+    #[test]
+    fn test_read_system_pressure() {
+        // PSI may be disabled (psi=0) or missing in containers
+        if let Ok(pressure) = PressureStall::read(PsiResource::Memory) {
+            assert!(pressure.some_avg10 >= pressure.full_avg10);
+        }
+    }
+}
@@ -1,427 +1,330 @@
+use crate::cpu_sampler::{CpuSampler, CpuSmoothing, SystemCpuTicks};
 use crate::{types::ProcessInfo, Error, Result};
-use std::process::Command;
+use procfs::process::{all_processes, Process};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Mutex;
+
+/// Снимок процесса, прочитанный напрямую из /proc/[pid]/{stat,status,cmdline}
+#[derive(Debug, Clone)]
+struct ProcEntry {
+    pid: u32,
+    /// Имя процесса (comm, как в `ps -o comm=`)
+    name: String,
+    /// Первый аргумент командной строки (argv[0])
+    argv0: String,
+    /// Полная командная строка (как в `ps -o cmd=`)
+    command: String,
+    /// utime + stime в тиках
+    cpu_ticks: u64,
+    /// Время старта процесса в тиках с момента загрузки
+    start_ticks: u64,
+    /// RSS в KB (VmRSS из status)
+    memory_kb: u64,
+}
 
-/// Сканер процессов
-pub struct ProcessScanner;
+impl ProcEntry {
+    /// Прочитать процесс из /proc. `None` если процесс исчез во время чтения
+    fn read(process: &Process) -> Option<Self> {
+        let stat = process.stat().ok()?;
+        let status = process.status().ok()?;
+        let args = process.cmdline().unwrap_or_default();
+
+        let pid = u32::try_from(stat.pid).ok()?;
+        let argv0 = args.first().cloned().unwrap_or_default();
+        let command = if args.is_empty() {
+            // Kernel threads have no cmdline, ps shows them as [comm]
+            format!("[{}]", stat.comm)
+        } else {
+            args.join(" ")
+        };
 
-impl ProcessScanner {
-    pub fn new() -> Self {
-        Self
+        Some(Self {
+            pid,
+            name: stat.comm,
+            argv0,
+            command,
+            cpu_ticks: stat.utime + stat.stime,
+            start_ticks: stat.starttime,
+            memory_kb: status.vmrss.unwrap_or(0),
+        })
     }
 
-    /// Найти KESL процесс и измерить CPU (3 замера с усреднением)
-    pub fn scan_kesl(&self) -> Result<Option<ProcessInfo>> {
-        // Найти PID процесса kesl
-        let pid = self.find_kesl_pid()?;
-
-        if let Some(pid) = pid {
-            // Измерить CPU (3 замера)
-            let cpu = self.measure_cpu_average(pid, 3)?;
+    /// Средняя загрузка CPU за время жизни процесса (как в первом кадре top)
+    fn lifetime_cpu_percent(&self, uptime_secs: f64, ticks_per_sec: u64) -> f64 {
+        let ticks_per_sec = ticks_per_sec as f64;
+        let elapsed = uptime_secs - self.start_ticks as f64 / ticks_per_sec;
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        (self.cpu_ticks as f64 / ticks_per_sec) / elapsed * 100.0
+    }
 
-            // Получить память
-            let memory_kb = self.get_memory_kb(pid)?;
+    fn into_process_info(self, cpu_percent: f64) -> ProcessInfo {
+        ProcessInfo::new(
+            self.pid,
+            self.name,
+            self.command,
+            cpu_percent,
+            self.memory_kb,
+        )
+        .with_start_ticks(self.start_ticks)
+    }
 
-            // Получить имя и команду
-            let (name, command) = self.get_process_info(pid)?;
+    // Main KESL process, not wdserver or kesl-starter
+    fn is_kesl(&self) -> bool {
+        self.command.contains("/opt/kaspersky/kesl/libexec/kesl")
+            && !self.command.contains("grep")
+            && !self.command.contains("wdserver")
+            && !self.command.contains("kesl-starter")
+    }
 
-            Ok(Some(ProcessInfo::new(pid, name, command, cpu, memory_kb)))
-        } else {
-            Ok(None)
-        }
+    // Команда "node" или заканчивается на "/node"
+    fn is_node(&self) -> bool {
+        self.argv0 == "node" || self.argv0.ends_with("/node")
     }
 
-    /// Найти все Node.js процессы
-    pub fn scan_node_processes(&self) -> Result<Vec<ProcessInfo>> {
-        let pids = self.find_node_pids()?;
-        let mut processes = Vec::new();
+    // snap, snapd, snap-store, snap-confine, /snap/...
+    fn is_snap(&self) -> bool {
+        self.argv0.contains("snap")
+    }
 
-        for pid in pids {
-            // Измерить CPU через top
-            let cpu = self.measure_cpu_top(pid)?;
+    // firefox, /usr/bin/firefox, /usr/lib/firefox/firefox
+    fn is_firefox(&self) -> bool {
+        self.argv0.contains("firefox")
+    }
 
-            // Получить память
-            let memory_kb = self.get_memory_kb(pid)?;
+    // brave, /usr/bin/brave, /opt/brave.com/brave/brave
+    fn is_brave(&self) -> bool {
+        self.argv0.contains("brave")
+    }
 
-            // Получить имя и команду
-            let (name, command) = self.get_process_info(pid)?;
+    // telegram-desktop, /usr/bin/telegram-desktop, /snap/telegram-desktop/...
+    fn is_telegram(&self) -> bool {
+        self.argv0.contains("telegram")
+    }
 
-            processes.push(ProcessInfo::new(pid, name, command, cpu, memory_kb));
-        }
+    // nvim, /usr/bin/nvim, /path/to/nvim
+    fn is_nvim(&self) -> bool {
+        self.argv0.contains("nvim")
+    }
+}
 
-        Ok(processes)
-    }
-
-    /// Найти PID процесса KESL
-    fn find_kesl_pid(&self) -> Result<Option<u32>> {
-        let output = Command::new("ps")
-            .args(&["aux"])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run ps: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-
-        for line in stdout.lines() {
-            // Find main KESL process, not wdserver or kesl-starter
-            if line.contains("/opt/kaspersky/kesl/libexec/kesl")
-                && !line.contains("grep")
-                && !line.contains("wdserver")
-                && !line.contains("kesl-starter") {
-                // Parse PID (second field in ps aux)
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() > 1 {
-                    if let Ok(pid) = parts[1].parse::<u32>() {
-                        return Ok(Some(pid));
-                    }
-                }
-            }
-        }
+/// Процесс из прохода по /proc с уже посчитанным CPU
+#[derive(Debug, Clone)]
+struct Scanned {
+    entry: ProcEntry,
+    cpu_percent: f64,
+}
 
-        Ok(None)
-    }
-
-    /// Найти все PID процессов node
-    fn find_node_pids(&self) -> Result<Vec<u32>> {
-        let output = Command::new("ps")
-            .args(&["aux"])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run ps: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut pids = Vec::new();
-
-        for line in stdout.lines() {
-            // Проверяем: команда заканчивается на "node" или содержит "/node"
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() > 10 {
-                let cmd = parts[10];
-                if cmd == "node" || cmd.ends_with("/node") {
-                    if let Ok(pid) = parts[1].parse::<u32>() {
-                        pids.push(pid);
-                    }
-                }
-            }
-        }
+/// Снимок всех процессов одного прохода по /proc
+///
+/// CPU уже посчитан при снятии снимка: выборки из него не сдвигают
+/// сэмплер, поэтому один снимок за цикл мониторинга можно читать сколько
+/// угодно раз (проверки, статистика, dashboard).
+#[derive(Debug, Clone, Default)]
+pub struct ProcessScan {
+    processes: Vec<Scanned>,
+}
 
-        Ok(pids)
+impl ProcessScan {
+    /// KESL процесс
+    pub fn kesl(&self) -> Option<ProcessInfo> {
+        self.matching(ProcEntry::is_kesl).into_iter().next()
     }
 
-    /// Найти все snap/snapd процессы
-    pub fn scan_snap_processes(&self) -> Result<Vec<ProcessInfo>> {
-        let pids = self.find_snap_pids()?;
-        let mut processes = Vec::new();
-
-        for pid in pids {
-            // Измерить CPU через top
-            let cpu = self.measure_cpu_top(pid)?;
+    /// Все процессы системы (для движка правил)
+    pub fn all_processes(&self) -> Vec<ProcessInfo> {
+        self.matching(|_| true)
+    }
 
-            // Получить память
-            let memory_kb = self.get_memory_kb(pid)?;
+    /// Все Node.js процессы
+    pub fn node_processes(&self) -> Vec<ProcessInfo> {
+        self.matching(ProcEntry::is_node)
+    }
 
-            // Получить имя и команду
-            let (name, command) = self.get_process_info(pid)?;
+    /// Все snap/snapd процессы
+    pub fn snap_processes(&self) -> Vec<ProcessInfo> {
+        self.matching(ProcEntry::is_snap)
+    }
 
-            processes.push(ProcessInfo::new(pid, name, command, cpu, memory_kb));
-        }
+    /// Все Firefox процессы
+    pub fn firefox_processes(&self) -> Vec<ProcessInfo> {
+        self.matching(ProcEntry::is_firefox)
+    }
 
-        Ok(processes)
-    }
-
-    /// Найти все PID процессов snap/snapd
-    fn find_snap_pids(&self) -> Result<Vec<u32>> {
-        let output = Command::new("ps")
-            .args(&["aux"])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run ps: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut pids = Vec::new();
-
-        for line in stdout.lines() {
-            // Ищем процессы snap, snapd, snap-store, snap-confine
-            if line.contains("snap") && !line.contains("grep") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() > 10 {
-                    let cmd = parts[10];
-                    // Проверяем что это действительно snap процесс
-                    if cmd.contains("snap") || cmd.contains("/snap/") {
-                        if let Ok(pid) = parts[1].parse::<u32>() {
-                            pids.push(pid);
-                        }
-                    }
-                }
-            }
-        }
+    /// Все Brave процессы
+    pub fn brave_processes(&self) -> Vec<ProcessInfo> {
+        self.matching(ProcEntry::is_brave)
+    }
 
-        Ok(pids)
+    /// Все Telegram процессы
+    pub fn telegram_processes(&self) -> Vec<ProcessInfo> {
+        self.matching(ProcEntry::is_telegram)
     }
 
-    /// Найти все Firefox процессы
-    pub fn scan_firefox_processes(&self) -> Result<Vec<ProcessInfo>> {
-        let pids = self.find_firefox_pids()?;
-        let mut processes = Vec::new();
+    /// Все Neovim процессы
+    pub fn nvim_processes(&self) -> Vec<ProcessInfo> {
+        self.matching(ProcEntry::is_nvim)
+    }
 
-        for pid in pids {
-            // Измерить CPU через top
-            let cpu = self.measure_cpu_top(pid)?;
+    fn matching(&self, predicate: fn(&ProcEntry) -> bool) -> Vec<ProcessInfo> {
+        self.processes
+            .iter()
+            .filter(|scanned| predicate(&scanned.entry))
+            .map(|scanned| scanned.entry.clone().into_process_info(scanned.cpu_percent))
+            .collect()
+    }
+}
 
-            // Получить память
-            let memory_kb = self.get_memory_kb(pid)?;
+/// Сканер процессов
+///
+/// Читает /proc напрямую (один проход на вызов) вместо запуска ps/top.
+/// CPU считается по дельте тиков между вызовами (без sleep), поэтому сканер
+/// нужно переиспользовать между циклами мониторинга.
+///
+/// Каждый вызов `scan`/`scan_*` - новое измерение CPU. Чтобы проверки
+/// одного цикла не сокращали окно измерения друг другу, снимайте один
+/// [`ProcessScan`] за цикл.
+pub struct ProcessScanner {
+    sampler: Mutex<CpuSampler>,
+}
 
-            // Получить имя и команду
-            let (name, command) = self.get_process_info(pid)?;
+impl Default for ProcessScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            processes.push(ProcessInfo::new(pid, name, command, cpu, memory_kb));
-        }
+impl ProcessScanner {
+    pub fn new() -> Self {
+        Self::with_smoothing(CpuSmoothing::default())
+    }
 
-        Ok(processes)
-    }
-
-    /// Найти все PID процессов Firefox
-    fn find_firefox_pids(&self) -> Result<Vec<u32>> {
-        let output = Command::new("ps")
-            .args(&["aux"])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run ps: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut pids = Vec::new();
-
-        for line in stdout.lines() {
-            // Проверяем: команда содержит "firefox" (включая /usr/lib/firefox/firefox)
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() > 10 {
-                let cmd = parts[10];
-                // Ищем firefox в команде (может быть firefox, /usr/bin/firefox, /usr/lib/firefox/firefox)
-                if cmd.contains("firefox") && !line.contains("grep") {
-                    if let Ok(pid) = parts[1].parse::<u32>() {
-                        pids.push(pid);
-                    }
-                }
-            }
+    /// Создать сканер с заданным сглаживанием CPU
+    pub fn with_smoothing(smoothing: CpuSmoothing) -> Self {
+        Self {
+            sampler: Mutex::new(CpuSampler::new(smoothing)),
         }
-
-        Ok(pids)
     }
 
-    /// Найти все Brave процессы
-    pub fn scan_brave_processes(&self) -> Result<Vec<ProcessInfo>> {
-        let pids = self.find_brave_pids()?;
-        let mut processes = Vec::new();
+    /// Текущее сглаживание CPU
+    pub fn cpu_smoothing(&self) -> CpuSmoothing {
+        self.lock_sampler().smoothing()
+    }
 
-        for pid in pids {
-            // Измерить CPU через top
-            let cpu = self.measure_cpu_top(pid)?;
+    /// Найти KESL процесс и измерить CPU (дельта с предыдущего вызова, со сглаживанием)
+    pub fn scan_kesl(&self) -> Result<Option<ProcessInfo>> {
+        Ok(self.scan()?.kesl())
+    }
 
-            // Получить память
-            let memory_kb = self.get_memory_kb(pid)?;
+    /// Все процессы системы (для движка правил)
+    pub fn scan_all_processes(&self) -> Result<Vec<ProcessInfo>> {
+        self.scan_matching(|_| true)
+    }
 
-            // Получить имя и команду
-            let (name, command) = self.get_process_info(pid)?;
+    /// Найти все Node.js процессы
+    pub fn scan_node_processes(&self) -> Result<Vec<ProcessInfo>> {
+        self.scan_matching(ProcEntry::is_node)
+    }
 
-            processes.push(ProcessInfo::new(pid, name, command, cpu, memory_kb));
-        }
+    /// Найти все snap/snapd процессы
+    pub fn scan_snap_processes(&self) -> Result<Vec<ProcessInfo>> {
+        self.scan_matching(ProcEntry::is_snap)
+    }
 
-        Ok(processes)
-    }
-
-    /// Найти все PID процессов Brave
-    fn find_brave_pids(&self) -> Result<Vec<u32>> {
-        let output = Command::new("ps")
-            .args(&["aux"])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run ps: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut pids = Vec::new();
-
-        for line in stdout.lines() {
-            // Проверяем: команда содержит "brave" (включая /opt/brave.com/brave/brave)
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() > 10 {
-                let cmd = parts[10];
-                // Ищем brave в команде (может быть brave, /usr/bin/brave, /opt/brave.com/brave/brave)
-                if cmd.contains("brave") && !line.contains("grep") {
-                    if let Ok(pid) = parts[1].parse::<u32>() {
-                        pids.push(pid);
-                    }
-                }
-            }
-        }
+    /// Найти все Firefox процессы
+    pub fn scan_firefox_processes(&self) -> Result<Vec<ProcessInfo>> {
+        self.scan_matching(ProcEntry::is_firefox)
+    }
 
-        Ok(pids)
+    /// Найти все Brave процессы
+    pub fn scan_brave_processes(&self) -> Result<Vec<ProcessInfo>> {
+        self.scan_matching(ProcEntry::is_brave)
     }
 
     /// Найти все Telegram процессы
     pub fn scan_telegram_processes(&self) -> Result<Vec<ProcessInfo>> {
-        let pids = self.find_telegram_pids()?;
-        let mut processes = Vec::new();
-
-        for pid in pids {
-            // Измерить CPU через top
-            let cpu = self.measure_cpu_top(pid)?;
-
-            // Получить память
-            let memory_kb = self.get_memory_kb(pid)?;
-
-            // Получить имя и команду
-            let (name, command) = self.get_process_info(pid)?;
-
-            processes.push(ProcessInfo::new(pid, name, command, cpu, memory_kb));
-        }
-
-        Ok(processes)
-    }
-
-    /// Найти все PID процессов Telegram
-    fn find_telegram_pids(&self) -> Result<Vec<u32>> {
-        let output = Command::new("ps")
-            .args(&["aux"])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run ps: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut pids = Vec::new();
-
-        for line in stdout.lines() {
-            // Проверяем: команда содержит "telegram" (включая telegram-desktop, /snap/telegram-desktop)
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() > 10 {
-                let cmd = parts[10];
-                // Ищем telegram в команде (может быть telegram-desktop, /usr/bin/telegram-desktop, /snap/telegram-desktop/...)
-                if cmd.contains("telegram") && !line.contains("grep") {
-                    if let Ok(pid) = parts[1].parse::<u32>() {
-                        pids.push(pid);
-                    }
-                }
-            }
-        }
-
-        Ok(pids)
+        self.scan_matching(ProcEntry::is_telegram)
     }
 
     /// Найти все Neovim процессы
     pub fn scan_nvim_processes(&self) -> Result<Vec<ProcessInfo>> {
-        let pids = self.find_nvim_pids()?;
-        let mut processes = Vec::new();
-
-        for pid in pids {
-            let cpu = self.measure_cpu_top(pid)?;
-            let memory_kb = self.get_memory_kb(pid)?;
-            let (name, command) = self.get_process_info(pid)?;
-            processes.push(ProcessInfo::new(pid, name, command, cpu, memory_kb));
-        }
-
-        Ok(processes)
-    }
-
-    /// Найти все PID процессов Neovim
-    fn find_nvim_pids(&self) -> Result<Vec<u32>> {
-        let output = Command::new("ps")
-            .args(&["aux"])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run ps: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut pids = Vec::new();
-
-        for line in stdout.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() > 10 {
-                let cmd = parts[10];
-                // Ищем nvim в команде (может быть nvim, /usr/bin/nvim, /path/to/nvim)
-                if cmd.contains("nvim") && !line.contains("grep") {
-                    if let Ok(pid) = parts[1].parse::<u32>() {
-                        pids.push(pid);
-                    }
-                }
-            }
-        }
-
-        Ok(pids)
+        self.scan_matching(ProcEntry::is_nvim)
     }
 
-    /// Измерить CPU через top (3 замера с усреднением)
-    fn measure_cpu_average(&self, pid: u32, samples: usize) -> Result<f64> {
-        let mut sum = 0.0;
-        let mut count = 0;
-
-        for i in 0..samples {
-            let cpu = self.measure_cpu_top(pid)?;
-            if cpu > 0.0 {
-                sum += cpu;
-                count += 1;
-            }
-
-            // Спать 1 секунду между замерами (кроме последнего)
-            if i < samples - 1 {
-                std::thread::sleep(std::time::Duration::from_secs(1));
-            }
-        }
-
-        if count > 0 {
-            Ok(sum / count as f64)
-        } else {
-            Ok(0.0)
-        }
+    /// Один проход по /proc: посчитать CPU всех процессов
+    ///
+    /// CPU - дельта с предыдущего наблюдения процесса. При первом наблюдении
+    /// (базы ещё нет) используется средняя загрузка за время жизни.
+    pub fn scan(&self) -> Result<ProcessScan> {
+        let uptime = Self::read_uptime()?;
+        let ticks_per_sec = procfs::ticks_per_second();
+        let system = SystemCpuTicks::read()?;
+
+        let entries = self.read_all()?;
+        let mut sampler = self.lock_sampler();
+
+        // Забыть завершившиеся процессы
+        let alive: HashSet<u32> = entries.iter().map(|entry| entry.pid).collect();
+        sampler.retain_pids(&alive);
+
+        let processes = entries
+            .into_iter()
+            .map(|entry| {
+                let cpu_percent = sampler
+                    .sample(entry.pid, entry.start_ticks, entry.cpu_ticks, system)
+                    .unwrap_or_else(|| entry.lifetime_cpu_percent(uptime, ticks_per_sec));
+                Scanned { entry, cpu_percent }
+            })
+            .collect();
+
+        Ok(ProcessScan { processes })
     }
 
-    /// Измерить CPU через top (один замер)
-    fn measure_cpu_top(&self, pid: u32) -> Result<f64> {
-        let output = Command::new("top")
-            .args(&["-b", "-n1", "-p", &pid.to_string()])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to run top: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-
-        // Берем последнюю строку (данные процесса)
-        if let Some(last_line) = stdout.lines().last() {
-            let parts: Vec<&str> = last_line.split_whitespace().collect();
-            // CPU% обычно в 9-м столбце (считая с 1)
-            if parts.len() > 8 {
-                let cpu_str = parts[8].replace(',', ".");
-                if let Ok(cpu) = cpu_str.parse::<f64>() {
-                    return Ok(cpu);
-                }
-            }
-        }
+    /// Новый снимок, отфильтрованный по `predicate`
+    fn scan_matching(&self, predicate: fn(&ProcEntry) -> bool) -> Result<Vec<ProcessInfo>> {
+        Ok(self.scan()?.matching(predicate))
+    }
 
-        Ok(0.0)
+    fn lock_sampler(&self) -> std::sync::MutexGuard<'_, CpuSampler> {
+        // Паника в другом потоке не портит состояние сэмплера
+        self.sampler.lock().unwrap_or_else(|e| e.into_inner())
     }
 
-    /// Получить использование памяти в KB (RSS)
-    fn get_memory_kb(&self, pid: u32) -> Result<u64> {
-        let output = Command::new("ps")
-            .args(&["-p", &pid.to_string(), "-o", "rss="])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to get memory: {}", e)))?;
+    /// Прочитать все процессы из /proc
+    ///
+    /// Процессы, завершившиеся во время чтения, пропускаются
+    fn read_all(&self) -> Result<Vec<ProcEntry>> {
+        let processes =
+            all_processes().map_err(|e| Error::Scanner(format!("Failed to read /proc: {}", e)))?;
+
+        Ok(processes
+            .filter_map(|process| process.ok())
+            .filter_map(|process| ProcEntry::read(&process))
+            .collect())
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let rss_kb = stdout.trim().parse::<u64>().unwrap_or(0);
+    /// Прочитать один процесс по PID
+    #[cfg(test)]
+    fn read_pid(&self, pid: u32) -> Result<ProcEntry> {
+        let process = Process::new(pid as i32)
+            .map_err(|e| Error::Scanner(format!("Process {} not found: {}", pid, e)))?;
 
-        Ok(rss_kb)
+        ProcEntry::read(&process)
+            .ok_or_else(|| Error::Scanner(format!("Failed to read /proc/{}", pid)))
     }
 
-    /// Получить имя процесса и команду
-    fn get_process_info(&self, pid: u32) -> Result<(String, String)> {
-        let output = Command::new("ps")
-            .args(&["-p", &pid.to_string(), "-o", "comm=,cmd="])
-            .output()
-            .map_err(|e| Error::Scanner(format!("Failed to get process info: {}", e)))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let line = stdout.trim();
+    /// Время работы системы в секундах (/proc/uptime)
+    fn read_uptime() -> Result<f64> {
+        let content = fs::read_to_string("/proc/uptime")
+            .map_err(|e| Error::Scanner(format!("Failed to read /proc/uptime: {}", e)))?;
 
-        // Разделяем на имя и полную команду
-        let parts: Vec<&str> = line.splitn(2, ' ').collect();
-        let name = parts[0].to_string();
-        let command = if parts.len() > 1 {
-            parts[1].to_string()
-        } else {
-            name.clone()
-        };
-
-        Ok((name, command))
+        content
+            .split_whitespace()
+            .next()
+            .and_then(|s| s.parse::<f64>().ok())
+            .ok_or_else(|| Error::Parse(format!("Invalid /proc/uptime: {}", content.trim())))
     }
 }
 
@@ -429,11 +332,73 @@ impl ProcessScanner {
 mod tests {
     use super::*;
 
+    /// Fake /proc/<pid> with the given stat, status and cmdline files
+    ///
+    /// Returns the fake proc root (to remove) and the process.
+    fn fixture(pid: u32, stat: &str, status: &str, cmdline: &[u8]) -> (std::path::PathBuf, Process) {
+        let root = std::env::temp_dir()
+            .join(format!("freezr-scanner-{}-{}", pid, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        // procfs takes the PID from the directory name
+        let dir = root.join(pid.to_string());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("stat"), stat).unwrap();
+        fs::write(dir.join("status"), status).unwrap();
+        fs::write(dir.join("cmdline"), cmdline).unwrap();
+        let process = Process::new_with_root(dir).unwrap();
+        (root, process)
+    }
+
+    /// /proc/[pid]/status of a kernel 6.x process, VmRSS line optional
+    fn status(name: &str, pid: u32, vm_rss: Option<&str>) -> String {
+        let mut status = format!(
+            "Name:\t{name}\nUmask:\t0022\nState:\tS (sleeping)\nTgid:\t{pid}\nNgid:\t0\n\
+             Pid:\t{pid}\nPPid:\t1\nTracerPid:\t0\nUid:\t1000\t1000\t1000\t1000\n\
+             Gid:\t1000\t1000\t1000\t1000\nFDSize:\t64\nGroups:\t1000\nNStgid:\t{pid}\n\
+             NSpid:\t{pid}\nNSpgid:\t{pid}\nNSsid:\t{pid}\n"
+        );
+        if let Some(vm_rss) = vm_rss {
+            status.push_str(&format!("VmPeak:\t  900000 kB\nVmSize:\t  880000 kB\nVmRSS:\t{}\n", vm_rss));
+        }
+        status.push_str(
+            "Threads:\t11\nSigQ:\t0/63418\nSigPnd:\t0000000000000000\n\
+             ShdPnd:\t0000000000000000\nSigBlk:\t0000000000000000\n\
+             SigIgn:\t0000000001001000\nSigCgt:\t0000000108004602\n\
+             CapInh:\t0000000000000000\nCapPrm:\t0000000000000000\n\
+             CapEff:\t0000000000000000\nCapBnd:\t000001ffffffffff\n\
+             CapAmb:\t0000000000000000\nNoNewPrivs:\t0\nSeccomp:\t0\n\
+             Cpus_allowed:\tff\nCpus_allowed_list:\t0-7\nMems_allowed:\t1\n\
+             Mems_allowed_list:\t0\nvoluntary_ctxt_switches:\t120\n\
+             nonvoluntary_ctxt_switches:\t7\n",
+        );
+        status
+    }
+
     #[test]
-    fn test_scanner_creation() {
-        let _scanner = ProcessScanner::new();
-        // Just verify it can be created without panicking
-        assert!(true);
+    fn test_read_entry_fixture() {
+        let (dir, process) = fixture(
+            4242,
+            "4242 (node) S 1 4242 4242 0 -1 4194560 2130 0 0 0 250 50 0 0 20 0 11 0 5000 \
+             901120000 51200 18446744073709551615 1 1 0 0 0 0 0 4096 134234626 0 0 0 17 3 0 0 \
+             0 0 0 0 0 0 0 0 0 0 0\n",
+            &status("node", 4242, Some("  204800 kB")),
+            b"/usr/bin/node\0server.js\0--watch\0",
+        );
+
+        let entry = ProcEntry::read(&process).unwrap();
+        assert_eq!(entry.pid, 4242);
+        assert_eq!(entry.name, "node");
+        assert_eq!(entry.argv0, "/usr/bin/node");
+        assert_eq!(entry.command, "/usr/bin/node server.js --watch");
+        assert_eq!(entry.cpu_ticks, 300);
+        assert_eq!(entry.start_ticks, 5000);
+        assert_eq!(entry.memory_kb, 204800);
+        assert!(entry.is_node());
+
+        let info = entry.into_process_info(12.5);
+        assert_eq!(info.start_ticks, 5000);
+        assert_eq!(info.memory_mb, 200);
+        let _ = fs::remove_dir_all(dir);
     }
 
     #[test]
@@ -456,7 +421,9 @@ mod tests {
     fn test_find_kesl_pid_does_not_panic() {
         let scanner = ProcessScanner::new();
         // Should not panic even if KESL doesn't exist
-        let result = scanner.find_kesl_pid();
+        let result = scanner
+            .read_all()
+            .map(|entries| entries.into_iter().find(ProcEntry::is_kesl));
         assert!(result.is_ok());
     }
 
@@ -464,13 +431,13 @@ mod tests {
     fn test_find_node_pids_returns_vec() {
         let scanner = ProcessScanner::new();
         // Should return a Vec (possibly empty)
-        let result = scanner.find_node_pids();
+        let result = scanner.scan_node_processes();
         assert!(result.is_ok());
 
-        if let Ok(pids) = result {
+        if let Ok(processes) = result {
             // PIDs should be positive numbers
-            for pid in pids {
-                assert!(pid > 0);
+            for process in processes {
+                assert!(process.pid > 0);
             }
         }
     }
@@ -479,7 +446,7 @@ mod tests {
     fn test_get_memory_kb_with_init_process() {
         let scanner = ProcessScanner::new();
         // PID 1 (init/systemd) always exists
-        let result = scanner.get_memory_kb(1);
+        let result = scanner.read_pid(1).map(|e| e.memory_kb);
 
         if let Ok(mem) = result {
             // Init process should have some memory
@@ -491,7 +458,7 @@ mod tests {
     fn test_get_memory_kb_invalid_pid() {
         let scanner = ProcessScanner::new();
         // Invalid PID should return 0 or error
-        let result = scanner.get_memory_kb(999999);
+        let result = scanner.read_pid(999999).map(|e| e.memory_kb);
 
         if let Ok(mem) = result {
             // Should be 0 for non-existent process
@@ -503,9 +470,9 @@ mod tests {
     fn test_get_process_info_init() {
         let scanner = ProcessScanner::new();
         // PID 1 should have process info
-        let result = scanner.get_process_info(1);
+        let result = scanner.read_pid(1);
 
-        if let Ok((name, command)) = result {
+        if let Ok(ProcEntry { name, command, .. }) = result {
             // Name and command should not be empty
             assert!(!name.is_empty());
             assert!(!command.is_empty());
@@ -514,41 +481,163 @@ mod tests {
         }
     }
 
+    fn entry(argv0: &str, command: &str) -> ProcEntry {
+        ProcEntry {
+            pid: 42,
+            name: argv0.rsplit('/').next().unwrap_or(argv0).to_string(),
+            argv0: argv0.to_string(),
+            command: command.to_string(),
+            cpu_ticks: 0,
+            start_ticks: 0,
+            memory_kb: 0,
+        }
+    }
+
     #[test]
-    fn test_measure_cpu_top_init_process() {
+    fn test_read_pid_self() {
         let scanner = ProcessScanner::new();
-        // Measuring CPU for init (should be very low)
-        let result = scanner.measure_cpu_top(1);
+        // Our own process always exists
+        let entry = scanner.read_pid(std::process::id()).unwrap();
 
-        if let Ok(cpu) = result {
-            // CPU should be a valid percentage
-            assert!(cpu >= 0.0);
-            assert!(cpu <= 100.0); // Single core can't exceed 100% in top output
-        }
+        assert_eq!(entry.pid, std::process::id());
+        assert!(!entry.name.is_empty());
+        assert!(!entry.command.is_empty());
+        assert!(entry.memory_kb > 0);
     }
 
     #[test]
-    fn test_measure_cpu_average_bounds() {
+    fn test_read_all_contains_self() {
         let scanner = ProcessScanner::new();
-        // Test with init process (PID 1) and 1 sample to make it fast
-        let result = scanner.measure_cpu_average(1, 1);
+        let entries = scanner.read_all().unwrap();
+
+        assert!(entries.iter().any(|e| e.pid == std::process::id()));
+    }
+
+    #[test]
+    fn test_lifetime_cpu_percent() {
+        let mut e = entry("node", "node server.js");
+        e.cpu_ticks = 500; // 5 seconds of CPU at 100 ticks/sec
+        e.start_ticks = 1000; // started 10s after boot
+
+        // 20s uptime -> 10s alive, 5s CPU -> 50%
+        assert!((e.lifetime_cpu_percent(20.0, 100) - 50.0).abs() < 0.001);
+
+        // Process started "in the future" (clock skew) -> 0%
+        assert_eq!(e.lifetime_cpu_percent(5.0, 100), 0.0);
+    }
 
-        if let Ok(cpu) = result {
-            // CPU should be within valid range
-            assert!(cpu >= 0.0);
-            assert!(cpu <= 100.0);
+    #[test]
+    fn test_kesl_matching() {
+        assert!(entry(
+            "/opt/kaspersky/kesl/libexec/kesl",
+            "/opt/kaspersky/kesl/libexec/kesl -d"
+        )
+        .is_kesl());
+        assert!(!entry(
+            "/opt/kaspersky/kesl/libexec/kesl-starter",
+            "/opt/kaspersky/kesl/libexec/kesl-starter"
+        )
+        .is_kesl());
+        assert!(!entry(
+            "/opt/kaspersky/kesl/libexec/kesl",
+            "/opt/kaspersky/kesl/libexec/kesl --wdserver"
+        )
+        .is_kesl());
+    }
+
+    #[test]
+    fn test_node_matching() {
+        assert!(entry("node", "node server.js").is_node());
+        assert!(entry("/usr/bin/node", "/usr/bin/node app.js").is_node());
+        assert!(!entry("nodemon", "nodemon app.js").is_node());
+        assert!(!entry("python", "python node.py").is_node());
+    }
+
+    #[test]
+    fn test_app_matching() {
+        assert!(entry(
+            "/usr/lib/firefox/firefox",
+            "/usr/lib/firefox/firefox -contentproc"
+        )
+        .is_firefox());
+        assert!(entry(
+            "/opt/brave.com/brave/brave",
+            "/opt/brave.com/brave/brave --type=renderer"
+        )
+        .is_brave());
+        assert!(entry(
+            "/snap/telegram-desktop/6/usr/bin/telegram-desktop",
+            "telegram-desktop"
+        )
+        .is_telegram());
+        assert!(entry("/usr/lib/snapd/snapd", "/usr/lib/snapd/snapd").is_snap());
+        assert!(entry("nvim", "nvim src/main.rs").is_nvim());
+
+        // Matching uses argv[0], not arguments
+        assert!(!entry("vim", "vim firefox.txt").is_firefox());
+    }
+
+    #[test]
+    fn test_scan_cpu_is_sampled_between_calls() {
+        let scanner = ProcessScanner::with_smoothing(CpuSmoothing::None);
+        let pid = std::process::id();
+
+        // First scan seeds the sampler, second one uses the delta
+        let _ = scanner.scan_matching(|entry| entry.pid == std::process::id());
+        let busy_until = std::time::Instant::now() + std::time::Duration::from_millis(300);
+        while std::time::Instant::now() < busy_until {
+            std::hint::black_box(0u64);
         }
+
+        let processes = scanner
+            .scan_matching(|entry| entry.pid == std::process::id())
+            .unwrap();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].pid, pid);
+        assert!(processes[0].cpu_percent >= 0.0);
+        assert!(scanner.lock_sampler().tracked_count() > 0);
     }
 
     #[test]
-    fn test_scanner_multiple_calls() {
-        let scanner = ProcessScanner::new();
+    fn test_reading_a_scan_does_not_sample() {
+        let scanner = ProcessScanner::with_smoothing(CpuSmoothing::None);
+        let pid = std::process::id();
+        let own_cpu = |scan: &ProcessScan| {
+            scan.all_processes()
+                .into_iter()
+                .find(|p| p.pid == pid)
+                .map(|p| p.cpu_percent)
+        };
+
+        let scan = scanner.scan().unwrap();
+        let first = own_cpu(&scan);
+        assert!(first.is_some());
 
-        // Multiple calls should not panic or crash
-        let _ = scanner.find_kesl_pid();
-        let _ = scanner.find_node_pids();
-        let _ = scanner.get_memory_kb(1);
+        // Reads of the same scan (stats, dashboard) leave the sampler alone
+        let tracked = scanner.lock_sampler().tracked_count();
+        assert_eq!(own_cpu(&scan), first);
+        assert_eq!(scanner.lock_sampler().tracked_count(), tracked);
+    }
 
-        assert!(true);
+    #[test]
+    fn test_read_kernel_thread_fixture() {
+        // Kernel threads: empty cmdline, no Vm* lines in status
+        let (dir, process) = fixture(
+            2,
+            "2 (kthreadd) S 0 0 0 0 -1 2129984 0 0 0 0 0 3 0 0 20 0 1 0 2 0 0 \
+             18446744073709551615 0 0 0 0 0 0 0 2147483647 0 0 0 0 17 1 0 0 \
+             0 0 0 0 0 0 0 0 0 0 0\n",
+            &status("kthreadd", 2, None),
+            b"",
+        );
+
+        let entry = ProcEntry::read(&process).unwrap();
+        assert_eq!(entry.pid, 2);
+        assert_eq!(entry.argv0, "");
+        assert_eq!(entry.command, "[kthreadd]");
+        assert_eq!(entry.cpu_ticks, 3);
+        assert_eq!(entry.memory_kb, 0);
+        assert!(!entry.is_kesl() && !entry.is_node());
+        let _ = fs::remove_dir_all(dir);
     }
 }
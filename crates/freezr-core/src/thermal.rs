@@ -0,0 +1,295 @@
+//! Temperature sensors from sysfs
+//!
+//! Two kernel interfaces report temperatures:
+//!
+//! - thermal zones (`/sys/class/thermal/thermal_zone*`): `type`, `temp` and
+//!   the zone's trip points (`trip_point_N_temp`, `trip_point_N_type`)
+//! - hwmon chips (`/sys/class/hwmon/hwmon*`): `name` plus `tempN_input`,
+//!   `tempN_label` and `tempN_max`/`tempN_crit` per sensor
+//!
+//! Laptops usually have both (e.g. `x86_pkg_temp` and `coretemp`), desktop
+//! boards often only hwmon. Values are read in millidegrees Celsius.
+//!
+//! The sysfs root is configurable so tests can use a fake tree.
+
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default sysfs mount point
+pub const DEFAULT_SYSFS_ROOT: &str = "/sys";
+
+/// Interface a sensor was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorSource {
+    ThermalZone,
+    Hwmon,
+}
+
+/// Temperature at which the kernel or firmware acts
+#[derive(Debug, Clone, PartialEq)]
+pub struct TripPoint {
+    /// "passive", "active", "hot" or "critical" for zones; "max" or "crit" for hwmon
+    pub kind: String,
+    pub celsius: f64,
+}
+
+/// One temperature reading
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalSensor {
+    /// Zone type ("x86_pkg_temp") or hwmon chip and label ("coretemp/Package id 0")
+    pub name: String,
+    pub source: SensorSource,
+    pub celsius: f64,
+    pub trip_points: Vec<TripPoint>,
+}
+
+impl ThermalSensor {
+    /// Lowest trip point of a kind, e.g. "critical"
+    pub fn trip_point(&self, kind: &str) -> Option<f64> {
+        self.trip_points
+            .iter()
+            .filter(|t| t.kind == kind)
+            .map(|t| t.celsius)
+            .min_by(f64::total_cmp)
+    }
+}
+
+/// Reads thermal zones and hwmon sensors below a sysfs root
+#[derive(Debug, Clone)]
+pub struct ThermalReader {
+    root: PathBuf,
+}
+
+impl Default for ThermalReader {
+    fn default() -> Self {
+        Self::new(DEFAULT_SYSFS_ROOT)
+    }
+}
+
+impl ThermalReader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// All thermal zones and hwmon sensors
+    ///
+    /// Sensors that fail to read (disabled zones report EINVAL or ENODATA)
+    /// are skipped. Fails only if neither class directory can be listed.
+    pub fn read_all(&self) -> Result<Vec<ThermalSensor>> {
+        let zones = self.read_zones();
+        let hwmon = self.read_hwmon();
+        match (zones, hwmon) {
+            (Err(e), Err(_)) => Err(e),
+            (zones, hwmon) => Ok(zones
+                .unwrap_or_default()
+                .into_iter()
+                .chain(hwmon.unwrap_or_default())
+                .collect()),
+        }
+    }
+
+    /// Sensors of /sys/class/thermal/thermal_zone*
+    pub fn read_zones(&self) -> Result<Vec<ThermalSensor>> {
+        let mut sensors = Vec::new();
+        for dir in self.class_entries("thermal", "thermal_zone")? {
+            let Some(celsius) = read_millidegrees(&dir.join("temp")) else {
+                continue;
+            };
+            let name = read_trimmed(&dir.join("type")).unwrap_or_else(|| file_name(&dir));
+
+            let mut trip_points = Vec::new();
+            for index in 0.. {
+                let Some(trip) = read_millidegrees(&dir.join(format!("trip_point_{}_temp", index)))
+                else {
+                    break;
+                };
+                let kind = read_trimmed(&dir.join(format!("trip_point_{}_type", index)))
+                    .unwrap_or_default();
+                trip_points.push(TripPoint {
+                    kind,
+                    celsius: trip,
+                });
+            }
+
+            sensors.push(ThermalSensor {
+                name,
+                source: SensorSource::ThermalZone,
+                celsius,
+                trip_points,
+            });
+        }
+        Ok(sensors)
+    }
+
+    /// Sensors of /sys/class/hwmon/hwmon* (`tempN_input` files)
+    pub fn read_hwmon(&self) -> Result<Vec<ThermalSensor>> {
+        let mut sensors = Vec::new();
+        for dir in self.class_entries("hwmon", "hwmon")? {
+            let chip = read_trimmed(&dir.join("name")).unwrap_or_else(|| file_name(&dir));
+
+            let mut inputs: Vec<u32> = fs::read_dir(&dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter_map(|e| {
+                            let name = e.file_name().into_string().ok()?;
+                            name.strip_prefix("temp")?
+                                .strip_suffix("_input")?
+                                .parse()
+                                .ok()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            inputs.sort_unstable();
+
+            for index in inputs {
+                let file = |suffix: &str| dir.join(format!("temp{}_{}", index, suffix));
+                let Some(celsius) = read_millidegrees(&file("input")) else {
+                    continue;
+                };
+                let label =
+                    read_trimmed(&file("label")).unwrap_or_else(|| format!("temp{}", index));
+
+                let trip_points = ["max", "crit"]
+                    .into_iter()
+                    .filter_map(|kind| {
+                        let celsius = read_millidegrees(&file(kind))?;
+                        Some(TripPoint {
+                            kind: kind.to_string(),
+                            celsius,
+                        })
+                    })
+                    .collect();
+
+                sensors.push(ThermalSensor {
+                    name: format!("{}/{}", chip, label),
+                    source: SensorSource::Hwmon,
+                    celsius,
+                    trip_points,
+                });
+            }
+        }
+        Ok(sensors)
+    }
+
+    /// Entries of /sys/class/<class> named `<prefix>N`, sorted by N
+    fn class_entries(&self, class: &str, prefix: &str) -> Result<Vec<PathBuf>> {
+        let class_dir = self.root.join("class").join(class);
+        let entries = fs::read_dir(&class_dir)
+            .map_err(|e| Error::Other(format!("Failed to read {}: {}", class_dir.display(), e)))?;
+
+        let mut numbered: Vec<(u32, PathBuf)> = entries
+            .flatten()
+            .filter_map(|e| {
+                let index = e.file_name().to_str()?.strip_prefix(prefix)?.parse().ok()?;
+                Some((index, e.path()))
+            })
+            .collect();
+        numbered.sort_unstable_by_key(|(index, _)| *index);
+        Ok(numbered.into_iter().map(|(_, path)| path).collect())
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let trimmed = content.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+/// Millidegree file in degrees Celsius
+fn read_millidegrees(path: &Path) -> Option<f64> {
+    let millidegrees: i64 = read_trimmed(path)?.parse().ok()?;
+    Some(millidegrees as f64 / 1000.0)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_sysfs(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("freezr-thermal-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    fn write(root: &Path, file: &str, content: &str) {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_read_zones() {
+        let root = fake_sysfs("zones");
+        write(&root, "class/thermal/thermal_zone0/type", "acpitz\n");
+        write(&root, "class/thermal/thermal_zone0/temp", "45000\n");
+        write(
+            &root,
+            "class/thermal/thermal_zone0/trip_point_0_temp",
+            "105000\n",
+        );
+        write(
+            &root,
+            "class/thermal/thermal_zone0/trip_point_0_type",
+            "critical\n",
+        );
+        write(&root, "class/thermal/thermal_zone10/type", "x86_pkg_temp\n");
+        write(&root, "class/thermal/thermal_zone10/temp", "87500\n");
+        write(&root, "class/thermal/thermal_zone2/type", "iwlwifi_1\n");
+        // Disabled zone: temp unreadable
+        write(&root, "class/thermal/thermal_zone2/temp", "");
+        write(&root, "class/thermal/cooling_device0/type", "Processor\n");
+
+        let sensors = ThermalReader::new(&root).read_zones().unwrap();
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0].name, "acpitz");
+        assert_eq!(sensors[0].celsius, 45.0);
+        assert_eq!(sensors[0].trip_point("critical"), Some(105.0));
+        assert_eq!(sensors[0].trip_point("passive"), None);
+        assert_eq!(sensors[1].name, "x86_pkg_temp");
+        assert_eq!(sensors[1].celsius, 87.5);
+        assert_eq!(sensors[1].source, SensorSource::ThermalZone);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_read_hwmon() {
+        let root = fake_sysfs("hwmon");
+        write(&root, "class/hwmon/hwmon1/name", "coretemp\n");
+        write(&root, "class/hwmon/hwmon1/temp1_input", "72000\n");
+        write(&root, "class/hwmon/hwmon1/temp1_label", "Package id 0\n");
+        write(&root, "class/hwmon/hwmon1/temp1_max", "100000\n");
+        write(&root, "class/hwmon/hwmon1/temp1_crit", "110000\n");
+        write(&root, "class/hwmon/hwmon1/temp2_input", "-5000\n");
+        write(&root, "class/hwmon/hwmon0/name", "AC\n");
+
+        let reader = ThermalReader::new(&root);
+        let sensors = reader.read_hwmon().unwrap();
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0].name, "coretemp/Package id 0");
+        assert_eq!(sensors[0].celsius, 72.0);
+        assert_eq!(sensors[0].trip_point("crit"), Some(110.0));
+        assert_eq!(sensors[1].name, "coretemp/temp2");
+        assert_eq!(sensors[1].celsius, -5.0);
+
+        // No thermal class: hwmon alone is enough
+        assert_eq!(reader.read_all().unwrap(), sensors);
+        assert!(ThermalReader::new(root.join("missing")).read_all().is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
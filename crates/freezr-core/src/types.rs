@@ -9,6 +9,10 @@ pub struct ProcessInfo {
     pub cpu_percent: f64,
     pub memory_mb: u64,
     pub memory_kb: u64,
+    /// Время старта процесса в тиках с момента загрузки (/proc/[pid]/stat, поле 22)
+    /// Вместе с PID однозначно идентифицирует процесс, 0 если неизвестно
+    #[serde(default)]
+    pub start_ticks: u64,
 }
 
 impl ProcessInfo {
@@ -20,9 +24,21 @@ impl ProcessInfo {
             cpu_percent,
             memory_mb: memory_kb / 1024,
             memory_kb,
+            start_ticks: 0,
         }
     }
 
+    /// Задать время старта процесса (идентичность для ProcessHandle)
+    pub fn with_start_ticks(mut self, start_ticks: u64) -> Self {
+        self.start_ticks = start_ticks;
+        self
+    }
+
+    /// Открыть handle именно этого (просканированного) процесса
+    pub fn handle(&self) -> crate::Result<crate::process_handle::ProcessHandle> {
+        crate::process_handle::ProcessHandle::from_process(self)
+    }
+
     // Проверка: это KESL процесс?
     pub fn is_kesl(&self) -> bool {
         self.command.contains("/opt/kaspersky/kesl/libexec/kesl")
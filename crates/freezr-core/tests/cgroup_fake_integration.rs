@@ -0,0 +1,337 @@
+// Integration tests of the cgroup manager against an in-memory cgroupfs
+//
+// The manager runs on a FakeCgroupFs mounted at a made-up root, so these
+// tests need neither root nor cgroup v2 and run in CI. Processes are real
+// (spawned `sleep`), only their cgroup membership is simulated.
+
+use freezr_core::cgroups::cgroupfs;
+use freezr_core::{
+    CgroupConfig, CgroupManager, CgroupStrategy, CpuController, FakeCgroupFs, FreezerController,
+    HealthStatus, MemoryController, OriginJournal, ProcessHandle, ResourceLimits,
+    StaticCgroupConfig,
+};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+const MB: u64 = 1024 * 1024;
+
+/// Fake tree with FreezR's slice configured under it
+fn fake_manager(
+    name: &str,
+    static_groups: Vec<StaticCgroupConfig>,
+) -> (Arc<FakeCgroupFs>, CgroupConfig) {
+    let fake = FakeCgroupFs::new(format!("/fake-cgroupfs/{}", name));
+    let config = CgroupConfig {
+        enabled: true,
+        cgroupfs_root: fake.root().to_path_buf(),
+        root_path: fake.root().join("freezr.slice"),
+        static_groups,
+        journal_path: None,
+        ..CgroupConfig::default()
+    };
+    (fake, config)
+}
+
+fn static_group(name: &str, cpu: f64, memory_mb: u64) -> StaticCgroupConfig {
+    StaticCgroupConfig {
+        name: name.to_string(),
+        process_patterns: vec![name.to_string()],
+        limits: ResourceLimits::default(),
+        cpu_limit_percent_compat: Some(cpu),
+        memory_max_mb: Some(memory_mb),
+        memory_high_mb: None,
+    }
+}
+
+fn spawn_sleep() -> Child {
+    Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap()
+}
+
+fn read(path: &Path) -> String {
+    cgroupfs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn test_validate_system_checks_mount() {
+    let (fake, config) = fake_manager("validate", Vec::new());
+
+    // Not mounted: the made-up root does not exist
+    assert!(CgroupManager::new(config.clone()).is_err());
+
+    let _mount = fake.mount();
+    assert!(CgroupManager::new(config).is_ok());
+}
+
+#[test]
+fn test_initialize_creates_static_groups() {
+    let (fake, config) = fake_manager("initialize", vec![static_group("kesl", 30.0, 512)]);
+    let _mount = fake.mount();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+
+    let slice = fake.root().join("freezr.slice");
+    let kesl = slice.join("kesl");
+    assert_eq!(
+        read(&slice.join("cgroup.subtree_control")),
+        "cpuset cpu io memory pids\n"
+    );
+    assert_eq!(read(&kesl.join("cpu.max")), "30000 100000\n");
+    assert_eq!(MemoryController::get_max(&kesl).unwrap(), Some(512 * MB));
+    assert_eq!(manager.static_cgroups().len(), 1);
+
+    let report = manager.health_check().unwrap();
+    assert_eq!(report.status, HealthStatus::Healthy);
+}
+
+#[test]
+fn test_create_apply_assign_remove() {
+    let (fake, config) = fake_manager("lifecycle", Vec::new());
+    let _mount = fake.mount();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+
+    let mut cgroup = manager.create_cgroup("worker").unwrap();
+    assert!(manager.create_cgroup("worker").is_err());
+
+    cgroup.limits = ResourceLimits::new()
+        .with_cpu_limit(25.0)
+        .with_memory_high(256 * MB)
+        .with_pids_max(64)
+        .with_cpuset_cpus("0");
+    manager.apply_limits(&cgroup).unwrap();
+    assert_eq!(CpuController::get_quota(&cgroup.path).unwrap(), Some(25.0));
+    assert_eq!(read(&cgroup.path.join("memory.high")), "268435456\n");
+    assert_eq!(read(&cgroup.path.join("pids.max")), "64\n");
+    assert_eq!(read(&cgroup.path.join("cpuset.cpus.effective")), "0\n");
+
+    let mut child = spawn_sleep();
+    manager.assign_process(&cgroup, child.id()).unwrap();
+    assert_eq!(cgroup.get_processes().unwrap(), vec![child.id()]);
+
+    // The process has no recorded origin: it goes back to the root cgroup
+    manager.remove_cgroup("worker").unwrap();
+    assert!(!cgroup.exists());
+    assert_eq!(fake.procs(fake.root()), vec![child.id()]);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_freeze_and_thaw_tree() {
+    let (fake, config) = fake_manager("freeze", Vec::new());
+    let _mount = fake.mount();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+
+    let mut child = spawn_sleep();
+    let tree = manager
+        .freeze_tree(child.id(), Duration::from_secs(1))
+        .unwrap();
+    assert!(FreezerController::is_frozen(&tree.cgroup.path).unwrap());
+    assert_eq!(tree.cgroup.pids, vec![child.id()]);
+
+    manager.thaw_tree(&tree, Duration::from_secs(1)).unwrap();
+    assert!(!tree.cgroup.exists());
+    assert_eq!(manager.count_dynamic_cgroups(), 0);
+
+    // Its real cgroup is not in the fake tree: restored to the root
+    assert_eq!(fake.procs(fake.root()), vec![child.id()]);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_throttle_and_release_tree() {
+    let (fake, config) = fake_manager("throttle", Vec::new());
+    let _mount = fake.mount();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+
+    let mut child = spawn_sleep();
+    let limits = manager.dynamic_settings().default_limits();
+    let tree = manager.throttle_tree(child.id(), limits).unwrap();
+
+    assert_eq!(tree.cgroup.name, format!("throttle-{}", child.id()));
+    assert_eq!(
+        CpuController::get_quota(&tree.cgroup.path).unwrap(),
+        Some(50.0)
+    );
+    assert_eq!(fake.procs(&tree.cgroup.path), vec![child.id()]);
+
+    manager.release_tree(&tree).unwrap();
+    assert!(!tree.cgroup.exists());
+    assert_eq!(fake.procs(fake.root()), vec![child.id()]);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_on_service_stop_restores_and_cleans_up() {
+    let (fake, config) = fake_manager("stop", vec![static_group("kesl", 30.0, 512)]);
+    let _mount = fake.mount();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+
+    let mut child = spawn_sleep();
+    let kesl = manager.get_cgroup("kesl").unwrap().clone();
+    manager.assign_process(&kesl, child.id()).unwrap();
+    let dynamic = manager.create_cgroup("worker").unwrap();
+
+    manager.on_service_stop().unwrap();
+
+    assert_eq!(fake.procs(fake.root()), vec![child.id()]);
+    assert!(!kesl.exists());
+    assert!(!dynamic.exists());
+    assert_eq!(fake.cgroups(), vec![fake.root().to_path_buf()]);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_remove_busy_cgroup_fails() {
+    let (fake, config) = fake_manager("busy", Vec::new());
+    let _mount = fake.mount();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+
+    // A child cgroup the manager does not know about keeps it busy
+    let cgroup = manager.create_cgroup("worker").unwrap();
+    cgroupfs::create_dir(&cgroup.path.join("nested")).unwrap();
+
+    assert!(manager.remove_cgroup("worker").is_err());
+    assert!(cgroup.exists());
+
+    let report = manager.health_check().unwrap();
+    assert_eq!(report.dynamic_cgroups, 0);
+}
+
+#[test]
+fn test_restart_adopts_cgroups_and_restores_origins() {
+    let (fake, mut config) = fake_manager("restart", vec![static_group("kesl", 30.0, 512)]);
+    let journal = std::env::temp_dir().join(format!("freezr-fake-journal-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&journal);
+    config.journal_path = Some(journal.join("cgroups.json"));
+    let _mount = fake.mount();
+
+    // Mirror the real cgroup of the test process in the fake tree
+    let origin = std::fs::read_to_string("/proc/self/cgroup")
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("0::").map(str::to_string))
+        .unwrap();
+    let mut origin_path = fake.root().to_path_buf();
+    for part in origin.split('/').filter(|p| !p.is_empty()) {
+        origin_path.push(part);
+        cgroupfs::create_dir(&origin_path).unwrap();
+    }
+
+    let mut child = spawn_sleep();
+    let tree = {
+        let mut manager = CgroupManager::new(config.clone()).unwrap();
+        manager.initialize().unwrap();
+        let limits = manager.dynamic_settings().default_limits();
+        manager.throttle_tree(child.id(), limits).unwrap()
+        // Crash: dropped without on_service_stop
+    };
+
+    let entries = OriginJournal::new(journal.join("cgroups.json"))
+        .load()
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].pid, child.id());
+    assert_eq!(entries[0].origin, origin);
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+    assert_eq!(manager.adopted_cgroups(), [tree.cgroup.name.as_str()]);
+
+    let adopted = manager.get_cgroup(&tree.cgroup.name).unwrap();
+    assert_eq!(adopted.pids, vec![child.id()]);
+    assert_eq!(adopted.limits.cpu_limit_percent, Some(50.0));
+    assert_eq!(manager.static_cgroups().len(), 1);
+
+    assert_eq!(manager.release_adopted(), vec![tree.cgroup.name.clone()]);
+    assert!(!tree.cgroup.exists());
+    assert!(manager.adopted_cgroups().is_empty());
+    assert_eq!(fake.procs(&origin_path), vec![child.id()]);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let _ = std::fs::remove_dir_all(&journal);
+}
+
+#[test]
+fn test_journal_drops_exited_processes_with_dynamic_strategy() {
+    let (fake, mut config) = fake_manager("journal-prune", Vec::new());
+    let journal =
+        std::env::temp_dir().join(format!("freezr-fake-journal-prune-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&journal);
+    config.strategy = CgroupStrategy::Dynamic;
+    config.journal_path = Some(journal.join("cgroups.json"));
+    let _mount = fake.mount();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+    let limits = manager.dynamic_settings().default_limits();
+
+    let mut first = spawn_sleep();
+    manager.throttle_tree(first.id(), limits.clone()).unwrap();
+    first.kill().unwrap();
+    first.wait().unwrap();
+
+    let mut second = spawn_sleep();
+    let start_ticks = ProcessHandle::open(second.id()).unwrap().start_ticks();
+    manager.throttle_tree(second.id(), limits).unwrap();
+
+    // Only the live process is journaled, with the identity seen at the move
+    let entries = OriginJournal::new(journal.join("cgroups.json"))
+        .load()
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].pid, second.id());
+    assert_eq!(entries[0].start_ticks, start_ticks);
+
+    second.kill().unwrap();
+    second.wait().unwrap();
+    let _ = std::fs::remove_dir_all(&journal);
+}
+
+#[test]
+fn test_frozen_adopted_cgroup_is_kept() {
+    let (fake, config) = fake_manager("adopt-frozen", Vec::new());
+    let _mount = fake.mount();
+
+    // Left frozen by a previous run
+    let slice = fake.root().join("freezr.slice");
+    let leftover = slice.join("freeze-4242");
+    cgroupfs::create_dir(&slice).unwrap();
+    cgroupfs::create_dir(&leftover).unwrap();
+    FreezerController::freeze(&leftover).unwrap();
+
+    let mut manager = CgroupManager::new(config).unwrap();
+    manager.initialize().unwrap();
+    assert_eq!(manager.count_dynamic_cgroups(), 1);
+
+    assert!(manager.release_adopted().is_empty());
+    assert_eq!(manager.adopted_cgroups(), &["freeze-4242".to_string()]);
+
+    FreezerController::thaw(&leftover).unwrap();
+    assert_eq!(manager.release_adopted(), vec!["freeze-4242".to_string()]);
+    assert!(!cgroupfs::exists(&leftover));
+}
@@ -0,0 +1,90 @@
+// Integration tests for freezing process trees through cgroup.freeze
+//
+// Require root and a pure cgroup v2 hierarchy at /sys/fs/cgroup,
+// so they are marked as #[ignore]
+
+use freezr_core::{CgroupConfig, CgroupEventKind, CgroupManager, CgroupWatcher, FreezerController};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+fn test_manager() -> CgroupManager {
+    let config = CgroupConfig {
+        enabled: true,
+        root_path: PathBuf::from("/sys/fs/cgroup/freezr-test.slice"),
+        ..CgroupConfig::default()
+    };
+
+    let mut manager = CgroupManager::new(config).expect("cgroup v2 not available");
+    manager.initialize().expect("Failed to create test slice");
+    manager
+}
+
+#[test]
+#[ignore] // Requires root and cgroup v2
+fn test_freeze_and_thaw_process_tree() {
+    let mut manager = test_manager();
+
+    // Shell with two children: the whole tree must be frozen
+    let mut child = Command::new("sh")
+        .args(["-c", "sleep 60 & sleep 60 & wait"])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+
+    let tree = manager
+        .freeze_tree(child.id(), Duration::from_secs(1))
+        .unwrap();
+
+    assert!(tree.cgroup.pids.len() >= 3);
+    assert!(FreezerController::is_frozen(&tree.cgroup.path).unwrap());
+
+    manager.thaw_tree(&tree, Duration::from_secs(1)).unwrap();
+    assert!(!tree.cgroup.path.exists());
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    manager.on_service_stop().unwrap();
+}
+
+#[test]
+#[ignore] // Requires root and cgroup v2
+fn test_freeze_missing_process() {
+    let mut manager = test_manager();
+
+    assert!(manager
+        .freeze_tree(9_999_999, Duration::from_secs(1))
+        .is_err());
+    assert_eq!(manager.count_dynamic_cgroups(), 0);
+
+    manager.on_service_stop().unwrap();
+}
+
+#[test]
+#[ignore] // Requires root and cgroup v2
+fn test_watcher_reports_populated_and_frozen() {
+    let mut manager = test_manager();
+    let cgroup = manager.create_cgroup("watch-test").unwrap();
+
+    let mut watcher = CgroupWatcher::new().unwrap();
+    watcher.watch(&cgroup.path).unwrap();
+
+    let mut child = Command::new("sleep").arg("60").spawn().unwrap();
+    manager.assign_process(&cgroup, child.id()).unwrap();
+    FreezerController::freeze(&cgroup.path).unwrap();
+    FreezerController::wait(&cgroup.path, true, Duration::from_secs(1)).unwrap();
+
+    let kinds: Vec<CgroupEventKind> = watcher
+        .poll(Duration::from_secs(1))
+        .unwrap()
+        .into_iter()
+        .map(|event| event.kind)
+        .collect();
+    assert!(kinds.contains(&CgroupEventKind::Populated(true)));
+    assert!(kinds.contains(&CgroupEventKind::Frozen(true)));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    manager.on_service_stop().unwrap();
+}
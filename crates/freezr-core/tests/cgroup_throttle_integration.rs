@@ -0,0 +1,89 @@
+// Integration tests for throttling process trees in dynamic cgroups
+//
+// Require root and a pure cgroup v2 hierarchy at /sys/fs/cgroup with the
+// cpu, memory and pids controllers, so they are marked as #[ignore]
+
+use freezr_core::{CgroupConfig, CgroupManager, CpuController, PidsController, ResourceLimits};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+fn test_manager() -> CgroupManager {
+    let config = CgroupConfig {
+        enabled: true,
+        root_path: PathBuf::from("/sys/fs/cgroup/freezr-throttle-test.slice"),
+        ..CgroupConfig::default()
+    };
+
+    let mut manager = CgroupManager::new(config).expect("cgroup v2 not available");
+    manager.initialize().expect("Failed to create test slice");
+    manager
+}
+
+#[test]
+#[ignore] // Requires root and cgroup v2
+fn test_throttle_and_release_process_tree() {
+    let mut manager = test_manager();
+
+    let mut child = Command::new("sh")
+        .args(["-c", "sleep 60 & wait"])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+
+    let limits = manager.dynamic_settings().default_limits();
+    let tree = manager.throttle_tree(child.id(), limits).unwrap();
+
+    assert!(tree.cgroup.pids.len() >= 2);
+    assert_eq!(
+        CpuController::get_quota(&tree.cgroup.path).unwrap(),
+        Some(50.0)
+    );
+
+    manager.release_tree(&tree).unwrap();
+    assert!(!tree.cgroup.path.exists());
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    manager.on_service_stop().unwrap();
+}
+
+#[test]
+#[ignore] // Requires root and cgroup v2
+fn test_throttle_rejects_invalid_limits() {
+    let mut manager = test_manager();
+
+    let limits = ResourceLimits::new().with_cpu_limit(-1.0);
+    assert!(manager.throttle_tree(std::process::id(), limits).is_err());
+    assert_eq!(manager.count_dynamic_cgroups(), 0);
+
+    manager.on_service_stop().unwrap();
+}
+
+#[test]
+#[ignore] // Requires root and cgroup v2
+fn test_throttle_with_pids_cap() {
+    let mut manager = test_manager();
+
+    let mut child = Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let limits = ResourceLimits::new().with_pids_max(4);
+    let tree = manager.throttle_tree(child.id(), limits).unwrap();
+
+    assert_eq!(PidsController::get_max(&tree.cgroup.path).unwrap(), Some(4));
+    assert_eq!(PidsController::get_current(&tree.cgroup.path).unwrap(), 1);
+    assert_eq!(
+        PidsController::get_events(&tree.cgroup.path).unwrap().max,
+        0
+    );
+
+    manager.release_tree(&tree).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    manager.on_service_stop().unwrap();
+}
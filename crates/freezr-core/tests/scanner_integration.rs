@@ -117,9 +117,9 @@ fn test_scanner_performance() {
     let kesl_duration = start.elapsed();
     println!("KESL scan took: {:?}", kesl_duration);
 
-    // Should complete within reasonable time (5 seconds)
-    // Note: scan_kesl does 3 measurements with 1s sleep each
-    assert!(kesl_duration.as_secs() < 5);
+    // Should complete within reasonable time (2 seconds)
+    // Note: CPU is sampled between calls, scan_kesl no longer sleeps
+    assert!(kesl_duration.as_secs() < 2);
 
     // Test Node scan performance
     let start = Instant::now();
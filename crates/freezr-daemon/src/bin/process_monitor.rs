@@ -5,6 +5,8 @@
 use anyhow::Result;
 use chrono::Timelike;
 use clap::Parser;
+use freezr_core::MemInfo;
+use freezr_daemon::pressure::{self, PressureTrigger};
 use freezr_daemon::{Config, ResourceMonitor};
 use nix::libc;
 use std::os::unix::fs::PermissionsExt;
@@ -206,34 +208,9 @@ fn check_system_health() -> Result<String> {
         .and_then(|s| s.parse::<f64>().ok())
         .unwrap_or(0.0);
 
-    // Read meminfo
-    let meminfo = fs::read_to_string("/proc/meminfo")
-        .map_err(|e| anyhow::anyhow!("Failed to read /proc/meminfo: {}", e))?;
-
-    let mut mem_total = 0u64;
-    let mut mem_available = 0u64;
-
-    for line in meminfo.lines() {
-        if line.starts_with("MemTotal:") {
-            mem_total = line
-                .split_whitespace()
-                .nth(1)
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(0);
-        } else if line.starts_with("MemAvailable:") {
-            mem_available = line
-                .split_whitespace()
-                .nth(1)
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(0);
-        }
-    }
-
-    let mem_used_pct = if mem_total > 0 {
-        ((mem_total - mem_available) as f64 / mem_total as f64) * 100.0
-    } else {
-        0.0
-    };
+    let mem_used_pct = MemInfo::read()
+        .map_err(|e| anyhow::anyhow!("Failed to read /proc/meminfo: {}", e))?
+        .used_percent();
 
     Ok(format!(
         "Load: {:.2}, Memory: {:.1}% used",
@@ -312,19 +289,8 @@ fn display_startup_banner(config: &Config) {
               config.snap.cpu_threshold, config.snap.action, config.snap.nice_level);
     }
 
-    if config.firefox.enabled {
-        info!("   └─ Firefox: Freeze@{:.1}%, Kill@{:.1}%",
-              config.firefox.cpu_threshold_freeze, config.firefox.cpu_threshold_kill);
-    }
-
-    if config.brave.enabled {
-        info!("   └─ Brave: Freeze@{:.1}%, Kill@{:.1}%",
-              config.brave.cpu_threshold_freeze, config.brave.cpu_threshold_kill);
-    }
-
-    if config.telegram.enabled {
-        info!("   └─ Telegram: Freeze@{:.1}%, Kill@{:.1}%",
-              config.telegram.cpu_threshold_freeze, config.telegram.cpu_threshold_kill);
+    for rule in config.rules.iter().filter(|r| r.enabled) {
+        info!("   └─ Rule '{}': {}", rule.name, describe_rule_steps(&rule.actions));
     }
 
     if config.memory_pressure.enabled {
@@ -337,10 +303,63 @@ fn display_startup_banner(config: &Config) {
               config.memory_pressure.action_critical);
     }
 
+    for (label, stall) in [("CPU", &config.cpu_pressure), ("IO", &config.io_pressure)] {
+        if stall.enabled {
+            info!("   └─ {} Pressure: some {:.1}%/{:.1}%, full {:.1}%/{:.1}% ({}|{})",
+                  label,
+                  stall.some_threshold_warning,
+                  stall.some_threshold_critical,
+                  stall.full_threshold_warning,
+                  stall.full_threshold_critical,
+                  stall.action_warning,
+                  stall.action_critical);
+        }
+    }
+
+    if config.thermal.enabled {
+        info!("   └─ Thermal: {:.0}°C/{:.0}°C, top {} processes ({}|{})",
+              config.thermal.warning_celsius,
+              config.thermal.critical_celsius,
+              config.thermal.top_processes,
+              config.thermal.action_warning,
+              config.thermal.action_critical);
+    }
+
+    if !config.profiles.is_empty() {
+        let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        info!("   └─ Profiles: {} (freezr profile set <name>)", names.join(", "));
+    }
+
+    if config.power.enabled {
+        info!("   └─ Power profiles: AC {}, battery {}",
+              config.power.ac_profile.as_deref().unwrap_or("base"),
+              config.power.battery_profile.as_deref().unwrap_or("base"));
+    }
+
+    if config.spawn_guard.enabled {
+        info!("   └─ Spawn guard: +{} processes per check, headroom {} tasks",
+              config.spawn_guard.growth_threshold,
+              config.spawn_guard.pids_headroom);
+    }
+
     info!("   └─ Check interval: {}s", config.monitoring.check_interval_secs);
     info!("");
 }
 
+/// Short description of a rule's action ladder, e.g. "freeze@80.0%, kill@95.0%"
+fn describe_rule_steps(steps: &[freezr_daemon::config::RuleStep]) -> String {
+    steps
+        .iter()
+        .map(|step| match (step.cpu_threshold, step.memory_threshold_mb) {
+            (Some(cpu), Some(mem)) => format!("{}@{:.1}%|{}MB", step.action, cpu, mem),
+            (Some(cpu), None) => format!("{}@{:.1}%", step.action, cpu),
+            (None, Some(mem)) => format!("{}@{}MB", step.action, mem),
+            (None, None) => step.action.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Clear screen and move cursor to top
 /// Export statistics to JSON file for dashboard consumption
 fn export_stats_to_file(stats: &freezr_daemon::MonitorStats) -> Result<()> {
@@ -361,76 +380,15 @@ fn clear_screen() {
 /// Run monitoring with periodic statistics reporting
 async fn run_with_stats(config: Config, report_interval: u64) -> Result<()> {
     use std::time::Duration;
-    use tokio::time::{interval, sleep};
+    use tokio::time::{interval, interval_at, sleep, Instant, MissedTickBehavior};
 
     info!("📊 Extended statistics mode enabled");
     info!("   └─ Report interval: {}s", report_interval);
     info!("   └─ Live dashboard mode: ON (screen will refresh)");
     info!("");
 
-    let mut monitor = ResourceMonitor::new(
-        &config.kesl.service_name,
-        config.kesl.cpu_threshold,
-        config.kesl.memory_threshold_mb,
-        config.kesl.max_violations,
-        config.monitoring.min_restart_interval_secs,
-    );
-
-    if config.node.enabled {
-        monitor.enable_node_monitoring(config.node.cpu_threshold, config.node.auto_kill);
-    }
-
-    if config.snap.enabled {
-        monitor.enable_snap_monitoring(
-            config.snap.cpu_threshold,
-            config.snap.action.clone(),
-            config.snap.nice_level,
-            config.snap.freeze_duration_secs,
-            config.snap.max_violations,
-        );
-    }
-
-    if config.firefox.enabled {
-        monitor.enable_firefox_monitoring(
-            config.firefox.cpu_threshold_freeze,
-            config.firefox.cpu_threshold_kill,
-            config.firefox.freeze_duration_secs,
-            config.firefox.max_violations_freeze,
-            config.firefox.max_violations_kill,
-        );
-    }
-
-    if config.brave.enabled {
-        monitor.enable_brave_monitoring(
-            config.brave.cpu_threshold_freeze,
-            config.brave.cpu_threshold_kill,
-            config.brave.freeze_duration_secs,
-            config.brave.max_violations_freeze,
-            config.brave.max_violations_kill,
-        );
-    }
-
-    if config.telegram.enabled {
-        monitor.enable_telegram_monitoring(
-            config.telegram.cpu_threshold_freeze,
-            config.telegram.cpu_threshold_kill,
-            config.telegram.freeze_duration_secs,
-            config.telegram.max_violations_freeze,
-            config.telegram.max_violations_kill,
-        );
-    }
-
-    if config.memory_pressure.enabled {
-        monitor.enable_memory_pressure_monitoring(
-            config.memory_pressure.some_threshold_warning,
-            config.memory_pressure.some_threshold_critical,
-            config.memory_pressure.full_threshold_warning,
-            config.memory_pressure.full_threshold_critical,
-            config.memory_pressure.action_warning.clone(),
-            config.memory_pressure.action_critical.clone(),
-            config.memory_pressure.check_interval_secs,
-        );
-    }
+    let mut monitor = ResourceMonitor::from_config(&config)?;
+    let memory_trigger = PressureTrigger::memory(&config.memory_pressure);
 
     let check_interval = Duration::from_secs(config.monitoring.check_interval_secs);
     let mut report_timer = interval(Duration::from_secs(report_interval));
@@ -440,9 +398,18 @@ async fn run_with_stats(config: Config, report_interval: u64) -> Result<()> {
     // Wait 3 seconds before first dashboard render
     sleep(Duration::from_secs(3)).await;
 
+    // Trigger wake-ups must not postpone the regular checks
+    let mut check_timer = interval_at(Instant::now() + check_interval, check_interval);
+    check_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
     loop {
         tokio::select! {
-            _ = sleep(check_interval) => {
+            _ = pressure::triggered(memory_trigger.as_ref()) => {
+                if let Some(trigger) = &memory_trigger {
+                    monitor.on_memory_pressure_trigger(trigger.floor_percent());
+                }
+            }
+            _ = check_timer.tick() => {
                 // Perform monitoring check (silently, no logs to stdout)
                 if let Err(e) = monitor.check() {
                     // Only log errors to file, not stdout
@@ -516,17 +483,8 @@ async fn run_with_stats(config: Config, report_interval: u64) -> Result<()> {
                 if config.snap.enabled {
                     println!("   ⚡ Snap actions: {} ({})", stats.total_kills, config.snap.action);
                 }
-                if config.firefox.enabled {
-                    println!("   🦊 Firefox: Freeze@{:.1}%, Kill@{:.1}%",
-                             config.firefox.cpu_threshold_freeze, config.firefox.cpu_threshold_kill);
-                }
-                if config.brave.enabled {
-                    println!("   🦁 Brave: Freeze@{:.1}%, Kill@{:.1}%",
-                             config.brave.cpu_threshold_freeze, config.brave.cpu_threshold_kill);
-                }
-                if config.telegram.enabled {
-                    println!("   ✈️  Telegram: Freeze@{:.1}%, Kill@{:.1}%",
-                             config.telegram.cpu_threshold_freeze, config.telegram.cpu_threshold_kill);
+                for rule in config.rules.iter().filter(|r| r.enabled) {
+                    println!("   📋 {}: {}", rule.name, describe_rule_steps(&rule.actions));
                 }
 
                 // Memory pressure status
@@ -986,9 +944,42 @@ async fn show_dashboard(interval_secs: u64) -> Result<()> {
         println!("   🔄 KESL restarts: {}", stats.kesl.total_restarts);
         println!("   🔪 Node.js kills: {}", stats.node.total_kills);
         println!("   ⚡ Snap actions: {} ({})", stats.snap.total_actions, stats.snap.action);
-        println!("   🦊 Firefox: Freeze@{:.1}%, Kill@{:.1}%", stats.firefox.freeze_threshold, stats.firefox.kill_threshold);
-        println!("   🦁 Brave: Freeze@{:.1}%, Kill@{:.1}%", stats.brave.freeze_threshold, stats.brave.kill_threshold);
-        println!("   ✈️  Telegram: Freeze@{:.1}%, Kill@{:.1}%", stats.telegram.freeze_threshold, stats.telegram.kill_threshold);
+        for rule in &stats.rules {
+            let steps: Vec<String> = rule
+                .steps
+                .iter()
+                .map(|step| {
+                    format!(
+                        "{} {}/{} (done {})",
+                        step.action, step.violations, step.max_violations, step.total_actions
+                    )
+                })
+                .collect();
+            println!("   📋 {} ({} procs): {}", rule.name, rule.matched_processes, steps.join(", "));
+        }
+        if stats.throttle.enabled {
+            println!("   🐢 Throttled: {} now, {} total, {} released (cooldown {}s), {} spawners contained",
+                stats.throttle.throttled_processes,
+                stats.throttle.total_throttled,
+                stats.throttle.total_released,
+                stats.throttle.cooldown_secs,
+                stats.throttle.contained_spawners
+            );
+            if stats.throttle.adaptive {
+                println!("   🎚️  Adaptive limits: {} adjustments", stats.throttle.total_adjusted);
+            }
+        }
+        for group in &stats.static_cgroups {
+            let cpu = group.cpu_limit_percent.map_or("-".to_string(), |c| format!("{:.0}%", c));
+            let mem = group.memory_max_mb.map_or("-".to_string(), |m| format!("{}MB", m));
+            println!("   📦 cgroup {}: {} procs, CPU limit {}, memory max {}, using {}MB",
+                group.name,
+                group.pids.len(),
+                cpu,
+                mem,
+                group.memory_current_mb.unwrap_or(0)
+            );
+        }
 
         // Memory Pressure
         let mp_icon = match stats.memory_pressure.status.as_str() {
@@ -1006,6 +997,40 @@ async fn show_dashboard(interval_secs: u64) -> Result<()> {
             stats.memory_pressure.warning_count,
             stats.memory_pressure.critical_count
         );
+        // A slow drift forecasts hours ahead with little confidence
+        if let (Some(secs), true) = (
+            stats.memory_pressure.oom_forecast_secs,
+            stats.memory_pressure.oom_forecast_confidence >= 0.5,
+        ) {
+            println!("   ⏳ OOM in ~{:.0}s (confidence {:.0}%)",
+                secs,
+                stats.memory_pressure.oom_forecast_confidence * 100.0
+            );
+        }
+
+        // Temperatures
+        if stats.thermal.enabled {
+            let thermal_icon = match stats.thermal.status.as_str() {
+                "CRITICAL" => "🔴",
+                "WARNING" => "🟠",
+                "OK" => "🟢",
+                _ => "⚪",
+            };
+            println!("   {} Thermal: {} (w:{}/c:{})",
+                thermal_icon,
+                stats.thermal.status,
+                stats.thermal.warning_count,
+                stats.thermal.critical_count
+            );
+            for sensor in &stats.thermal.sensors {
+                println!("      🌡️  {}: {:.1}°C (warning {:.0}°C, critical {:.0}°C)",
+                    sensor.name,
+                    sensor.celsius,
+                    sensor.warning_celsius,
+                    sensor.critical_celsius
+                );
+            }
+        }
         println!();
 
         // System Health
@@ -1016,6 +1041,19 @@ async fn show_dashboard(interval_secs: u64) -> Result<()> {
             stats.system_health.load_1min,
             stats.system_health.memory_used_percent
         );
+        if let Some(source) = &stats.system_health.power_source {
+            let power = match (source.as_str(), stats.system_health.battery_percent) {
+                ("battery", Some(percent)) => format!("🔋 Battery {}%", percent),
+                ("battery", None) => "🔋 Battery".to_string(),
+                (_, _) => "🔌 AC".to_string(),
+            };
+            println!("   {}, profile: {}",
+                power,
+                stats.system_health.active_profile.as_deref().unwrap_or("base")
+            );
+        } else if let Some(profile) = &stats.system_health.active_profile {
+            println!("   Profile: {}", profile);
+        }
         println!();
 
         // Log Statistics
@@ -1165,71 +1203,40 @@ async fn main() -> Result<()> {
         run_with_stats(config, args.report_interval).await?;
     } else {
         // Normal monitoring mode
-        use freezr_daemon::ResourceMonitor;
         use std::time::Duration;
-        use tokio::time::sleep;
-
-        let mut monitor = ResourceMonitor::new(
-            &config.kesl.service_name,
-            config.kesl.cpu_threshold,
-            config.kesl.memory_threshold_mb,
-            config.kesl.max_violations,
-            config.monitoring.min_restart_interval_secs,
-        );
-
-        if config.node.enabled {
-            monitor.enable_node_monitoring(config.node.cpu_threshold, config.node.auto_kill);
-        }
-
-        if config.snap.enabled {
-            monitor.enable_snap_monitoring(
-                config.snap.cpu_threshold,
-                config.snap.action.clone(),
-                config.snap.nice_level,
-                config.snap.freeze_duration_secs,
-                config.snap.max_violations,
-            );
-        }
-
-        if config.firefox.enabled {
-            monitor.enable_firefox_monitoring(
-                config.firefox.cpu_threshold_freeze,
-                config.firefox.cpu_threshold_kill,
-                config.firefox.freeze_duration_secs,
-                config.firefox.max_violations_freeze,
-                config.firefox.max_violations_kill,
-            );
-        }
+        use tokio::time::{interval, MissedTickBehavior};
 
-        if config.brave.enabled {
-            monitor.enable_brave_monitoring(
-                config.brave.cpu_threshold_freeze,
-                config.brave.cpu_threshold_kill,
-                config.brave.freeze_duration_secs,
-                config.brave.max_violations_freeze,
-                config.brave.max_violations_kill,
-            );
-        }
+        let mut monitor = ResourceMonitor::from_config(&config)?;
+        let memory_trigger = PressureTrigger::memory(&config.memory_pressure);
 
         let check_interval = Duration::from_secs(config.monitoring.check_interval_secs);
+        let mut check_timer = interval(check_interval);
+        check_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
         info!("🚀 Starting monitoring loop...");
         info!("");
 
         loop {
-            if let Err(e) = monitor.check() {
-                error!("Monitoring check failed: {}", e);
-            }
-
-            let stats = monitor.stats();
-            let (cpu_viol, mem_viol) = monitor.violations();
+            tokio::select! {
+                _ = pressure::triggered(memory_trigger.as_ref()) => {
+                    if let Some(trigger) = &memory_trigger {
+                        monitor.on_memory_pressure_trigger(trigger.floor_percent());
+                    }
+                }
+                _ = check_timer.tick() => {
+                    if let Err(e) = monitor.check() {
+                        error!("Monitoring check failed: {}", e);
+                    }
 
-            info!(
-                "Stats: checks={}, violations={}/{}, restarts={}, kills={}",
-                stats.total_checks, cpu_viol, mem_viol, stats.total_restarts, stats.total_kills
-            );
+                    let stats = monitor.stats();
+                    let (cpu_viol, mem_viol) = monitor.violations();
 
-            sleep(check_interval).await;
+                    info!(
+                        "Stats: checks={}, violations={}/{}, restarts={}, kills={}",
+                        stats.total_checks, cpu_viol, mem_viol, stats.total_restarts, stats.total_kills
+                    );
+                }
+            }
         }
     }
 
@@ -0,0 +1,257 @@
+//! Reactions to events of managed cgroups
+//!
+//! `memory.events` and `cgroup.events` of every cgroup under freezr.slice
+//! are watched through inotify ([`CgroupWatcher`]) instead of being polled.
+//! OOM kills inside the slice are logged. A group that keeps breaching
+//! `memory.high` while its usage does not go down is stuck in reclaim: its
+//! `memory.high` is raised step by step, up to twice the value it started
+//! with and never above `memory.max`.
+
+use freezr_core::{
+    error::Result, CgroupEvent, CgroupEventKind, CgroupManager, CgroupWatcher, MemoryController,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Checks in a row with memory.high breaches and no drop in usage before
+/// memory.high is relaxed
+const HIGH_STREAK: u32 = 3;
+
+/// Each relaxation raises memory.high by a quarter
+const RELAX_STEP: f64 = 1.25;
+
+/// memory.high is never relaxed beyond this multiple of its initial value
+const MAX_RELAX_FACTOR: u64 = 2;
+
+/// Group breaching memory.high in consecutive checks
+#[derive(Debug, Default)]
+struct HighPressure {
+    /// Consecutive checks with breaches and no progress
+    streak: u32,
+    /// memory.current at the previous breach
+    usage: u64,
+    /// memory.high before the first relaxation
+    initial_high: Option<u64>,
+}
+
+impl HighPressure {
+    /// Record a check with memory.high breaches at `usage` bytes
+    ///
+    /// Returns true once the group made no progress for [`HIGH_STREAK`]
+    /// checks; the streak then starts over.
+    fn observe(&mut self, usage: u64) -> bool {
+        // Reclaim is working if usage went down since the last breach
+        self.streak = if self.streak > 0 && usage >= self.usage {
+            self.streak + 1
+        } else {
+            1
+        };
+        self.usage = usage;
+
+        if self.streak >= HIGH_STREAK {
+            self.streak = 0;
+            return true;
+        }
+        false
+    }
+}
+
+/// Watches managed cgroups and reacts to their events
+pub struct CgroupEventMonitor {
+    watcher: CgroupWatcher,
+    cgroups: Arc<Mutex<CgroupManager>>,
+    /// memory.high breaches of managed cgroups
+    pressure: HashMap<PathBuf, HighPressure>,
+    total_oom_kills: u64,
+    total_relaxed: u64,
+}
+
+impl CgroupEventMonitor {
+    pub fn new(cgroups: Arc<Mutex<CgroupManager>>) -> Result<Self> {
+        Ok(Self {
+            watcher: CgroupWatcher::new()?,
+            cgroups,
+            pressure: HashMap::new(),
+            total_oom_kills: 0,
+            total_relaxed: 0,
+        })
+    }
+
+    /// Follow created/removed cgroups and handle events since the last check
+    ///
+    /// Returns the number of events handled.
+    pub fn check(&mut self) -> usize {
+        let paths = self
+            .cgroups
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .cgroup_paths();
+        let unwatched = self.watcher.sync(&paths);
+        if unwatched > 0 {
+            debug!("{} cgroups cannot be watched for events", unwatched);
+        }
+
+        let events = match self.watcher.poll(Duration::ZERO) {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Failed to read cgroup events: {}", e);
+                return 0;
+            }
+        };
+
+        let mut breached: Vec<PathBuf> = Vec::new();
+        for event in &events {
+            self.handle(event);
+            if matches!(event.kind, CgroupEventKind::MemoryHigh(_))
+                && !breached.contains(&event.cgroup)
+            {
+                breached.push(event.cgroup.clone());
+            }
+        }
+
+        // A check without breaches ends the streak; the initial memory.high
+        // is kept for as long as the cgroup exists
+        self.pressure.retain(|path, _| paths.contains(path));
+        for (path, pressure) in self.pressure.iter_mut() {
+            if !breached.contains(path) {
+                pressure.streak = 0;
+            }
+        }
+        for path in breached {
+            self.on_high(&path);
+        }
+
+        events.len()
+    }
+
+    /// OOM kills seen inside the managed cgroups since start
+    pub fn total_oom_kills(&self) -> u64 {
+        self.total_oom_kills
+    }
+
+    /// Times memory.high of a stuck group was raised
+    pub fn total_relaxed(&self) -> u64 {
+        self.total_relaxed
+    }
+
+    fn handle(&mut self, event: &CgroupEvent) {
+        let cgroup = &event.cgroup;
+        match event.kind {
+            CgroupEventKind::OomKill(count) => {
+                self.total_oom_kills += count;
+                warn!("OOM killer killed {} processes in {:?}", count, cgroup);
+            }
+            CgroupEventKind::Oom(count) => {
+                warn!(
+                    "{:?} ran out of memory at memory.max ({} times)",
+                    cgroup, count
+                );
+            }
+            CgroupEventKind::MemoryMax(count) => {
+                debug!("{:?} hit memory.max {} times", cgroup, count);
+            }
+            CgroupEventKind::MemoryHigh(count) => {
+                debug!("{:?} breached memory.high {} times", cgroup, count);
+            }
+            CgroupEventKind::Populated(populated) => {
+                debug!("{:?} populated: {}", cgroup, populated);
+            }
+            CgroupEventKind::Frozen(frozen) => {
+                debug!("{:?} frozen: {}", cgroup, frozen);
+            }
+        }
+    }
+
+    /// Track a memory.high breach, relaxing the limit of a stuck group
+    fn on_high(&mut self, cgroup: &Path) {
+        let Ok(usage) = MemoryController::get_current(cgroup) else {
+            return;
+        };
+        let pressure = self.pressure.entry(cgroup.to_path_buf()).or_default();
+        if !pressure.observe(usage) {
+            return;
+        }
+
+        let Ok(Some(high)) = MemoryController::get_high(cgroup) else {
+            return;
+        };
+        let initial = *pressure.initial_high.get_or_insert(high);
+        let max = MemoryController::get_max(cgroup).ok().flatten();
+
+        let Some(relaxed) = relaxed_high(high, initial, max) else {
+            debug!(
+                "{:?} keeps breaching memory.high but is at its ceiling ({} MB)",
+                cgroup,
+                high / 1024 / 1024
+            );
+            return;
+        };
+
+        match MemoryController::set_high(cgroup, relaxed) {
+            Ok(()) => {
+                self.total_relaxed += 1;
+                info!(
+                    "{:?} stuck at memory.high without progress, raised it {} MB -> {} MB",
+                    cgroup,
+                    high / 1024 / 1024,
+                    relaxed / 1024 / 1024
+                );
+            }
+            Err(e) => warn!("Failed to relax memory.high of {:?}: {}", cgroup, e),
+        }
+    }
+}
+
+/// Next memory.high for a stuck group, None if it cannot grow any further
+fn relaxed_high(current: u64, initial: u64, max: Option<u64>) -> Option<u64> {
+    let mut ceiling = initial.saturating_mul(MAX_RELAX_FACTOR);
+    if let Some(max) = max {
+        ceiling = ceiling.min(max);
+    }
+
+    let relaxed = ((current as f64 * RELAX_STEP) as u64).min(ceiling);
+    (relaxed > current).then_some(relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_high_streak_needs_no_progress() {
+        let mut pressure = HighPressure::default();
+        assert!(!pressure.observe(500 * MB));
+        assert!(!pressure.observe(510 * MB));
+        assert!(pressure.observe(510 * MB));
+
+        // Streak starts over after a relaxation
+        assert!(!pressure.observe(520 * MB));
+
+        // Usage going down means reclaim works
+        let mut pressure = HighPressure::default();
+        pressure.observe(500 * MB);
+        pressure.observe(500 * MB);
+        assert!(!pressure.observe(450 * MB));
+        assert!(!pressure.observe(450 * MB));
+        assert!(pressure.observe(460 * MB));
+    }
+
+    #[test]
+    fn test_relaxed_high() {
+        assert_eq!(relaxed_high(400 * MB, 400 * MB, None), Some(500 * MB));
+        // Capped at twice the initial value
+        assert_eq!(relaxed_high(700 * MB, 400 * MB, None), Some(800 * MB));
+        assert_eq!(relaxed_high(800 * MB, 400 * MB, None), None);
+        // Never above memory.max
+        assert_eq!(
+            relaxed_high(400 * MB, 400 * MB, Some(450 * MB)),
+            Some(450 * MB)
+        );
+        assert_eq!(relaxed_high(450 * MB, 400 * MB, Some(450 * MB)), None);
+    }
+}
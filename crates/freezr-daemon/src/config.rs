@@ -1,5 +1,9 @@
-use freezr_core::CgroupConfig;
+use freezr_core::{
+    frozen_registry::DEFAULT_REGISTRY_PATH, profile_request::DEFAULT_PROFILE_REQUEST_PATH,
+    CgroupConfig, CpuSmoothing, IoLimit, ResourceLimits,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Main configuration for FreezR daemon
@@ -15,26 +19,59 @@ pub struct Config {
     #[serde(default)]
     pub snap: SnapConfig,
 
-    /// Firefox monitoring configuration
-    #[serde(default)]
-    pub firefox: FirefoxConfig,
+    /// Declarative per-application rules (`[[rules]]` tables)
+    /// Defaults to Firefox, Brave and Telegram rules when omitted
+    #[serde(default = "default_rules")]
+    pub rules: Vec<RuleConfig>,
 
-    /// Brave browser monitoring configuration
-    #[serde(default)]
-    pub brave: BraveConfig,
+    /// Legacy `[firefox]` section, turned into the "firefox" rule on load
+    #[serde(default, skip_serializing)]
+    pub firefox: Option<LegacyAppConfig>,
 
-    /// Telegram messenger monitoring configuration
-    #[serde(default)]
-    pub telegram: TelegramConfig,
+    /// Legacy `[brave]` section, turned into the "brave" rule on load
+    #[serde(default, skip_serializing)]
+    pub brave: Option<LegacyAppConfig>,
+
+    /// Legacy `[telegram]` section, turned into the "telegram" rule on load
+    #[serde(default, skip_serializing)]
+    pub telegram: Option<LegacyAppConfig>,
 
     /// Memory pressure monitoring configuration
     #[serde(default)]
     pub memory_pressure: MemoryPressureConfig,
 
+    /// CPU stall monitoring (/proc/pressure/cpu)
+    #[serde(default)]
+    pub cpu_pressure: PressureStallConfig,
+
+    /// I/O stall monitoring (/proc/pressure/io)
+    #[serde(default)]
+    pub io_pressure: PressureStallConfig,
+
     /// Cgroup v2 integration configuration
     #[serde(default)]
     pub cgroups: CgroupConfig,
 
+    /// Runaway spawner detection (fork bombs, unbounded `make -j`)
+    #[serde(default)]
+    pub spawn_guard: SpawnGuardConfig,
+
+    /// Victim selection of the "kill" pressure action
+    #[serde(default)]
+    pub oom_killer: OomKillerConfig,
+
+    /// Temperature monitoring (thermal zones and hwmon sensors)
+    #[serde(default)]
+    pub thermal: ThermalConfig,
+
+    /// Profile switching by power source (AC or battery)
+    #[serde(default)]
+    pub power: PowerConfig,
+
+    /// Named overlays of the sections above (`[profiles.<name>.<section>]`)
+    #[serde(default)]
+    pub profiles: BTreeMap<String, toml::Table>,
+
     /// Logging configuration
     pub logging: LogConfig,
 
@@ -61,6 +98,11 @@ pub struct KeslConfig {
 
     /// Enable KESL monitoring (default: true)
     pub enabled: bool,
+
+    /// Action when max violations are reached (default: "restart")
+    /// Options: "restart", "throttle" (dynamic cgroup with default limits)
+    #[serde(default = "default_kesl_action")]
+    pub action: String,
 }
 
 /// Node.js process monitoring configuration
@@ -92,7 +134,7 @@ pub struct SnapConfig {
     pub enabled: bool,
 
     /// Action to take when threshold exceeded
-    /// Options: "freeze", "nice", "kill"
+    /// Options: "freeze", "nice", "throttle", "kill"
     pub action: String,
 
     /// Nice level to set (0-19, higher = lower priority)
@@ -107,17 +149,18 @@ pub struct SnapConfig {
     pub max_violations: u32,
 }
 
-/// Firefox process monitoring configuration
+/// Per-application section of configs written before `[[rules]]`
 /// Two-tier strategy: freeze at high load, kill at critical
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FirefoxConfig {
+#[serde(default)]
+pub struct LegacyAppConfig {
     /// CPU threshold for freezing (default: 80.0%)
     pub cpu_threshold_freeze: f64,
 
     /// CPU threshold for killing (default: 95.0%)
     pub cpu_threshold_kill: f64,
 
-    /// Enable Firefox monitoring (default: true)
+    /// Enable monitoring (default: true)
     pub enabled: bool,
 
     /// Freeze duration in seconds (default: 5)
@@ -130,50 +173,158 @@ pub struct FirefoxConfig {
     pub max_violations_kill: u32,
 }
 
-/// Brave browser process monitoring configuration
-/// Two-tier strategy: freeze at high load, kill at critical
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BraveConfig {
-    /// CPU threshold for freezing (default: 80.0%)
-    pub cpu_threshold_freeze: f64,
+impl Default for LegacyAppConfig {
+    fn default() -> Self {
+        Self {
+            cpu_threshold_freeze: 80.0,
+            cpu_threshold_kill: 95.0,
+            enabled: true,
+            freeze_duration_secs: 5,
+            max_violations_freeze: 2,
+            max_violations_kill: 3,
+        }
+    }
+}
 
-    /// CPU threshold for killing (default: 95.0%)
-    pub cpu_threshold_kill: f64,
+impl LegacyAppConfig {
+    /// Equivalent two-tier rule
+    pub fn to_rule(&self, name: &str, cmdline: &str) -> RuleConfig {
+        let mut rule = RuleConfig::two_tier(
+            name,
+            cmdline,
+            self.cpu_threshold_freeze,
+            self.cpu_threshold_kill,
+        );
+        rule.enabled = self.enabled;
+        rule.actions[0].max_violations = self.max_violations_freeze;
+        rule.actions[0].freeze_duration_secs = self.freeze_duration_secs;
+        rule.actions[1].max_violations = self.max_violations_kill;
+        rule
+    }
+}
 
-    /// Enable Brave monitoring (default: true)
+/// Declarative process rule
+///
+/// A rule selects processes with a matcher and escalates through an action
+/// ladder: each step has its own thresholds and violation counter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleConfig {
+    /// Rule name (used in logs and stats)
+    pub name: String,
+
+    /// Enable this rule (default: true)
+    #[serde(default = "default_true")]
     pub enabled: bool,
 
-    /// Freeze duration in seconds (default: 5)
-    pub freeze_duration_secs: u64,
+    /// Which processes the rule applies to
+    #[serde(rename = "match")]
+    pub matcher: RuleMatcher,
 
-    /// Maximum violations before freezing (default: 2)
-    pub max_violations_freeze: u32,
+    /// Action ladder, from mildest to most severe
+    pub actions: Vec<RuleStep>,
+}
 
-    /// Maximum violations before killing (default: 3)
-    pub max_violations_kill: u32,
+/// Process matcher of a rule
+/// All specified criteria must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleMatcher {
+    /// Exact process name (comm, e.g. "firefox")
+    pub name: Option<String>,
+
+    /// Regex matched against the full command line
+    pub cmdline: Option<String>,
+
+    /// Exact executable path (/proc/[pid]/exe)
+    pub exe: Option<PathBuf>,
+
+    /// Owner user name or numeric UID
+    pub user: Option<String>,
+
+    /// Substring of the process cgroup path (/proc/[pid]/cgroup)
+    pub cgroup: Option<String>,
 }
 
-/// Telegram messenger monitoring configuration
-/// Two-tier strategy: freeze at high load, kill at critical
+impl RuleMatcher {
+    /// No criterion set (would match every process)
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.cmdline.is_none()
+            && self.exe.is_none()
+            && self.user.is_none()
+            && self.cgroup.is_none()
+    }
+}
+
+/// One step of a rule's action ladder
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TelegramConfig {
-    /// CPU threshold for freezing (default: 80.0%)
-    pub cpu_threshold_freeze: f64,
+pub struct RuleStep {
+    /// CPU threshold in percent (can be >100% for multi-threaded processes)
+    #[serde(default)]
+    pub cpu_threshold: Option<f64>,
 
-    /// CPU threshold for killing (default: 95.0%)
-    pub cpu_threshold_kill: f64,
+    /// Memory (RSS) threshold in MB
+    #[serde(default)]
+    pub memory_threshold_mb: Option<u64>,
 
-    /// Enable Telegram monitoring (default: true)
-    pub enabled: bool,
+    /// Consecutive violations before the action is taken (default: 1)
+    #[serde(default = "default_max_violations")]
+    pub max_violations: u32,
 
-    /// Freeze duration in seconds (default: 5)
+    /// Action to take
+    /// Options: "log", "nice", "throttle", "freeze", "kill"
+    pub action: String,
+
+    /// Nice level for "nice" action (0-19, default: 15)
+    #[serde(default = "default_nice_level")]
+    pub nice_level: i32,
+
+    /// Freeze duration in seconds for "freeze" action (default: 5)
+    #[serde(default = "default_freeze_duration_secs")]
     pub freeze_duration_secs: u64,
 
-    /// Maximum violations before freezing (default: 2)
-    pub max_violations_freeze: u32,
+    /// Read bandwidth cap in MB/s for "throttle" action (cgroup io.max)
+    #[serde(default)]
+    pub io_read_mbps: Option<u64>,
 
-    /// Maximum violations before killing (default: 3)
-    pub max_violations_kill: u32,
+    /// Write bandwidth cap in MB/s for "throttle" action (cgroup io.max)
+    #[serde(default)]
+    pub io_write_mbps: Option<u64>,
+
+    /// Disk the I/O caps apply to: mount point, device node or MAJ:MIN
+    /// (default: the disk holding "/")
+    #[serde(default)]
+    pub io_device: Option<String>,
+
+    /// CPUs for "throttle" action, e.g. "6-7" (cgroup cpuset.cpus)
+    #[serde(default)]
+    pub cpuset_cpus: Option<String>,
+}
+
+impl RuleStep {
+    /// I/O caps of a "throttle" step (empty = CPU/memory limits only)
+    pub fn io_limits(&self) -> Vec<IoLimit> {
+        if self.io_read_mbps.is_none() && self.io_write_mbps.is_none() {
+            return Vec::new();
+        }
+
+        let mb = |mbps: u64| mbps * 1024 * 1024;
+        vec![IoLimit {
+            device: self.io_device.clone().unwrap_or_else(|| "/".to_string()),
+            read_bps: self.io_read_mbps.map(mb),
+            write_bps: self.io_write_mbps.map(mb),
+            ..IoLimit::default()
+        }]
+    }
+
+    /// Limits a "throttle" step adds on top of the default dynamic limits
+    pub fn limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            io_limits: self.io_limits(),
+            cpuset_cpus: self.cpuset_cpus.clone(),
+            ..ResourceLimits::default()
+        }
+    }
 }
 
 /// Memory pressure monitoring configuration (PSI - Pressure Stall Information)
@@ -200,17 +351,494 @@ pub struct MemoryPressureConfig {
     pub full_threshold_critical: f64,
 
     /// Action to take on warning level
-    /// Options: "log", "nice", "freeze", "kill"
+    /// Options: "log", "nice", "throttle", "freeze", "kill"
+    pub action_warning: String,
+
+    /// Action to take on critical level
+    /// Options: "log", "nice", "throttle", "freeze", "kill"
+    pub action_critical: String,
+
+    /// Check interval in seconds (default: 5)
+    pub check_interval_secs: u64,
+
+    /// React to a kernel PSI trigger instead of waiting for the next check
+    /// (default: true, falls back to polling when triggers are unsupported)
+    #[serde(default = "default_true")]
+    pub trigger_enabled: bool,
+
+    /// Trigger fires after this much 'some' stall within the window
+    /// (default: 150ms)
+    #[serde(default = "default_trigger_stall_ms")]
+    pub trigger_stall_ms: u64,
+
+    /// Trigger window, 500-10000ms (default: 1000ms)
+    #[serde(default = "default_trigger_window_ms")]
+    pub trigger_window_ms: u64,
+
+    /// MemAvailable floor, works without PSI (default: warning 10%, critical 5%)
+    #[serde(default = "default_mem_available_floor")]
+    pub mem_available: MemoryFloor,
+
+    /// SwapFree floor (default: warning 10%, critical 5%)
+    /// Only counts together with the MemAvailable floor; no swap counts as empty
+    #[serde(default = "default_swap_free_floor")]
+    pub swap_free: MemoryFloor,
+
+    /// Act before memory runs out, from the MemAvailable trend
+    #[serde(default)]
+    pub forecast: OomForecastConfig,
+}
+
+/// OOM time-to-exhaustion forecast (`[memory_pressure.forecast]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OomForecastConfig {
+    /// Enable forecasting (default: true)
+    pub enabled: bool,
+
+    /// Act when memory is forecast to run out within this (default: 30s)
+    pub horizon_secs: u64,
+
+    /// MemAvailable history the trend is fitted to (default: 60s)
+    pub history_secs: u64,
+
+    /// Forecasts below this confidence (0-1) are ignored (default: 0.6)
+    pub min_confidence: f64,
+
+    /// Pressure level a forecast within the horizon counts as:
+    /// "warning" or "critical" (default: critical)
+    pub level: String,
+}
+
+impl OomForecastConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.horizon_secs == 0 {
+            return Err("OOM forecast horizon must be > 0".to_string());
+        }
+
+        if self.history_secs < 10 {
+            return Err(format!(
+                "OOM forecast history must be >= 10s, got: {}",
+                self.history_secs
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.min_confidence) {
+            return Err(format!(
+                "OOM forecast min confidence must be 0-1, got: {}",
+                self.min_confidence
+            ));
+        }
+
+        if !["warning", "critical"].contains(&self.level.as_str()) {
+            return Err(format!(
+                "OOM forecast level must be 'warning' or 'critical', got: {}",
+                self.level
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Low-memory threshold in percent of the total and/or absolute MB
+///
+/// A level is reached below either value; unset values are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryFloor {
+    pub warning_percent: Option<f64>,
+    pub critical_percent: Option<f64>,
+    pub warning_mb: Option<u64>,
+    pub critical_mb: Option<u64>,
+}
+
+impl MemoryFloor {
+    /// Floor in percent of the total
+    pub fn percent(warning: f64, critical: f64) -> Self {
+        Self {
+            warning_percent: Some(warning),
+            critical_percent: Some(critical),
+            ..Default::default()
+        }
+    }
+
+    /// At least one threshold configured
+    pub fn is_set(&self) -> bool {
+        self.warning_percent.is_some()
+            || self.critical_percent.is_some()
+            || self.warning_mb.is_some()
+            || self.critical_mb.is_some()
+    }
+
+    /// `free_kb` of `total_kb` is below the warning (or critical) floor
+    ///
+    /// A zero total (e.g. no swap) is below any percent floor.
+    pub fn reached(&self, free_kb: u64, total_kb: u64, critical: bool) -> bool {
+        let (percent, mb) = if critical {
+            (self.critical_percent, self.critical_mb)
+        } else {
+            (self.warning_percent, self.warning_mb)
+        };
+
+        let below_percent = percent.is_some_and(|percent| {
+            total_kb == 0 || (free_kb as f64 / total_kb as f64 * 100.0) < percent
+        });
+        let below_mb = mb.is_some_and(|mb| free_kb < mb * 1024);
+        below_percent || below_mb
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        for percent in [self.warning_percent, self.critical_percent].into_iter().flatten() {
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(format!("{} floor must be 0-100%, got: {}", name, percent));
+            }
+        }
+
+        if let (Some(warning), Some(critical)) = (self.warning_percent, self.critical_percent) {
+            if warning < critical {
+                return Err(format!(
+                    "{} warning floor ({}%) must not be below critical ({}%)",
+                    name, warning, critical
+                ));
+            }
+        }
+
+        if let (Some(warning), Some(critical)) = (self.warning_mb, self.critical_mb) {
+            if warning < critical {
+                return Err(format!(
+                    "{} warning floor ({}MB) must not be below critical ({}MB)",
+                    name, warning, critical
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl MemoryPressureConfig {
+    /// Validate the kernel trigger settings and memory floors
+    pub fn validate(&self) -> Result<(), String> {
+        self.mem_available.validate("MemAvailable")?;
+        self.swap_free.validate("SwapFree")?;
+        self.forecast.validate()?;
+
+        if !self.trigger_enabled {
+            return Ok(());
+        }
+
+        if !(500..=10_000).contains(&self.trigger_window_ms) {
+            return Err(format!(
+                "Memory pressure trigger window must be 500-10000ms, got: {}",
+                self.trigger_window_ms
+            ));
+        }
+
+        if self.trigger_stall_ms == 0 || self.trigger_stall_ms > self.trigger_window_ms {
+            return Err(format!(
+                "Memory pressure trigger stall must be 1-{}ms, got: {}",
+                self.trigger_window_ms, self.trigger_stall_ms
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// CPU or I/O stall monitoring (PSI - Pressure Stall Information)
+///
+/// Same thresholds and actions as [`MemoryPressureConfig`], applied to
+/// `/proc/pressure/cpu` or `/proc/pressure/io`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PressureStallConfig {
+    /// Enable stall monitoring (default: false)
+    pub enabled: bool,
+
+    /// Warning threshold for 'some' metric (avg10, %)
+    pub some_threshold_warning: f64,
+
+    /// Critical threshold for 'some' metric (avg10, %)
+    pub some_threshold_critical: f64,
+
+    /// Warning threshold for 'full' metric (avg10, %)
+    /// CPU has no 'full' line before Linux 5.13: reads as 0
+    pub full_threshold_warning: f64,
+
+    /// Critical threshold for 'full' metric (avg10, %)
+    pub full_threshold_critical: f64,
+
+    /// Action to take on warning level
+    /// Options: "log", "nice", "throttle", "freeze", "kill"
     pub action_warning: String,
 
     /// Action to take on critical level
-    /// Options: "log", "nice", "freeze", "kill"
+    /// Options: "log", "nice", "throttle", "freeze", "kill"
     pub action_critical: String,
 
     /// Check interval in seconds (default: 5)
     pub check_interval_secs: u64,
 }
 
+impl PressureStallConfig {
+    /// Validate thresholds and actions (`name` prefixes the error)
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        let thresholds = [
+            ("some warning", self.some_threshold_warning),
+            ("some critical", self.some_threshold_critical),
+            ("full warning", self.full_threshold_warning),
+            ("full critical", self.full_threshold_critical),
+        ];
+        for (label, value) in thresholds {
+            if !(0.0..=100.0).contains(&value) {
+                return Err(format!(
+                    "{} {} threshold must be 0-100, got: {}",
+                    name, label, value
+                ));
+            }
+        }
+
+        if self.some_threshold_warning > self.some_threshold_critical
+            || self.full_threshold_warning > self.full_threshold_critical
+        {
+            return Err(format!(
+                "{} warning thresholds must not exceed critical thresholds",
+                name
+            ));
+        }
+
+        for action in [&self.action_warning, &self.action_critical] {
+            if !PRESSURE_ACTIONS.contains(&action.as_str()) {
+                return Err(format!(
+                    "{} action must be one of {:?}, got: {}",
+                    name, PRESSURE_ACTIONS, action
+                ));
+            }
+        }
+
+        if self.check_interval_secs == 0 {
+            return Err(format!("{} check interval must be > 0", name));
+        }
+
+        Ok(())
+    }
+}
+
+/// Actions a pressure level can trigger
+const PRESSURE_ACTIONS: [&str; 5] = ["log", "nice", "throttle", "freeze", "kill"];
+
+/// Thermal monitoring (`[thermal]`)
+///
+/// Every thermal zone and hwmon sensor is compared with its
+/// `[[thermal.zones]]` entry, or with the default temperatures if none
+/// names it. Above a threshold the busiest processes get the level's action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThermalConfig {
+    /// Enable thermal monitoring (default: false)
+    pub enabled: bool,
+
+    /// Where sysfs is mounted (default: /sys)
+    pub sysfs_root: PathBuf,
+
+    /// Default warning temperature in °C (default: 85)
+    pub warning_celsius: f64,
+
+    /// Default critical temperature in °C (default: 95)
+    pub critical_celsius: f64,
+
+    /// Per-sensor temperatures (`[[thermal.zones]]`)
+    pub zones: Vec<ThermalZoneConfig>,
+
+    /// Action on warning level: "log", "nice", "throttle" or "freeze" (default: nice)
+    pub action_warning: String,
+
+    /// Action on critical level (default: throttle)
+    pub action_critical: String,
+
+    /// Busiest processes the action applies to (default: 3)
+    pub top_processes: usize,
+
+    /// Processes below this CPU usage are left alone (default: 10%)
+    pub min_cpu_percent: f64,
+
+    /// Process names never acted on (default: init, session and display servers)
+    pub exclude: Vec<String>,
+
+    /// Check interval in seconds (default: 5)
+    pub check_interval_secs: u64,
+}
+
+/// Temperatures of one sensor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThermalZoneConfig {
+    /// Sensor name as shown by the dashboard: zone type ("x86_pkg_temp")
+    /// or hwmon chip and label ("coretemp/Package id 0")
+    pub sensor: String,
+
+    /// Ignore the sensor entirely (default: false)
+    #[serde(default)]
+    pub ignore: bool,
+
+    /// Warning temperature in °C (default: `[thermal]` warning)
+    pub warning_celsius: Option<f64>,
+
+    /// Critical temperature in °C (default: `[thermal]` critical)
+    pub critical_celsius: Option<f64>,
+}
+
+impl ThermalConfig {
+    /// Warning and critical temperature of a sensor, None if ignored
+    pub fn thresholds(&self, sensor: &str) -> Option<(f64, f64)> {
+        match self.zones.iter().find(|z| z.sensor == sensor) {
+            Some(zone) if zone.ignore => None,
+            Some(zone) => Some((
+                zone.warning_celsius.unwrap_or(self.warning_celsius),
+                zone.critical_celsius.unwrap_or(self.critical_celsius),
+            )),
+            None => Some((self.warning_celsius, self.critical_celsius)),
+        }
+    }
+
+    /// Validate temperatures and actions
+    pub fn validate(&self) -> Result<(), String> {
+        let mut sensors = vec![("default".to_string(), self.warning_celsius, self.critical_celsius)];
+        for zone in &self.zones {
+            if zone.sensor.is_empty() {
+                return Err("Thermal zone sensor name must not be empty".to_string());
+            }
+            if let Some((warning, critical)) = self.thresholds(&zone.sensor) {
+                sensors.push((zone.sensor.clone(), warning, critical));
+            }
+        }
+
+        for (sensor, warning, critical) in sensors {
+            for celsius in [warning, critical] {
+                if !(20.0..=150.0).contains(&celsius) {
+                    return Err(format!(
+                        "Thermal {} temperature must be 20-150°C, got: {}",
+                        sensor, celsius
+                    ));
+                }
+            }
+            if warning > critical {
+                return Err(format!(
+                    "Thermal {} warning temperature ({}°C) must not exceed critical ({}°C)",
+                    sensor, warning, critical
+                ));
+            }
+        }
+
+        for action in [&self.action_warning, &self.action_critical] {
+            if !THERMAL_ACTIONS.contains(&action.as_str()) {
+                return Err(format!(
+                    "Thermal action must be one of {:?}, got: {}",
+                    THERMAL_ACTIONS, action
+                ));
+            }
+        }
+
+        if self.top_processes == 0 {
+            return Err("Thermal top processes must be > 0".to_string());
+        }
+
+        if self.check_interval_secs == 0 {
+            return Err("Thermal check interval must be > 0".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Actions a thermal level can trigger
+const THERMAL_ACTIONS: [&str; 4] = ["log", "nice", "throttle", "freeze"];
+
+/// Profile switching by power source (`[power]`)
+///
+/// A profile is an overlay of config sections, see [`Config::with_profile`].
+/// An unset profile means the base config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerConfig {
+    /// Switch profiles when the power source changes (default: false)
+    pub enabled: bool,
+
+    /// Where sysfs is mounted (default: /sys)
+    pub sysfs_root: PathBuf,
+
+    /// Profile on AC power (default: none, the base config)
+    pub ac_profile: Option<String>,
+
+    /// Profile on battery (default: "battery")
+    pub battery_profile: Option<String>,
+
+    /// Profile on battery below `low_battery_percent` (default: none, the battery profile)
+    pub low_battery_profile: Option<String>,
+
+    /// Charge below which the low battery profile applies (default: 20%)
+    pub low_battery_percent: u8,
+
+    /// How often the power source is read (default: 10s)
+    pub check_interval_secs: u64,
+}
+
+impl PowerConfig {
+    /// Profile for a power state, None for the base config
+    pub fn profile_for(&self, state: &freezr_core::PowerState) -> Option<&str> {
+        match state.source {
+            freezr_core::PowerSource::Ac => self.ac_profile.as_deref(),
+            freezr_core::PowerSource::Battery => {
+                let low = state
+                    .battery_percent
+                    .is_some_and(|percent| percent < self.low_battery_percent);
+                match &self.low_battery_profile {
+                    Some(profile) if low => Some(profile),
+                    _ => self.battery_profile.as_deref(),
+                }
+            }
+        }
+    }
+}
+
+/// Merge `overlay` into `base`: tables key by key, `[[rules]]`-style arrays
+/// of named tables by name, anything else replaced
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (toml::Value::Array(base), toml::Value::Array(overlay)) if is_named(base) && is_named(&overlay) => {
+            for value in overlay {
+                let name = value.get("name").cloned();
+                match base.iter_mut().find(|b| b.get("name") == name.as_ref()) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => base.push(value),
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Non-empty array of tables that all have a string `name`
+fn is_named(values: &[toml::Value]) -> bool {
+    !values.is_empty()
+        && values
+            .iter()
+            .all(|v| v.get("name").is_some_and(toml::Value::is_str))
+}
+
+/// Sections a profile cannot change: they are only read at start
+const STARTUP_SECTIONS: [&str; 5] = ["cgroups", "logging", "monitoring", "power", "profiles"];
+
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogConfig {
@@ -223,14 +851,107 @@ pub struct LogConfig {
     /// Node monitor log file name (default: node-monitor.log)
     pub node_log: String,
 
-    /// Actions log file name (default: actions.log)
-    pub actions_log: String,
+    /// Actions log file name (default: actions.log)
+    pub actions_log: String,
+
+    /// Maximum log file size in MB before rotation (default: 10)
+    pub max_file_size_mb: u64,
+
+    /// Number of rotated log files to keep (default: 5)
+    pub rotate_count: u32,
+}
+
+/// Runaway spawner detection
+///
+/// A process subtree that grows by `growth_threshold` processes between two
+/// checks is contained: its tasks are capped with cgroup `pids.max` (needs
+/// `[cgroups]`, otherwise the root process is only reniced).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnGuardConfig {
+    /// Enable detection (default: true)
+    pub enabled: bool,
+
+    /// New processes in one subtree between two checks (default: 100)
+    pub growth_threshold: usize,
+
+    /// Tasks allowed above the current count once contained (default: 32)
+    pub pids_headroom: u64,
+
+    /// Process names never contained (default: init and user managers)
+    pub exclude: Vec<String>,
+}
+
+/// Victim selection of the "kill" pressure action
+///
+/// Candidates are ranked by memory (PSS, RSS if unreadable), the kernel's
+/// `oom_score` and their priority. One victim is killed at a time until the
+/// stall measured after the kill falls below the warning thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OomKillerConfig {
+    /// Victims killed at most per pressure event (default: 5)
+    pub max_kills: u32,
+
+    /// Stall is measured over this window after each kill (default: 500ms)
+    pub settle_ms: u64,
+
+    /// Processes below this RSS are never chosen (default: 50MB)
+    pub min_rss_mb: u64,
+
+    /// Process names (comm) never killed (default: init, session and display servers)
+    pub protected: Vec<String>,
+
+    /// Score adjustments of matching processes (`[[oom_killer.priorities]]`)
+    /// Defaults to Brave, Telegram and Firefox, in the order they used to be killed
+    pub priorities: Vec<VictimPriority>,
+}
+
+/// Priority of processes matched like a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VictimPriority {
+    /// Which processes get the priority
+    #[serde(rename = "match")]
+    pub matcher: RuleMatcher,
+
+    /// -100 (never killed) to 100 (score doubled), first match wins
+    pub priority: i32,
+}
+
+impl OomKillerConfig {
+    /// Validate kill limits and priorities
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_kills == 0 {
+            return Err("OOM killer max kills must be > 0".to_string());
+        }
 
-    /// Maximum log file size in MB before rotation (default: 10)
-    pub max_file_size_mb: u64,
+        if !(100..=5000).contains(&self.settle_ms) {
+            return Err(format!(
+                "OOM killer settle time must be 100-5000ms, got: {}",
+                self.settle_ms
+            ));
+        }
 
-    /// Number of rotated log files to keep (default: 5)
-    pub rotate_count: u32,
+        for entry in &self.priorities {
+            if entry.matcher.is_empty() {
+                return Err("OOM killer priority must have at least one match criterion".to_string());
+            }
+
+            if let Some(pattern) = &entry.matcher.cmdline {
+                regex::Regex::new(pattern)
+                    .map_err(|e| format!("OOM killer priority has invalid cmdline regex: {}", e))?;
+            }
+
+            if !(-100..=100).contains(&entry.priority) {
+                return Err(format!(
+                    "OOM killer priority must be -100 to 100, got: {}",
+                    entry.priority
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// General monitoring settings
@@ -242,6 +963,29 @@ pub struct MonitoringConfig {
     /// Minimum restart interval in seconds (default: 100)
     /// Prevents restart loops
     pub min_restart_interval_secs: u64,
+
+    /// Smoothing of per-process CPU samples between checks (default: mean of 3)
+    /// Examples: { type = "none" }, { type = "ewma", alpha = 0.5 }, { type = "mean", samples = 3 }
+    #[serde(default)]
+    pub cpu_smoothing: CpuSmoothing,
+
+    /// Journal of frozen processes, thawed on the next start after a crash
+    /// (default: /run/freezr/frozen.json)
+    #[serde(default = "default_frozen_registry_path")]
+    pub frozen_registry_path: PathBuf,
+
+    /// Profile requested with `freezr profile set`, read every check
+    /// (default: /run/freezr/profile.json)
+    #[serde(default = "default_profile_request_path")]
+    pub profile_request_path: PathBuf,
+}
+
+fn default_frozen_registry_path() -> PathBuf {
+    PathBuf::from(DEFAULT_REGISTRY_PATH)
+}
+
+fn default_profile_request_path() -> PathBuf {
+    PathBuf::from(DEFAULT_PROFILE_REQUEST_PATH)
 }
 
 impl Default for Config {
@@ -250,11 +994,19 @@ impl Default for Config {
             kesl: KeslConfig::default(),
             node: NodeConfig::default(),
             snap: SnapConfig::default(),
-            firefox: FirefoxConfig::default(),
-            brave: BraveConfig::default(),
-            telegram: TelegramConfig::default(),
+            rules: default_rules(),
+            firefox: None,
+            brave: None,
+            telegram: None,
             memory_pressure: MemoryPressureConfig::default(),
+            cpu_pressure: PressureStallConfig::default(),
+            io_pressure: PressureStallConfig::default(),
             cgroups: CgroupConfig::default(),
+            spawn_guard: SpawnGuardConfig::default(),
+            oom_killer: OomKillerConfig::default(),
+            thermal: ThermalConfig::default(),
+            power: PowerConfig::default(),
+            profiles: BTreeMap::new(),
             logging: LogConfig::default(),
             monitoring: MonitoringConfig::default(),
         }
@@ -269,6 +1021,7 @@ impl Default for KeslConfig {
             max_violations: 3,
             service_name: "kesl".to_string(),
             enabled: true,
+            action: default_kesl_action(),
         }
     }
 }
@@ -297,42 +1050,192 @@ impl Default for SnapConfig {
     }
 }
 
-impl Default for FirefoxConfig {
-    fn default() -> Self {
-        Self {
-            cpu_threshold_freeze: 80.0,    // Freeze at 80% CPU
-            cpu_threshold_kill: 95.0,      // Kill at 95% CPU (critical)
-            enabled: true,
-            freeze_duration_secs: 5,       // Freeze for 5 seconds
-            max_violations_freeze: 2,      // Freeze after 2 violations
-            max_violations_kill: 3,        // Kill after 3 violations
-        }
-    }
+fn default_true() -> bool {
+    true
 }
 
-impl Default for BraveConfig {
-    fn default() -> Self {
+fn default_trigger_stall_ms() -> u64 {
+    150
+}
+
+fn default_trigger_window_ms() -> u64 {
+    1000
+}
+
+fn default_mem_available_floor() -> MemoryFloor {
+    MemoryFloor::percent(10.0, 5.0)
+}
+
+fn default_swap_free_floor() -> MemoryFloor {
+    MemoryFloor::percent(10.0, 5.0)
+}
+
+fn default_kesl_action() -> String {
+    "restart".to_string()
+}
+
+fn default_max_violations() -> u32 {
+    1
+}
+
+fn default_nice_level() -> i32 {
+    15
+}
+
+fn default_freeze_duration_secs() -> u64 {
+    5
+}
+
+/// Applications of the default rules and the legacy sections, with their
+/// command line regex
+const LEGACY_APPS: [(&str, &str); 3] = [
+    ("firefox", "^\\S*firefox"),
+    ("brave", "^\\S*brave"),
+    ("telegram", "^\\S*telegram"),
+];
+
+/// Default rules: two-tier strategy for browsers and Telegram
+/// (freeze at 80% CPU after 2 violations, kill at 95% after 3)
+pub fn default_rules() -> Vec<RuleConfig> {
+    LEGACY_APPS
+        .into_iter()
+    .map(|(name, cmdline)| RuleConfig::two_tier(name, cmdline, 80.0, 95.0))
+    .collect()
+}
+
+impl RuleConfig {
+    /// Two-tier rule: freeze at high load, kill at critical
+    ///
+    /// # Arguments
+    /// * `name` - Rule name
+    /// * `cmdline` - Command line regex
+    /// * `cpu_threshold_freeze` - CPU threshold for freezing (e.g., 80.0)
+    /// * `cpu_threshold_kill` - CPU threshold for killing (e.g., 95.0)
+    pub fn two_tier(
+        name: &str,
+        cmdline: &str,
+        cpu_threshold_freeze: f64,
+        cpu_threshold_kill: f64,
+    ) -> Self {
         Self {
-            cpu_threshold_freeze: 80.0,    // Freeze at 80% CPU
-            cpu_threshold_kill: 95.0,      // Kill at 95% CPU (critical)
+            name: name.to_string(),
             enabled: true,
-            freeze_duration_secs: 5,       // Freeze for 5 seconds
-            max_violations_freeze: 2,      // Freeze after 2 violations
-            max_violations_kill: 3,        // Kill after 3 violations
+            matcher: RuleMatcher {
+                cmdline: Some(cmdline.to_string()),
+                ..Default::default()
+            },
+            actions: vec![
+                RuleStep {
+                    cpu_threshold: Some(cpu_threshold_freeze),
+                    memory_threshold_mb: None,
+                    max_violations: 2,       // Freeze after 2 violations
+                    action: "freeze".to_string(),
+                    nice_level: default_nice_level(),
+                    freeze_duration_secs: default_freeze_duration_secs(),
+                    io_read_mbps: None,
+                    io_write_mbps: None,
+                    io_device: None,
+                    cpuset_cpus: None,
+                },
+                RuleStep {
+                    cpu_threshold: Some(cpu_threshold_kill),
+                    memory_threshold_mb: None,
+                    max_violations: 3,       // Kill after 3 violations
+                    action: "kill".to_string(),
+                    nice_level: default_nice_level(),
+                    freeze_duration_secs: default_freeze_duration_secs(),
+                    io_read_mbps: None,
+                    io_write_mbps: None,
+                    io_device: None,
+                    cpuset_cpus: None,
+                },
+            ],
         }
     }
-}
 
-impl Default for TelegramConfig {
-    fn default() -> Self {
-        Self {
-            cpu_threshold_freeze: 80.0,    // Freeze at 80% CPU
-            cpu_threshold_kill: 95.0,      // Kill at 95% CPU (critical)
-            enabled: true,
-            freeze_duration_secs: 5,       // Freeze for 5 seconds
-            max_violations_freeze: 2,      // Freeze after 2 violations
-            max_violations_kill: 3,        // Kill after 3 violations
+    /// Validate rule
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Rule name must not be empty".to_string());
+        }
+
+        let m = &self.matcher;
+        if m.is_empty() {
+            return Err(format!(
+                "Rule '{}' must have at least one match criterion",
+                self.name
+            ));
+        }
+
+        if let Some(pattern) = &m.cmdline {
+            regex::Regex::new(pattern)
+                .map_err(|e| format!("Rule '{}' has invalid cmdline regex: {}", self.name, e))?;
+        }
+
+        if self.actions.is_empty() {
+            return Err(format!("Rule '{}' must have at least one action", self.name));
+        }
+
+        for step in &self.actions {
+            if step.cpu_threshold.is_none() && step.memory_threshold_mb.is_none() {
+                return Err(format!(
+                    "Rule '{}': each action needs cpu_threshold or memory_threshold_mb",
+                    self.name
+                ));
+            }
+
+            if let Some(cpu) = step.cpu_threshold {
+                if cpu < 0.0 {
+                    return Err(format!(
+                        "Rule '{}' CPU threshold must be >= 0, got: {}",
+                        self.name, cpu
+                    ));
+                }
+            }
+
+            if !["log", "nice", "throttle", "freeze", "kill"].contains(&step.action.as_str()) {
+                return Err(format!(
+                    "Rule '{}' action must be 'log', 'nice', 'throttle', 'freeze', or 'kill', got: {}",
+                    self.name, step.action
+                ));
+            }
+
+            if step.nice_level < 0 || step.nice_level > 19 {
+                return Err(format!(
+                    "Rule '{}' nice level must be 0-19, got: {}",
+                    self.name, step.nice_level
+                ));
+            }
+
+            if step.max_violations == 0 {
+                return Err(format!("Rule '{}' max violations must be > 0", self.name));
+            }
+
+            if !step.io_limits().is_empty() && step.action != "throttle" {
+                return Err(format!(
+                    "Rule '{}': io_read_mbps/io_write_mbps need the 'throttle' action",
+                    self.name
+                ));
+            }
+
+            if step.io_read_mbps == Some(0) || step.io_write_mbps == Some(0) {
+                return Err(format!("Rule '{}' I/O caps must be > 0 MB/s", self.name));
+            }
+
+            if step.cpuset_cpus.is_some() {
+                if step.action != "throttle" {
+                    return Err(format!(
+                        "Rule '{}': cpuset_cpus needs the 'throttle' action",
+                        self.name
+                    ));
+                }
+                step.limits()
+                    .validate()
+                    .map_err(|e| format!("Rule '{}': {}", self.name, e))?;
+            }
         }
+
+        Ok(())
     }
 }
 
@@ -347,6 +1250,77 @@ impl Default for MemoryPressureConfig {
             action_warning: "log".to_string(), // Just log warnings
             action_critical: "freeze".to_string(), // Freeze non-critical processes
             check_interval_secs: 5,            // Check every 5 seconds
+            trigger_enabled: true,             // Kernel wakes us on a stall
+            trigger_stall_ms: 150,             // 150ms of stall...
+            trigger_window_ms: 1000,           // ...within 1 second
+            mem_available: default_mem_available_floor(), // earlyoom: 10%/5%
+            swap_free: default_swap_free_floor(),
+            forecast: OomForecastConfig::default(),
+        }
+    }
+}
+
+impl Default for OomForecastConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            horizon_secs: 30,
+            history_secs: 60,
+            min_confidence: 0.6,
+            level: "critical".to_string(),
+        }
+    }
+}
+
+impl Default for PressureStallConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            some_threshold_warning: 20.0,
+            some_threshold_critical: 50.0,
+            full_threshold_warning: 10.0,
+            full_threshold_critical: 30.0,
+            action_warning: "log".to_string(),
+            action_critical: "throttle".to_string(),
+            check_interval_secs: 5,
+        }
+    }
+}
+
+impl Default for ThermalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sysfs_root: PathBuf::from(freezr_core::thermal::DEFAULT_SYSFS_ROOT),
+            warning_celsius: 85.0,
+            critical_celsius: 95.0,
+            zones: Vec::new(),
+            action_warning: "nice".to_string(),
+            action_critical: "throttle".to_string(),
+            top_processes: 3,
+            min_cpu_percent: 10.0,
+            exclude: [
+                "systemd", "init", "Xorg", "Xwayland", "gnome-shell",
+                "kwin_x11", "kwin_wayland", "pipewire", "pulseaudio",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            check_interval_secs: 5,
+        }
+    }
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sysfs_root: PathBuf::from(freezr_core::thermal::DEFAULT_SYSFS_ROOT),
+            ac_profile: None,
+            battery_profile: Some("battery".to_string()),
+            low_battery_profile: None,
+            low_battery_percent: 20,
+            check_interval_secs: 10,
         }
     }
 }
@@ -364,11 +1338,55 @@ impl Default for LogConfig {
     }
 }
 
+impl Default for SpawnGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            growth_threshold: 100,
+            pids_headroom: 32,
+            exclude: vec!["systemd".to_string(), "init".to_string()],
+        }
+    }
+}
+
+impl Default for OomKillerConfig {
+    fn default() -> Self {
+        let priorities = [("^\\S*brave", 30), ("^\\S*telegram", 20), ("^\\S*firefox", 10)]
+            .into_iter()
+            .map(|(cmdline, priority)| VictimPriority {
+                matcher: RuleMatcher {
+                    cmdline: Some(cmdline.to_string()),
+                    ..Default::default()
+                },
+                priority,
+            })
+            .collect();
+
+        Self {
+            max_kills: 5,
+            settle_ms: 500,
+            min_rss_mb: 50,
+            protected: [
+                "systemd", "init", "sshd", "dbus-daemon", "Xorg", "Xwayland",
+                "gnome-shell", "kwin_x11", "kwin_wayland", "plasmashell",
+                "gdm", "sddm", "pipewire", "kesl",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            priorities,
+        }
+    }
+}
+
 impl Default for MonitoringConfig {
     fn default() -> Self {
         Self {
             check_interval_secs: 3,
             min_restart_interval_secs: 100,
+            cpu_smoothing: CpuSmoothing::default(),
+            frozen_registry_path: default_frozen_registry_path(),
+            profile_request_path: default_profile_request_path(),
         }
     }
 }
@@ -388,10 +1406,36 @@ impl Config {
     /// ```
     pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        let mut config: Config = toml::from_str(&content)?;
+        for section in config.migrate_legacy_sections() {
+            tracing::warn!(
+                "[{}] is deprecated, applied as [[rules]] name = \"{}\"; move it to a rule",
+                section, section
+            );
+        }
         Ok(config)
     }
 
+    /// Turn legacy `[firefox]`, `[brave]` and `[telegram]` sections into rules
+    ///
+    /// Each replaces the rule of the same name (the default one unless
+    /// `[[rules]]` defines it), or is added. Returns the migrated sections.
+    pub fn migrate_legacy_sections(&mut self) -> Vec<&'static str> {
+        let mut migrated = Vec::new();
+        let sections = [self.firefox.take(), self.brave.take(), self.telegram.take()];
+        for ((name, cmdline), section) in LEGACY_APPS.into_iter().zip(sections) {
+            let Some(section) = section else { continue };
+
+            let rule = section.to_rule(name, cmdline);
+            match self.rules.iter_mut().find(|r| r.name == name) {
+                Some(existing) => *existing = rule,
+                None => self.rules.push(rule),
+            }
+            migrated.push(name);
+        }
+        migrated
+    }
+
     /// Save configuration to TOML file
     ///
     /// # Arguments
@@ -404,8 +1448,14 @@ impl Config {
 
     /// Validate configuration values
     ///
-    /// Checks that all thresholds and intervals are within reasonable ranges
+    /// Checks that all thresholds and intervals are within reasonable ranges,
+    /// also with each profile applied
     pub fn validate(&self) -> Result<(), String> {
+        self.validate_sections()?;
+        self.validate_profiles()
+    }
+
+    fn validate_sections(&self) -> Result<(), String> {
         // Validate KESL config
         if self.kesl.cpu_threshold < 0.0 || self.kesl.cpu_threshold > 100.0 {
             return Err(format!(
@@ -422,6 +1472,13 @@ impl Config {
             return Err("KESL max violations must be > 0".to_string());
         }
 
+        if !["restart", "throttle"].contains(&self.kesl.action.as_str()) {
+            return Err(format!(
+                "KESL action must be 'restart' or 'throttle', got: {}",
+                self.kesl.action
+            ));
+        }
+
         // Validate Node config
         if self.node.cpu_threshold < 0.0 || self.node.cpu_threshold > 100.0 {
             return Err(format!(
@@ -438,9 +1495,9 @@ impl Config {
             ));
         }
 
-        if !["freeze", "nice", "kill"].contains(&self.snap.action.as_str()) {
+        if !["freeze", "nice", "throttle", "kill"].contains(&self.snap.action.as_str()) {
             return Err(format!(
-                "Snap action must be 'freeze', 'nice', or 'kill', got: {}",
+                "Snap action must be 'freeze', 'nice', 'throttle', or 'kill', got: {}",
                 self.snap.action
             ));
         }
@@ -456,65 +1513,46 @@ impl Config {
             return Err("Snap max violations must be > 0".to_string());
         }
 
-        // Validate Firefox config
-        if self.firefox.cpu_threshold_freeze < 0.0 || self.firefox.cpu_threshold_freeze > 100.0 {
-            return Err(format!(
-                "Firefox freeze CPU threshold must be 0-100, got: {}",
-                self.firefox.cpu_threshold_freeze
-            ));
-        }
-
-        if self.firefox.cpu_threshold_kill < 0.0 || self.firefox.cpu_threshold_kill > 100.0 {
-            return Err(format!(
-                "Firefox kill CPU threshold must be 0-100, got: {}",
-                self.firefox.cpu_threshold_kill
-            ));
-        }
-
-        if self.firefox.cpu_threshold_kill <= self.firefox.cpu_threshold_freeze {
-            return Err(format!(
-                "Firefox kill threshold ({}) must be > freeze threshold ({})",
-                self.firefox.cpu_threshold_kill, self.firefox.cpu_threshold_freeze
-            ));
+        // Validate rules
+        let mut names = std::collections::HashSet::new();
+        for rule in &self.rules {
+            rule.validate()?;
+            if !names.insert(rule.name.as_str()) {
+                return Err(format!("Duplicate rule name: {}", rule.name));
+            }
         }
 
-        if self.firefox.max_violations_freeze == 0 {
-            return Err("Firefox max violations (freeze) must be > 0".to_string());
-        }
+        // Validate pressure monitoring
+        self.memory_pressure.validate()?;
+        self.cpu_pressure.validate("CPU pressure")?;
+        self.io_pressure.validate("IO pressure")?;
 
-        if self.firefox.max_violations_kill == 0 {
-            return Err("Firefox max violations (kill) must be > 0".to_string());
+        // Validate spawn guard config
+        if self.spawn_guard.growth_threshold == 0 {
+            return Err("Spawn guard growth threshold must be > 0".to_string());
         }
 
-        // Validate Brave config
-        if self.brave.cpu_threshold_freeze < 0.0 || self.brave.cpu_threshold_freeze > 100.0 {
-            return Err(format!(
-                "Brave freeze CPU threshold must be 0-100, got: {}",
-                self.brave.cpu_threshold_freeze
-            ));
+        if self.spawn_guard.pids_headroom == 0 {
+            return Err("Spawn guard pids headroom must be > 0".to_string());
         }
 
-        if self.brave.cpu_threshold_kill < 0.0 || self.brave.cpu_threshold_kill > 100.0 {
-            return Err(format!(
-                "Brave kill CPU threshold must be 0-100, got: {}",
-                self.brave.cpu_threshold_kill
-            ));
-        }
+        self.oom_killer.validate()?;
+        self.thermal.validate()?;
 
-        if self.brave.cpu_threshold_kill <= self.brave.cpu_threshold_freeze {
+        // Validate cgroup paths
+        if !self.cgroups.root_path.starts_with(&self.cgroups.cgroupfs_root) {
             return Err(format!(
-                "Brave kill threshold ({}) must be > freeze threshold ({})",
-                self.brave.cpu_threshold_kill, self.brave.cpu_threshold_freeze
+                "Cgroup root path {:?} must be below cgroupfs root {:?}",
+                self.cgroups.root_path, self.cgroups.cgroupfs_root
             ));
         }
 
-        if self.brave.max_violations_freeze == 0 {
-            return Err("Brave max violations (freeze) must be > 0".to_string());
-        }
-
-        if self.brave.max_violations_kill == 0 {
-            return Err("Brave max violations (kill) must be > 0".to_string());
-        }
+        // Validate adaptive throttle limits
+        self.cgroups
+            .dynamic_settings
+            .adaptive
+            .validate()
+            .map_err(|e| e.to_string())?;
 
         // Validate monitoring config
         if self.monitoring.check_interval_secs == 0 {
@@ -525,6 +1563,11 @@ impl Config {
             return Err("Min restart interval must be > 0".to_string());
         }
 
+        self.monitoring
+            .cpu_smoothing
+            .validate()
+            .map_err(|e| format!("Invalid CPU smoothing: {}", e))?;
+
         // Validate logging config
         if self.logging.max_file_size_mb == 0 {
             return Err("Max log file size must be > 0".to_string());
@@ -537,6 +1580,77 @@ impl Config {
         Ok(())
     }
 
+    /// Config with a profile laid over it
+    ///
+    /// Each `[profiles.<name>.<section>]` table is merged key by key into
+    /// its section. Arrays are replaced, except `[[rules]]`, which are
+    /// merged by name: a profile rule changes the base rule of the same
+    /// name or is added. Startup-only sections cannot be overlaid.
+    pub fn with_profile(&self, name: &str) -> Result<Config, String> {
+        let overlay = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("Unknown profile: {}", name))?;
+
+        let toml::Value::Table(mut merged) = toml::Value::try_from(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?
+        else {
+            return Err("Config did not serialize to a table".to_string());
+        };
+
+        for (section, value) in overlay {
+            if STARTUP_SECTIONS.contains(&section.as_str()) {
+                return Err(format!(
+                    "Profile '{}' cannot change [{}]: it is only read at start",
+                    name, section
+                ));
+            }
+            match merged.get_mut(section) {
+                Some(base) => merge_toml(base, value.clone()),
+                None => {
+                    return Err(format!(
+                        "Profile '{}' overlays unknown section [{}]",
+                        name, section
+                    ))
+                }
+            }
+        }
+
+        let mut config: Config = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e| format!("Profile '{}' is invalid: {}", name, e))?;
+        config.profiles = self.profiles.clone();
+        Ok(config)
+    }
+
+    /// Every profile applies cleanly and the power profiles exist
+    fn validate_profiles(&self) -> Result<(), String> {
+        for name in self.profiles.keys() {
+            self.with_profile(name)?
+                .validate_sections()
+                .map_err(|e| format!("Profile '{}': {}", name, e))?;
+        }
+
+        if self.power.enabled {
+            let profiles = [
+                &self.power.ac_profile,
+                &self.power.battery_profile,
+                &self.power.low_battery_profile,
+            ];
+            for profile in profiles.into_iter().flatten() {
+                if !self.profiles.contains_key(profile) {
+                    return Err(format!("Power profile '{}' is not defined in [profiles]", profile));
+                }
+            }
+
+            if self.power.check_interval_secs == 0 {
+                return Err("Power check interval must be > 0".to_string());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get full path to KESL log file
     pub fn kesl_log_path(&self) -> PathBuf {
         self.logging.log_dir.join(&self.logging.kesl_log)
@@ -577,6 +1691,7 @@ mod tests {
         assert_eq!(kesl.max_violations, 3);
         assert_eq!(kesl.service_name, "kesl");
         assert!(kesl.enabled);
+        assert_eq!(kesl.action, "restart");
     }
 
     #[test]
@@ -645,6 +1760,384 @@ mod tests {
         assert!(result.unwrap_err().contains("Check interval"));
     }
 
+    #[test]
+    fn test_config_validation_cgroup_root_outside_mount() {
+        let mut config = Config::default();
+        config.cgroups.cgroupfs_root = PathBuf::from("/sys/fs/cgroup/unified");
+
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("must be below cgroupfs root"));
+    }
+
+    #[test]
+    fn test_config_validation_memory_trigger() {
+        let mut config = Config::default();
+        config.memory_pressure.trigger_window_ms = 100;
+        assert!(config.validate().unwrap_err().contains("trigger window"));
+
+        config.memory_pressure.trigger_window_ms = 1000;
+        config.memory_pressure.trigger_stall_ms = 1500;
+        assert!(config.validate().unwrap_err().contains("trigger stall"));
+
+        // Not registered, not checked
+        config.memory_pressure.trigger_enabled = false;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_memory_floor() {
+        let floor = MemoryFloor {
+            critical_mb: Some(512),
+            ..MemoryFloor::percent(10.0, 1.0)
+        };
+        let total = 16 * 1024 * 1024;
+
+        assert!(!floor.reached(total / 5, total, false));
+        assert!(floor.reached(total / 12, total, false));
+        assert!(!floor.reached(total / 12, total, true));
+        // 400MB is above 1% of 16GB but below the absolute floor
+        assert!(floor.reached(400 * 1024, total, true));
+        // No swap: below any percent floor
+        assert!(floor.reached(0, 0, false));
+        assert!(!MemoryFloor::default().reached(0, 0, true));
+    }
+
+    #[test]
+    fn test_config_validation_memory_floors() {
+        let mut config = Config::default();
+        config.memory_pressure.mem_available.warning_percent = Some(150.0);
+        assert!(config.validate().unwrap_err().contains("0-100%"));
+
+        config.memory_pressure.mem_available = MemoryFloor::percent(5.0, 10.0);
+        assert!(config.validate().unwrap_err().contains("must not be below critical"));
+
+        config.memory_pressure.mem_available = MemoryFloor::default();
+        config.memory_pressure.swap_free.warning_mb = Some(100);
+        config.memory_pressure.swap_free.critical_mb = Some(200);
+        assert!(config.validate().unwrap_err().contains("SwapFree"));
+    }
+
+    #[test]
+    fn test_config_validation_oom_forecast() {
+        let mut config = Config::default();
+        config.memory_pressure.forecast.history_secs = 5;
+        assert!(config.validate().unwrap_err().contains("history"));
+
+        config.memory_pressure.forecast.history_secs = 60;
+        config.memory_pressure.forecast.min_confidence = 1.5;
+        assert!(config.validate().unwrap_err().contains("confidence"));
+
+        config.memory_pressure.forecast.min_confidence = 0.5;
+        config.memory_pressure.forecast.level = "kill".to_string();
+        assert!(config.validate().unwrap_err().contains("'warning' or 'critical'"));
+    }
+
+    #[test]
+    fn test_config_validation_oom_killer() {
+        let mut config = Config::default();
+        config.oom_killer.max_kills = 0;
+        assert!(config.validate().unwrap_err().contains("max kills"));
+
+        config.oom_killer.max_kills = 1;
+        config.oom_killer.priorities[0].priority = 150;
+        assert!(config.validate().unwrap_err().contains("-100 to 100"));
+
+        config.oom_killer.priorities[0].priority = -100;
+        config.oom_killer.priorities[1].matcher = RuleMatcher::default();
+        assert!(config.validate().unwrap_err().contains("match criterion"));
+    }
+
+    #[test]
+    fn test_thermal_thresholds() {
+        let mut config = ThermalConfig {
+            zones: vec![
+                ThermalZoneConfig {
+                    sensor: "x86_pkg_temp".to_string(),
+                    ignore: false,
+                    warning_celsius: Some(90.0),
+                    critical_celsius: None,
+                },
+                ThermalZoneConfig {
+                    sensor: "acpitz".to_string(),
+                    ignore: true,
+                    warning_celsius: None,
+                    critical_celsius: None,
+                },
+            ],
+            ..ThermalConfig::default()
+        };
+
+        assert_eq!(config.thresholds("x86_pkg_temp"), Some((90.0, 95.0)));
+        assert_eq!(config.thresholds("acpitz"), None);
+        assert_eq!(config.thresholds("coretemp/Core 0"), Some((85.0, 95.0)));
+        assert!(config.validate().is_ok());
+
+        config.zones[0].warning_celsius = Some(100.0);
+        assert!(config.validate().unwrap_err().contains("x86_pkg_temp"));
+
+        let mut config = Config::default();
+        config.thermal.critical_celsius = 200.0;
+        assert!(config.validate().unwrap_err().contains("20-150"));
+
+        config.thermal.critical_celsius = 95.0;
+        config.thermal.action_critical = "kill".to_string();
+        assert!(config.validate().unwrap_err().contains("Thermal action"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "quiet".to_string(),
+            toml::from_str(
+                r#"
+                node.enabled = false
+
+                [[rules]]
+                name = "brave"
+                enabled = false
+
+                [[rules]]
+                name = "zoom"
+                match = { name = "zoom" }
+                actions = [{ action = "nice", cpu_threshold = 50.0 }]
+                "#,
+            )
+            .unwrap(),
+        );
+        assert!(config.validate().is_ok());
+
+        let quiet = config.with_profile("quiet").unwrap();
+        assert!(!quiet.node.enabled);
+        assert_eq!(quiet.node.cpu_threshold, config.node.cpu_threshold);
+        let rules: Vec<(&str, bool)> = quiet.rules.iter().map(|r| (r.name.as_str(), r.enabled)).collect();
+        assert_eq!(rules, vec![("firefox", true), ("brave", false), ("telegram", true), ("zoom", true)]);
+        assert!(config.with_profile("missing").is_err());
+
+        // Power profiles must exist
+        config.power.enabled = true;
+        assert!(config.validate().unwrap_err().contains("'battery'"));
+        config.power.battery_profile = Some("quiet".to_string());
+        assert!(config.validate().is_ok());
+
+        // Startup sections, unknown sections and invalid values
+        let overlays = [
+            ("[cgroups]\nenabled = false", "only read at start"),
+            ("[kesl_typo]\ncpu_threshold = 10.0", "unknown section"),
+            ("[kesl]\ncpu_threshold = 150.0", "Profile 'bad'"),
+            ("[kesl]\ncpu_threshold = \"high\"", "invalid"),
+        ];
+        for (overlay, error) in overlays {
+            let mut config = config.clone();
+            config.profiles.insert("bad".to_string(), toml::from_str(overlay).unwrap());
+            let result = config.validate().unwrap_err();
+            assert!(result.contains(error), "{}: {}", overlay, result);
+        }
+    }
+
+    #[test]
+    fn test_config_validation_stall_thresholds() {
+        let mut config = Config::default();
+        config.io_pressure.some_threshold_warning = 60.0;
+        assert!(config.validate().unwrap_err().contains("IO pressure"));
+
+        let mut config = Config::default();
+        config.cpu_pressure.full_threshold_critical = 120.0;
+        assert!(config.validate().unwrap_err().contains("0-100"));
+
+        let mut config = Config::default();
+        config.cpu_pressure.action_critical = "restart".to_string();
+        assert!(config.validate().unwrap_err().contains("CPU pressure action"));
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let config = Config::default();
+        let names: Vec<&str> = config.rules.iter().map(|r| r.name.as_str()).collect();
+
+        assert_eq!(names, vec!["firefox", "brave", "telegram"]);
+        assert_eq!(config.rules[0].actions.len(), 2);
+        assert_eq!(config.rules[0].actions[0].action, "freeze");
+        assert_eq!(config.rules[0].actions[1].cpu_threshold, Some(95.0));
+    }
+
+    #[test]
+    fn test_legacy_sections_become_rules() {
+        let mut content = toml::to_string(&Config::default()).unwrap();
+        content.push_str(
+            r#"
+            [firefox]
+            cpu_threshold_freeze = 70.0
+            max_violations_kill = 5
+
+            [telegram]
+            enabled = false
+            "#,
+        );
+        let path = std::env::temp_dir().join(format!("freezr-legacy-{}.toml", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        let config = Config::load_from_file(path.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(config.firefox.is_none() && config.telegram.is_none());
+        let names: Vec<&str> = config.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["firefox", "brave", "telegram"]);
+
+        let firefox = &config.rules[0];
+        assert_eq!(firefox.actions[0].cpu_threshold, Some(70.0));
+        assert_eq!(firefox.actions[0].max_violations, 2);
+        assert_eq!(firefox.actions[1].max_violations, 5);
+        assert!(!config.rules[2].enabled);
+        assert!(config.validate().is_ok());
+
+        // Explicit rules without the default ones: the section is added
+        let mut config = Config {
+            rules: Vec::new(),
+            brave: Some(LegacyAppConfig::default()),
+            ..Config::default()
+        };
+        assert_eq!(config.migrate_legacy_sections(), vec!["brave"]);
+        assert_eq!(config.rules[0].name, "brave");
+        assert_eq!(config.rules[0].matcher.cmdline.as_deref(), Some("^\\S*brave"));
+    }
+
+    #[test]
+    fn test_rules_deserialization() {
+        let toml_str = r#"
+            [[rules]]
+            name = "vscode"
+
+            [rules.match]
+            cmdline = "^\\S*code"
+            user = "alice"
+
+            [[rules.actions]]
+            cpu_threshold = 150.0
+            max_violations = 3
+            action = "nice"
+            nice_level = 10
+
+            [[rules.actions]]
+            memory_threshold_mb = 4096
+            action = "kill"
+        "#;
+
+        #[derive(Deserialize)]
+        struct Rules {
+            rules: Vec<RuleConfig>,
+        }
+
+        let parsed: Rules = toml::from_str(toml_str).unwrap();
+        let rule = &parsed.rules[0];
+
+        assert!(rule.enabled);
+        assert_eq!(rule.matcher.cmdline.as_deref(), Some("^\\S*code"));
+        assert_eq!(rule.matcher.user.as_deref(), Some("alice"));
+        assert_eq!(rule.actions[0].nice_level, 10);
+        assert_eq!(rule.actions[1].max_violations, 1);
+        assert_eq!(rule.actions[1].freeze_duration_secs, 5);
+        assert!(rule.validate().is_ok());
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let mut config = Config::default();
+        config.rules[0].matcher.cmdline = Some("(".to_string());
+        assert!(config.validate().unwrap_err().contains("regex"));
+
+        let mut config = Config::default();
+        config.rules[1].name = "firefox".to_string();
+        assert!(config.validate().unwrap_err().contains("Duplicate"));
+
+        let mut config = Config::default();
+        config.rules[0].actions[0].action = "explode".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.rules[0].actions[0].action = "throttle".to_string();
+        config.snap.action = "throttle".to_string();
+        config.kesl.action = "throttle".to_string();
+        assert!(config.validate().is_ok());
+
+        config.kesl.action = "kill".to_string();
+        assert!(config.validate().unwrap_err().contains("KESL action"));
+
+        let mut config = Config::default();
+        config.rules[0].matcher = RuleMatcher::default();
+        assert!(config.validate().unwrap_err().contains("match criterion"));
+
+        let mut config = Config::default();
+        let step = &mut config.rules[0].actions[0];
+        step.action = "throttle".to_string();
+        step.cpuset_cpus = Some("0".to_string());
+        assert!(config.validate().is_ok());
+        let limits = config.rules[0].actions[0].limits();
+        assert_eq!(limits.cpuset_cpus.as_deref(), Some("0"));
+
+        config.rules[0].actions[0].cpuset_cpus = Some("3-1".to_string());
+        assert!(config.validate().unwrap_err().contains("Rule 'firefox'"));
+    }
+
+    #[test]
+    fn test_example_config_is_valid() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/examples/config.toml");
+        let config = Config::load_from_file(path).unwrap();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.rules[0].actions[0].action, "throttle");
+        assert_eq!(config.cgroups.dynamic_settings.release_cooldown_secs, 60);
+        let adaptive = &config.cgroups.dynamic_settings.adaptive;
+        assert!(!adaptive.enabled);
+        assert_eq!(adaptive.target_load, 1.0);
+        assert_eq!(
+            config.cgroups.cgroupfs_root,
+            PathBuf::from("/sys/fs/cgroup")
+        );
+
+        assert_eq!(config.spawn_guard.growth_threshold, 100);
+        assert!(!config.cpu_pressure.enabled);
+        assert_eq!(config.io_pressure.some_threshold_critical, 50.0);
+        assert_eq!(config.oom_killer.max_kills, 3);
+        assert_eq!(config.memory_pressure.mem_available.critical_percent, Some(5.0));
+        assert_eq!(config.memory_pressure.swap_free.critical_mb, None);
+        assert_eq!(config.memory_pressure.forecast.horizon_secs, 30);
+        assert_eq!(config.oom_killer.priorities[0].priority, 30);
+        assert!(!config.thermal.enabled);
+        assert!(!config.power.enabled);
+
+        let battery = config.with_profile("battery").unwrap();
+        assert_eq!(battery.kesl.cpu_threshold, 20.0);
+        assert_eq!(battery.memory_pressure.action_warning, "freeze");
+        assert_eq!(battery.memory_pressure.action_critical, config.memory_pressure.action_critical);
+        assert_eq!(battery.rules[0].actions[0].cpu_threshold, Some(30.0));
+        assert_eq!(battery.rules[0].matcher.cmdline, config.rules[0].matcher.cmdline);
+        assert_eq!(battery.rules.len(), config.rules.len());
+
+        let gaming = config.with_profile("gaming").unwrap();
+        assert_eq!(gaming.node.cpu_threshold, 95.0);
+        assert_eq!(gaming.rules[0].actions.len(), 1);
+        let presentation = config.with_profile("presentation").unwrap();
+        assert_eq!(presentation.memory_pressure.action_critical, "throttle");
+        assert_eq!(presentation.rules.len(), config.rules.len() + 1);
+        assert!(presentation
+            .rules
+            .iter()
+            .flat_map(|rule| &rule.actions)
+            .all(|step| step.action != "freeze"));
+        assert!(!config.with_profile("work").unwrap().node.auto_kill);
+        assert_eq!(config.thermal.thresholds("x86_pkg_temp"), Some((90.0, 100.0)));
+
+        let rsync = &config.rules[2].actions[0];
+        assert_eq!(rsync.io_limits()[0].write_bps, Some(20 * 1024 * 1024));
+        assert_eq!(rsync.io_limits()[0].device, "/");
+
+        let kesl = &config.cgroups.static_groups[0];
+        assert!(kesl.matches("kesl"));
+        assert_eq!(kesl.get_limits().cpu_limit_percent, Some(30.0));
+        assert_eq!(kesl.get_limits().memory_max, Some(512 * 1024 * 1024));
+    }
+
     #[test]
     fn test_log_paths() {
         let config = Config::default();
@@ -706,6 +2199,7 @@ mod tests {
         let config: Config = toml::from_str(toml_str).unwrap();
 
         assert_eq!(config.kesl.cpu_threshold, 25.0);
+        assert_eq!(config.kesl.action, "restart");
         assert_eq!(config.node.cpu_threshold, 90.0);
         assert_eq!(config.monitoring.check_interval_secs, 5);
         assert_eq!(config.logging.log_dir, PathBuf::from("/var/log/freezr"));
@@ -0,0 +1,292 @@
+//! OOM time-to-exhaustion forecast
+//!
+//! PSI and MemAvailable floors react once memory is already short. The
+//! forecaster keeps a short history of MemAvailable and PSI `some_total`,
+//! fits a least-squares line to MemAvailable and estimates when it reaches
+//! zero, so the memory pressure actions can run before that happens.
+//!
+//! Confidence is the fit's R², reduced while the history is shorter than
+//! its window, and halved when memory falls without any stall: the kernel
+//! then still reclaims page cache easily, the drop may be a short burst.
+
+use crate::config::OomForecastConfig;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Samples needed before a forecast is made
+const MIN_SAMPLES: usize = 4;
+
+/// Forecast made from the current history
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OomForecast {
+    /// Seconds until MemAvailable runs out, None if it is not falling
+    pub seconds_to_oom: Option<f64>,
+    /// 0-1, see the module docs
+    pub confidence: f64,
+    /// MemAvailable trend in MB/s (negative: falling)
+    pub trend_mb_per_sec: f64,
+    /// Memory 'some' stall over the history in percent, None without PSI
+    pub stall_percent: Option<f64>,
+}
+
+impl OomForecast {
+    /// Memory forecast to run out within `horizon` with enough confidence
+    pub fn within(&self, horizon: Duration, min_confidence: f64) -> bool {
+        self.confidence >= min_confidence
+            && self
+                .seconds_to_oom
+                .is_some_and(|secs| secs <= horizon.as_secs_f64())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    available_kb: u64,
+    some_total: Option<u64>,
+}
+
+/// Short MemAvailable/PSI history with a linear trend
+pub struct OomForecaster {
+    config: OomForecastConfig,
+    samples: VecDeque<Sample>,
+    last: Option<OomForecast>,
+}
+
+impl OomForecaster {
+    pub fn new(config: OomForecastConfig) -> Self {
+        Self {
+            config,
+            samples: VecDeque::new(),
+            last: None,
+        }
+    }
+
+    pub fn config(&self) -> &OomForecastConfig {
+        &self.config
+    }
+
+    /// Forecast of the last [`record`](Self::record)
+    pub fn last(&self) -> Option<OomForecast> {
+        self.last
+    }
+
+    /// Add a sample and forecast from the history
+    ///
+    /// Samples older than `history_secs` are dropped. None until
+    /// there are enough samples for a trend.
+    pub fn record(
+        &mut self,
+        at: Instant,
+        available_kb: u64,
+        some_total: Option<u64>,
+    ) -> Option<OomForecast> {
+        let window = Duration::from_secs(self.config.history_secs);
+        self.samples
+            .retain(|s| at.saturating_duration_since(s.at) <= window);
+        self.samples.push_back(Sample {
+            at,
+            available_kb,
+            some_total,
+        });
+
+        self.last = self.forecast(window);
+        self.last
+    }
+
+    fn forecast(&self, window: Duration) -> Option<OomForecast> {
+        let first = self.samples.front()?;
+        let last = self.samples.back()?;
+        if self.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .map(|s| {
+                let secs = s.at.duration_since(first.at).as_secs_f64();
+                (secs, s.available_kb as f64)
+            })
+            .collect();
+        let (slope, r_squared) = linear_fit(&points)?;
+
+        let span = last.at.duration_since(first.at);
+        let coverage = (span.as_secs_f64() / window.as_secs_f64()).min(1.0);
+
+        // 'some' is cumulative stall time in microseconds
+        let stall_percent = match (first.some_total, last.some_total) {
+            (Some(before), Some(after)) if !span.is_zero() => {
+                let stalled = after.saturating_sub(before) as f64;
+                Some((stalled / span.as_micros() as f64 * 100.0).min(100.0))
+            }
+            _ => None,
+        };
+        let stall_factor = if stall_percent == Some(0.0) { 0.5 } else { 1.0 };
+
+        let seconds_to_oom = (slope < 0.0).then(|| last.available_kb as f64 / -slope);
+
+        Some(OomForecast {
+            seconds_to_oom,
+            confidence: r_squared * coverage * stall_factor,
+            trend_mb_per_sec: slope / 1024.0,
+            stall_percent,
+        })
+    }
+}
+
+/// Least-squares slope and R² of (x, y) points
+///
+/// None if all x are equal. A perfectly flat line has R² 0: there is no
+/// trend to be confident in.
+fn linear_fit(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in points {
+        sxx += (x - mean_x) * (x - mean_x);
+        sxy += (x - mean_x) * (y - mean_y);
+        syy += (y - mean_y) * (y - mean_y);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let r_squared = if syy == 0.0 {
+        0.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    Some((slope, r_squared))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024;
+
+    fn forecaster() -> OomForecaster {
+        OomForecaster::new(OomForecastConfig {
+            history_secs: 60,
+            ..OomForecastConfig::default()
+        })
+    }
+
+    /// Record `count` samples every `step` seconds starting at `start`
+    fn feed(
+        forecaster: &mut OomForecaster,
+        start: Instant,
+        count: u64,
+        step: u64,
+        available: impl Fn(u64) -> u64,
+        stall_us_per_sec: u64,
+    ) -> Option<OomForecast> {
+        let mut forecast = None;
+        for i in 0..count {
+            let secs = i * step;
+            forecast = forecaster.record(
+                start + Duration::from_secs(secs),
+                available(secs),
+                Some(secs * stall_us_per_sec),
+            );
+        }
+        forecast
+    }
+
+    #[test]
+    fn test_linear_fit() {
+        let (slope, r2) = linear_fit(&[(0.0, 10.0), (1.0, 8.0), (2.0, 6.0)]).unwrap();
+        assert!((slope + 2.0).abs() < 1e-9);
+        assert!((r2 - 1.0).abs() < 1e-9);
+
+        assert_eq!(linear_fit(&[(1.0, 1.0), (1.0, 2.0)]), None);
+        assert_eq!(linear_fit(&[(0.0, 5.0), (1.0, 5.0)]), Some((0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_steady_decline_forecasts_oom() {
+        let mut forecaster = forecaster();
+        // 2000MB falling by 20MB/s with 5% stall: 1000MB left after 50s
+        let forecast = feed(
+            &mut forecaster,
+            Instant::now(),
+            11,
+            5,
+            |secs| 2000 * MB - secs * 20 * MB,
+            50_000,
+        )
+        .unwrap();
+
+        let secs = forecast.seconds_to_oom.unwrap();
+        assert!((secs - 50.0).abs() < 0.01, "{}", secs);
+        assert!((forecast.trend_mb_per_sec + 20.0).abs() < 0.01);
+        assert!((forecast.stall_percent.unwrap() - 5.0).abs() < 0.01);
+        assert!(forecast.confidence > 0.8);
+
+        assert!(forecast.within(Duration::from_secs(60), 0.5));
+        assert!(!forecast.within(Duration::from_secs(30), 0.5));
+        assert_eq!(forecaster.last(), Some(forecast));
+    }
+
+    #[test]
+    fn test_needs_samples_and_falling_memory() {
+        let mut forecaster = forecaster();
+        let start = Instant::now();
+        assert_eq!(feed(&mut forecaster, start, 3, 5, |_| 1000 * MB, 0), None);
+
+        // Growing memory: no exhaustion
+        let mut forecaster = OomForecaster::new(OomForecastConfig::default());
+        let forecast = feed(
+            &mut forecaster,
+            start,
+            8,
+            5,
+            |secs| 1000 * MB + secs * MB,
+            1000,
+        )
+        .unwrap();
+        assert_eq!(forecast.seconds_to_oom, None);
+        assert!(!forecast.within(Duration::from_secs(3600), 0.0));
+    }
+
+    #[test]
+    fn test_confidence_drops_without_stall_and_short_history() {
+        let start = Instant::now();
+        let falling = |secs| 2000 * MB - secs * 20 * MB;
+
+        let mut stalled = forecaster();
+        let with_stall = feed(&mut stalled, start, 13, 5, falling, 10_000).unwrap();
+        let mut calm = forecaster();
+        let without_stall = feed(&mut calm, start, 13, 5, falling, 0).unwrap();
+        assert!((without_stall.confidence - with_stall.confidence / 2.0).abs() < 1e-9);
+
+        // 15s of a 60s window
+        let mut short = forecaster();
+        let partial = feed(&mut short, start, 4, 5, falling, 10_000).unwrap();
+        assert!((partial.confidence - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_old_samples_are_dropped() {
+        let mut forecaster = forecaster();
+        let start = Instant::now();
+        // Falling for a minute, then flat for a minute
+        feed(
+            &mut forecaster,
+            start,
+            13,
+            5,
+            |secs| 3000 * MB - secs * 20 * MB,
+            1000,
+        );
+        let later = start + Duration::from_secs(65);
+        let forecast = feed(&mut forecaster, later, 13, 5, |_| 1800 * MB, 1000).unwrap();
+
+        assert_eq!(forecast.seconds_to_oom, None);
+        assert_eq!(forecast.confidence, 0.0);
+    }
+}
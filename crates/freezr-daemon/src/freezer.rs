@@ -0,0 +1,613 @@
+//! Freeze scheduler
+//!
+//! Freezing a process registers a deadline; a tokio timer sends SIGCONT when
+//! it expires, so the monitoring loop never sleeps while processes are frozen.
+//! Freezing a process that is already frozen only extends its deadline.
+//!
+//! With a [`CgroupManager`] the whole process tree is frozen through
+//! `cgroup.freeze` (children forked during the freeze are frozen too);
+//! without cgroup v2 the process alone gets SIGSTOP.
+//!
+//! With a [`FrozenRegistry`] every frozen process is also journaled to disk,
+//! so a crashed daemon can thaw it on the next start.
+
+use chrono::Utc;
+use freezr_core::{
+    error::{Error, Result},
+    executor::ProcessExecutor,
+    CgroupManager, FrozenEntry, FrozenRegistry, FrozenTree, ProcessHandle,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::{sleep_until, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Result of a freeze request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreezeOutcome {
+    /// Process was running and is now frozen
+    Frozen,
+    /// Process was already frozen, deadline moved later
+    Extended,
+    /// Process was already frozen until a later deadline
+    AlreadyFrozen,
+}
+
+/// Maximum wait for `cgroup.events` to confirm a freeze or thaw
+const CGROUP_FREEZE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How a process was frozen
+enum FreezeMethod {
+    /// SIGSTOP to the process alone
+    Signal,
+    /// Whole tree moved to a dynamic cgroup with `cgroup.freeze`
+    Cgroup(Box<FrozenTree>),
+}
+
+/// Process frozen by the scheduler
+struct FrozenProcess {
+    handle: ProcessHandle,
+    method: FreezeMethod,
+    deadline: Instant,
+    journal: FrozenEntry,
+}
+
+impl FrozenProcess {
+    /// Is `pid` frozen by this entry? (the process itself or a tree member)
+    fn covers(&self, pid: u32) -> bool {
+        match &self.method {
+            FreezeMethod::Signal => false,
+            // Live membership: includes children forked during the freeze
+            FreezeMethod::Cgroup(tree) => tree
+                .cgroup
+                .get_processes()
+                .map(|pids| pids.contains(&pid))
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    frozen: Mutex<HashMap<u32, FrozenProcess>>,
+    /// Notified every time a process is unfrozen
+    released: Notify,
+}
+
+/// Schedules unfreeze of frozen processes
+///
+/// Cheap to clone: all clones share the same set of frozen processes.
+#[derive(Clone, Default)]
+pub struct FreezeScheduler {
+    inner: Arc<Inner>,
+    /// On-disk journal of frozen processes (None = not persisted)
+    registry: Option<FrozenRegistry>,
+    /// Cgroup manager for tree freezing (None = SIGSTOP only)
+    cgroups: Option<Arc<Mutex<CgroupManager>>>,
+}
+
+impl FreezeScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Journal frozen processes to `registry`
+    pub fn with_registry(mut self, registry: FrozenRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Freeze process trees through `cgroup.freeze` of dynamic cgroups
+    pub fn with_cgroups(mut self, manager: Arc<Mutex<CgroupManager>>) -> Self {
+        self.cgroups = Some(manager);
+        self
+    }
+
+    /// Registry used for crash recovery, if any
+    pub fn registry(&self) -> Option<&FrozenRegistry> {
+        self.registry.as_ref()
+    }
+
+    /// Freeze a process for `duration`
+    ///
+    /// Must be called from within a tokio runtime: the unfreeze timer is a
+    /// spawned task.
+    ///
+    /// # Arguments
+    /// * `handle` - Process to freeze
+    /// * `name` - Process name for the journal
+    /// * `duration` - How long the process stays frozen
+    pub fn freeze(
+        &self,
+        handle: ProcessHandle,
+        name: &str,
+        duration: Duration,
+    ) -> Result<FreezeOutcome> {
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+            Error::Executor("Freeze scheduler requires a tokio runtime".to_string())
+        })?;
+
+        let pid = handle.pid();
+        let deadline = Instant::now() + duration;
+        let mut frozen = self.lock();
+
+        // Same process frozen again, or a member of an already frozen tree
+        let existing = frozen.iter_mut().find_map(|(&root, entry)| {
+            let same = root == pid
+                && entry.handle.start_ticks() == handle.start_ticks()
+                && entry.handle.is_alive();
+            (same || entry.covers(pid)).then_some(entry)
+        });
+        if let Some(entry) = existing {
+            if deadline <= entry.deadline {
+                return Ok(FreezeOutcome::AlreadyFrozen);
+            }
+            debug!("Extending freeze of process {} by {:?}", pid, duration);
+            entry.deadline = deadline;
+            entry.journal.thaw_at = thaw_at(duration);
+            self.persist(&frozen);
+            return Ok(FreezeOutcome::Extended);
+        }
+
+        let start_ticks = handle.start_ticks();
+        let mut journal = FrozenEntry {
+            pid,
+            start_ticks,
+            name: name.to_string(),
+            frozen_at: Utc::now(),
+            thaw_at: thaw_at(duration),
+            cgroup: None,
+        };
+
+        // Journal before SIGSTOP: a crash in between leaves at most a stale
+        // entry for a running process, never an unjournaled frozen one
+        if let Some(registry) = &self.registry {
+            if let Err(e) = registry.record(journal.clone()) {
+                warn!("Failed to journal freeze of process {}: {}", pid, e);
+            }
+        }
+
+        // The cgroup freeze waits up to CGROUP_FREEZE_TIMEOUT for the kernel:
+        // other callers and the unfreeze timers must not wait on the lock
+        drop(frozen);
+        let method = match self.freeze_tree(pid) {
+            Some(tree) => {
+                journal.cgroup = Some(tree.cgroup.path.clone());
+                FreezeMethod::Cgroup(Box::new(tree))
+            }
+            None => match ProcessExecutor::freeze_process(&handle) {
+                Ok(()) => FreezeMethod::Signal,
+                Err(e) => {
+                    self.persist(&self.lock());
+                    return Err(e);
+                }
+            },
+        };
+        let mut frozen = self.lock();
+
+        // Frozen by another caller in the meantime: keep its entry and timer,
+        // undo this freeze
+        if let Some(entry) = frozen
+            .get_mut(&pid)
+            .filter(|entry| entry.handle.start_ticks() == start_ticks)
+        {
+            if deadline > entry.deadline {
+                entry.deadline = deadline;
+                entry.journal.thaw_at = journal.thaw_at;
+            }
+            // SIGCONT would also end a SIGSTOP of the kept entry
+            let redundant = match (&entry.method, method) {
+                (FreezeMethod::Signal, FreezeMethod::Signal) => None,
+                (_, method) => Some(FrozenProcess {
+                    handle,
+                    method,
+                    deadline,
+                    journal,
+                }),
+            };
+            self.persist(&frozen);
+            drop(frozen);
+
+            if let Some(redundant) = redundant {
+                debug!(
+                    "Process {} frozen concurrently, releasing second freeze",
+                    pid
+                );
+                self.thaw(&redundant);
+            }
+            return Ok(FreezeOutcome::Extended);
+        }
+
+        // A stale entry (process exited, PID reused) is replaced; its timer
+        // notices the different start time and exits
+        frozen.insert(
+            pid,
+            FrozenProcess {
+                handle,
+                method,
+                deadline,
+                journal,
+            },
+        );
+        self.persist(&frozen);
+        drop(frozen);
+
+        runtime.spawn(self.clone().unfreeze_at(pid, start_ticks, deadline));
+
+        Ok(FreezeOutcome::Frozen)
+    }
+
+    /// Number of processes currently frozen
+    pub fn frozen_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Is this PID frozen by the scheduler?
+    pub fn is_frozen(&self, pid: u32) -> bool {
+        self.lock().contains_key(&pid)
+    }
+
+    /// Unfreeze everything immediately (e.g. on shutdown)
+    pub fn thaw_all(&self) -> usize {
+        let drained: Vec<FrozenProcess> = self.lock().drain().map(|(_, p)| p).collect();
+        let count = drained.iter().filter(|p| self.thaw(p)).count();
+        self.persist(&self.lock());
+
+        self.inner.released.notify_waiters();
+        count
+    }
+
+    /// Wait until every scheduled unfreeze has happened
+    pub async fn wait_idle(&self) {
+        loop {
+            let released = self.inner.released.notified();
+            tokio::pin!(released);
+            // Register before checking so a release in between is not lost
+            released.as_mut().enable();
+
+            if self.lock().is_empty() {
+                return;
+            }
+            released.await;
+        }
+    }
+
+    /// Timer task: sleep until the deadline, follow extensions, then unfreeze
+    async fn unfreeze_at(self, pid: u32, start_ticks: u64, mut deadline: Instant) {
+        loop {
+            sleep_until(deadline).await;
+
+            let entry = {
+                let mut frozen = self.lock();
+                match frozen.get(&pid) {
+                    Some(entry) if entry.handle.start_ticks() == start_ticks => {
+                        if entry.deadline > deadline {
+                            deadline = entry.deadline;
+                            continue;
+                        }
+                    }
+                    // Already thawed, or replaced by a newer freeze of a reused PID
+                    _ => return,
+                }
+                frozen.remove(&pid)
+            };
+            let Some(entry) = entry else {
+                return;
+            };
+
+            // A cgroup thaw blocks until the kernel confirms it: off the
+            // runtime workers and without the lock
+            let scheduler = self.clone();
+            let thawed = tokio::task::spawn_blocking(move || scheduler.thaw(&entry))
+                .await
+                .unwrap_or(false);
+            if thawed {
+                info!("Process {} unfrozen", pid);
+            }
+
+            self.persist(&self.lock());
+            self.inner.released.notify_waiters();
+            return;
+        }
+    }
+
+    /// Freeze the tree of `pid` through a dynamic cgroup
+    ///
+    /// None if cgroups are not configured or the cgroup freeze failed
+    /// (the caller falls back to SIGSTOP).
+    fn freeze_tree(&self, pid: u32) -> Option<FrozenTree> {
+        let manager = self.cgroups.as_ref()?;
+        let mut manager = manager.lock().unwrap_or_else(|p| p.into_inner());
+
+        match manager.freeze_tree(pid, CGROUP_FREEZE_TIMEOUT) {
+            Ok(tree) => {
+                debug!(
+                    "Froze tree of process {} via {}",
+                    pid,
+                    tree.cgroup.path.display()
+                );
+                Some(tree)
+            }
+            Err(e) => {
+                warn!(
+                    "cgroup freeze of process {} failed, using SIGSTOP: {}",
+                    pid, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Unfreeze one entry; a process that exited while frozen is not an error
+    fn thaw(&self, process: &FrozenProcess) -> bool {
+        let FreezeMethod::Cgroup(tree) = &process.method else {
+            return thaw_signal(&process.handle);
+        };
+
+        let Some(manager) = &self.cgroups else {
+            return false;
+        };
+        let mut manager = manager.lock().unwrap_or_else(|p| p.into_inner());
+
+        match manager.thaw_tree(tree, CGROUP_FREEZE_TIMEOUT) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Failed to thaw process tree of {}: {}", tree.root_pid, e);
+                false
+            }
+        }
+    }
+
+    /// Rewrite the journal from the in-memory set (called with the lock held)
+    fn persist(&self, frozen: &HashMap<u32, FrozenProcess>) {
+        let Some(registry) = &self.registry else {
+            return;
+        };
+
+        let entries: Vec<FrozenEntry> = frozen.values().map(|p| p.journal.clone()).collect();
+        if let Err(e) = registry.save(&entries) {
+            warn!(
+                "Failed to update frozen registry {}: {}",
+                registry.path().display(),
+                e
+            );
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<u32, FrozenProcess>> {
+        self.inner
+            .frozen
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Wall-clock time of a planned unfreeze (for the journal)
+fn thaw_at(duration: Duration) -> chrono::DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)
+}
+
+/// Send SIGCONT; a process that exited while frozen is not an error
+fn thaw_signal(handle: &ProcessHandle) -> bool {
+    if !handle.is_alive() {
+        debug!("Process {} exited while frozen", handle.pid());
+        return false;
+    }
+
+    match ProcessExecutor::unfreeze_process(handle) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Failed to unfreeze process {}: {}", handle.pid(), e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freezr_core::{CgroupConfig, FakeCgroupFs};
+    use std::fs;
+    use std::process::{Child, Command, Stdio};
+
+    fn spawn_sleep() -> Child {
+        Command::new("sleep")
+            .arg("60")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to spawn test process")
+    }
+
+    /// Wait until the process is stopped (state T) or running
+    ///
+    /// Signal delivery is asynchronous, so poll briefly for the expected state.
+    fn reaches_state(pid: u32, stopped: bool) -> bool {
+        for _ in 0..50 {
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+            let state = stat[stat.rfind(')').unwrap() + 2..].chars().next().unwrap();
+            if (state == 'T') == stopped {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn test_freeze_requires_runtime() {
+        let scheduler = FreezeScheduler::new();
+        let handle = ProcessHandle::open(std::process::id()).unwrap();
+
+        assert!(scheduler
+            .freeze(handle, "sleep", Duration::from_secs(1))
+            .is_err());
+        assert_eq!(scheduler.frozen_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_freeze_does_not_block_and_unfreezes() {
+        let mut child = spawn_sleep();
+        let scheduler = FreezeScheduler::new();
+
+        let started = std::time::Instant::now();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        let outcome = scheduler
+            .freeze(handle, "sleep", Duration::from_millis(200))
+            .unwrap();
+
+        assert_eq!(outcome, FreezeOutcome::Frozen);
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert!(scheduler.is_frozen(child.id()));
+        assert!(reaches_state(child.id(), true));
+
+        scheduler.wait_idle().await;
+        assert_eq!(scheduler.frozen_count(), 0);
+        assert!(reaches_state(child.id(), false));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_freeze_extends_deadline() {
+        let mut child = spawn_sleep();
+        let scheduler = FreezeScheduler::new();
+
+        let first = ProcessHandle::open(child.id()).unwrap();
+        scheduler
+            .freeze(first, "sleep", Duration::from_millis(100))
+            .unwrap();
+
+        let second = ProcessHandle::open(child.id()).unwrap();
+        let outcome = scheduler
+            .freeze(second, "sleep", Duration::from_millis(400))
+            .unwrap();
+        assert_eq!(outcome, FreezeOutcome::Extended);
+        assert_eq!(scheduler.frozen_count(), 1);
+
+        let shorter = ProcessHandle::open(child.id()).unwrap();
+        let outcome = scheduler
+            .freeze(shorter, "sleep", Duration::from_millis(50))
+            .unwrap();
+        assert_eq!(outcome, FreezeOutcome::AlreadyFrozen);
+
+        // Past the first deadline: still frozen
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(reaches_state(child.id(), true));
+
+        scheduler.wait_idle().await;
+        assert!(reaches_state(child.id(), false));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_freeze_keeps_one() {
+        let fake = FakeCgroupFs::new("/fake-cgroupfs/freezer-concurrent");
+        let _mount = fake.mount();
+        let config = CgroupConfig {
+            enabled: true,
+            cgroupfs_root: fake.root().to_path_buf(),
+            root_path: fake.root().join("freezr.slice"),
+            journal_path: None,
+            ..CgroupConfig::default()
+        };
+        let mut manager = CgroupManager::new(config).unwrap();
+        manager.initialize().unwrap();
+        let manager = Arc::new(Mutex::new(manager));
+        let scheduler = FreezeScheduler::new().with_cgroups(manager.clone());
+        let mut child = spawn_sleep();
+        let pid = child.id();
+
+        // Both calls find nothing frozen, then wait for the cgroup manager:
+        // one freezes the tree, the other falls back to SIGSTOP
+        let runtime = tokio::runtime::Handle::current();
+        let mut outcomes = std::thread::scope(|scope| {
+            let busy = manager.lock().unwrap();
+            let calls: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let _runtime = runtime.enter();
+                        let handle = ProcessHandle::open(pid).unwrap();
+                        scheduler.freeze(handle, "sleep", Duration::from_secs(60))
+                    })
+                })
+                .collect();
+            std::thread::sleep(Duration::from_millis(100));
+            drop(busy);
+            calls
+                .into_iter()
+                .map(|call| call.join().unwrap().unwrap())
+                .collect::<Vec<_>>()
+        });
+        outcomes.sort_by_key(|outcome| *outcome != FreezeOutcome::Frozen);
+        assert_eq!(outcomes, [FreezeOutcome::Frozen, FreezeOutcome::Extended]);
+        assert_eq!(scheduler.frozen_count(), 1);
+
+        // Only the kept freeze holds the process
+        let freeze_cgroup = || {
+            fake.cgroups()
+                .iter()
+                .any(|path| path.ends_with(format!("freeze-{}", pid)))
+        };
+        let by_cgroup = matches!(scheduler.lock()[&pid].method, FreezeMethod::Cgroup(_));
+        assert_eq!(freeze_cgroup(), by_cgroup);
+        assert!(reaches_state(pid, !by_cgroup));
+
+        assert_eq!(scheduler.thaw_all(), 1);
+        assert!(!freeze_cgroup());
+        assert!(reaches_state(pid, false));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_registry_journals_frozen_processes() {
+        let dir = std::env::temp_dir().join(format!("freezr-freezer-{}", std::process::id()));
+        let registry = FrozenRegistry::new(dir.join("frozen.json"));
+        let mut child = spawn_sleep();
+        let scheduler = FreezeScheduler::new().with_registry(registry.clone());
+
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        scheduler
+            .freeze(handle, "sleep", Duration::from_millis(200))
+            .unwrap();
+
+        let journal = registry.load().unwrap();
+        assert_eq!(journal.len(), 1);
+        assert_eq!(journal[0].pid, child.id());
+        assert_eq!(journal[0].name, "sleep");
+        assert!(journal[0].thaw_at > journal[0].frozen_at);
+
+        scheduler.wait_idle().await;
+        assert!(registry.load().unwrap().is_empty());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_thaw_all() {
+        let mut child = spawn_sleep();
+        let scheduler = FreezeScheduler::new();
+
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        scheduler
+            .freeze(handle, "sleep", Duration::from_secs(60))
+            .unwrap();
+
+        assert_eq!(scheduler.thaw_all(), 1);
+        assert_eq!(scheduler.frozen_count(), 0);
+        assert!(reaches_state(child.id(), false));
+        scheduler.wait_idle().await;
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
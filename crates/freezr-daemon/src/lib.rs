@@ -2,10 +2,30 @@
 //!
 //! System daemon for FreezR - prevents system freezes by managing runaway processes.
 
+pub mod cgroup_events;
 pub mod config;
+pub mod forecast;
+pub mod freezer;
 pub mod monitor;
+pub mod pressure;
+pub mod profile;
+pub mod rules;
+pub mod spawn_guard;
 pub mod stats;
+pub mod thermal;
+pub mod throttle;
+pub mod victim;
 
+pub use cgroup_events::CgroupEventMonitor;
 pub use config::Config;
+pub use forecast::OomForecaster;
+pub use freezer::FreezeScheduler;
 pub use monitor::ResourceMonitor;
+pub use pressure::StallMonitor;
+pub use profile::PolicyProfiles;
+pub use rules::RuleEngine;
+pub use spawn_guard::SpawnGuard;
 pub use stats::MonitorStats;
+pub use thermal::ThermalMonitor;
+pub use throttle::Throttler;
+pub use victim::VictimSelector;
@@ -1,10 +1,11 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use freezr_core::VERSION;
+use freezr_daemon::pressure::{self, PressureTrigger};
 use freezr_daemon::{Config, ResourceMonitor};
 use std::path::PathBuf;
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::time::{interval, MissedTickBehavior};
 use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -83,7 +84,7 @@ async fn main() -> Result<()> {
 async fn run_monitor_once(config: Config) -> Result<()> {
     info!("Running single monitoring check...");
 
-    let mut monitor = create_monitor(&config);
+    let mut monitor = ResourceMonitor::from_config(&config)?;
 
     // Perform single check
     monitor.check()?;
@@ -105,6 +106,15 @@ async fn run_monitor_once(config: Config) -> Result<()> {
     info!("Total restarts: {}", stats.total_restarts);
     info!("Total kills: {}", stats.total_kills);
 
+    // Don't exit while processes frozen by this check are still stopped
+    if monitor.freezer().frozen_count() > 0 {
+        info!(
+            "Waiting for {} frozen processes to be unfrozen...",
+            monitor.freezer().frozen_count()
+        );
+        monitor.freezer().wait_idle().await;
+    }
+
     info!("Single check completed");
     Ok(())
 }
@@ -124,27 +134,37 @@ async fn run_watch_loop(config: Config) -> Result<()> {
         );
     }
 
-    let mut monitor = create_monitor(&config);
+    let mut monitor = ResourceMonitor::from_config(&config)?;
+    let memory_trigger = PressureTrigger::memory(&config.memory_pressure);
 
+    // Trigger wake-ups must not postpone the regular checks
     let check_interval = Duration::from_secs(config.monitoring.check_interval_secs);
+    let mut check_timer = interval(check_interval);
+    check_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
     loop {
-        // Perform monitoring check
-        if let Err(e) = monitor.check() {
-            error!("Monitoring check failed: {}", e);
+        tokio::select! {
+            _ = pressure::triggered(memory_trigger.as_ref()) => {
+                if let Some(trigger) = &memory_trigger {
+                    monitor.on_memory_pressure_trigger(trigger.floor_percent());
+                }
+            }
+            _ = check_timer.tick() => {
+                // Perform monitoring check
+                if let Err(e) = monitor.check() {
+                    error!("Monitoring check failed: {}", e);
+                }
+
+                // Display current status
+                let stats = monitor.stats();
+                let (cpu_violations, mem_violations) = monitor.violations();
+
+                info!(
+                    "Stats: checks={}, violations={}/{}, restarts={}, kills={}",
+                    stats.total_checks, cpu_violations, mem_violations, stats.total_restarts, stats.total_kills
+                );
+            }
         }
-
-        // Display current status
-        let stats = monitor.stats();
-        let (cpu_violations, mem_violations) = monitor.violations();
-
-        info!(
-            "Stats: checks={}, violations={}/{}, restarts={}, kills={}",
-            stats.total_checks, cpu_violations, mem_violations, stats.total_restarts, stats.total_kills
-        );
-
-        // Sleep until next check
-        sleep(check_interval).await;
     }
 }
 
@@ -182,24 +202,6 @@ fn generate_config(output: PathBuf) -> Result<()> {
     Ok(())
 }
 
-/// Create ResourceMonitor from configuration
-fn create_monitor(config: &Config) -> ResourceMonitor {
-    let mut monitor = ResourceMonitor::new(
-        &config.kesl.service_name,
-        config.kesl.cpu_threshold,
-        config.kesl.memory_threshold_mb,
-        config.kesl.max_violations,
-        config.monitoring.min_restart_interval_secs,
-    );
-
-    // Enable Node.js monitoring if configured
-    if config.node.enabled {
-        monitor.enable_node_monitoring(config.node.cpu_threshold, config.node.auto_kill);
-    }
-
-    monitor
-}
-
 /// Load configuration from file or use defaults
 fn load_config(path: &PathBuf) -> Result<Config> {
     if path.exists() {
@@ -2,25 +2,47 @@ use freezr_core::{
     error::{Error, Result},
     executor::ProcessExecutor,
     memory_pressure::MemoryPressure,
-    scanner::ProcessScanner,
+    scanner::{ProcessScan, ProcessScanner},
     systemd::SystemdService,
-    types::MonitorStats,
-    CgroupManager,
+    types::{MonitorStats, ProcessInfo},
+    CgroupManager, CpuSmoothing, EventDetails, EventType, FrozenRegistry, MemInfo,
+    MemoryController, PressureStall, ProcessEvent, ProfileRequests, PsiResource,
 };
+use crate::cgroup_events::CgroupEventMonitor;
+use crate::config::{
+    Config, MemoryFloor, OomForecastConfig, OomKillerConfig, PressureStallConfig, RuleConfig,
+    SpawnGuardConfig, ThermalConfig,
+};
+use crate::forecast::{OomForecast, OomForecaster};
+use crate::freezer::FreezeScheduler;
+use crate::pressure::{low_memory_level, StallLevel, StallMonitor};
+use crate::profile::{PolicyProfiles, ProfileSwitch, SwitchReason};
+use crate::rules::RuleEngine;
+use crate::spawn_guard::SpawnGuard;
+use crate::thermal::ThermalMonitor;
+use crate::throttle::Throttler;
+use crate::victim::VictimSelector;
 use tracing::{debug, error, info, warn};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+/// Profile switches kept for [`ResourceMonitor::profile_events`]
+const MAX_PROFILE_EVENTS: usize = 100;
+
 /// Resource monitor with violation tracking
 ///
 /// Monitors KESL and Node.js processes, tracks resource violations,
 /// and executes actions (restart service, kill processes) when limits are exceeded.
 pub struct ResourceMonitor {
     scanner: ProcessScanner,
+    /// Processes of the last check: CPU is sampled once per check
+    processes: ProcessScan,
     kesl_service: SystemdService,
     stats: MonitorStats,
 
-    // Cgroup integration (optional)
-    cgroup_manager: Option<CgroupManager>,
+    // Cgroup integration (optional, shared with the freeze scheduler)
+    cgroup_manager: Option<Arc<Mutex<CgroupManager>>>,
 
     // Violation counters
     cpu_violations: u32,
@@ -31,6 +53,9 @@ pub struct ResourceMonitor {
     memory_threshold_mb: u64,
     max_violations: u32,
 
+    // Action on max violations: "restart" or "throttle"
+    kesl_action: String,
+
     // Minimum restart interval (used by SystemdService internally)
     #[allow(dead_code)]
     min_restart_interval_secs: u64,
@@ -49,35 +74,23 @@ pub struct ResourceMonitor {
     snap_violations: u32,
     snap_max_violations: u32,
 
-    // Firefox monitoring (two-tier strategy)
-    firefox_enabled: bool,
-    firefox_cpu_threshold_freeze: f64,
-    firefox_cpu_threshold_kill: f64,
-    firefox_freeze_duration_secs: u64,
-    firefox_violations_freeze: u32,
-    firefox_violations_kill: u32,
-    firefox_max_violations_freeze: u32,
-    firefox_max_violations_kill: u32,
-
-    // Brave monitoring (two-tier strategy)
-    brave_enabled: bool,
-    brave_cpu_threshold_freeze: f64,
-    brave_cpu_threshold_kill: f64,
-    brave_freeze_duration_secs: u64,
-    brave_violations_freeze: u32,
-    brave_violations_kill: u32,
-    brave_max_violations_freeze: u32,
-    brave_max_violations_kill: u32,
-
-    // Telegram monitoring (two-tier strategy)
-    telegram_enabled: bool,
-    telegram_cpu_threshold_freeze: f64,
-    telegram_cpu_threshold_kill: f64,
-    telegram_freeze_duration_secs: u64,
-    telegram_violations_freeze: u32,
-    telegram_violations_kill: u32,
-    telegram_max_violations_freeze: u32,
-    telegram_max_violations_kill: u32,
+    // Declarative per-application rules
+    rule_engine: RuleEngine,
+
+    // Unfreezes frozen processes on a timer instead of blocking the loop
+    freezer: FreezeScheduler,
+
+    // Holds offenders in throttle cgroups until they behave
+    throttler: Throttler,
+
+    // Caps tasks of fast-growing process subtrees (None = disabled)
+    spawn_guard: Option<SpawnGuard>,
+
+    // Reacts to memory.events/cgroup.events of managed cgroups (None = no cgroups)
+    cgroup_events: Option<CgroupEventMonitor>,
+
+    // Ranks processes for the "kill" pressure action
+    victim_selector: VictimSelector,
 
     // Memory pressure monitoring (PSI - Pressure Stall Information)
     memory_pressure_enabled: bool,
@@ -91,6 +104,19 @@ pub struct ResourceMonitor {
     memory_pressure_last_check: Instant,
     memory_pressure_warning_count: u32,
     memory_pressure_critical_count: u32,
+    memory_available_floor: MemoryFloor,
+    swap_free_floor: MemoryFloor,
+    oom_forecaster: Option<OomForecaster>,
+
+    // CPU and I/O stall monitoring (PSI)
+    stall_monitors: Vec<StallMonitor>,
+
+    // Thermal zone and hwmon monitoring
+    thermal_monitor: Option<ThermalMonitor>,
+
+    // Profile switching by request and power source
+    profiles: Option<PolicyProfiles>,
+    profile_events: Vec<ProcessEvent>,
 }
 
 impl ResourceMonitor {
@@ -111,6 +137,7 @@ impl ResourceMonitor {
     ) -> Self {
         Self {
             scanner: ProcessScanner::new(),
+            processes: ProcessScan::default(),
             kesl_service: SystemdService::new(service_name),
             stats: MonitorStats::new(),
             cgroup_manager: None,  // Initialized later if enabled
@@ -121,6 +148,7 @@ impl ResourceMonitor {
             cpu_threshold,
             memory_threshold_mb,
             max_violations,
+            kesl_action: "restart".to_string(),
             min_restart_interval_secs,
 
             node_enabled: false,
@@ -135,32 +163,13 @@ impl ResourceMonitor {
             snap_violations: 0,
             snap_max_violations: 3,
 
-            firefox_enabled: false,
-            firefox_cpu_threshold_freeze: 80.0,
-            firefox_cpu_threshold_kill: 95.0,
-            firefox_freeze_duration_secs: 5,
-            firefox_violations_freeze: 0,
-            firefox_violations_kill: 0,
-            firefox_max_violations_freeze: 2,
-            firefox_max_violations_kill: 3,
-
-            brave_enabled: false,
-            brave_cpu_threshold_freeze: 80.0,
-            brave_cpu_threshold_kill: 95.0,
-            brave_freeze_duration_secs: 5,
-            brave_violations_freeze: 0,
-            brave_violations_kill: 0,
-            brave_max_violations_freeze: 2,
-            brave_max_violations_kill: 3,
-
-            telegram_enabled: false,
-            telegram_cpu_threshold_freeze: 80.0,
-            telegram_cpu_threshold_kill: 95.0,
-            telegram_freeze_duration_secs: 5,
-            telegram_violations_freeze: 0,
-            telegram_violations_kill: 0,
-            telegram_max_violations_freeze: 2,
-            telegram_max_violations_kill: 3,
+            rule_engine: RuleEngine::default(),
+
+            freezer: FreezeScheduler::new(),
+            throttler: Throttler::new(),
+            spawn_guard: None,
+            cgroup_events: None,
+            victim_selector: VictimSelector::default(),
 
             memory_pressure_enabled: false,
             memory_pressure_some_threshold_warning: 10.0,
@@ -173,7 +182,56 @@ impl ResourceMonitor {
             memory_pressure_last_check: Instant::now(),
             memory_pressure_warning_count: 0,
             memory_pressure_critical_count: 0,
+            memory_available_floor: MemoryFloor::default(),
+            swap_free_floor: MemoryFloor::default(),
+            oom_forecaster: None,
+
+            stall_monitors: Vec::new(),
+
+            thermal_monitor: None,
+
+            profiles: None,
+            profile_events: Vec::new(),
+        }
+    }
+
+    /// Create a monitor set up from `config`, as the daemons start it
+    ///
+    /// Thaws processes left frozen by a previous run, initializes cgroups
+    /// (freezing falls back to SIGSTOP without cgroup v2), applies the
+    /// policy sections and enables the `[profiles]`.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut monitor = Self::new(
+            &config.kesl.service_name,
+            config.kesl.cpu_threshold,
+            config.kesl.memory_threshold_mb,
+            config.kesl.max_violations,
+            config.monitoring.min_restart_interval_secs,
+        );
+
+        monitor.set_cpu_smoothing(config.monitoring.cpu_smoothing);
+
+        let registry = FrozenRegistry::new(&config.monitoring.frozen_registry_path);
+        match monitor.enable_frozen_registry(registry) {
+            Ok(0) => {}
+            Ok(thawed) => warn!("Thawed {} processes left frozen by a previous run", thawed),
+            Err(e) => error!("Failed to recover frozen processes: {}", e),
+        }
+
+        if let Err(e) = monitor.initialize_cgroups(config.cgroups.clone()) {
+            error!("Cgroup integration unavailable, freezing with SIGSTOP: {}", e);
         }
+
+        monitor.apply_policy(config)?;
+
+        if !config.profiles.is_empty() {
+            monitor.enable_profiles(config.clone());
+            monitor.enable_profile_requests(ProfileRequests::new(
+                &config.monitoring.profile_request_path,
+            ));
+        }
+
+        Ok(monitor)
     }
 
     /// Initialize cgroup manager (call after construction if enabled)
@@ -185,19 +243,87 @@ impl ResourceMonitor {
         let mut manager = CgroupManager::new(config)?;
         manager.initialize()?;
 
-        info!("Cgroup integration initialized");
+        // Throttled and frozen trees of a previous run are not tracked anymore
+        let released = manager.release_adopted();
+        if !released.is_empty() {
+            warn!(
+                "Released {} cgroups left by a previous run: {}",
+                released.len(),
+                released.join(", ")
+            );
+        }
+
+        info!("Cgroup integration initialized, freezing and throttling process trees via cgroups");
+        let manager = Arc::new(Mutex::new(manager));
+        self.freezer = std::mem::take(&mut self.freezer).with_cgroups(manager.clone());
+        self.throttler = std::mem::take(&mut self.throttler).with_cgroups(manager.clone());
+        self.cgroup_events = match CgroupEventMonitor::new(manager.clone()) {
+            Ok(events) => Some(events),
+            Err(e) => {
+                warn!("Cgroup events unavailable, OOM kills will not be reported: {}", e);
+                None
+            }
+        };
         self.cgroup_manager = Some(manager);
         Ok(())
     }
 
     /// Cleanup cgroups on shutdown
     pub fn cleanup_cgroups(&mut self) -> Result<()> {
-        if let Some(manager) = &mut self.cgroup_manager {
-            manager.on_service_stop()?;
+        // Throttled processes go back to their original cgroups, not the root one
+        let released = self.throttler.release_all();
+        if released > 0 {
+            info!("Released {} throttled process trees", released);
+        }
+
+        if let Some(manager) = &self.cgroup_manager {
+            manager
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .on_service_stop()?;
         }
         Ok(())
     }
 
+    /// Set smoothing of CPU samples between checks
+    ///
+    /// Resets the sampler: CPU history starts again from the next check.
+    ///
+    /// # Arguments
+    /// * `smoothing` - Smoothing mode (none, EWMA or N-sample mean)
+    pub fn set_cpu_smoothing(&mut self, smoothing: CpuSmoothing) {
+        self.scanner = ProcessScanner::with_smoothing(smoothing);
+        info!("CPU smoothing: {:?}", smoothing);
+    }
+
+    /// Journal frozen processes to `registry` and thaw leftovers of a previous run
+    ///
+    /// Processes frozen by a daemon that crashed before unfreezing them are
+    /// resumed here, before monitoring starts. Returns how many were thawed.
+    pub fn enable_frozen_registry(&mut self, registry: FrozenRegistry) -> Result<usize> {
+        let leftovers = registry.thaw_leftovers();
+        info!("Frozen process registry: {}", registry.path().display());
+        self.freezer = std::mem::take(&mut self.freezer).with_registry(registry);
+
+        let thawed = leftovers?;
+        for entry in &thawed {
+            warn!(
+                "Thawed process {} ({}) left frozen by a previous run (planned thaw at {})",
+                entry.pid, entry.name, entry.thaw_at
+            );
+        }
+        Ok(thawed.len())
+    }
+
+    /// Set the KESL action on max violations
+    ///
+    /// # Arguments
+    /// * `action` - "restart" (systemd restart) or "throttle" (dynamic cgroup)
+    pub fn set_kesl_action(&mut self, action: String) {
+        info!("KESL action on max violations: {}", action);
+        self.kesl_action = action;
+    }
+
     /// Enable Node.js process monitoring
     ///
     /// # Arguments
@@ -241,87 +367,47 @@ impl ResourceMonitor {
         );
     }
 
-    /// Enable Firefox process monitoring (two-tier strategy)
+    /// Set declarative process rules (replaces previously set rules)
+    ///
+    /// Rules that keep their name keep their violation and action counters.
     ///
     /// # Arguments
-    /// * `cpu_threshold_freeze` - CPU threshold for freezing (e.g., 80.0)
-    /// * `cpu_threshold_kill` - CPU threshold for killing (e.g., 95.0)
-    /// * `freeze_duration_secs` - Freeze duration in seconds
-    /// * `max_violations_freeze` - Maximum violations before freeze
-    /// * `max_violations_kill` - Maximum violations before kill
-    pub fn enable_firefox_monitoring(
-        &mut self,
-        cpu_threshold_freeze: f64,
-        cpu_threshold_kill: f64,
-        freeze_duration_secs: u64,
-        max_violations_freeze: u32,
-        max_violations_kill: u32,
-    ) {
-        self.firefox_enabled = true;
-        self.firefox_cpu_threshold_freeze = cpu_threshold_freeze;
-        self.firefox_cpu_threshold_kill = cpu_threshold_kill;
-        self.firefox_freeze_duration_secs = freeze_duration_secs;
-        self.firefox_max_violations_freeze = max_violations_freeze;
-        self.firefox_max_violations_kill = max_violations_kill;
-        info!(
-            "Firefox monitoring enabled: freeze at {:.1}% ({} violations), kill at {:.1}% ({} violations)",
-            cpu_threshold_freeze, max_violations_freeze, cpu_threshold_kill, max_violations_kill
-        );
+    /// * `rules` - Rules from `[[rules]]` config tables (disabled rules are skipped)
+    pub fn set_rules(&mut self, rules: &[RuleConfig]) -> Result<()> {
+        let engine = self.rule_engine.rebuilt(rules)?;
+        self.use_rules(engine);
+        Ok(())
+    }
+
+    fn use_rules(&mut self, engine: RuleEngine) {
+        self.rule_engine = engine;
+        info!("Process rules enabled: {:?}", self.rule_engine.rule_names());
     }
 
-    /// Enable Brave browser process monitoring (two-tier strategy)
+    /// Enable detection of runaway spawners (fork bombs, unbounded `make -j`)
     ///
-    /// # Arguments
-    /// * `cpu_threshold_freeze` - CPU threshold for freezing (e.g., 80.0)
-    /// * `cpu_threshold_kill` - CPU threshold for killing (e.g., 95.0)
-    /// * `freeze_duration_secs` - Freeze duration in seconds
-    /// * `max_violations_freeze` - Maximum violations before freeze
-    /// * `max_violations_kill` - Maximum violations before kill
-    pub fn enable_brave_monitoring(
-        &mut self,
-        cpu_threshold_freeze: f64,
-        cpu_threshold_kill: f64,
-        freeze_duration_secs: u64,
-        max_violations_freeze: u32,
-        max_violations_kill: u32,
-    ) {
-        self.brave_enabled = true;
-        self.brave_cpu_threshold_freeze = cpu_threshold_freeze;
-        self.brave_cpu_threshold_kill = cpu_threshold_kill;
-        self.brave_freeze_duration_secs = freeze_duration_secs;
-        self.brave_max_violations_freeze = max_violations_freeze;
-        self.brave_max_violations_kill = max_violations_kill;
+    /// Subtrees growing by `growth_threshold` processes between checks get
+    /// their tasks capped through throttle cgroups.
+    pub fn enable_spawn_guard(&mut self, config: SpawnGuardConfig) {
         info!(
-            "Brave monitoring enabled: freeze at {:.1}% ({} violations), kill at {:.1}% ({} violations)",
-            cpu_threshold_freeze, max_violations_freeze, cpu_threshold_kill, max_violations_kill
+            "Spawn guard enabled: {} new processes per check, headroom {} tasks",
+            config.growth_threshold, config.pids_headroom
         );
+        self.spawn_guard = Some(SpawnGuard::new(config));
     }
 
-    /// Enable Telegram messenger process monitoring (two-tier strategy)
-    ///
-    /// # Arguments
-    /// * `cpu_threshold_freeze` - CPU threshold for freezing (e.g., 80.0)
-    /// * `cpu_threshold_kill` - CPU threshold for killing (e.g., 95.0)
-    /// * `freeze_duration_secs` - Freeze duration in seconds
-    /// * `max_violations_freeze` - Maximum violations before freeze
-    /// * `max_violations_kill` - Maximum violations before kill
-    pub fn enable_telegram_monitoring(
-        &mut self,
-        cpu_threshold_freeze: f64,
-        cpu_threshold_kill: f64,
-        freeze_duration_secs: u64,
-        max_violations_freeze: u32,
-        max_violations_kill: u32,
-    ) {
-        self.telegram_enabled = true;
-        self.telegram_cpu_threshold_freeze = cpu_threshold_freeze;
-        self.telegram_cpu_threshold_kill = cpu_threshold_kill;
-        self.telegram_freeze_duration_secs = freeze_duration_secs;
-        self.telegram_max_violations_freeze = max_violations_freeze;
-        self.telegram_max_violations_kill = max_violations_kill;
+    /// Configure victim selection of the "kill" pressure action
+    pub fn set_oom_killer(&mut self, config: OomKillerConfig) -> Result<()> {
+        self.use_victim_selector(VictimSelector::new(config)?);
+        Ok(())
+    }
+
+    fn use_victim_selector(&mut self, selector: VictimSelector) {
+        self.victim_selector = selector;
         info!(
-            "Telegram monitoring enabled: freeze at {:.1}% ({} violations), kill at {:.1}% ({} violations)",
-            cpu_threshold_freeze, max_violations_freeze, cpu_threshold_kill, max_violations_kill
+            "OOM victim selection: up to {} kills per event, {} priorities",
+            self.victim_selector.config().max_kills,
+            self.victim_selector.config().priorities.len()
         );
     }
 
@@ -361,6 +447,255 @@ impl ResourceMonitor {
         );
     }
 
+    /// Set earlyoom-style MemAvailable and SwapFree floors
+    ///
+    /// Checked with memory pressure, they reach the same warning/critical
+    /// actions without PSI. An unset MemAvailable floor disables them.
+    pub fn set_low_memory_floors(&mut self, mem_available: MemoryFloor, swap_free: MemoryFloor) {
+        if mem_available.is_set() {
+            info!(
+                "Low memory floors: MemAvailable {:?}, SwapFree {:?}",
+                mem_available, swap_free
+            );
+        }
+        self.memory_available_floor = mem_available;
+        self.swap_free_floor = swap_free;
+    }
+
+    /// Enable OOM forecasting from the MemAvailable trend
+    ///
+    /// Sampled with memory pressure: a forecast within the horizon reaches
+    /// the configured level before memory actually runs out.
+    pub fn enable_oom_forecast(&mut self, config: OomForecastConfig) {
+        info!(
+            "OOM forecast enabled: act {}s ahead ({} level), {}s history, confidence >= {:.0}%",
+            config.horizon_secs,
+            config.level,
+            config.history_secs,
+            config.min_confidence * 100.0
+        );
+        self.oom_forecaster = Some(OomForecaster::new(config));
+    }
+
+    /// Enable CPU or I/O stall monitoring (PSI)
+    ///
+    /// Thresholds and actions work like memory pressure monitoring;
+    /// enabling a resource twice replaces its settings and keeps its counters.
+    pub fn enable_stall_monitoring(&mut self, resource: PsiResource, config: PressureStallConfig) {
+        info!(
+            "{} pressure monitoring enabled: some {:.1}%/{:.1}%, full {:.1}%/{:.1}%, actions: {}/{}",
+            resource.label(),
+            config.some_threshold_warning, config.some_threshold_critical,
+            config.full_threshold_warning, config.full_threshold_critical,
+            config.action_warning, config.action_critical
+        );
+        match self.stall_monitors.iter_mut().find(|m| m.resource() == resource) {
+            Some(monitor) => monitor.set_config(config),
+            None => self.stall_monitors.push(StallMonitor::new(resource, config)),
+        }
+    }
+
+    /// Enable thermal monitoring
+    ///
+    /// Above a sensor's warning or critical temperature the busiest
+    /// processes get the level's action.
+    pub fn enable_thermal_monitoring(&mut self, config: ThermalConfig) {
+        info!(
+            "Thermal monitoring enabled: {:.0}°C/{:.0}°C ({} per-sensor overrides), actions: {}/{} on top {} processes",
+            config.warning_celsius,
+            config.critical_celsius,
+            config.zones.len(),
+            config.action_warning,
+            config.action_critical,
+            config.top_processes
+        );
+        self.thermal_monitor = Some(ThermalMonitor::new(config));
+    }
+
+    /// Apply the policy sections of a config
+    ///
+    /// Sets thresholds and actions and enables or disables each monitor,
+    /// at start and on every profile switch. Frozen and throttled processes
+    /// are kept, and so are the counters of monitors and rules that stay
+    /// enabled. `[cgroups]`, `[monitoring]` and `[logging]`, like the memory
+    /// pressure trigger, are only read at start.
+    ///
+    /// The switch is atomic: if the rules or the OOM killer priorities are
+    /// invalid, nothing is changed.
+    pub fn apply_policy(&mut self, config: &Config) -> Result<()> {
+        let rule_engine = self.rule_engine.rebuilt(&config.rules)?;
+        let victim_selector = VictimSelector::new(config.oom_killer.clone())?;
+
+        self.cpu_threshold = config.kesl.cpu_threshold;
+        self.memory_threshold_mb = config.kesl.memory_threshold_mb;
+        self.max_violations = config.kesl.max_violations;
+        self.set_kesl_action(config.kesl.action.clone());
+
+        self.node_enabled = false;
+        if config.node.enabled {
+            self.enable_node_monitoring(config.node.cpu_threshold, config.node.auto_kill);
+        }
+
+        self.snap_enabled = false;
+        if config.snap.enabled {
+            self.enable_snap_monitoring(
+                config.snap.cpu_threshold,
+                config.snap.action.clone(),
+                config.snap.nice_level,
+                config.snap.freeze_duration_secs,
+                config.snap.max_violations,
+            );
+        }
+
+        self.use_rules(rule_engine);
+        self.use_victim_selector(victim_selector);
+
+        match (&mut self.spawn_guard, config.spawn_guard.enabled) {
+            (Some(guard), true) => guard.set_config(config.spawn_guard.clone()),
+            (None, true) => self.enable_spawn_guard(config.spawn_guard.clone()),
+            (_, false) => self.spawn_guard = None,
+        }
+
+        let memory = &config.memory_pressure;
+        self.memory_pressure_enabled = false;
+        self.oom_forecaster = None;
+        self.set_low_memory_floors(MemoryFloor::default(), MemoryFloor::default());
+        if memory.enabled {
+            self.enable_memory_pressure_monitoring(
+                memory.some_threshold_warning,
+                memory.some_threshold_critical,
+                memory.full_threshold_warning,
+                memory.full_threshold_critical,
+                memory.action_warning.clone(),
+                memory.action_critical.clone(),
+                memory.check_interval_secs,
+            );
+            self.set_low_memory_floors(memory.mem_available.clone(), memory.swap_free.clone());
+            if memory.forecast.enabled {
+                self.enable_oom_forecast(memory.forecast.clone());
+            }
+        }
+
+        for (resource, stall) in [(PsiResource::Cpu, &config.cpu_pressure), (PsiResource::Io, &config.io_pressure)] {
+            if stall.enabled {
+                self.enable_stall_monitoring(resource, stall.clone());
+            } else {
+                self.stall_monitors.retain(|m| m.resource() != resource);
+            }
+        }
+
+        match (&mut self.thermal_monitor, config.thermal.enabled) {
+            (Some(monitor), true) => monitor.set_config(config.thermal.clone()),
+            (None, true) => self.enable_thermal_monitoring(config.thermal.clone()),
+            (_, false) => self.thermal_monitor = None,
+        }
+
+        Ok(())
+    }
+
+    /// Switch between the `[profiles]` of `config` at runtime
+    ///
+    /// `config` is the base config, already applied with [`apply_policy`](Self::apply_policy).
+    /// From the next check on, the profile set with
+    /// [`request_profile`](Self::request_profile) or, with `[power]`, the
+    /// profile for the power source is laid over it.
+    pub fn enable_profiles(&mut self, config: Config) {
+        let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        info!("Policy profiles: {}", names.join(", "));
+        if config.power.enabled {
+            info!(
+                "Power profiles enabled: AC {}, battery {}, below {}% {}",
+                config.power.ac_profile.as_deref().unwrap_or("(base)"),
+                config.power.battery_profile.as_deref().unwrap_or("(base)"),
+                config.power.low_battery_percent,
+                config.power.low_battery_profile.as_deref().unwrap_or("(battery)")
+            );
+        }
+        self.profiles = Some(PolicyProfiles::new(config));
+    }
+
+    /// Take profile requests of `freezr profile set` from `requests`
+    ///
+    /// Requires [`enable_profiles`](Self::enable_profiles).
+    pub fn enable_profile_requests(&mut self, requests: ProfileRequests) {
+        if let Some(profiles) = &mut self.profiles {
+            info!("Profile requests: {}", requests.path().display());
+            profiles.watch_requests(requests);
+        }
+    }
+
+    /// Switch to profile `name` (None = automatic selection)
+    ///
+    /// Applied at the start of the next check, never in the middle of one.
+    pub fn request_profile(&mut self, name: Option<&str>) -> Result<()> {
+        let profiles = self
+            .profiles
+            .as_mut()
+            .ok_or_else(|| Error::Other("No policy profiles configured".to_string()))?;
+        profiles.request(name).map_err(Error::Other)
+    }
+
+    /// Active profile, None for the base config
+    pub fn active_profile(&self) -> Option<&str> {
+        self.profiles.as_ref().and_then(|p| p.active())
+    }
+
+    /// Profile switches of this run, oldest first (last 100)
+    pub fn profile_events(&self) -> &[ProcessEvent] {
+        &self.profile_events
+    }
+
+    /// Apply a profile chosen between two checks
+    fn switch_profile(&mut self, switch: ProfileSwitch) {
+        let power = self.profiles.as_ref().and_then(|p| p.power());
+        let from = switch.from.as_deref().unwrap_or("base");
+        let to = switch.profile.as_deref().unwrap_or("base");
+        match (switch.reason, power) {
+            (SwitchReason::Power, Some(state)) => info!(
+                "Power source {}{}: switching from profile '{}' to '{}'",
+                state.source,
+                state
+                    .battery_percent
+                    .map_or(String::new(), |percent| format!(" ({}%)", percent)),
+                from,
+                to
+            ),
+            _ => info!("Switching from profile '{}' to '{}' on request", from, to),
+        }
+
+        if let Err(e) = self.apply_policy(&switch.config) {
+            error!("Failed to apply profile '{}', keeping '{}': {}", to, from, e);
+            return;
+        }
+        if let Some(profiles) = &mut self.profiles {
+            profiles.activate(switch.profile.clone());
+        }
+
+        let event = ProcessEvent {
+            timestamp: chrono::Utc::now(),
+            pid: std::process::id(),
+            process_name: "freezr".to_string(),
+            event_type: EventType::ProfileSwitched {
+                from: switch.from,
+                to: switch.profile,
+            },
+            details: EventDetails {
+                data: serde_json::json!({
+                    "reason": switch.reason.as_str(),
+                    "power_source": power.map(|state| state.source.as_str()),
+                    "battery_percent": power.and_then(|state| state.battery_percent),
+                }),
+            },
+        };
+        if let Ok(json) = serde_json::to_string(&event) {
+            info!("Event: {}", json);
+        }
+        if self.profile_events.len() >= MAX_PROFILE_EVENTS {
+            self.profile_events.remove(0);
+        }
+        self.profile_events.push(event);
+    }
+
     /// Perform single monitoring check
     ///
     /// This is the main monitoring loop that:
@@ -373,6 +708,37 @@ impl ResourceMonitor {
         self.stats.increment_checks();
         debug!("Starting monitoring check #{}", self.stats.total_checks);
 
+        // Apply a requested profile or the one of a new power source before
+        // anything is checked
+        if let Some(switch) = self.profiles.as_mut().and_then(|p| p.check()) {
+            self.switch_profile(switch);
+        }
+
+        // Lift limits of throttled processes that behaved for the cooldown
+        self.throttler.release_calm();
+
+        // Follow load and memory pressure with the limits of throttled trees
+        self.throttler.adapt_limits();
+
+        // Move new processes matching static cgroup patterns into their groups
+        self.sync_static_cgroups();
+
+        // Remove dynamic cgroups left empty by exited processes
+        self.reap_dynamic_cgroups();
+
+        // OOM kills and memory.high stalls reported since the last check
+        if let Some(events) = &mut self.cgroup_events {
+            events.check();
+        }
+
+        // Cap tasks of process trees that spawn too fast
+        if let Some(guard) = &mut self.spawn_guard {
+            guard.check(&self.throttler);
+        }
+
+        // One pass over /proc for all checks below and the stats
+        self.scan_processes();
+
         // Monitor KESL process
         if let Err(e) = self.check_kesl() {
             error!("KESL monitoring error: {}", e);
@@ -392,24 +758,10 @@ impl ResourceMonitor {
             }
         }
 
-        // Monitor Firefox processes
-        if self.firefox_enabled {
-            if let Err(e) = self.check_firefox_processes() {
-                error!("Firefox monitoring error: {}", e);
-            }
-        }
-
-        // Monitor Brave processes
-        if self.brave_enabled {
-            if let Err(e) = self.check_brave_processes() {
-                error!("Brave monitoring error: {}", e);
-            }
-        }
-
-        // Monitor Telegram processes
-        if self.telegram_enabled {
-            if let Err(e) = self.check_telegram_processes() {
-                error!("Telegram monitoring error: {}", e);
+        // Evaluate declarative rules
+        if !self.rule_engine.is_empty() {
+            if let Err(e) = self.check_rules() {
+                error!("Rule monitoring error: {}", e);
             }
         }
 
@@ -425,13 +777,97 @@ impl ResourceMonitor {
             }
         }
 
+        // Monitor CPU and I/O stalls (PSI)
+        let stalls: Vec<_> = self
+            .stall_monitors
+            .iter_mut()
+            .filter_map(|m| m.check().map(|(level, action)| (m.resource(), level, action)))
+            .collect();
+        for (resource, level, action) in stalls {
+            if let Err(e) = self.execute_pressure_action(resource, &action, level.as_str()) {
+                error!("{} pressure action error: {}", resource.label(), e);
+            }
+        }
+
+        // Monitor temperatures
+        let thermal = self.thermal_monitor.as_mut().and_then(|m| m.check());
+        if let Some((level, action)) = thermal {
+            if let Err(e) = self.execute_thermal_action(&action, level.as_str()) {
+                error!("Thermal action error: {}", e);
+            }
+        }
+
         Ok(())
     }
 
+    /// Scan /proc and sample CPU for this check
+    ///
+    /// The only place CPU is sampled: the checks, the stats export and the
+    /// dashboard all read this scan, so none of them shortens the window of
+    /// the next measurement.
+    fn scan_processes(&mut self) {
+        self.processes = self.scanner.scan().unwrap_or_else(|e| {
+            error!("Process scan error: {}", e);
+            ProcessScan::default()
+        });
+    }
+
+    /// Assign processes to static cgroups by `process_patterns`
+    fn sync_static_cgroups(&self) {
+        let Some(manager) = &self.cgroup_manager else {
+            return;
+        };
+
+        let migrated = manager
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .sync_static_groups();
+        if migrated > 0 {
+            info!("Moved {} processes into static cgroups", migrated);
+        }
+    }
+
+    /// Garbage-collect dynamic cgroups whose processes all exited
+    fn reap_dynamic_cgroups(&self) {
+        let Some(manager) = &self.cgroup_manager else {
+            return;
+        };
+
+        let reaped = manager
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .reap_dynamic_cgroups();
+        if !reaped.is_empty() {
+            info!("Removed empty dynamic cgroups: {}", reaped.join(", "));
+        }
+    }
+
+    /// Static cgroup membership and limits for the dashboard
+    fn static_cgroup_stats(&self) -> Vec<crate::stats::StaticCgroupStats> {
+        let Some(manager) = &self.cgroup_manager else {
+            return Vec::new();
+        };
+        let manager = manager.lock().unwrap_or_else(|p| p.into_inner());
+        let mb = |bytes: u64| bytes / 1024 / 1024;
+
+        manager
+            .static_cgroups()
+            .into_iter()
+            .map(|cgroup| crate::stats::StaticCgroupStats {
+                name: cgroup.name.clone(),
+                pids: cgroup.pids.clone(),
+                cpu_limit_percent: cgroup.limits.cpu_limit_percent,
+                memory_max_mb: cgroup.limits.memory_max.map(mb),
+                memory_high_mb: cgroup.limits.memory_high.map(mb),
+                memory_current_mb: MemoryController::get_current(&cgroup.path).ok().map(mb),
+            })
+            .collect()
+    }
+
     /// Monitor KESL process
     fn check_kesl(&mut self) -> Result<()> {
         // Scan KESL process
-        let process = match self.scanner.scan_kesl()? {
+        let process = match self.processes.kesl() {
             Some(p) => p,
             None => {
                 warn!("KESL process not found");
@@ -487,11 +923,19 @@ impl ResourceMonitor {
         // Check if max violations reached
         if self.cpu_violations >= self.max_violations || self.memory_violations >= self.max_violations
         {
-            error!(
-                "Max violations reached (CPU: {}, Memory: {}), restarting service",
-                self.cpu_violations, self.memory_violations
-            );
-            self.restart_kesl_service()?;
+            if self.kesl_action == "throttle" {
+                error!(
+                    "Max violations reached (CPU: {}, Memory: {}), throttling KESL",
+                    self.cpu_violations, self.memory_violations
+                );
+                self.throttle_kesl(&process)?;
+            } else {
+                error!(
+                    "Max violations reached (CPU: {}, Memory: {}), restarting service",
+                    self.cpu_violations, self.memory_violations
+                );
+                self.restart_kesl_service()?;
+            }
         }
 
         Ok(())
@@ -499,7 +943,7 @@ impl ResourceMonitor {
 
     /// Monitor Node.js processes
     fn check_node_processes(&mut self) -> Result<()> {
-        let processes = self.scanner.scan_node_processes()?;
+        let processes = self.processes.node_processes();
 
         if processes.is_empty() {
             debug!("No Node.js processes found");
@@ -520,7 +964,9 @@ impl ResourceMonitor {
                         "Auto-killing Node.js process PID {} (CPU {:.1}%)",
                         process.pid, process.cpu_percent
                     );
-                    if let Err(e) = ProcessExecutor::kill_process(process.pid) {
+                    if let Err(e) =
+                        process.handle().and_then(|h| ProcessExecutor::kill_process(&h))
+                    {
                         error!("Failed to kill Node.js process {}: {}", process.pid, e);
                     } else {
                         self.stats.record_kill();
@@ -535,10 +981,7 @@ impl ResourceMonitor {
 
     /// Monitor Snap/snapd processes
     fn check_snap_processes(&mut self) -> Result<()> {
-        use std::thread;
-        use std::time::Duration;
-
-        let processes = self.scanner.scan_snap_processes()?;
+        let processes = self.processes.snap_processes();
 
         if processes.is_empty() {
             debug!("No Snap processes found");
@@ -595,8 +1038,10 @@ impl ResourceMonitor {
                             "Setting nice level {} for snap process PID {}",
                             self.snap_nice_level, process.pid
                         );
-                        if let Err(e) =
-                            ProcessExecutor::renice_process(process.pid, self.snap_nice_level)
+                        let nice_level = self.snap_nice_level;
+                        if let Err(e) = process
+                            .handle()
+                            .and_then(|h| ProcessExecutor::renice_process(&h, nice_level))
                         {
                             error!("Failed to renice snap process {}: {}", process.pid, e);
                         } else {
@@ -606,27 +1051,43 @@ impl ResourceMonitor {
                             );
                         }
                     }
+                    "throttle" => {
+                        info!("Throttling snap process PID {}", process.pid);
+                        match process
+                            .handle()
+                            .and_then(|h| self.throttler.throttle(h, &process.name))
+                        {
+                            Ok(outcome) => {
+                                info!("Snap process {} {:?}", process.pid, outcome);
+                            }
+                            Err(e) => {
+                                error!("Failed to throttle snap process {}: {}", process.pid, e);
+                            }
+                        }
+                    }
                     "freeze" => {
                         info!(
                             "Freezing snap process PID {} for {} seconds",
                             process.pid, self.snap_freeze_duration_secs
                         );
-                        if let Err(e) = ProcessExecutor::freeze_process(process.pid) {
-                            error!("Failed to freeze snap process {}: {}", process.pid, e);
-                        } else {
-                            info!("Snap process {} frozen, waiting...", process.pid);
-                            thread::sleep(Duration::from_secs(self.snap_freeze_duration_secs));
-
-                            if let Err(e) = ProcessExecutor::unfreeze_process(process.pid) {
-                                error!("Failed to unfreeze snap process {}: {}", process.pid, e);
-                            } else {
-                                info!("Snap process {} unfrozen", process.pid);
+                        let duration = Duration::from_secs(self.snap_freeze_duration_secs);
+                        match process
+                            .handle()
+                            .and_then(|h| self.freezer.freeze(h, &process.name, duration))
+                        {
+                            Ok(outcome) => {
+                                info!("Snap process {} {:?}, unfreeze scheduled", process.pid, outcome);
+                            }
+                            Err(e) => {
+                                error!("Failed to freeze snap process {}: {}", process.pid, e);
                             }
                         }
                     }
                     "kill" => {
                         info!("Killing snap process PID {}", process.pid);
-                        if let Err(e) = ProcessExecutor::kill_process(process.pid) {
+                        if let Err(e) =
+                            process.handle().and_then(|h| ProcessExecutor::kill_process(&h))
+                        {
                             error!("Failed to kill snap process {}: {}", process.pid, e);
                         } else {
                             self.stats.record_kill();
@@ -646,430 +1107,64 @@ impl ResourceMonitor {
         Ok(())
     }
 
-    /// Monitor Firefox processes (two-tier strategy: freeze then kill)
-    fn check_firefox_processes(&mut self) -> Result<()> {
-        use std::thread;
-        use std::time::Duration;
+    /// Evaluate all rules against the scan of this check
+    fn check_rules(&mut self) -> Result<()> {
+        let processes = self.processes.all_processes();
+        self.rule_engine
+            .check(&processes, &mut self.stats, &self.freezer, &self.throttler);
+        Ok(())
+    }
+
+    /// Put KESL into a throttle cgroup instead of restarting the service
+    ///
+    /// Limits are lifted by the throttler once KESL stays below them for
+    /// the release cooldown.
+    fn throttle_kesl(&mut self, process: &ProcessInfo) -> Result<()> {
+        let outcome = process
+            .handle()
+            .and_then(|h| self.throttler.throttle(h, &process.name))?;
+        info!("KESL process {} {:?}", process.pid, outcome);
 
-        let processes = self.scanner.scan_firefox_processes()?;
+        self.cpu_violations = 0;
+        self.memory_violations = 0;
+        Ok(())
+    }
 
-        if processes.is_empty() {
-            debug!("No Firefox processes found");
-            // Reset violations if no processes
-            if self.firefox_violations_freeze > 0 || self.firefox_violations_kill > 0 {
-                debug!("Firefox process ended, resetting violations");
-                self.firefox_violations_freeze = 0;
-                self.firefox_violations_kill = 0;
-            }
-            return Ok(());
+    /// Restart KESL service with protection against frequent restarts
+    fn restart_kesl_service(&mut self) -> Result<()> {
+        // Check if service is active
+        if !self.kesl_service.is_active()? {
+            error!("KESL service is not active, cannot restart");
+            return Err(Error::Systemd("Service is not active".to_string()));
         }
 
-        debug!("Found {} Firefox processes", processes.len());
+        // Restart with daemon-reload
+        info!("Restarting KESL service with daemon-reload");
+        self.kesl_service.restart_with_reload()?;
 
-        // Check for critical CPU (>kill threshold)
-        let critical_processes: Vec<_> = processes
-            .iter()
-            .filter(|p| p.cpu_percent > self.firefox_cpu_threshold_kill)
-            .collect();
+        // Reset violation counters after successful restart
+        self.cpu_violations = 0;
+        self.memory_violations = 0;
+        self.stats.record_restart();
 
-        // Check for high CPU (>freeze threshold, but <kill threshold)
-        let high_cpu_processes: Vec<_> = processes
-            .iter()
-            .filter(|p| {
-                p.cpu_percent > self.firefox_cpu_threshold_freeze
-                    && p.cpu_percent <= self.firefox_cpu_threshold_kill
-            })
-            .collect();
+        info!("KESL service successfully restarted, violations reset");
+        Ok(())
+    }
 
-        // Handle critical CPU (kill strategy)
-        if !critical_processes.is_empty() {
-            self.firefox_violations_kill += 1;
-            warn!(
-                "Firefox CRITICAL CPU violation #{}: {} processes exceed {:.1}%",
-                self.firefox_violations_kill,
-                critical_processes.len(),
-                self.firefox_cpu_threshold_kill
-            );
+    /// Get current monitoring statistics
+    pub fn stats(&self) -> &MonitorStats {
+        &self.stats
+    }
 
-            for process in &critical_processes {
-                warn!(
-                    "CRITICAL Firefox process: PID {}, CPU {:.1}%, Command: {}",
-                    process.pid, process.cpu_percent, process.command
-                );
-            }
+    /// Scheduler holding processes frozen by this monitor
+    pub fn freezer(&self) -> &FreezeScheduler {
+        &self.freezer
+    }
 
-            if self.firefox_violations_kill >= self.firefox_max_violations_kill {
-                error!(
-                    "Firefox critical violations ({}) reached, KILLING processes",
-                    self.firefox_max_violations_kill
-                );
-
-                for process in critical_processes {
-                    info!("Killing Firefox process PID {} (CPU {:.1}%)", process.pid, process.cpu_percent);
-                    if let Err(e) = ProcessExecutor::kill_process(process.pid) {
-                        error!("Failed to kill Firefox process {}: {}", process.pid, e);
-                    } else {
-                        self.stats.record_kill();
-                        info!("Successfully killed Firefox process {}", process.pid);
-                    }
-                }
-
-                self.firefox_violations_kill = 0;
-                self.firefox_violations_freeze = 0; // Reset freeze violations too
-            }
-        } else if !high_cpu_processes.is_empty() {
-            // Handle high CPU (freeze strategy)
-            self.firefox_violations_freeze += 1;
-            self.firefox_violations_kill = 0; // Reset kill violations
-
-            warn!(
-                "Firefox high CPU violation #{}: {} processes exceed {:.1}%",
-                self.firefox_violations_freeze,
-                high_cpu_processes.len(),
-                self.firefox_cpu_threshold_freeze
-            );
-
-            for process in &high_cpu_processes {
-                warn!(
-                    "High-CPU Firefox process: PID {}, CPU {:.1}%, Command: {}",
-                    process.pid, process.cpu_percent, process.command
-                );
-            }
-
-            if self.firefox_violations_freeze >= self.firefox_max_violations_freeze {
-                warn!(
-                    "Firefox freeze violations ({}) reached, FREEZING processes",
-                    self.firefox_max_violations_freeze
-                );
-
-                for process in high_cpu_processes {
-                    info!(
-                        "Freezing Firefox process PID {} for {} seconds (CPU {:.1}%)",
-                        process.pid, self.firefox_freeze_duration_secs, process.cpu_percent
-                    );
-
-                    if let Err(e) = ProcessExecutor::freeze_process(process.pid) {
-                        error!("Failed to freeze Firefox process {}: {}", process.pid, e);
-                    } else {
-                        info!("Firefox process {} frozen, waiting...", process.pid);
-                        thread::sleep(Duration::from_secs(self.firefox_freeze_duration_secs));
-
-                        if let Err(e) = ProcessExecutor::unfreeze_process(process.pid) {
-                            error!("Failed to unfreeze Firefox process {}: {}", process.pid, e);
-                        } else {
-                            info!("Firefox process {} unfrozen", process.pid);
-                        }
-                    }
-                }
-
-                self.firefox_violations_freeze = 0;
-            }
-        } else {
-            // CPU back to normal, reset violations
-            if self.firefox_violations_freeze > 0 || self.firefox_violations_kill > 0 {
-                debug!(
-                    "Firefox CPU back to normal, resetting violations (freeze: {}, kill: {})",
-                    self.firefox_violations_freeze, self.firefox_violations_kill
-                );
-                self.firefox_violations_freeze = 0;
-                self.firefox_violations_kill = 0;
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Monitor Brave browser processes (two-tier strategy: freeze then kill)
-    fn check_brave_processes(&mut self) -> Result<()> {
-        use std::thread;
-        use std::time::Duration;
-
-        let processes = self.scanner.scan_brave_processes()?;
-
-        if processes.is_empty() {
-            debug!("No Brave processes found");
-            // Reset violations if no processes
-            if self.brave_violations_freeze > 0 || self.brave_violations_kill > 0 {
-                debug!("Brave process ended, resetting violations");
-                self.brave_violations_freeze = 0;
-                self.brave_violations_kill = 0;
-            }
-            return Ok(());
-        }
-
-        debug!("Found {} Brave processes", processes.len());
-
-        // Check for critical CPU (>kill threshold)
-        let critical_processes: Vec<_> = processes
-            .iter()
-            .filter(|p| p.cpu_percent > self.brave_cpu_threshold_kill)
-            .collect();
-
-        // Check for high CPU (>freeze threshold, but <kill threshold)
-        let high_cpu_processes: Vec<_> = processes
-            .iter()
-            .filter(|p| {
-                p.cpu_percent > self.brave_cpu_threshold_freeze
-                    && p.cpu_percent <= self.brave_cpu_threshold_kill
-            })
-            .collect();
-
-        // Handle critical CPU (kill strategy)
-        if !critical_processes.is_empty() {
-            self.brave_violations_kill += 1;
-            warn!(
-                "Brave CRITICAL CPU violation #{}: {} processes exceed {:.1}%",
-                self.brave_violations_kill,
-                critical_processes.len(),
-                self.brave_cpu_threshold_kill
-            );
-
-            for process in &critical_processes {
-                warn!(
-                    "CRITICAL Brave process: PID {}, CPU {:.1}%, Command: {}",
-                    process.pid, process.cpu_percent, process.command
-                );
-            }
-
-            if self.brave_violations_kill >= self.brave_max_violations_kill {
-                error!(
-                    "Brave critical violations ({}) reached, KILLING processes",
-                    self.brave_max_violations_kill
-                );
-
-                for process in critical_processes {
-                    info!("Killing Brave process PID {} (CPU {:.1}%)", process.pid, process.cpu_percent);
-                    if let Err(e) = ProcessExecutor::kill_process(process.pid) {
-                        error!("Failed to kill Brave process {}: {}", process.pid, e);
-                    } else {
-                        self.stats.record_kill();
-                        info!("Successfully killed Brave process {}", process.pid);
-                    }
-                }
-
-                self.brave_violations_kill = 0;
-                self.brave_violations_freeze = 0; // Reset freeze violations too
-            }
-        } else if !high_cpu_processes.is_empty() {
-            // Handle high CPU (freeze strategy)
-            self.brave_violations_freeze += 1;
-            self.brave_violations_kill = 0; // Reset kill violations
-
-            warn!(
-                "Brave high CPU violation #{}: {} processes exceed {:.1}%",
-                self.brave_violations_freeze,
-                high_cpu_processes.len(),
-                self.brave_cpu_threshold_freeze
-            );
-
-            for process in &high_cpu_processes {
-                warn!(
-                    "High-CPU Brave process: PID {}, CPU {:.1}%, Command: {}",
-                    process.pid, process.cpu_percent, process.command
-                );
-            }
-
-            if self.brave_violations_freeze >= self.brave_max_violations_freeze {
-                warn!(
-                    "Brave freeze violations ({}) reached, FREEZING processes",
-                    self.brave_max_violations_freeze
-                );
-
-                for process in high_cpu_processes {
-                    info!(
-                        "Freezing Brave process PID {} for {} seconds (CPU {:.1}%)",
-                        process.pid, self.brave_freeze_duration_secs, process.cpu_percent
-                    );
-
-                    if let Err(e) = ProcessExecutor::freeze_process(process.pid) {
-                        error!("Failed to freeze Brave process {}: {}", process.pid, e);
-                    } else {
-                        info!("Brave process {} frozen, waiting...", process.pid);
-                        thread::sleep(Duration::from_secs(self.brave_freeze_duration_secs));
-
-                        if let Err(e) = ProcessExecutor::unfreeze_process(process.pid) {
-                            error!("Failed to unfreeze Brave process {}: {}", process.pid, e);
-                        } else {
-                            info!("Brave process {} unfrozen", process.pid);
-                        }
-                    }
-                }
-
-                self.brave_violations_freeze = 0;
-            }
-        } else {
-            // CPU back to normal, reset violations
-            if self.brave_violations_freeze > 0 || self.brave_violations_kill > 0 {
-                debug!(
-                    "Brave CPU back to normal, resetting violations (freeze: {}, kill: {})",
-                    self.brave_violations_freeze, self.brave_violations_kill
-                );
-                self.brave_violations_freeze = 0;
-                self.brave_violations_kill = 0;
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Check and manage Telegram messenger processes (two-tier strategy: freeze/kill)
-    fn check_telegram_processes(&mut self) -> Result<()> {
-        use std::thread;
-        use std::time::Duration;
-
-        let processes = self.scanner.scan_telegram_processes()?;
-
-        if processes.is_empty() {
-            debug!("No Telegram processes found");
-            // Reset violations if no processes
-            if self.telegram_violations_freeze > 0 || self.telegram_violations_kill > 0 {
-                debug!("Telegram process ended, resetting violations");
-                self.telegram_violations_freeze = 0;
-                self.telegram_violations_kill = 0;
-            }
-            return Ok(());
-        }
-
-        debug!("Found {} Telegram processes", processes.len());
-
-        // Check for critical CPU (>kill threshold)
-        let critical_processes: Vec<_> = processes
-            .iter()
-            .filter(|p| p.cpu_percent > self.telegram_cpu_threshold_kill)
-            .collect();
-
-        // Check for high CPU (>freeze threshold, but <kill threshold)
-        let high_cpu_processes: Vec<_> = processes
-            .iter()
-            .filter(|p| {
-                p.cpu_percent > self.telegram_cpu_threshold_freeze
-                    && p.cpu_percent <= self.telegram_cpu_threshold_kill
-            })
-            .collect();
-
-        // Handle critical CPU (kill strategy)
-        if !critical_processes.is_empty() {
-            self.telegram_violations_kill += 1;
-            warn!(
-                "Telegram CRITICAL CPU violation #{}: {} processes exceed {:.1}%",
-                self.telegram_violations_kill,
-                critical_processes.len(),
-                self.telegram_cpu_threshold_kill
-            );
-
-            for process in &critical_processes {
-                warn!(
-                    "CRITICAL Telegram process: PID {}, CPU {:.1}%, Command: {}",
-                    process.pid, process.cpu_percent, process.command
-                );
-            }
-
-            if self.telegram_violations_kill >= self.telegram_max_violations_kill {
-                error!(
-                    "Telegram critical violations ({}) reached, KILLING processes",
-                    self.telegram_max_violations_kill
-                );
-
-                for process in critical_processes {
-                    info!("Killing Telegram process PID {} (CPU {:.1}%)", process.pid, process.cpu_percent);
-                    if let Err(e) = ProcessExecutor::kill_process(process.pid) {
-                        error!("Failed to kill Telegram process {}: {}", process.pid, e);
-                    } else {
-                        self.stats.record_kill();
-                        info!("Successfully killed Telegram process {}", process.pid);
-                    }
-                }
-
-                self.telegram_violations_kill = 0;
-                self.telegram_violations_freeze = 0; // Reset freeze violations too
-            }
-        } else if !high_cpu_processes.is_empty() {
-            // Handle high CPU (freeze strategy)
-            self.telegram_violations_freeze += 1;
-            self.telegram_violations_kill = 0; // Reset kill violations
-
-            warn!(
-                "Telegram high CPU violation #{}: {} processes exceed {:.1}%",
-                self.telegram_violations_freeze,
-                high_cpu_processes.len(),
-                self.telegram_cpu_threshold_freeze
-            );
-
-            for process in &high_cpu_processes {
-                warn!(
-                    "High-CPU Telegram process: PID {}, CPU {:.1}%, Command: {}",
-                    process.pid, process.cpu_percent, process.command
-                );
-            }
-
-            if self.telegram_violations_freeze >= self.telegram_max_violations_freeze {
-                warn!(
-                    "Telegram freeze violations ({}) reached, FREEZING processes",
-                    self.telegram_max_violations_freeze
-                );
-
-                for process in high_cpu_processes {
-                    info!(
-                        "Freezing Telegram process PID {} for {} seconds (CPU {:.1}%)",
-                        process.pid, self.telegram_freeze_duration_secs, process.cpu_percent
-                    );
-
-                    if let Err(e) = ProcessExecutor::freeze_process(process.pid) {
-                        error!("Failed to freeze Telegram process {}: {}", process.pid, e);
-                    } else {
-                        info!("Telegram process {} frozen, waiting...", process.pid);
-                        thread::sleep(Duration::from_secs(self.telegram_freeze_duration_secs));
-
-                        if let Err(e) = ProcessExecutor::unfreeze_process(process.pid) {
-                            error!("Failed to unfreeze Telegram process {}: {}", process.pid, e);
-                        } else {
-                            info!("Telegram process {} unfrozen", process.pid);
-                        }
-                    }
-                }
-
-                self.telegram_violations_freeze = 0;
-            }
-        } else {
-            // CPU back to normal, reset violations
-            if self.telegram_violations_freeze > 0 || self.telegram_violations_kill > 0 {
-                debug!(
-                    "Telegram CPU back to normal, resetting violations (freeze: {}, kill: {})",
-                    self.telegram_violations_freeze, self.telegram_violations_kill
-                );
-                self.telegram_violations_freeze = 0;
-                self.telegram_violations_kill = 0;
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Restart KESL service with protection against frequent restarts
-    fn restart_kesl_service(&mut self) -> Result<()> {
-        // Check if service is active
-        if !self.kesl_service.is_active()? {
-            error!("KESL service is not active, cannot restart");
-            return Err(Error::Systemd("Service is not active".to_string()));
-        }
-
-        // Restart with daemon-reload
-        info!("Restarting KESL service with daemon-reload");
-        self.kesl_service.restart_with_reload()?;
-
-        // Reset violation counters after successful restart
-        self.cpu_violations = 0;
-        self.memory_violations = 0;
-        self.stats.record_restart();
-
-        info!("KESL service successfully restarted, violations reset");
-        Ok(())
-    }
-
-    /// Get current monitoring statistics
-    pub fn stats(&self) -> &MonitorStats {
-        &self.stats
-    }
+    /// Throttler holding processes in throttle cgroups
+    pub fn throttler(&self) -> &Throttler {
+        &self.throttler
+    }
 
     /// Get current violation counters
     pub fn violations(&self) -> (u32, u32) {
@@ -1083,17 +1178,12 @@ impl ResourceMonitor {
         debug!("Violation counters manually reset");
     }
 
-    /// Get current KESL CPU and memory status
+    /// Get KESL CPU and memory status of the last check
     /// Returns (cpu_percent, memory_mb)
     pub fn get_kesl_status(&self) -> Option<(f64, u64)> {
-        match self.scanner.scan_kesl() {
-            Ok(Some(process)) => {
-                let memory_mb = process.memory_kb / 1024;
-                Some((process.cpu_percent, memory_mb))
-            }
-            Ok(None) => None,
-            Err(_) => None,
-        }
+        self.processes
+            .kesl()
+            .map(|process| (process.cpu_percent, process.memory_kb / 1024))
     }
 
     /// Export complete statistics for dashboard
@@ -1105,11 +1195,14 @@ impl ResourceMonitor {
 
         // Get current KESL status
         let (kesl_cpu, kesl_mem) = self.get_kesl_status().unwrap_or((0.0, 0));
-        let kesl_pid = self.scanner.scan_kesl().ok().flatten().map(|p| p.pid);
+        let kesl_pid = self.processes.kesl().map(|p| p.pid);
+
+        // Forecast of the last memory pressure check
+        let forecast = self.oom_forecaster.as_ref().and_then(|f| f.last());
 
         // Read memory pressure (if enabled)
         let (mp_some, mp_full, mp_status) = if self.memory_pressure_enabled {
-            match freezr_core::memory_pressure::MemoryPressure::read() {
+            match MemoryPressure::read() {
                 Ok(pressure) => {
                     let status = if pressure.full_avg10 >= self.memory_pressure_full_threshold_critical {
                         "CRITICAL".to_string()
@@ -1142,26 +1235,9 @@ impl ResourceMonitor {
             (0.0, 0.0, 0.0)
         };
 
-        let (mem_total, mem_available, mem_used_pct) = if let Ok(content) = std::fs::read_to_string("/proc/meminfo") {
-            let mut total_kb = 0u64;
-            let mut available_kb = 0u64;
-            for line in content.lines() {
-                if line.starts_with("MemTotal:") {
-                    total_kb = line.split_whitespace().nth(1).and_then(|s| s.parse().ok()).unwrap_or(0);
-                } else if line.starts_with("MemAvailable:") {
-                    available_kb = line.split_whitespace().nth(1).and_then(|s| s.parse().ok()).unwrap_or(0);
-                }
-            }
-            let total_mb = total_kb / 1024;
-            let available_mb = available_kb / 1024;
-            let used_pct = if total_kb > 0 {
-                ((total_kb - available_kb) as f64 / total_kb as f64) * 100.0
-            } else {
-                0.0
-            };
-            (total_mb, available_mb, used_pct)
-        } else {
-            (0, 0, 0.0)
+        let (mem_total, mem_available, mem_used_pct) = match MemInfo::read() {
+            Ok(m) => (m.mem_total_kb / 1024, m.mem_available_kb / 1024, m.used_percent()),
+            Err(_) => (0, 0, 0.0),
         };
 
         // Log statistics
@@ -1202,39 +1278,24 @@ impl ResourceMonitor {
                 nice_level: self.snap_nice_level,
                 total_actions: 0, // TODO: Track snap actions
             },
-            firefox: BrowserStats {
-                enabled: self.firefox_enabled,
-                freeze_threshold: self.firefox_cpu_threshold_freeze,
-                kill_threshold: self.firefox_cpu_threshold_kill,
-                freeze_violations: self.firefox_violations_freeze,
-                kill_violations: self.firefox_violations_kill,
-                max_violations_freeze: self.firefox_max_violations_freeze,
-                max_violations_kill: self.firefox_max_violations_kill,
-                total_freezes: 0, // TODO: Track freezes
-                total_kills: 0,   // TODO: Track kills
-            },
-            brave: BrowserStats {
-                enabled: self.brave_enabled,
-                freeze_threshold: self.brave_cpu_threshold_freeze,
-                kill_threshold: self.brave_cpu_threshold_kill,
-                freeze_violations: self.brave_violations_freeze,
-                kill_violations: self.brave_violations_kill,
-                max_violations_freeze: self.brave_max_violations_freeze,
-                max_violations_kill: self.brave_max_violations_kill,
-                total_freezes: 0, // TODO: Track freezes
-                total_kills: 0,   // TODO: Track kills
-            },
-            telegram: BrowserStats {
-                enabled: self.telegram_enabled,
-                freeze_threshold: self.telegram_cpu_threshold_freeze,
-                kill_threshold: self.telegram_cpu_threshold_kill,
-                freeze_violations: self.telegram_violations_freeze,
-                kill_violations: self.telegram_violations_kill,
-                max_violations_freeze: self.telegram_max_violations_freeze,
-                max_violations_kill: self.telegram_max_violations_kill,
-                total_freezes: 0, // TODO: Track freezes
-                total_kills: 0,   // TODO: Track kills
+            rules: self.rule_engine.stats(),
+            throttle: {
+                let (total_throttled, total_released) = self.throttler.totals();
+                ThrottleStats {
+                    enabled: self.throttler.uses_cgroups(),
+                    throttled_processes: self.throttler.throttled_count(),
+                    total_throttled,
+                    total_released,
+                    cooldown_secs: self.throttler.cooldown().map_or(0, |c| c.as_secs()),
+                    contained_spawners: self
+                        .spawn_guard
+                        .as_ref()
+                        .map_or(0, |guard| guard.total_contained()),
+                    adaptive: self.throttler.adaptive_settings().is_some(),
+                    total_adjusted: self.throttler.total_adjusted(),
+                }
             },
+            static_cgroups: self.static_cgroup_stats(),
             memory_pressure: MemoryPressureStats {
                 enabled: self.memory_pressure_enabled,
                 some_avg10: mp_some,
@@ -1248,6 +1309,40 @@ impl ResourceMonitor {
                 full_threshold_critical: self.memory_pressure_full_threshold_critical,
                 action_warning: self.memory_pressure_action_warning.clone(),
                 action_critical: self.memory_pressure_action_critical.clone(),
+                oom_forecast_secs: forecast.and_then(|f| f.seconds_to_oom),
+                oom_forecast_confidence: forecast.map_or(0.0, |f| f.confidence),
+            },
+            thermal: match &self.thermal_monitor {
+                Some(thermal) => {
+                    let (warning_count, critical_count) = thermal.counts();
+                    let readings = thermal.readings();
+                    let status = match readings.iter().filter_map(|r| r.level).max() {
+                        _ if readings.is_empty() => "UNKNOWN",
+                        Some(level) => level.as_str(),
+                        None => "OK",
+                    };
+                    ThermalStats {
+                        enabled: true,
+                        status: status.to_string(),
+                        sensors: readings
+                            .iter()
+                            .map(|r| SensorStats {
+                                name: r.name.clone(),
+                                celsius: r.celsius,
+                                warning_celsius: r.warning_celsius,
+                                critical_celsius: r.critical_celsius,
+                            })
+                            .collect(),
+                        warning_count,
+                        critical_count,
+                        action_warning: thermal.config().action_warning.clone(),
+                        action_critical: thermal.config().action_critical.clone(),
+                    }
+                }
+                None => ThermalStats {
+                    status: "DISABLED".to_string(),
+                    ..ThermalStats::default()
+                },
             },
             system_health: SystemHealth {
                 load_1min: load_1,
@@ -1256,6 +1351,17 @@ impl ResourceMonitor {
                 memory_used_percent: mem_used_pct,
                 memory_total_mb: mem_total,
                 memory_available_mb: mem_available,
+                active_profile: self.active_profile().map(str::to_string),
+                power_source: self
+                    .profiles
+                    .as_ref()
+                    .and_then(|p| p.power())
+                    .map(|state| state.source.to_string()),
+                battery_percent: self
+                    .profiles
+                    .as_ref()
+                    .and_then(|p| p.power())
+                    .and_then(|state| state.battery_percent),
             },
             log_stats,
         }
@@ -1263,146 +1369,348 @@ impl ResourceMonitor {
 
     /// Monitor memory pressure (PSI - Pressure Stall Information)
     ///
-    /// Reads /proc/pressure/memory and takes proactive actions based on thresholds
+    /// Reads /proc/pressure/memory and /proc/meminfo and takes proactive
+    /// actions based on thresholds. Either source alone is enough.
     fn check_memory_pressure(&mut self) -> Result<()> {
-        // Read current memory pressure
+        let meminfo = match MemInfo::read() {
+            Ok(m) => Some(m),
+            Err(e) => {
+                warn!("Failed to read meminfo: {}", e);
+                None
+            }
+        };
+
         let pressure = match MemoryPressure::read() {
-            Ok(p) => p,
+            Ok(p) => Some(p),
+            Err(e) if meminfo.is_some() && self.memory_available_floor.is_set() => {
+                // psi=0: MemAvailable/SwapFree floors still work
+                debug!("Memory PSI unavailable, checking meminfo only: {}", e);
+                None
+            }
             Err(e) => {
                 warn!("Failed to read memory pressure: {}", e);
                 return Ok(()); // Don't fail monitoring on PSI read error
             }
         };
 
+        self.handle_memory_pressure(pressure, meminfo)
+    }
+
+    /// React to a fired kernel PSI trigger without waiting for the next check
+    ///
+    /// avg10 lags behind a sudden spike, so the stall a firing guarantees
+    /// (`floor_percent` = trigger stall / window) counts as the 'some' value
+    /// if it is higher.
+    pub fn on_memory_pressure_trigger(&mut self, floor_percent: f64) {
+        if !self.memory_pressure_enabled {
+            return;
+        }
+
+        let mut pressure = match MemoryPressure::read() {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to read memory pressure: {}", e);
+                return;
+            }
+        };
+        // Acts between checks: its victims need a scan of their own
+        self.scan_processes();
         debug!(
-            "Memory pressure: some {:.2}%, full {:.2}%",
-            pressure.some_avg10, pressure.full_avg10
+            "Memory pressure trigger fired (some avg10 {:.2}%, at least {:.1}% in window)",
+            pressure.some_avg10, floor_percent
         );
+        pressure.some_avg10 = pressure.some_avg10.max(floor_percent);
+
+        if let Err(e) = self.handle_memory_pressure(Some(pressure), MemInfo::read().ok()) {
+            error!("Memory pressure monitoring error: {}", e);
+        }
+        self.memory_pressure_last_check = Instant::now();
+    }
 
-        // Check if pressure is at critical level
+    /// Record a memory sample and check the forecast against its horizon
+    ///
+    /// Returns the configured level and the forecast if memory is expected
+    /// to run out within the horizon.
+    fn forecast_oom(
+        &mut self,
+        pressure: Option<&MemoryPressure>,
+        meminfo: Option<&MemInfo>,
+    ) -> Option<(StallLevel, OomForecast)> {
+        let forecaster = self.oom_forecaster.as_mut()?;
+        let meminfo = meminfo?;
+        let forecast = forecaster.record(
+            Instant::now(),
+            meminfo.mem_available_kb,
+            pressure.map(|p| p.some_total),
+        )?;
+
+        let config = forecaster.config();
+        if !forecast.within(Duration::from_secs(config.horizon_secs), config.min_confidence) {
+            return None;
+        }
+
+        let level = if config.level == "warning" {
+            StallLevel::Warning
+        } else {
+            StallLevel::Critical
+        };
+        Some((level, forecast))
+    }
+
+    /// PSI level of a memory pressure reading
+    fn memory_psi_level(&self, pressure: &MemoryPressure) -> Option<StallLevel> {
         if pressure.is_critical(
             self.memory_pressure_some_threshold_critical,
             self.memory_pressure_full_threshold_critical,
         ) {
-            self.memory_pressure_critical_count += 1;
-            warn!("╔═══════════════════════════════════════════════════════════╗");
-            warn!("║           🚨 CRITICAL MEMORY PRESSURE DETECTED 🚨         ║");
-            warn!("╚═══════════════════════════════════════════════════════════╝");
-            warn!(
-                "PSI Metrics: some={:.2}%, full={:.2}% (thresholds: some={:.1}%, full={:.1}%)",
-                pressure.some_avg10,
-                pressure.full_avg10,
-                self.memory_pressure_some_threshold_critical,
-                self.memory_pressure_full_threshold_critical
+            Some(StallLevel::Critical)
+        } else if pressure.is_warning(
+            self.memory_pressure_some_threshold_warning,
+            self.memory_pressure_full_threshold_warning,
+        ) {
+            Some(StallLevel::Warning)
+        } else {
+            None
+        }
+    }
+
+    /// Compare memory pressure and free memory with the thresholds and execute the action
+    ///
+    /// The higher of the PSI and the MemAvailable/SwapFree level wins.
+    fn handle_memory_pressure(
+        &mut self,
+        pressure: Option<MemoryPressure>,
+        meminfo: Option<MemInfo>,
+    ) -> Result<()> {
+        if let Some(pressure) = &pressure {
+            debug!(
+                "Memory pressure: some {:.2}%, full {:.2}%",
+                pressure.some_avg10, pressure.full_avg10
             );
+        }
+
+        let psi_level = pressure.as_ref().and_then(|p| self.memory_psi_level(p));
+        let low_memory = meminfo.as_ref().and_then(|m| {
+            low_memory_level(&self.memory_available_floor, &self.swap_free_floor, m)
+        });
+        let forecast = self.forecast_oom(pressure.as_ref(), meminfo.as_ref());
+        if let Some((level, f)) = &forecast {
             warn!(
-                "PSI Averages: some(10s/60s/300s)={:.2}/{:.2}/{:.2}%, full(10s/60s/300s)={:.2}/{:.2}/{:.2}%",
-                pressure.some_avg10, pressure.some_avg60, pressure.some_avg300,
-                pressure.full_avg10, pressure.full_avg60, pressure.full_avg300
+                "OOM forecast: memory runs out in ~{:.0}s ({:.1}MB/s, confidence {:.0}%), {} level",
+                f.seconds_to_oom.unwrap_or(0.0),
+                f.trend_mb_per_sec,
+                f.confidence * 100.0,
+                level.as_str()
             );
+        }
 
-            // Get system memory info
-            if let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") {
-                if let Some(total) = meminfo.lines().find(|l| l.starts_with("MemTotal:")) {
-                    if let Some(avail) = meminfo.lines().find(|l| l.starts_with("MemAvailable:")) {
-                        warn!("System Memory: {} | {}", total.trim(), avail.trim());
-                    }
+        match psi_level.max(low_memory).max(forecast.map(|(level, _)| level)) {
+            Some(StallLevel::Critical) => {
+                self.memory_pressure_critical_count += 1;
+                warn!("╔═══════════════════════════════════════════════════════════╗");
+                warn!("║           🚨 CRITICAL MEMORY PRESSURE DETECTED 🚨         ║");
+                warn!("╚═══════════════════════════════════════════════════════════╝");
+                if let Some(pressure) = &pressure {
+                    warn!(
+                        "PSI Metrics: some={:.2}%, full={:.2}% (thresholds: some={:.1}%, full={:.1}%)",
+                        pressure.some_avg10,
+                        pressure.full_avg10,
+                        self.memory_pressure_some_threshold_critical,
+                        self.memory_pressure_full_threshold_critical
+                    );
+                    warn!(
+                        "PSI Averages: some(10s/60s/300s)={:.2}/{:.2}/{:.2}%, full(10s/60s/300s)={:.2}/{:.2}/{:.2}%",
+                        pressure.some_avg10, pressure.some_avg60, pressure.some_avg300,
+                        pressure.full_avg10, pressure.full_avg60, pressure.full_avg300
+                    );
+                }
+                if low_memory == Some(StallLevel::Critical) {
+                    warn!("Free memory below critical floors (MemAvailable {:?}, SwapFree {:?})",
+                          self.memory_available_floor, self.swap_free_floor);
+                }
+                if let Some(m) = &meminfo {
+                    log_system_memory(m);
                 }
-            }
 
-            // Execute critical action
-            self.execute_memory_pressure_action(&self.memory_pressure_action_critical.clone(), "CRITICAL")?;
-        }
-        // Check if pressure is at warning level
-        else if pressure.is_warning(
-            self.memory_pressure_some_threshold_warning,
-            self.memory_pressure_full_threshold_warning,
-        ) {
-            self.memory_pressure_warning_count += 1;
-            warn!(
-                "WARNING memory pressure detected! some={:.2}%, full={:.2}% (thresholds: some={:.1}%, full={:.1}%)",
-                pressure.some_avg10,
-                pressure.full_avg10,
-                self.memory_pressure_some_threshold_warning,
-                self.memory_pressure_full_threshold_warning
-            );
+                // Execute critical action
+                self.execute_pressure_action(PsiResource::Memory, &self.memory_pressure_action_critical.clone(), "CRITICAL")?;
+            }
+            Some(StallLevel::Warning) => {
+                self.memory_pressure_warning_count += 1;
+                if let Some(pressure) = &pressure {
+                    warn!(
+                        "WARNING memory pressure detected! some={:.2}%, full={:.2}% (thresholds: some={:.1}%, full={:.1}%)",
+                        pressure.some_avg10,
+                        pressure.full_avg10,
+                        self.memory_pressure_some_threshold_warning,
+                        self.memory_pressure_full_threshold_warning
+                    );
+                }
+                if let (Some(StallLevel::Warning), Some(m)) = (low_memory, &meminfo) {
+                    warn!("WARNING low memory detected! MemAvailable {}MB ({:.1}%), SwapFree {}MB",
+                          m.mem_available_kb / 1024, m.available_percent(), m.swap_free_kb / 1024);
+                }
 
-            // Execute warning action
-            self.execute_memory_pressure_action(&self.memory_pressure_action_warning.clone(), "WARNING")?;
-        } else {
-            // No pressure detected - reset counters
-            if self.memory_pressure_warning_count > 0 || self.memory_pressure_critical_count > 0 {
-                debug!("Memory pressure normalized (some={:.2}%, full={:.2}%)",
-                    pressure.some_avg10, pressure.full_avg10);
-                self.memory_pressure_warning_count = 0;
-                self.memory_pressure_critical_count = 0;
+                // Execute warning action
+                self.execute_pressure_action(PsiResource::Memory, &self.memory_pressure_action_warning.clone(), "WARNING")?;
+            }
+            None => {
+                // No pressure detected - reset counters
+                if self.memory_pressure_warning_count > 0 || self.memory_pressure_critical_count > 0 {
+                    if let Some(pressure) = &pressure {
+                        debug!("Memory pressure normalized (some={:.2}%, full={:.2}%)",
+                            pressure.some_avg10, pressure.full_avg10);
+                    }
+                    self.memory_pressure_warning_count = 0;
+                    self.memory_pressure_critical_count = 0;
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Execute action based on memory pressure level
-    fn execute_memory_pressure_action(&mut self, action: &str, level: &str) -> Result<()> {
+    /// Execute action based on memory, CPU or I/O pressure level
+    fn execute_pressure_action(&mut self, resource: PsiResource, action: &str, level: &str) -> Result<()> {
+        let label = resource.label();
         match action {
             "log" => {
-                info!("[Memory Pressure {}] Logging event", level);
-                // Already logged in check_memory_pressure
+                info!("[{} Pressure {}] Logging event", label, level);
+                // Already logged when the pressure was checked
                 Ok(())
             }
             "nice" => {
-                info!("[Memory Pressure {}] Applying nice to non-critical processes", level);
+                info!("[{} Pressure {}] Applying nice to non-critical processes", label, level);
                 // Nice down non-critical processes (Firefox, Brave, Telegram)
                 self.nice_non_critical_processes()
             }
+            "throttle" => {
+                info!("[{} Pressure {}] Throttling non-critical processes", label, level);
+                // Put non-critical processes under dynamic cgroup limits
+                self.throttle_non_critical_processes()
+            }
             "freeze" => {
-                info!("[Memory Pressure {}] Freezing non-critical processes", level);
+                info!("[{} Pressure {}] Freezing non-critical processes", label, level);
                 // Freeze non-critical processes temporarily
                 self.freeze_non_critical_processes()
             }
             "kill" => {
-                warn!("[Memory Pressure {}] Killing non-critical processes", level);
-                // Kill non-critical processes (most aggressive)
-                self.kill_non_critical_processes()
+                warn!("[{} Pressure {}] Killing top-scored processes", label, level);
+                // Kill one victim at a time until the stall is gone (most aggressive)
+                self.kill_pressure_victims(resource)
             }
             _ => {
-                warn!("Unknown memory pressure action: {}", action);
+                warn!("Unknown {} pressure action: {}", label, action);
                 Ok(())
             }
         }
     }
 
+    /// Apply a thermal action to the busiest processes
+    fn execute_thermal_action(&mut self, action: &str, level: &str) -> Result<()> {
+        let Some(thermal) = &self.thermal_monitor else {
+            return Ok(());
+        };
+        if action == "log" {
+            info!("[Thermal {}] Logging event", level);
+            return Ok(());
+        }
+
+        let processes = thermal.top_cpu_consumers(&self.processes.all_processes());
+        if processes.is_empty() {
+            info!(
+                "[Thermal {}] No process above {:.0}% CPU to {}",
+                level,
+                thermal.config().min_cpu_percent,
+                action
+            );
+            return Ok(());
+        }
+
+        let duration = Duration::from_secs(5);
+        let mut acted = 0;
+        for process in &processes {
+            let handle = match process.handle() {
+                Ok(handle) => handle,
+                Err(e) => {
+                    debug!("Process {} gone before thermal action: {}", process.pid, e);
+                    continue;
+                }
+            };
+
+            let outcome = match action {
+                "nice" => ProcessExecutor::renice_process(&handle, 15)
+                    .map(|()| "reniced to 15".to_string()),
+                "throttle" => self
+                    .throttler
+                    .throttle(handle, &process.name)
+                    .map(|outcome| format!("{:?}", outcome)),
+                "freeze" => self
+                    .freezer
+                    .freeze(handle, &process.name, duration)
+                    .map(|outcome| format!("{:?}", outcome)),
+                _ => {
+                    warn!("Unknown thermal action: {}", action);
+                    return Ok(());
+                }
+            };
+
+            match outcome {
+                Ok(outcome) => {
+                    info!(
+                        "[Thermal {}] {} ({}) at {:.1}% CPU: {}",
+                        level, process.name, process.pid, process.cpu_percent, outcome
+                    );
+                    acted += 1;
+                }
+                Err(e) => warn!(
+                    "[Thermal {}] Failed to {} {} ({}): {}",
+                    level, action, process.name, process.pid, e
+                ),
+            }
+        }
+
+        info!(
+            "Thermal {}: {} applied to {} of the busiest processes",
+            level, action, acted
+        );
+        Ok(())
+    }
+
     /// Lower priority of non-critical processes
     fn nice_non_critical_processes(&mut self) -> Result<()> {
         let mut niced_count = 0;
 
         // Nice Firefox processes
-        if let Ok(processes) = self.scanner.scan_firefox_processes() {
-            for process in processes {
-                if let Ok(()) = ProcessExecutor::renice_process(process.pid, 15) {
-                    info!("Niced Firefox process {} to priority 15", process.pid);
-                    niced_count += 1;
-                }
+        for process in self.processes.firefox_processes() {
+            if let Ok(()) = process
+                .handle()
+                .and_then(|h| ProcessExecutor::renice_process(&h, 15))
+            {
+                info!("Niced Firefox process {} to priority 15", process.pid);
+                niced_count += 1;
             }
         }
 
         // Nice Brave processes
-        if let Ok(processes) = self.scanner.scan_brave_processes() {
-            for process in processes {
-                if let Ok(()) = ProcessExecutor::renice_process(process.pid, 15) {
-                    info!("Niced Brave process {} to priority 15", process.pid);
-                    niced_count += 1;
-                }
+        for process in self.processes.brave_processes() {
+            if let Ok(()) = process
+                .handle()
+                .and_then(|h| ProcessExecutor::renice_process(&h, 15))
+            {
+                info!("Niced Brave process {} to priority 15", process.pid);
+                niced_count += 1;
             }
         }
 
         // Nice Telegram processes
-        if let Ok(processes) = self.scanner.scan_telegram_processes() {
-            for process in processes {
-                if let Ok(()) = ProcessExecutor::renice_process(process.pid, 15) {
-                    info!("Niced Telegram process {} to priority 15", process.pid);
-                    niced_count += 1;
-                }
+        for process in self.processes.telegram_processes() {
+            if let Ok(()) = process
+                .handle()
+                .and_then(|h| ProcessExecutor::renice_process(&h, 15))
+            {
+                info!("Niced Telegram process {} to priority 15", process.pid);
+                niced_count += 1;
             }
         }
 
@@ -1412,181 +1720,197 @@ impl ResourceMonitor {
 
     /// Freeze non-critical processes temporarily (5 seconds)
     fn freeze_non_critical_processes(&mut self) -> Result<()> {
+        let duration = Duration::from_secs(5);
         let mut frozen_count = 0;
 
-        // Freeze Firefox
-        if let Ok(processes) = self.scanner.scan_firefox_processes() {
-            for process in processes {
-                if let Ok(()) = ProcessExecutor::freeze_process(process.pid) {
-                    info!("Froze Firefox process {}", process.pid);
-                    frozen_count += 1;
-                }
-            }
-        }
-
-        // Freeze Brave
-        if let Ok(processes) = self.scanner.scan_brave_processes() {
-            for process in processes {
-                if let Ok(()) = ProcessExecutor::freeze_process(process.pid) {
-                    info!("Froze Brave process {}", process.pid);
-                    frozen_count += 1;
-                }
-            }
-        }
+        let groups = [
+            ("Firefox", self.processes.firefox_processes()),
+            ("Brave", self.processes.brave_processes()),
+            ("Telegram", self.processes.telegram_processes()),
+        ];
 
-        // Freeze Telegram
-        if let Ok(processes) = self.scanner.scan_telegram_processes() {
+        for (name, processes) in groups {
             for process in processes {
-                if let Ok(()) = ProcessExecutor::freeze_process(process.pid) {
-                    info!("Froze Telegram process {}", process.pid);
+                if let Ok(outcome) = process
+                    .handle()
+                    .and_then(|h| self.freezer.freeze(h, &process.name, duration))
+                {
+                    info!("Froze {} process {} ({:?})", name, process.pid, outcome);
                     frozen_count += 1;
                 }
             }
         }
 
+        // Unfreeze happens on the scheduler's timer, monitoring continues
         info!("Memory pressure: froze {} non-critical processes for {} seconds",
-            frozen_count, 5);
+            frozen_count, duration.as_secs());
+        Ok(())
+    }
 
-        // Unfreeze after 5 seconds
-        std::thread::sleep(std::time::Duration::from_secs(5));
+    /// Throttle non-critical processes until they behave
+    fn throttle_non_critical_processes(&mut self) -> Result<()> {
+        let mut throttled_count = 0;
 
-        // Unfreeze all
-        let mut unfrozen_count = 0;
-        if let Ok(processes) = self.scanner.scan_firefox_processes() {
-            for process in processes {
-                if let Ok(()) = ProcessExecutor::unfreeze_process(process.pid) {
-                    unfrozen_count += 1;
-                }
-            }
-        }
-        if let Ok(processes) = self.scanner.scan_brave_processes() {
-            for process in processes {
-                if let Ok(()) = ProcessExecutor::unfreeze_process(process.pid) {
-                    unfrozen_count += 1;
-                }
-            }
-        }
-        if let Ok(processes) = self.scanner.scan_telegram_processes() {
+        let groups = [
+            ("Firefox", self.processes.firefox_processes()),
+            ("Brave", self.processes.brave_processes()),
+            ("Telegram", self.processes.telegram_processes()),
+        ];
+
+        for (name, processes) in groups {
             for process in processes {
-                if let Ok(()) = ProcessExecutor::unfreeze_process(process.pid) {
-                    unfrozen_count += 1;
+                if let Ok(outcome) = process
+                    .handle()
+                    .and_then(|h| self.throttler.throttle(h, &process.name))
+                {
+                    info!("Throttled {} process {} ({:?})", name, process.pid, outcome);
+                    throttled_count += 1;
                 }
             }
         }
 
-        info!("Memory pressure: unfroze {} processes", unfrozen_count);
+        info!("Memory pressure: throttled {} non-critical processes", throttled_count);
         Ok(())
     }
 
-    /// Kill non-critical processes (most aggressive action)
-    /// Priority: Brave → Telegram → nvim (>1GB)
-    fn kill_non_critical_processes(&mut self) -> Result<()> {
-        let mut killed_count = 0;
-        let mut total_memory_freed = 0u64;
+    /// Kill the highest-scored processes, one at a time (most aggressive action)
+    ///
+    /// After each kill the stall of `resource` is measured over `settle_ms`
+    /// from the PSI totals (avg10 lags seconds behind a kill) and killing
+    /// stops as soon as it is below the warning thresholds. Blocks the
+    /// monitor for up to `max_kills` kills: an emergency, not a routine action.
+    fn kill_pressure_victims(&mut self, resource: PsiResource) -> Result<()> {
+        let max_kills = self.victim_selector.config().max_kills;
+        let settle = Duration::from_millis(self.victim_selector.config().settle_ms);
 
-        // Log all potential culprits before killing
         warn!("=== OOM Prevention: Analyzing memory consumers ===");
+        let available_before = available_kb();
+        // Re-ranked every round: OOM scores and PSS are read afresh
+        let processes = self.processes.all_processes();
+        let mut tried = HashSet::new();
+        let mut killed_count = 0;
 
-        // Collect all processes and their memory usage
-        let mut all_consumers: Vec<(String, u32, u64, f64, String)> = Vec::new();
-
-        if let Ok(processes) = self.scanner.scan_brave_processes() {
-            for p in processes {
-                all_consumers.push(("Brave".to_string(), p.pid, p.memory_kb, p.cpu_percent, p.command.clone()));
-            }
-        }
-        if let Ok(processes) = self.scanner.scan_telegram_processes() {
-            for p in processes {
-                all_consumers.push(("Telegram".to_string(), p.pid, p.memory_kb, p.cpu_percent, p.command.clone()));
-            }
-        }
-        if let Ok(processes) = self.scanner.scan_nvim_processes() {
-            for p in processes {
-                all_consumers.push(("nvim".to_string(), p.pid, p.memory_kb, p.cpu_percent, p.command.clone()));
-            }
-        }
-        if let Ok(processes) = self.scanner.scan_firefox_processes() {
-            for p in processes {
-                all_consumers.push(("Firefox".to_string(), p.pid, p.memory_kb, p.cpu_percent, p.command.clone()));
+        for round in 0..max_kills {
+            let victims: Vec<_> = self
+                .victim_selector
+                .rank(&processes)
+                .into_iter()
+                .filter(|v| !tried.contains(&v.process.pid))
+                .collect();
+
+            if round == 0 {
+                warn!("Top kill candidates:");
+                for (idx, v) in victims.iter().take(10).enumerate() {
+                    warn!(
+                        "  #{} {} PID:{} score:{:.0} MEM:{}MB oom_score:{} priority:{} CMD:{}",
+                        idx + 1,
+                        v.process.name,
+                        v.process.pid,
+                        v.score,
+                        v.memory_kb / 1024,
+                        v.oom_score,
+                        v.priority,
+                        v.process.command.chars().take(60).collect::<String>()
+                    );
+                }
             }
-        }
-
-        // Sort by memory consumption (descending)
-        all_consumers.sort_by(|a, b| b.2.cmp(&a.2));
 
-        // Log top memory consumers
-        warn!("Top memory consumers before OOM prevention:");
-        for (idx, (name, pid, mem_kb, cpu, cmd)) in all_consumers.iter().take(10).enumerate() {
-            let mem_mb = mem_kb / 1024;
-            let cmd_short: String = cmd.chars().take(60).collect();
-            warn!("  #{} {} PID:{} RAM:{}MB CPU:{:.1}% CMD:{}",
-                  idx + 1, name, pid, mem_mb, cpu, cmd_short);
-        }
+            let Some(victim) = victims.into_iter().next() else {
+                warn!("No kill candidates left");
+                break;
+            };
 
-        // Priority 1: Kill Brave (браузер можно пожертвовать)
-        if let Ok(processes) = self.scanner.scan_brave_processes() {
-            for process in processes {
-                let mem_mb = process.memory_kb / 1024;
-                warn!("🔴 [Priority 1] Killing Brave PID:{} RAM:{}MB CPU:{:.1}% CMD:{}",
-                      process.pid, mem_mb, process.cpu_percent,
-                      process.command.chars().take(60).collect::<String>());
-                if let Ok(()) = ProcessExecutor::kill_process(process.pid) {
-                    killed_count += 1;
-                    total_memory_freed += process.memory_kb;
-                }
+            warn!(
+                "🔴 Killing {} PID:{} score:{:.0} MEM:{}MB oom_score:{} priority:{}",
+                victim.process.name,
+                victim.process.pid,
+                victim.score,
+                victim.memory_kb / 1024,
+                victim.oom_score,
+                victim.priority
+            );
+            tried.insert(victim.process.pid);
+            if let Err(e) = victim
+                .process
+                .handle()
+                .and_then(|h| ProcessExecutor::kill_process(&h))
+            {
+                error!("Failed to kill process {}: {}", victim.process.pid, e);
+                continue;
             }
-        }
+            killed_count += 1;
+            self.stats.record_kill();
 
-        // Priority 2: Kill Telegram (мессенджер менее критичен)
-        if let Ok(processes) = self.scanner.scan_telegram_processes() {
-            for process in processes {
-                let mem_mb = process.memory_kb / 1024;
-                warn!("🟠 [Priority 2] Killing Telegram PID:{} RAM:{}MB CPU:{:.1}% CMD:{}",
-                      process.pid, mem_mb, process.cpu_percent,
-                      process.command.chars().take(60).collect::<String>());
-                if let Ok(()) = ProcessExecutor::kill_process(process.pid) {
-                    killed_count += 1;
-                    total_memory_freed += process.memory_kb;
-                }
+            if !self.still_under_pressure(resource, settle, victim.process.pid) {
+                info!(
+                    "{} pressure below warning thresholds, no more kills needed",
+                    resource.label()
+                );
+                break;
             }
         }
 
-        // Priority 3: Kill nvim if memory > 1GB (крайняя мера)
-        if let Ok(processes) = self.scanner.scan_nvim_processes() {
-            for process in processes {
-                let memory_mb = process.memory_kb / 1024;
-                if memory_mb > 1024 {
-                    warn!("🟡 [Priority 3] Killing nvim PID:{} RAM:{}MB CPU:{:.1}% CMD:{}",
-                          process.pid, memory_mb, process.cpu_percent,
-                          process.command.chars().take(60).collect::<String>());
-                    if let Ok(()) = ProcessExecutor::kill_process(process.pid) {
-                        killed_count += 1;
-                        total_memory_freed += process.memory_kb;
-                    }
-                } else {
-                    info!("⚪ Skipping nvim PID:{} ({}MB < 1GB threshold)", process.pid, memory_mb);
-                }
+        let available_after = available_kb();
+        warn!(
+            "=== OOM Prevention completed: killed {} processes, MemAvailable +{}MB ===",
+            killed_count,
+            available_after.saturating_sub(available_before) / 1024
+        );
+        Ok(())
+    }
+
+    /// Whether another victim is needed after killing `pid`
+    ///
+    /// Measures the stall of `resource` over `settle` from the PSI totals;
+    /// for memory, MemAvailable/SwapFree below their warning floors count
+    /// too. Without any fresh reading there is no telling, so no.
+    fn still_under_pressure(&self, resource: PsiResource, settle: Duration, pid: u32) -> bool {
+        let (some_warning, full_warning) = self.warning_thresholds(resource);
+
+        let before = PressureStall::read(resource).ok();
+        let started = Instant::now();
+        std::thread::sleep(settle);
+        let stalled = match (before, PressureStall::read(resource).ok()) {
+            (Some(before), Some(after)) => {
+                let (some, full) = after.stall_since(&before, started.elapsed());
+                info!("After killing PID {}: {} stall some {:.1}%, full {:.1}% over {}ms",
+                      pid, resource.label(), some, full, settle.as_millis());
+                some >= some_warning || full >= full_warning
             }
-        }
+            _ => {
+                warn!("{} pressure unreadable after killing PID {}", resource.label(), pid);
+                false
+            }
+        };
 
-        // Priority 4: Kill Firefox (дополнительная защита)
-        if let Ok(processes) = self.scanner.scan_firefox_processes() {
-            for process in processes {
-                let mem_mb = process.memory_kb / 1024;
-                warn!("🔵 [Priority 4] Killing Firefox PID:{} RAM:{}MB CPU:{:.1}% CMD:{}",
-                      process.pid, mem_mb, process.cpu_percent,
-                      process.command.chars().take(60).collect::<String>());
-                if let Ok(()) = ProcessExecutor::kill_process(process.pid) {
-                    killed_count += 1;
-                    total_memory_freed += process.memory_kb;
-                }
+        let low_memory = match MemInfo::read() {
+            Ok(m) => {
+                info!("After killing PID {}: MemAvailable {}MB ({:.1}%), SwapFree {}MB",
+                      pid, m.mem_available_kb / 1024, m.available_percent(), m.swap_free_kb / 1024);
+                resource == PsiResource::Memory
+                    && low_memory_level(&self.memory_available_floor, &self.swap_free_floor, &m).is_some()
             }
+            Err(_) => false,
+        };
+
+        stalled || low_memory
+    }
+
+    /// Warning (some, full) thresholds of a PSI resource
+    fn warning_thresholds(&self, resource: PsiResource) -> (f64, f64) {
+        if resource == PsiResource::Memory {
+            return (
+                self.memory_pressure_some_threshold_warning,
+                self.memory_pressure_full_threshold_warning,
+            );
         }
 
-        let freed_mb = total_memory_freed / 1024;
-        warn!("=== OOM Prevention completed: killed {} processes, freed {}MB ===",
-              killed_count, freed_mb);
-        Ok(())
+        self.stall_monitors
+            .iter()
+            .find(|m| m.resource() == resource)
+            .map_or((0.0, 0.0), |m| {
+                (m.config().some_threshold_warning, m.config().full_threshold_warning)
+            })
     }
 
     /// Get current memory pressure status (for dashboard)
@@ -1611,6 +1935,25 @@ impl ResourceMonitor {
     }
 }
 
+/// MemAvailable in KB, 0 if unreadable
+fn available_kb() -> u64 {
+    MemInfo::read().map_or(0, |m| m.mem_available_kb)
+}
+
+/// Log the system memory state of a critical event
+fn log_system_memory(meminfo: &MemInfo) {
+    warn!(
+        "System Memory: MemTotal {}MB | MemAvailable {}MB ({:.1}%) | SwapFree {}/{}MB | Dirty {}MB | Shmem {}MB",
+        meminfo.mem_total_kb / 1024,
+        meminfo.mem_available_kb / 1024,
+        meminfo.available_percent(),
+        meminfo.swap_free_kb / 1024,
+        meminfo.swap_total_kb / 1024,
+        meminfo.dirty_kb / 1024,
+        meminfo.shmem_kb / 1024
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1711,4 +2054,103 @@ mod tests {
         // Stats should be incremented even if process not found
         assert_eq!(monitor.stats().total_checks, 1);
     }
+
+    #[test]
+    fn test_apply_policy_enables_and_disables_monitors() {
+        let mut monitor = ResourceMonitor::new("kesl", 30.0, 600, 3, 100);
+        let mut config = Config::default();
+        config.cpu_pressure.enabled = true;
+        config.thermal.enabled = true;
+        monitor.apply_policy(&config).unwrap();
+
+        assert!(monitor.node_enabled);
+        assert!(monitor.memory_pressure_enabled);
+        assert!(monitor.oom_forecaster.is_some());
+        assert_eq!(monitor.stall_monitors.len(), 1);
+        assert!(monitor.thermal_monitor.is_some());
+
+        config.kesl.cpu_threshold = 15.0;
+        config.node.enabled = false;
+        config.memory_pressure.enabled = false;
+        config.cpu_pressure.enabled = false;
+        config.thermal.enabled = false;
+        monitor.apply_policy(&config).unwrap();
+
+        assert_eq!(monitor.cpu_threshold, 15.0);
+        assert!(!monitor.node_enabled);
+        assert!(!monitor.memory_pressure_enabled);
+        assert!(monitor.oom_forecaster.is_none());
+        assert!(monitor.stall_monitors.is_empty());
+        assert!(monitor.thermal_monitor.is_none());
+        assert!(monitor.spawn_guard.is_some());
+    }
+
+    #[test]
+    fn test_failed_profile_switch_changes_nothing() {
+        let mut monitor = ResourceMonitor::new("kesl", 30.0, 600, 3, 100);
+        let mut config = Config::default();
+        let overlay: toml::Table = toml::from_str(
+            r#"
+            [kesl]
+            cpu_threshold = 15.0
+
+            [[rules]]
+            name = "unknown-user"
+            match = { user = "no-such-user-freezr" }
+            actions = [{ action = "log", cpu_threshold = 50.0 }]
+            "#,
+        )
+        .unwrap();
+        config.profiles.insert("broken".to_string(), overlay);
+        monitor.apply_policy(&config).unwrap();
+        monitor.enable_profiles(config.clone());
+
+        monitor.request_profile(Some("broken")).unwrap();
+        let switch = monitor.profiles.as_mut().unwrap().check().unwrap();
+        monitor.switch_profile(switch);
+
+        assert_eq!(monitor.cpu_threshold, config.kesl.cpu_threshold);
+        assert_eq!(monitor.active_profile(), None);
+        assert!(monitor.profile_events().is_empty());
+    }
+
+    #[test]
+    fn test_from_config_applies_policy() {
+        let dir = std::env::temp_dir().join(format!("freezr-monitor-{}", std::process::id()));
+        let mut config = Config::default();
+        config.cgroups.enabled = false;
+        config.monitoring.frozen_registry_path = dir.join("frozen.json");
+        config.monitoring.profile_request_path = dir.join("profile.json");
+        config.snap.enabled = true;
+        config.thermal.enabled = true;
+        config.profiles.insert("quiet".to_string(), toml::Table::new());
+
+        let monitor = ResourceMonitor::from_config(&config).unwrap();
+        assert!(monitor.snap_enabled);
+        assert!(monitor.memory_pressure_enabled);
+        assert!(monitor.thermal_monitor.is_some());
+        assert!(!monitor.rule_engine.is_empty());
+        assert!(monitor.profiles.is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_memory_pressure_trigger_counts_as_stall() {
+        let mut monitor = ResourceMonitor::new("kesl", 30.0, 600, 3, 100);
+
+        // Ignored while memory pressure monitoring is off
+        monitor.on_memory_pressure_trigger(15.0);
+        assert!(monitor.get_memory_pressure_status().is_none());
+
+        monitor.enable_memory_pressure_monitoring(
+            10.0, 30.0, 5.0, 15.0, "log".to_string(), "log".to_string(), 5,
+        );
+        monitor.on_memory_pressure_trigger(15.0);
+
+        // PSI may be disabled (psi=0) or missing in containers
+        if let Some((_, _, _, warnings, criticals)) = monitor.get_memory_pressure_status() {
+            assert_eq!(warnings + criticals, 1);
+        }
+    }
 }
@@ -0,0 +1,460 @@
+//! CPU and I/O stall monitoring
+//!
+//! Memory pressure has its own path in [`ResourceMonitor`]; CPU and I/O
+//! stalls are watched by one [`StallMonitor`] each. A monitor reads its PSI
+//! file every `check_interval_secs`, counts warning and critical readings
+//! and returns the action configured for the level reached.
+//!
+//! Memory stalls can freeze the desktop between two checks, so memory
+//! pressure can also be signalled by the kernel: a [`PressureTrigger`]
+//! waits for a PSI trigger in a background thread and wakes the main loop
+//! within milliseconds of the stall threshold being crossed.
+//!
+//! Without PSI (`psi=0`) or on swapless machines, where memory runs out
+//! before stall time builds up, [`low_memory_level`] derives the same
+//! warning/critical levels from MemAvailable and SwapFree.
+//!
+//! [`ResourceMonitor`]: crate::ResourceMonitor
+
+use crate::config::{MemoryFloor, MemoryPressureConfig, PressureStallConfig};
+use freezr_core::{error::Result, MemInfo, PressureStall, PsiResource, PsiTrigger, StallKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+/// How often the trigger thread checks for shutdown
+const TRIGGER_POLL: Duration = Duration::from_millis(500);
+
+/// Unprivileged triggers need a window that is a multiple of this
+const UNPRIVILEGED_WINDOW_STEP_MS: u128 = 2000;
+
+/// Level of a stall reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StallLevel {
+    Warning,
+    Critical,
+}
+
+impl StallLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Warning => "WARNING",
+            Self::Critical => "CRITICAL",
+        }
+    }
+}
+
+/// earlyoom-style level from MemAvailable and SwapFree
+///
+/// Reached when MemAvailable is below its floor and SwapFree below its own
+/// (an unset swap floor or no swap at all counts as below): full swap is no
+/// emergency while memory is available, swapless machines only watch
+/// MemAvailable. None if the MemAvailable floor is unset.
+pub fn low_memory_level(
+    mem_available: &MemoryFloor,
+    swap_free: &MemoryFloor,
+    meminfo: &MemInfo,
+) -> Option<StallLevel> {
+    if !mem_available.is_set() {
+        return None;
+    }
+
+    let reached = |critical| {
+        mem_available.reached(meminfo.mem_available_kb, meminfo.mem_total_kb, critical)
+            && (!swap_free.is_set()
+                || swap_free.reached(meminfo.swap_free_kb, meminfo.swap_total_kb, critical))
+    };
+
+    if reached(true) {
+        Some(StallLevel::Critical)
+    } else if reached(false) {
+        Some(StallLevel::Warning)
+    } else {
+        None
+    }
+}
+
+/// Threshold checks of one PSI resource
+pub struct StallMonitor {
+    resource: PsiResource,
+    config: PressureStallConfig,
+    /// None until the first check
+    last_check: Option<Instant>,
+    warning_count: u32,
+    critical_count: u32,
+}
+
+impl StallMonitor {
+    pub fn new(resource: PsiResource, config: PressureStallConfig) -> Self {
+        Self {
+            resource,
+            config,
+            last_check: None,
+            warning_count: 0,
+            critical_count: 0,
+        }
+    }
+
+    pub fn resource(&self) -> PsiResource {
+        self.resource
+    }
+
+    pub fn config(&self) -> &PressureStallConfig {
+        &self.config
+    }
+
+    /// Replace the thresholds and actions, keeping the counters
+    pub fn set_config(&mut self, config: PressureStallConfig) {
+        self.config = config;
+    }
+
+    /// Consecutive (warning, critical) readings
+    pub fn counts(&self) -> (u32, u32) {
+        (self.warning_count, self.critical_count)
+    }
+
+    /// Read the PSI file if the check interval has passed
+    ///
+    /// Returns the level reached and its configured action.
+    pub fn check(&mut self) -> Option<(StallLevel, String)> {
+        let now = Instant::now();
+        let interval = Duration::from_secs(self.config.check_interval_secs);
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return None;
+        }
+        self.last_check = Some(now);
+
+        match PressureStall::read(self.resource) {
+            Ok(pressure) => self.evaluate(&pressure),
+            Err(e) => {
+                // Don't fail monitoring on PSI read error
+                warn!("Failed to read {} pressure: {}", self.resource, e);
+                None
+            }
+        }
+    }
+
+    /// Compare a reading with the thresholds
+    pub fn evaluate(&mut self, pressure: &PressureStall) -> Option<(StallLevel, String)> {
+        let label = self.resource.label();
+        debug!(
+            "{} pressure: some {:.2}%, full {:.2}%",
+            label, pressure.some_avg10, pressure.full_avg10
+        );
+
+        if pressure.is_critical(
+            self.config.some_threshold_critical,
+            self.config.full_threshold_critical,
+        ) {
+            self.critical_count += 1;
+            warn!(
+                "CRITICAL {} pressure detected! some={:.2}%, full={:.2}% (thresholds: some={:.1}%, full={:.1}%)",
+                label,
+                pressure.some_avg10,
+                pressure.full_avg10,
+                self.config.some_threshold_critical,
+                self.config.full_threshold_critical
+            );
+            warn!(
+                "PSI Averages: some(10s/60s/300s)={:.2}/{:.2}/{:.2}%, full(10s/60s/300s)={:.2}/{:.2}/{:.2}%",
+                pressure.some_avg10, pressure.some_avg60, pressure.some_avg300,
+                pressure.full_avg10, pressure.full_avg60, pressure.full_avg300
+            );
+            Some((StallLevel::Critical, self.config.action_critical.clone()))
+        } else if pressure.is_warning(
+            self.config.some_threshold_warning,
+            self.config.full_threshold_warning,
+        ) {
+            self.warning_count += 1;
+            warn!(
+                "WARNING {} pressure detected! some={:.2}%, full={:.2}% (thresholds: some={:.1}%, full={:.1}%)",
+                label,
+                pressure.some_avg10,
+                pressure.full_avg10,
+                self.config.some_threshold_warning,
+                self.config.full_threshold_warning
+            );
+            Some((StallLevel::Warning, self.config.action_warning.clone()))
+        } else {
+            if self.warning_count > 0 || self.critical_count > 0 {
+                debug!(
+                    "{} pressure normalized (some={:.2}%, full={:.2}%)",
+                    label, pressure.some_avg10, pressure.full_avg10
+                );
+                self.warning_count = 0;
+                self.critical_count = 0;
+            }
+            None
+        }
+    }
+}
+
+/// Kernel PSI trigger waited on in a background thread
+///
+/// Dropping it stops the thread and unregisters the trigger.
+pub struct PressureTrigger {
+    fired: Arc<Notify>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    spec: String,
+    /// 'some' stall in percent a firing guarantees (stall / window)
+    floor_percent: f64,
+}
+
+impl PressureTrigger {
+    /// Register a 'some' trigger on `resource` and start waiting for it
+    ///
+    /// Without CAP_SYS_RESOURCE the kernel only accepts 2s-aligned windows;
+    /// the trigger is then retried with the window rounded up and the stall
+    /// scaled to the same ratio.
+    pub fn start(resource: PsiResource, stall: Duration, window: Duration) -> Result<Self> {
+        let trigger = match PsiTrigger::new(resource, StallKind::Some, stall, window) {
+            Ok(trigger) => trigger,
+            Err(e) => {
+                let Some((stall, window)) = aligned_trigger(stall, window) else {
+                    return Err(e);
+                };
+                debug!("{}, retrying with a {}ms window", e, window.as_millis());
+                PsiTrigger::new(resource, StallKind::Some, stall, window)?
+            }
+        };
+
+        let spec = trigger.spec().to_string();
+        let floor_percent = stall.as_secs_f64() / window.as_secs_f64() * 100.0;
+        let fired = Arc::new(Notify::new());
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let fired = fired.clone();
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name(format!("psi-{}", resource))
+                .spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        match trigger.wait(TRIGGER_POLL) {
+                            Ok(true) => {
+                                debug!("PSI trigger fired: {} ({})", trigger.spec(), resource);
+                                fired.notify_one();
+                            }
+                            Ok(false) => {}
+                            Err(e) => {
+                                warn!("{}, falling back to polling", e);
+                                break;
+                            }
+                        }
+                    }
+                })?
+        };
+
+        info!("{} pressure trigger registered: {}", resource.label(), spec);
+        Ok(Self {
+            fired,
+            stop,
+            thread: Some(thread),
+            spec,
+            floor_percent,
+        })
+    }
+
+    /// Trigger as written to the kernel
+    pub fn spec(&self) -> &str {
+        &self.spec
+    }
+
+    /// Lowest 'some' stall percentage implied by a firing
+    pub fn floor_percent(&self) -> f64 {
+        self.floor_percent
+    }
+
+    /// Wait for the next firing (one missed while not waiting is kept)
+    pub async fn fired(&self) {
+        self.fired.notified().await
+    }
+
+    /// Memory trigger of `config`
+    ///
+    /// None if memory pressure monitoring or the trigger is disabled, or the
+    /// kernel does not support triggers (memory pressure is polled then).
+    pub fn memory(config: &MemoryPressureConfig) -> Option<Self> {
+        if !config.enabled || !config.trigger_enabled {
+            return None;
+        }
+
+        match Self::start(
+            PsiResource::Memory,
+            Duration::from_millis(config.trigger_stall_ms),
+            Duration::from_millis(config.trigger_window_ms),
+        ) {
+            Ok(trigger) => Some(trigger),
+            Err(e) => {
+                warn!(
+                    "PSI triggers unsupported ({}), polling memory pressure every {}s",
+                    e, config.check_interval_secs
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Completes when `trigger` fires (never without one)
+///
+/// For `tokio::select!` next to the check timer of a monitoring loop.
+pub async fn triggered(trigger: Option<&PressureTrigger>) {
+    match trigger {
+        Some(trigger) => trigger.fired().await,
+        None => std::future::pending().await,
+    }
+}
+
+impl Drop for PressureTrigger {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Trigger with the window rounded up to the unprivileged 2s step
+///
+/// None if the window is already aligned or would exceed the kernel limit.
+fn aligned_trigger(stall: Duration, window: Duration) -> Option<(Duration, Duration)> {
+    let window_ms = window.as_millis();
+    let aligned_ms = (window_ms + UNPRIVILEGED_WINDOW_STEP_MS - 1) / UNPRIVILEGED_WINDOW_STEP_MS
+        * UNPRIVILEGED_WINDOW_STEP_MS;
+    let aligned = Duration::from_millis(aligned_ms as u64);
+    if aligned_ms == window_ms || aligned > PsiTrigger::MAX_WINDOW {
+        return None;
+    }
+
+    let scaled = stall.as_micros() * aligned_ms / window_ms;
+    Some((Duration::from_micros(scaled as u64), aligned))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stall(some_avg10: f64, full_avg10: f64) -> PressureStall {
+        PressureStall {
+            some_avg10,
+            full_avg10,
+            ..PressureStall::default()
+        }
+    }
+
+    #[test]
+    fn test_evaluate_levels() {
+        let mut monitor = StallMonitor::new(PsiResource::Io, PressureStallConfig::default());
+
+        assert_eq!(monitor.evaluate(&stall(5.0, 0.0)), None);
+        assert_eq!(
+            monitor.evaluate(&stall(25.0, 0.0)),
+            Some((StallLevel::Warning, "log".to_string()))
+        );
+        // full alone is enough
+        assert_eq!(
+            monitor.evaluate(&stall(25.0, 35.0)),
+            Some((StallLevel::Critical, "throttle".to_string()))
+        );
+        assert_eq!(monitor.counts(), (1, 1));
+
+        // Calm reading resets the counters
+        assert_eq!(monitor.evaluate(&stall(0.0, 0.0)), None);
+        assert_eq!(monitor.counts(), (0, 0));
+    }
+
+    #[test]
+    fn test_low_memory_level() {
+        const GB: u64 = 1024 * 1024;
+        let floor = MemoryFloor::percent(10.0, 5.0);
+        let meminfo = |available_gb: f64, swap_total_gb: u64, swap_free_gb: f64| MemInfo {
+            mem_total_kb: 10 * GB,
+            mem_available_kb: (available_gb * GB as f64) as u64,
+            swap_total_kb: swap_total_gb * GB,
+            swap_free_kb: (swap_free_gb * GB as f64) as u64,
+            ..MemInfo::default()
+        };
+        let level = |info: &MemInfo| low_memory_level(&floor, &floor, info);
+
+        assert_eq!(level(&meminfo(5.0, 0, 0.0)), None);
+        // Swapless: MemAvailable alone decides
+        assert_eq!(level(&meminfo(0.8, 0, 0.0)), Some(StallLevel::Warning));
+        assert_eq!(level(&meminfo(0.3, 0, 0.0)), Some(StallLevel::Critical));
+        // Swap still has room: no emergency yet
+        assert_eq!(level(&meminfo(0.3, 8, 4.0)), None);
+        assert_eq!(level(&meminfo(0.3, 8, 0.6)), Some(StallLevel::Warning));
+        assert_eq!(level(&meminfo(0.3, 8, 0.2)), Some(StallLevel::Critical));
+
+        // Full swap alone is not low memory
+        assert_eq!(
+            low_memory_level(&MemoryFloor::default(), &floor, &meminfo(5.0, 8, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_aligned_trigger() {
+        let ms = Duration::from_millis;
+
+        assert_eq!(
+            aligned_trigger(ms(150), ms(1000)),
+            Some((ms(300), ms(2000)))
+        );
+        assert_eq!(
+            aligned_trigger(ms(500), ms(2500)),
+            Some((ms(800), ms(4000)))
+        );
+        assert_eq!(aligned_trigger(ms(500), ms(2000)), None);
+        assert_eq!(
+            aligned_trigger(ms(900), ms(9000)),
+            Some((ms(1000), ms(10000)))
+        );
+    }
+
+    #[test]
+    fn test_trigger_start_and_stop() {
+        // PSI may be disabled (psi=0) or missing in containers
+        let Ok(trigger) = PressureTrigger::start(
+            PsiResource::Memory,
+            Duration::from_millis(150),
+            Duration::from_secs(1),
+        ) else {
+            return;
+        };
+
+        assert!(trigger.spec() == "some 150000 1000000" || trigger.spec() == "some 300000 2000000");
+        assert_eq!(trigger.floor_percent(), 15.0);
+
+        // Joins the thread within one poll interval
+        let started = Instant::now();
+        drop(trigger);
+        assert!(started.elapsed() < TRIGGER_POLL * 2);
+    }
+
+    #[test]
+    fn test_check_respects_interval() {
+        let config = PressureStallConfig {
+            enabled: true,
+            // Every reading is critical
+            some_threshold_warning: 0.0,
+            some_threshold_critical: 0.0,
+            check_interval_secs: 3600,
+            ..PressureStallConfig::default()
+        };
+        let mut monitor = StallMonitor::new(PsiResource::Cpu, config);
+
+        // PSI may be disabled (psi=0) or missing in containers
+        if PressureStall::read(PsiResource::Cpu).is_ok() {
+            assert_eq!(monitor.check().unwrap().0, StallLevel::Critical);
+        }
+        assert_eq!(monitor.check(), None);
+    }
+}
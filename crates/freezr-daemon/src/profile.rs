@@ -0,0 +1,387 @@
+//! Policy profiles
+//!
+//! A profile overlays config sections (see [`Config::with_profile`]).
+//! [`PolicyProfiles`] keeps the base config and decides which profile is
+//! active: one set by the user (`freezr profile set <name>`) wins, otherwise
+//! the `[power]` profile for the power source, read every
+//! `check_interval_secs`. The [`ResourceMonitor`] applies the resulting
+//! config between two checks, so switching never restarts the daemon.
+//!
+//! [`ResourceMonitor`]: crate::ResourceMonitor
+
+use crate::config::Config;
+use chrono::{DateTime, Utc};
+use freezr_core::{PowerReader, PowerState, ProfileRequests};
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+
+/// What triggered a profile switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchReason {
+    /// Set by the user
+    Request,
+    /// Power source changed
+    Power,
+}
+
+impl SwitchReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Request => "request",
+            Self::Power => "power",
+        }
+    }
+}
+
+/// Config to apply after a profile change
+#[derive(Debug, Clone)]
+pub struct ProfileSwitch {
+    /// Previous profile, None for the base config
+    pub from: Option<String>,
+    /// New profile, None for the base config
+    pub profile: Option<String>,
+    pub config: Config,
+    pub reason: SwitchReason,
+}
+
+/// Base config and the profile chosen by the user or the power source
+pub struct PolicyProfiles {
+    base: Config,
+    /// None without `[power]`
+    reader: Option<PowerReader>,
+    /// None until the first check
+    last_check: Option<Instant>,
+    power: Option<PowerState>,
+    /// Profile for the power source
+    automatic: Option<String>,
+    /// Profile set by the user, wins over `automatic`
+    requested: Option<String>,
+    /// `requested` changed since the last check
+    request_pending: bool,
+    /// Request file written by the CLI
+    requests: Option<ProfileRequests>,
+    /// Time of the last request read from the file
+    last_request: Option<DateTime<Utc>>,
+    active: Option<String>,
+    /// Unreadable power_supply is reported once, not every check
+    unavailable_reported: bool,
+    /// Same for an invalid request file
+    invalid_request_reported: bool,
+}
+
+impl PolicyProfiles {
+    pub fn new(base: Config) -> Self {
+        let reader = base
+            .power
+            .enabled
+            .then(|| PowerReader::new(&base.power.sysfs_root));
+        Self {
+            base,
+            reader,
+            last_check: None,
+            power: None,
+            automatic: None,
+            requested: None,
+            request_pending: false,
+            requests: None,
+            last_request: None,
+            active: None,
+            unavailable_reported: false,
+            invalid_request_reported: false,
+        }
+    }
+
+    pub fn base(&self) -> &Config {
+        &self.base
+    }
+
+    /// Active profile, None for the base config
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Record `profile` as active once the config of its switch is applied
+    ///
+    /// Until then, or if applying it fails, the previous profile stays active.
+    pub fn activate(&mut self, profile: Option<String>) {
+        self.active = profile;
+    }
+
+    /// Profile set by the user, None for automatic selection
+    pub fn requested(&self) -> Option<&str> {
+        self.requested.as_deref()
+    }
+
+    /// Power state of the last check, None without `[power]`
+    pub fn power(&self) -> Option<PowerState> {
+        self.power
+    }
+
+    /// Follow the requests of `freezr profile set`
+    ///
+    /// A request already in the file is applied on the next check, so a
+    /// restarted daemon keeps the profile the user chose.
+    pub fn watch_requests(&mut self, requests: ProfileRequests) {
+        self.requests = Some(requests);
+        self.last_request = None;
+    }
+
+    /// Switch to `profile` on the next check, None for automatic selection
+    pub fn request(&mut self, profile: Option<&str>) -> Result<(), String> {
+        if let Some(name) = profile {
+            if !self.base.profiles.contains_key(name) {
+                let known: Vec<&str> = self.base.profiles.keys().map(String::as_str).collect();
+                return Err(format!(
+                    "Unknown profile '{}' (configured: {})",
+                    name,
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                ));
+            }
+        }
+
+        self.requested = profile.map(str::to_string);
+        self.request_pending = true;
+        Ok(())
+    }
+
+    /// Read new requests and, if the check interval has passed, the power source
+    ///
+    /// Returns the config to apply if the profile changes.
+    pub fn check(&mut self) -> Option<ProfileSwitch> {
+        self.read_request();
+
+        if self.request_pending {
+            self.request_pending = false;
+            return self.resolve(SwitchReason::Request);
+        }
+
+        let now = Instant::now();
+        let interval = Duration::from_secs(self.base.power.check_interval_secs);
+        if self.reader.is_none()
+            || self
+                .last_check
+                .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return None;
+        }
+        self.last_check = Some(now);
+
+        match self.reader.as_ref()?.read_state() {
+            Ok(state) => self.select(state),
+            Err(e) => {
+                if !self.unavailable_reported {
+                    warn!(
+                        "Power source unavailable, keeping the current profile: {}",
+                        e
+                    );
+                    self.unavailable_reported = true;
+                }
+                None
+            }
+        }
+    }
+
+    /// Pick the profile for a power state
+    ///
+    /// Without effect while a requested profile is active.
+    pub fn select(&mut self, state: PowerState) -> Option<ProfileSwitch> {
+        self.power = Some(state);
+        self.automatic = self.base.power.profile_for(&state).map(str::to_string);
+        self.resolve(SwitchReason::Power)
+    }
+
+    /// Take over a new request from the request file
+    fn read_request(&mut self) {
+        let Some(requests) = &self.requests else {
+            return;
+        };
+
+        let request = match requests.load() {
+            Ok(Some(request)) => request,
+            Ok(None) => return,
+            Err(e) => {
+                if !self.invalid_request_reported {
+                    warn!("Ignoring profile request: {}", e);
+                    self.invalid_request_reported = true;
+                }
+                return;
+            }
+        };
+        self.invalid_request_reported = false;
+        if self.last_request == Some(request.requested_at) {
+            return;
+        }
+        self.last_request = Some(request.requested_at);
+
+        if let Err(e) = self.request(request.profile.as_deref()) {
+            error!("Ignoring profile request: {}", e);
+        }
+    }
+
+    /// Build the switch to the requested or automatic profile
+    fn resolve(&mut self, reason: SwitchReason) -> Option<ProfileSwitch> {
+        let wanted = self.requested.clone().or_else(|| self.automatic.clone());
+        if wanted == self.active {
+            return None;
+        }
+
+        let config = match &wanted {
+            Some(name) => self.base.with_profile(name),
+            None => Ok(self.base.clone()),
+        };
+        match config {
+            Ok(config) => Some(ProfileSwitch {
+                from: self.active.clone(),
+                profile: wanted,
+                config,
+                reason,
+            }),
+            Err(e) => {
+                // Validated at load, only reachable with a hand-built config
+                error!("Failed to apply profile: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freezr_core::PowerSource;
+
+    fn profiles() -> PolicyProfiles {
+        let config: Config = toml::from_str(
+            r#"
+            [kesl]
+            cpu_threshold = 30.0
+            memory_threshold_mb = 600
+            max_violations = 3
+            service_name = "kesl"
+            enabled = true
+
+            [node]
+            cpu_threshold = 80.0
+            enabled = true
+            auto_kill = false
+            confirm_kill = false
+
+            [power]
+            enabled = true
+            sysfs_root = "/nonexistent"
+            battery_profile = "battery"
+            low_battery_profile = "saver"
+            low_battery_percent = 15
+
+            [profiles.battery.kesl]
+            cpu_threshold = 15.0
+
+            [profiles.saver.node]
+            enabled = false
+
+            [profiles.gaming.node]
+            cpu_threshold = 95.0
+
+            [logging]
+            log_dir = "./logs"
+            kesl_log = "kesl.log"
+            node_log = "node.log"
+            actions_log = "actions.log"
+            max_file_size_mb = 10
+            rotate_count = 5
+
+            [monitoring]
+            check_interval_secs = 3
+            min_restart_interval_secs = 100
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        PolicyProfiles::new(config)
+    }
+
+    fn state(source: PowerSource, battery_percent: u8) -> PowerState {
+        PowerState {
+            source,
+            battery_percent: Some(battery_percent),
+        }
+    }
+
+    #[test]
+    fn test_switches_with_power_source() {
+        let mut profiles = profiles();
+
+        // Base config on AC: nothing to switch
+        assert!(profiles.select(state(PowerSource::Ac, 80)).is_none());
+        assert_eq!(profiles.active(), None);
+
+        let switch = profiles.select(state(PowerSource::Battery, 80)).unwrap();
+        assert_eq!(switch.profile.as_deref(), Some("battery"));
+        assert_eq!(switch.config.kesl.cpu_threshold, 15.0);
+        // Active only once the monitor applied it
+        assert_eq!(profiles.active(), None);
+        profiles.activate(switch.profile);
+        assert!(profiles.select(state(PowerSource::Battery, 50)).is_none());
+
+        let switch = profiles.select(state(PowerSource::Battery, 10)).unwrap();
+        profiles.activate(switch.profile.clone());
+        assert_eq!(switch.profile.as_deref(), Some("saver"));
+        assert!(!switch.config.node.enabled);
+        assert_eq!(switch.config.kesl.cpu_threshold, 30.0);
+
+        let switch = profiles.select(state(PowerSource::Ac, 12)).unwrap();
+        assert_eq!(switch.profile, None);
+        assert!(switch.config.node.enabled);
+        assert_eq!(profiles.power().unwrap().source, PowerSource::Ac);
+    }
+
+    #[test]
+    fn test_request_wins_over_power_source() {
+        let mut profiles = profiles();
+        let battery = state(PowerSource::Battery, 80);
+
+        assert!(profiles.request(Some("work")).is_err());
+        assert!(profiles.check().is_none());
+
+        profiles.request(Some("gaming")).unwrap();
+        assert_eq!(profiles.requested(), Some("gaming"));
+        // Not applied before the next check
+        assert_eq!(profiles.active(), None);
+        let switch = profiles.check().unwrap();
+        assert_eq!(switch.reason, SwitchReason::Request);
+        assert_eq!(switch.from, None);
+        assert_eq!(switch.profile.as_deref(), Some("gaming"));
+        assert_eq!(switch.config.node.cpu_threshold, 95.0);
+        profiles.activate(switch.profile);
+
+        // Power source changes are remembered, not applied
+        assert!(profiles.select(battery).is_none());
+        assert_eq!(profiles.active(), Some("gaming"));
+
+        profiles.request(None).unwrap();
+        let switch = profiles.check().unwrap();
+        assert_eq!(switch.from.as_deref(), Some("gaming"));
+        assert_eq!(switch.profile.as_deref(), Some("battery"));
+        profiles.activate(switch.profile);
+
+        // Requests written by the CLI
+        let dir = std::env::temp_dir().join(format!("freezr-profiles-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let requests = ProfileRequests::new(dir.join("profile.json"));
+        requests.request(Some("saver")).unwrap();
+        profiles.watch_requests(requests.clone());
+        let switch = profiles.check().unwrap();
+        assert_eq!(switch.profile.as_deref(), Some("saver"));
+        profiles.activate(switch.profile);
+        assert!(profiles.check().is_none());
+
+        requests.request(Some("unknown")).unwrap();
+        assert!(profiles.check().is_none());
+        assert_eq!(profiles.active(), Some("saver"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,660 @@
+//! Declarative rule engine
+//!
+//! Drives all `[[rules]]` from the config: matches processes, counts
+//! consecutive violations per ladder step and executes the step's action
+//! when its violation limit is reached.
+
+use crate::config::{RuleConfig, RuleMatcher, RuleStep};
+use crate::freezer::FreezeScheduler;
+use crate::stats::{RuleStats, RuleStepStats};
+use crate::throttle::Throttler;
+use freezr_core::{
+    error::{Error, Result},
+    executor::ProcessExecutor,
+    types::{MonitorStats, ProcessInfo},
+};
+use regex::Regex;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use tracing::{debug, error, info, warn};
+
+/// Matcher with compiled regex and resolved UID
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledMatcher {
+    name: Option<String>,
+    cmdline: Option<Regex>,
+    exe: Option<PathBuf>,
+    uid: Option<u32>,
+    cgroup: Option<String>,
+}
+
+impl CompiledMatcher {
+    pub(crate) fn compile(matcher: &RuleMatcher) -> Result<Self> {
+        let cmdline = matcher
+            .cmdline
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| Error::Other(format!("Invalid cmdline regex: {}", e)))?;
+
+        let uid = matcher.user.as_deref().map(resolve_uid).transpose()?;
+
+        Ok(Self {
+            name: matcher.name.clone(),
+            cmdline,
+            exe: matcher.exe.clone(),
+            uid,
+            cgroup: matcher.cgroup.clone(),
+        })
+    }
+
+    /// Check process against all criteria
+    ///
+    /// Cheap criteria (name, cmdline) are checked first, /proc is read
+    /// only for processes that passed them.
+    pub(crate) fn matches(&self, process: &ProcessInfo) -> bool {
+        if let Some(name) = &self.name {
+            if &process.name != name {
+                return false;
+            }
+        }
+
+        if let Some(cmdline) = &self.cmdline {
+            if !cmdline.is_match(&process.command) {
+                return false;
+            }
+        }
+
+        let proc_dir = PathBuf::from(format!("/proc/{}", process.pid));
+
+        if let Some(exe) = &self.exe {
+            match fs::read_link(proc_dir.join("exe")) {
+                Ok(path) if &path == exe => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(uid) = self.uid {
+            match fs::metadata(&proc_dir) {
+                Ok(meta) if meta.uid() == uid => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(cgroup) = &self.cgroup {
+            match fs::read_to_string(proc_dir.join("cgroup")) {
+                Ok(content) if content.contains(cgroup.as_str()) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+/// Resolve user name or numeric UID via /etc/passwd
+fn resolve_uid(user: &str) -> Result<u32> {
+    if let Ok(uid) = user.parse::<u32>() {
+        return Ok(uid);
+    }
+
+    let passwd = fs::read_to_string("/etc/passwd")?;
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse::<u32>().ok()?;
+            Some((name, uid))
+        })
+        .find(|(name, _)| *name == user)
+        .map(|(_, uid)| uid)
+        .ok_or_else(|| Error::Other(format!("Unknown user: {}", user)))
+}
+
+/// Step of the ladder that has to be executed in this cycle
+#[derive(Debug, Clone, PartialEq)]
+struct Escalation {
+    step: usize,
+    pids: Vec<u32>,
+}
+
+/// Runtime state of one rule
+struct Rule {
+    config: RuleConfig,
+    matcher: CompiledMatcher,
+    /// Consecutive violations per ladder step
+    violations: Vec<u32>,
+    /// Executed actions per ladder step
+    total_actions: Vec<u32>,
+    /// Processes matched in the last cycle
+    matched: usize,
+}
+
+impl Rule {
+    fn new(config: RuleConfig) -> Result<Self> {
+        let matcher = CompiledMatcher::compile(&config.matcher)
+            .map_err(|e| Error::Other(format!("Rule '{}': {}", config.name, e)))?;
+        let steps = config.actions.len();
+
+        Ok(Self {
+            config,
+            matcher,
+            violations: vec![0; steps],
+            total_actions: vec![0; steps],
+            matched: 0,
+        })
+    }
+
+    /// Take over the counters of the same rule before a config change
+    ///
+    /// Counters of ladder steps that no longer exist are dropped.
+    fn keep_counters(&mut self, previous: &Rule) {
+        for (counter, &old) in self.violations.iter_mut().zip(&previous.violations) {
+            *counter = old;
+        }
+        for (counter, &old) in self.total_actions.iter_mut().zip(&previous.total_actions) {
+            *counter = old;
+        }
+    }
+
+    /// Update violation counters for matched processes
+    ///
+    /// Only the most severe violated step counts: its counter grows, counters
+    /// of more severe steps reset. When the step reaches its limit, it and all
+    /// milder steps reset and the step is returned for execution.
+    fn evaluate(&mut self, processes: &[&ProcessInfo]) -> Option<Escalation> {
+        self.matched = processes.len();
+
+        let violated = self
+            .config
+            .actions
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(idx, step)| {
+                let pids: Vec<u32> = processes
+                    .iter()
+                    .filter(|p| step_exceeded(step, p))
+                    .map(|p| p.pid)
+                    .collect();
+                (!pids.is_empty()).then_some((idx, pids))
+            });
+
+        let (idx, pids) = match violated {
+            Some(v) => v,
+            None => {
+                // Back to normal (or no processes), reset violations
+                if self.violations.iter().any(|&v| v > 0) {
+                    debug!(
+                        "Rule '{}' back to normal, resetting violations {:?}",
+                        self.config.name, self.violations
+                    );
+                    self.violations.iter_mut().for_each(|v| *v = 0);
+                }
+                return None;
+            }
+        };
+
+        self.violations[idx] += 1;
+        self.violations[idx + 1..].iter_mut().for_each(|v| *v = 0);
+
+        let step = &self.config.actions[idx];
+        warn!(
+            "Rule '{}' violation #{} ({}): {} processes exceed {}",
+            self.config.name,
+            self.violations[idx],
+            step.action,
+            pids.len(),
+            describe_thresholds(step)
+        );
+
+        if self.violations[idx] < step.max_violations {
+            return None;
+        }
+
+        self.violations[..=idx].iter_mut().for_each(|v| *v = 0);
+        self.total_actions[idx] += 1;
+        Some(Escalation { step: idx, pids })
+    }
+
+    fn stats(&self) -> RuleStats {
+        RuleStats {
+            name: self.config.name.clone(),
+            enabled: self.config.enabled,
+            matched_processes: self.matched,
+            steps: self
+                .config
+                .actions
+                .iter()
+                .zip(self.violations.iter().zip(&self.total_actions))
+                .map(|(step, (&violations, &total_actions))| RuleStepStats {
+                    action: step.action.clone(),
+                    cpu_threshold: step.cpu_threshold,
+                    memory_threshold_mb: step.memory_threshold_mb,
+                    violations,
+                    max_violations: step.max_violations,
+                    total_actions,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Process exceeds any threshold of the step
+fn step_exceeded(step: &RuleStep, process: &ProcessInfo) -> bool {
+    step.cpu_threshold.is_some_and(|t| process.cpu_percent > t)
+        || step
+            .memory_threshold_mb
+            .is_some_and(|t| process.memory_mb > t)
+}
+
+fn describe_thresholds(step: &RuleStep) -> String {
+    match (step.cpu_threshold, step.memory_threshold_mb) {
+        (Some(cpu), Some(mem)) => format!("CPU {:.1}% or {}MB", cpu, mem),
+        (Some(cpu), None) => format!("CPU {:.1}%", cpu),
+        (None, Some(mem)) => format!("{}MB", mem),
+        (None, None) => "no threshold".to_string(),
+    }
+}
+
+/// Engine evaluating all enabled rules on each monitoring cycle
+#[derive(Default)]
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+}
+
+impl RuleEngine {
+    /// Build engine from config (disabled rules are skipped)
+    pub fn new(configs: &[RuleConfig]) -> Result<Self> {
+        let rules = configs
+            .iter()
+            .filter(|config| config.enabled)
+            .cloned()
+            .map(Rule::new)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Replace the rules, keeping the counters of rules that still exist
+    ///
+    /// Rules are matched by name. On error the current rules stay in place.
+    pub fn replace(&mut self, configs: &[RuleConfig]) -> Result<()> {
+        *self = self.rebuilt(configs)?;
+        Ok(())
+    }
+
+    /// New engine for `configs` with the counters of rules that still exist
+    pub fn rebuilt(&self, configs: &[RuleConfig]) -> Result<Self> {
+        let mut engine = Self::new(configs)?;
+        for rule in &mut engine.rules {
+            let name = &rule.config.name;
+            if let Some(previous) = self.rules.iter().find(|r| &r.config.name == name) {
+                rule.keep_counters(previous);
+            }
+        }
+        Ok(engine)
+    }
+
+    /// Any rule to evaluate?
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Names of active rules
+    pub fn rule_names(&self) -> Vec<&str> {
+        self.rules.iter().map(|r| r.config.name.as_str()).collect()
+    }
+
+    /// Evaluate all rules against one scan of the process list
+    pub fn check(
+        &mut self,
+        processes: &[ProcessInfo],
+        stats: &mut MonitorStats,
+        freezer: &FreezeScheduler,
+        throttler: &Throttler,
+    ) {
+        for rule in &mut self.rules {
+            let matched: Vec<&ProcessInfo> = processes
+                .iter()
+                .filter(|p| rule.matcher.matches(p))
+                .collect();
+
+            debug!(
+                "Rule '{}': {} processes matched",
+                rule.config.name,
+                matched.len()
+            );
+
+            if let Some(escalation) = rule.evaluate(&matched) {
+                let step = &rule.config.actions[escalation.step];
+                error!(
+                    "Rule '{}' max violations ({}) reached, taking action: {}",
+                    rule.config.name, step.max_violations, step.action
+                );
+
+                for process in matched.iter().filter(|p| escalation.pids.contains(&p.pid)) {
+                    execute_step(&rule.config.name, step, process, stats, freezer, throttler);
+                }
+            }
+        }
+    }
+
+    /// Per-rule statistics for dashboard
+    pub fn stats(&self) -> Vec<RuleStats> {
+        self.rules.iter().map(Rule::stats).collect()
+    }
+}
+
+/// Execute ladder step action on one process
+fn execute_step(
+    rule: &str,
+    step: &RuleStep,
+    process: &ProcessInfo,
+    stats: &mut MonitorStats,
+    freezer: &FreezeScheduler,
+    throttler: &Throttler,
+) {
+    use std::time::Duration;
+
+    match step.action.as_str() {
+        "log" => {
+            warn!(
+                "[{}] PID {} CPU {:.1}% RAM {}MB, Command: {}",
+                rule, process.pid, process.cpu_percent, process.memory_mb, process.command
+            );
+        }
+        "nice" => {
+            info!(
+                "[{}] Setting nice level {} for PID {}",
+                rule, step.nice_level, process.pid
+            );
+            if let Err(e) = process
+                .handle()
+                .and_then(|h| ProcessExecutor::renice_process(&h, step.nice_level))
+            {
+                error!("[{}] Failed to renice process {}: {}", rule, process.pid, e);
+            }
+        }
+        "throttle" => {
+            info!(
+                "[{}] Throttling PID {} (CPU {:.1}%, RAM {}MB)",
+                rule, process.pid, process.cpu_percent, process.memory_mb
+            );
+            let limits = step.limits();
+            match process
+                .handle()
+                .and_then(|h| throttler.throttle_with(h, &process.name, limits))
+            {
+                Ok(outcome) => debug!("[{}] Process {}: {:?}", rule, process.pid, outcome),
+                Err(e) => error!(
+                    "[{}] Failed to throttle process {}: {}",
+                    rule, process.pid, e
+                ),
+            }
+        }
+        "freeze" => {
+            info!(
+                "[{}] Freezing PID {} for {} seconds (CPU {:.1}%)",
+                rule, process.pid, step.freeze_duration_secs, process.cpu_percent
+            );
+            let duration = Duration::from_secs(step.freeze_duration_secs);
+            match process
+                .handle()
+                .and_then(|h| freezer.freeze(h, &process.name, duration))
+            {
+                Ok(outcome) => debug!("[{}] Process {}: {:?}", rule, process.pid, outcome),
+                Err(e) => error!("[{}] Failed to freeze process {}: {}", rule, process.pid, e),
+            }
+        }
+        "kill" => {
+            info!(
+                "[{}] Killing PID {} (CPU {:.1}%)",
+                rule, process.pid, process.cpu_percent
+            );
+            if let Err(e) = process
+                .handle()
+                .and_then(|h| ProcessExecutor::kill_process(&h))
+            {
+                error!("[{}] Failed to kill process {}: {}", rule, process.pid, e);
+            } else {
+                stats.record_kill();
+                info!("[{}] Successfully killed process {}", rule, process.pid);
+            }
+        }
+        other => {
+            warn!("[{}] Unknown action: {}", rule, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, command: &str, cpu: f64) -> ProcessInfo {
+        ProcessInfo::new(pid, name.to_string(), command.to_string(), cpu, 100 * 1024)
+    }
+
+    fn firefox_rule() -> Rule {
+        Rule::new(RuleConfig::two_tier("firefox", "^\\S*firefox", 80.0, 95.0)).unwrap()
+    }
+
+    #[test]
+    fn test_matcher_name_and_cmdline() {
+        let matcher = CompiledMatcher::compile(&RuleMatcher {
+            name: Some("code".to_string()),
+            cmdline: Some("--type=renderer".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(matcher.matches(&process(
+            1,
+            "code",
+            "/usr/share/code/code --type=renderer",
+            0.0
+        )));
+        assert!(!matcher.matches(&process(1, "code", "/usr/share/code/code", 0.0)));
+        assert!(!matcher.matches(&process(1, "slack", "slack --type=renderer", 0.0)));
+    }
+
+    #[test]
+    fn test_matcher_proc_criteria_on_self() {
+        let pid = std::process::id();
+        let exe = std::env::current_exe().unwrap();
+        let uid = fs::metadata("/proc/self").unwrap().uid();
+        let me = process(pid, "test", "test", 0.0);
+
+        let matcher = CompiledMatcher::compile(&RuleMatcher {
+            exe: Some(exe),
+            user: Some(uid.to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(matcher.matches(&me));
+
+        let matcher = CompiledMatcher::compile(&RuleMatcher {
+            exe: Some(PathBuf::from("/nonexistent/binary")),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!matcher.matches(&me));
+    }
+
+    #[test]
+    fn test_matcher_invalid_regex() {
+        let result = CompiledMatcher::compile(&RuleMatcher {
+            cmdline: Some("(".to_string()),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_resolve_uid() {
+        assert_eq!(resolve_uid("1000").unwrap(), 1000);
+        assert_eq!(resolve_uid("root").unwrap(), 0);
+        assert!(resolve_uid("no-such-user-freezr").is_err());
+    }
+
+    #[test]
+    fn test_ladder_freeze_after_max_violations() {
+        let mut rule = firefox_rule();
+        let p = process(10, "firefox", "/usr/bin/firefox", 85.0);
+
+        assert_eq!(rule.evaluate(&[&p]), None);
+        assert_eq!(rule.violations, vec![1, 0]);
+
+        assert_eq!(
+            rule.evaluate(&[&p]),
+            Some(Escalation {
+                step: 0,
+                pids: vec![10]
+            })
+        );
+        assert_eq!(rule.violations, vec![0, 0]);
+        assert_eq!(rule.total_actions, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_ladder_kill_tier_takes_precedence() {
+        let mut rule = firefox_rule();
+        let high = process(10, "firefox", "/usr/bin/firefox", 85.0);
+        let critical = process(11, "firefox", "/usr/bin/firefox -contentproc", 99.0);
+
+        rule.evaluate(&[&high, &critical]);
+        rule.evaluate(&[&high, &critical]);
+        let escalation = rule.evaluate(&[&high, &critical]).unwrap();
+
+        // Only the critical process is killed; freeze counter untouched by kill tier
+        assert_eq!(escalation.step, 1);
+        assert_eq!(escalation.pids, vec![11]);
+        assert_eq!(rule.violations, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_ladder_lower_tier_resets_higher() {
+        let mut rule = firefox_rule();
+        let critical = process(10, "firefox", "firefox", 99.0);
+        let high = process(10, "firefox", "firefox", 85.0);
+
+        rule.evaluate(&[&critical]);
+        rule.evaluate(&[&critical]);
+        assert_eq!(rule.violations, vec![0, 2]);
+
+        rule.evaluate(&[&high]);
+        assert_eq!(rule.violations, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_ladder_resets_when_normal() {
+        let mut rule = firefox_rule();
+        let high = process(10, "firefox", "firefox", 85.0);
+        let idle = process(10, "firefox", "firefox", 5.0);
+
+        rule.evaluate(&[&high]);
+        assert_eq!(rule.evaluate(&[&idle]), None);
+        assert_eq!(rule.violations, vec![0, 0]);
+
+        // Processes gone
+        rule.evaluate(&[&high]);
+        rule.evaluate(&[]);
+        assert_eq!(rule.violations, vec![0, 0]);
+        assert_eq!(rule.matched, 0);
+    }
+
+    #[test]
+    fn test_memory_threshold() {
+        let step = RuleStep {
+            cpu_threshold: None,
+            memory_threshold_mb: Some(50),
+            max_violations: 1,
+            action: "log".to_string(),
+            nice_level: 15,
+            freeze_duration_secs: 5,
+            io_read_mbps: None,
+            io_write_mbps: None,
+            io_device: None,
+            cpuset_cpus: None,
+        };
+
+        assert!(step_exceeded(&step, &process(1, "a", "a", 0.0))); // 100MB
+        assert!(!step_exceeded(
+            &step,
+            &ProcessInfo::new(1, "a".into(), "a".into(), 99.0, 10 * 1024)
+        ));
+    }
+
+    #[test]
+    fn test_engine_skips_disabled_rules() {
+        let mut disabled = RuleConfig::two_tier("brave", "brave", 80.0, 95.0);
+        disabled.enabled = false;
+        let configs = vec![
+            RuleConfig::two_tier("firefox", "firefox", 80.0, 95.0),
+            disabled,
+        ];
+
+        let engine = RuleEngine::new(&configs).unwrap();
+        assert_eq!(engine.rule_names(), vec!["firefox"]);
+        assert_eq!(engine.stats().len(), 1);
+        assert_eq!(engine.stats()[0].steps.len(), 2);
+    }
+
+    #[test]
+    fn test_engine_log_action() {
+        let mut config = RuleConfig::two_tier("test", "^fake-app", 10.0, 1000.0);
+        config.actions[0].action = "log".to_string();
+        config.actions[0].max_violations = 1;
+
+        let mut engine = RuleEngine::new(&[config]).unwrap();
+        let mut stats = MonitorStats::new();
+        let processes = vec![process(999_999, "fake-app", "fake-app --run", 50.0)];
+
+        engine.check(
+            &processes,
+            &mut stats,
+            &FreezeScheduler::new(),
+            &Throttler::new(),
+        );
+
+        let rule_stats = engine.stats();
+        assert_eq!(rule_stats[0].matched_processes, 1);
+        assert_eq!(rule_stats[0].steps[0].total_actions, 1);
+        assert_eq!(stats.total_kills, 0);
+    }
+
+    #[test]
+    fn test_engine_replace_keeps_counters() {
+        let mut config = RuleConfig::two_tier("test", "^fake-app", 10.0, 1000.0);
+        config.actions[0].action = "log".to_string();
+        config.actions[0].max_violations = 1;
+        let other = RuleConfig::two_tier("other", "^other-app", 10.0, 1000.0);
+
+        let mut engine = RuleEngine::new(&[config.clone(), other]).unwrap();
+        let processes = vec![process(999_999, "fake-app", "fake-app --run", 50.0)];
+        engine.check(
+            &processes,
+            &mut MonitorStats::new(),
+            &FreezeScheduler::new(),
+            &Throttler::new(),
+        );
+
+        // New threshold for "test", "other" removed, "added" is new
+        config.actions[0].cpu_threshold = Some(20.0);
+        let added = RuleConfig::two_tier("added", "^added-app", 10.0, 1000.0);
+        engine.replace(&[config, added]).unwrap();
+
+        let stats = engine.stats();
+        assert_eq!(engine.rule_names(), vec!["test", "added"]);
+        assert_eq!(stats[0].steps[0].total_actions, 1);
+        assert_eq!(stats[1].steps[0].total_actions, 0);
+
+        // Invalid rules leave the current ones in place
+        let invalid = RuleConfig::two_tier("broken", "(", 10.0, 1000.0);
+        assert!(engine.replace(&[invalid]).is_err());
+        assert_eq!(engine.rule_names(), vec!["test", "added"]);
+    }
+}
@@ -0,0 +1,238 @@
+//! Detection of runaway process spawners
+//!
+//! Fork bombs, broken shell loops and `make -j` without a job limit show up
+//! as a process subtree that grows by many processes between two checks.
+//! The guard picks the deepest such subtree and contains it by capping its
+//! tasks (`pids.max`) through the [`Throttler`] instead of killing it.
+
+use crate::config::SpawnGuardConfig;
+use crate::throttle::{ThrottleOutcome, Throttler};
+use freezr_core::cgroups::{list_processes, ProcEntry};
+use freezr_core::ProcessHandle;
+use std::collections::HashMap;
+use tracing::{debug, error, warn};
+
+/// Subtree that grew past the threshold since the previous check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnBurst {
+    /// Root of the subtree
+    pub pid: u32,
+    pub name: String,
+    /// Processes in the subtree, root included
+    pub processes: usize,
+    /// Processes added since the previous check
+    pub growth: usize,
+}
+
+/// Finds fast-growing process subtrees and caps their tasks
+pub struct SpawnGuard {
+    config: SpawnGuardConfig,
+    /// Subtree size of every process at the previous check
+    /// (None until the first check: everything would look new)
+    previous: Option<HashMap<u32, usize>>,
+    total_contained: u64,
+}
+
+impl SpawnGuard {
+    pub fn new(config: SpawnGuardConfig) -> Self {
+        Self {
+            config,
+            previous: None,
+            total_contained: 0,
+        }
+    }
+
+    /// Replace the thresholds, keeping the previous check's subtree sizes
+    pub fn set_config(&mut self, config: SpawnGuardConfig) {
+        self.config = config;
+    }
+
+    /// Scan /proc and contain every runaway subtree
+    ///
+    /// Returns the number of subtrees contained by this check.
+    pub fn check(&mut self, throttler: &Throttler) -> usize {
+        let bursts = self.detect(&list_processes());
+
+        let mut contained = 0;
+        for burst in bursts {
+            warn!(
+                "Process {} ({}) spawned {} processes since last check ({} in its tree)",
+                burst.pid, burst.name, burst.growth, burst.processes
+            );
+
+            let outcome = ProcessHandle::open(burst.pid)
+                .and_then(|h| throttler.contain(h, &burst.name, self.config.pids_headroom));
+            match outcome {
+                Ok(ThrottleOutcome::Reniced) => {
+                    warn!(
+                        "No cgroups to cap tasks of process {}, reniced it instead",
+                        burst.pid
+                    );
+                }
+                Ok(outcome) => {
+                    debug!("Spawner {}: {:?}", burst.pid, outcome);
+                    contained += 1;
+                }
+                Err(e) => error!("Failed to contain process tree of {}: {}", burst.pid, e),
+            }
+        }
+
+        self.total_contained += contained as u64;
+        contained
+    }
+
+    /// Subtrees that grew by at least `growth_threshold` since the last call
+    ///
+    /// Only the deepest growing subtree is reported: its ancestors grew by
+    /// the same processes. Bursts rooted at an excluded process, PID 1,
+    /// kthreadd or the daemon itself are ignored.
+    pub fn detect(&mut self, table: &[ProcEntry]) -> Vec<SpawnBurst> {
+        let sizes = subtree_sizes(table);
+        let Some(previous) = self.previous.replace(sizes.clone()) else {
+            return Vec::new();
+        };
+
+        let growth = |pid: u32| {
+            let before = previous.get(&pid).copied().unwrap_or(0);
+            sizes[&pid].saturating_sub(before)
+        };
+        let growing = |pid: u32| growth(pid) >= self.config.growth_threshold;
+
+        let own_pid = std::process::id();
+        table
+            .iter()
+            .filter(|entry| growing(entry.pid))
+            // Deepest: no child grew past the threshold by itself
+            .filter(|entry| {
+                !table
+                    .iter()
+                    .any(|child| child.ppid == entry.pid && growing(child.pid))
+            })
+            .filter(|entry| entry.pid > 2 && entry.pid != own_pid)
+            .filter(|entry| !self.config.exclude.contains(&entry.name))
+            .map(|entry| SpawnBurst {
+                pid: entry.pid,
+                name: entry.name.clone(),
+                processes: sizes[&entry.pid],
+                growth: growth(entry.pid),
+            })
+            .collect()
+    }
+
+    /// Subtrees contained since start
+    pub fn total_contained(&self) -> u64 {
+        self.total_contained
+    }
+}
+
+/// Number of processes in the subtree of every process (itself included)
+fn subtree_sizes(table: &[ProcEntry]) -> HashMap<u32, usize> {
+    let parents: HashMap<u32, u32> = table.iter().map(|e| (e.pid, e.ppid)).collect();
+    let mut sizes: HashMap<u32, usize> = table.iter().map(|e| (e.pid, 0)).collect();
+
+    // Count each process in itself and in every ancestor
+    for entry in table {
+        let mut current = entry.pid;
+        // Bounded walk: the table is a snapshot, parents may be stale
+        for _ in 0..table.len() {
+            match sizes.get_mut(&current) {
+                Some(size) => *size += 1,
+                None => break,
+            }
+            match parents.get(&current) {
+                Some(&parent) if parent != current => current = parent,
+                _ => break,
+            }
+        }
+    }
+
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, ppid: u32, name: &str) -> ProcEntry {
+        ProcEntry {
+            pid,
+            ppid,
+            name: name.to_string(),
+        }
+    }
+
+    fn guard(threshold: usize) -> SpawnGuard {
+        SpawnGuard::new(SpawnGuardConfig {
+            growth_threshold: threshold,
+            ..SpawnGuardConfig::default()
+        })
+    }
+
+    /// init -> bash -> make, with `workers` children of make
+    fn session(workers: u32) -> Vec<ProcEntry> {
+        let mut table = vec![
+            entry(1, 0, "systemd"),
+            entry(100, 1, "bash"),
+            entry(200, 100, "make"),
+        ];
+        table.extend((0..workers).map(|i| entry(1000 + i, 200, "cc1")));
+        table
+    }
+
+    #[test]
+    fn test_subtree_sizes() {
+        let sizes = subtree_sizes(&session(3));
+        assert_eq!(sizes[&1], 6);
+        assert_eq!(sizes[&100], 5);
+        assert_eq!(sizes[&200], 4);
+        assert_eq!(sizes[&1000], 1);
+    }
+
+    #[test]
+    fn test_first_check_only_records() {
+        let mut guard = guard(10);
+        assert!(guard.detect(&session(50)).is_empty());
+        assert!(guard.detect(&session(50)).is_empty());
+    }
+
+    #[test]
+    fn test_detects_deepest_growing_subtree() {
+        let mut guard = guard(10);
+        guard.detect(&session(2));
+
+        let bursts = guard.detect(&session(40));
+        assert_eq!(
+            bursts,
+            vec![SpawnBurst {
+                pid: 200,
+                name: "make".to_string(),
+                processes: 41,
+                growth: 38,
+            }]
+        );
+
+        // Same size at the next check: no longer growing
+        assert!(guard.detect(&session(40)).is_empty());
+    }
+
+    #[test]
+    fn test_slow_growth_is_ignored() {
+        let mut guard = guard(10);
+        guard.detect(&session(2));
+        assert!(guard.detect(&session(8)).is_empty());
+    }
+
+    #[test]
+    fn test_excluded_and_init_are_ignored() {
+        // Orphans of a fork bomb end up under init or a user manager
+        let orphans = |count: u32| {
+            let mut table = vec![entry(1, 0, "systemd"), entry(50, 1, "systemd")];
+            table.extend((0..count).map(|i| entry(1000 + i, 50, "bash")));
+            table
+        };
+
+        let mut guard = guard(10);
+        guard.detect(&orphans(1));
+        assert!(guard.detect(&orphans(30)).is_empty());
+    }
+}
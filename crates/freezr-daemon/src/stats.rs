@@ -27,18 +27,25 @@ pub struct MonitorStats {
     /// Snap statistics
     pub snap: SnapStats,
 
-    /// Firefox statistics
-    pub firefox: BrowserStats,
+    /// Per-rule statistics (`[[rules]]`)
+    #[serde(default)]
+    pub rules: Vec<RuleStats>,
 
-    /// Brave statistics
-    pub brave: BrowserStats,
+    /// Throttle cgroup statistics
+    #[serde(default)]
+    pub throttle: ThrottleStats,
 
-    /// Telegram statistics
-    pub telegram: BrowserStats,
+    /// Static cgroups (`[[cgroups.static_groups]]`) with their members
+    #[serde(default)]
+    pub static_cgroups: Vec<StaticCgroupStats>,
 
     /// Memory pressure statistics
     pub memory_pressure: MemoryPressureStats,
 
+    /// Temperature sensors and thermal actions
+    #[serde(default)]
+    pub thermal: ThermalStats,
+
     /// System health
     pub system_health: SystemHealth,
 
@@ -82,18 +89,56 @@ pub struct SnapStats {
     pub total_actions: u32,
 }
 
-/// Browser/Telegram statistics
+/// Rule statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleStats {
+    pub name: String,
+    pub enabled: bool,
+    pub matched_processes: usize,
+    pub steps: Vec<RuleStepStats>,
+}
+
+/// Statistics of one action ladder step
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BrowserStats {
+pub struct RuleStepStats {
+    pub action: String,
+    pub cpu_threshold: Option<f64>,
+    pub memory_threshold_mb: Option<u64>,
+    pub violations: u32,
+    pub max_violations: u32,
+    pub total_actions: u32,
+}
+
+/// Throttle statistics (processes held in dynamic cgroups)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThrottleStats {
+    /// Throttle cgroups available (otherwise processes are reniced)
     pub enabled: bool,
-    pub freeze_threshold: f64,
-    pub kill_threshold: f64,
-    pub freeze_violations: u32,
-    pub kill_violations: u32,
-    pub max_violations_freeze: u32,
-    pub max_violations_kill: u32,
-    pub total_freezes: u32,
-    pub total_kills: u32,
+    pub throttled_processes: usize,
+    pub total_throttled: u64,
+    pub total_released: u64,
+    pub cooldown_secs: u64,
+    /// Runaway spawners whose tasks were capped (spawn guard)
+    #[serde(default)]
+    pub contained_spawners: u64,
+    /// Limits follow load and memory pressure (adaptive mode)
+    #[serde(default)]
+    pub adaptive: bool,
+    /// Limit changes made in adaptive mode
+    #[serde(default)]
+    pub total_adjusted: u64,
+}
+
+/// Static cgroup membership and limits
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StaticCgroupStats {
+    pub name: String,
+    pub pids: Vec<u32>,
+    pub cpu_limit_percent: Option<f64>,
+    pub memory_max_mb: Option<u64>,
+    pub memory_high_mb: Option<u64>,
+    /// memory.current of the group (None if unreadable)
+    pub memory_current_mb: Option<u64>,
 }
 
 /// Memory pressure statistics
@@ -111,6 +156,34 @@ pub struct MemoryPressureStats {
     pub full_threshold_critical: f64,
     pub action_warning: String,
     pub action_critical: String,
+    /// Seconds until MemAvailable runs out by its trend (None: not falling)
+    #[serde(default)]
+    pub oom_forecast_secs: Option<f64>,
+    /// Confidence of the forecast, 0-1
+    #[serde(default)]
+    pub oom_forecast_confidence: f64,
+}
+
+/// Thermal monitoring statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThermalStats {
+    pub enabled: bool,
+    /// "OK", "WARNING", "CRITICAL", "UNKNOWN" (no sensors read) or "DISABLED"
+    pub status: String,
+    pub sensors: Vec<SensorStats>,
+    pub warning_count: u32,
+    pub critical_count: u32,
+    pub action_warning: String,
+    pub action_critical: String,
+}
+
+/// Last temperature of one sensor
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SensorStats {
+    pub name: String,
+    pub celsius: f64,
+    pub warning_celsius: f64,
+    pub critical_celsius: f64,
 }
 
 /// System health metrics
@@ -122,6 +195,14 @@ pub struct SystemHealth {
     pub memory_used_percent: f64,
     pub memory_total_mb: u64,
     pub memory_available_mb: u64,
+    /// Profile laid over the config, None for the base config
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    /// "ac" or "battery", None unless power profiles are enabled
+    #[serde(default)]
+    pub power_source: Option<String>,
+    #[serde(default)]
+    pub battery_percent: Option<u8>,
 }
 
 /// Log statistics
@@ -185,22 +266,6 @@ impl Default for SnapStats {
     }
 }
 
-impl Default for BrowserStats {
-    fn default() -> Self {
-        Self {
-            enabled: false,
-            freeze_threshold: 80.0,
-            kill_threshold: 95.0,
-            freeze_violations: 0,
-            kill_violations: 0,
-            max_violations_freeze: 2,
-            max_violations_kill: 3,
-            total_freezes: 0,
-            total_kills: 0,
-        }
-    }
-}
-
 impl Default for MemoryPressureStats {
     fn default() -> Self {
         Self {
@@ -216,6 +281,8 @@ impl Default for MemoryPressureStats {
             full_threshold_critical: 15.0,
             action_warning: "log".to_string(),
             action_critical: "freeze".to_string(),
+            oom_forecast_secs: None,
+            oom_forecast_confidence: 0.0,
         }
     }
 }
@@ -229,6 +296,9 @@ impl Default for SystemHealth {
             memory_used_percent: 0.0,
             memory_total_mb: 0,
             memory_available_mb: 0,
+            active_profile: None,
+            power_source: None,
+            battery_percent: None,
         }
     }
 }
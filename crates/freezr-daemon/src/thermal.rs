@@ -0,0 +1,277 @@
+//! Thermal monitoring
+//!
+//! A [`ThermalMonitor`] reads all thermal zones and hwmon sensors every
+//! `check_interval_secs` and compares each with its `[thermal]` thresholds.
+//! The hottest level reached selects the action, which
+//! [`ResourceMonitor`] applies to the busiest processes: heat follows CPU
+//! load, so niceing, throttling or freezing them is what cools the machine
+//! before the firmware throttles everything.
+//!
+//! [`ResourceMonitor`]: crate::ResourceMonitor
+
+use crate::config::ThermalConfig;
+use crate::pressure::StallLevel;
+use freezr_core::{types::ProcessInfo, ThermalReader, ThermalSensor};
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Last temperature of a sensor with its thresholds
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorReading {
+    pub name: String,
+    pub celsius: f64,
+    pub warning_celsius: f64,
+    pub critical_celsius: f64,
+    pub level: Option<StallLevel>,
+}
+
+/// Threshold checks of all temperature sensors
+pub struct ThermalMonitor {
+    config: ThermalConfig,
+    reader: ThermalReader,
+    /// None until the first check
+    last_check: Option<Instant>,
+    readings: Vec<SensorReading>,
+    warning_count: u32,
+    critical_count: u32,
+    /// Missing sensors are reported once, not every check
+    unavailable_reported: bool,
+}
+
+impl ThermalMonitor {
+    pub fn new(config: ThermalConfig) -> Self {
+        let reader = ThermalReader::new(&config.sysfs_root);
+        Self {
+            config,
+            reader,
+            last_check: None,
+            readings: Vec::new(),
+            warning_count: 0,
+            critical_count: 0,
+            unavailable_reported: false,
+        }
+    }
+
+    pub fn config(&self) -> &ThermalConfig {
+        &self.config
+    }
+
+    /// Replace the thresholds and actions, keeping the counters
+    pub fn set_config(&mut self, config: ThermalConfig) {
+        self.reader = ThermalReader::new(&config.sysfs_root);
+        self.config = config;
+    }
+
+    /// Sensors of the last check, ignored ones left out
+    pub fn readings(&self) -> &[SensorReading] {
+        &self.readings
+    }
+
+    /// Consecutive (warning, critical) readings
+    pub fn counts(&self) -> (u32, u32) {
+        (self.warning_count, self.critical_count)
+    }
+
+    /// Read the sensors if the check interval has passed
+    ///
+    /// Returns the level reached and its configured action.
+    pub fn check(&mut self) -> Option<(StallLevel, String)> {
+        let now = Instant::now();
+        let interval = Duration::from_secs(self.config.check_interval_secs);
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return None;
+        }
+        self.last_check = Some(now);
+
+        match self.reader.read_all() {
+            Ok(sensors) => self.evaluate(&sensors),
+            Err(e) => {
+                if !self.unavailable_reported {
+                    warn!("Temperature sensors unavailable: {}", e);
+                    self.unavailable_reported = true;
+                }
+                None
+            }
+        }
+    }
+
+    /// Compare sensor temperatures with their thresholds
+    pub fn evaluate(&mut self, sensors: &[ThermalSensor]) -> Option<(StallLevel, String)> {
+        self.readings = sensors
+            .iter()
+            .filter_map(|sensor| {
+                let (warning, critical) = self.config.thresholds(&sensor.name)?;
+                let level = if sensor.celsius >= critical {
+                    Some(StallLevel::Critical)
+                } else if sensor.celsius >= warning {
+                    Some(StallLevel::Warning)
+                } else {
+                    None
+                };
+                Some(SensorReading {
+                    name: sensor.name.clone(),
+                    celsius: sensor.celsius,
+                    warning_celsius: warning,
+                    critical_celsius: critical,
+                    level,
+                })
+            })
+            .collect();
+
+        let hottest = self
+            .readings
+            .iter()
+            .filter(|r| r.level.is_some())
+            .max_by(|a, b| {
+                a.level
+                    .cmp(&b.level)
+                    .then(a.celsius.partial_cmp(&b.celsius).unwrap_or(Ordering::Equal))
+            });
+
+        let Some(hottest) = hottest else {
+            if self.warning_count > 0 || self.critical_count > 0 {
+                debug!("Temperatures back below their thresholds");
+                self.warning_count = 0;
+                self.critical_count = 0;
+            }
+            return None;
+        };
+
+        match hottest.level? {
+            StallLevel::Critical => {
+                self.critical_count += 1;
+                warn!(
+                    "CRITICAL temperature: {} at {:.1}°C (threshold {:.1}°C)",
+                    hottest.name, hottest.celsius, hottest.critical_celsius
+                );
+                Some((StallLevel::Critical, self.config.action_critical.clone()))
+            }
+            StallLevel::Warning => {
+                self.warning_count += 1;
+                warn!(
+                    "WARNING temperature: {} at {:.1}°C (threshold {:.1}°C)",
+                    hottest.name, hottest.celsius, hottest.warning_celsius
+                );
+                Some((StallLevel::Warning, self.config.action_warning.clone()))
+            }
+        }
+    }
+
+    /// Processes the thermal action applies to, busiest first
+    ///
+    /// Skipped: PID 1 and 2, the daemon itself, excluded names and
+    /// processes below `min_cpu_percent`.
+    pub fn top_cpu_consumers(&self, processes: &[ProcessInfo]) -> Vec<ProcessInfo> {
+        let own_pid = std::process::id();
+
+        let mut candidates: Vec<&ProcessInfo> = processes
+            .iter()
+            .filter(|p| p.pid > 2 && p.pid != own_pid)
+            .filter(|p| p.cpu_percent >= self.config.min_cpu_percent)
+            .filter(|p| !self.config.exclude.contains(&p.name))
+            .collect();
+
+        candidates.sort_by(|a, b| {
+            b.cpu_percent
+                .partial_cmp(&a.cpu_percent)
+                .unwrap_or(Ordering::Equal)
+        });
+        candidates
+            .into_iter()
+            .take(self.config.top_processes)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ThermalZoneConfig;
+    use freezr_core::SensorSource;
+
+    fn sensor(name: &str, celsius: f64) -> ThermalSensor {
+        ThermalSensor {
+            name: name.to_string(),
+            source: SensorSource::ThermalZone,
+            celsius,
+            trip_points: Vec::new(),
+        }
+    }
+
+    fn process(pid: u32, name: &str, cpu_percent: f64) -> ProcessInfo {
+        ProcessInfo::new(
+            pid,
+            name.to_string(),
+            format!("/usr/bin/{}", name),
+            cpu_percent,
+            0,
+        )
+    }
+
+    fn monitor() -> ThermalMonitor {
+        ThermalMonitor::new(ThermalConfig {
+            zones: vec![ThermalZoneConfig {
+                sensor: "acpitz".to_string(),
+                ignore: true,
+                warning_celsius: None,
+                critical_celsius: None,
+            }],
+            ..ThermalConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_hottest_level_selects_action() {
+        let mut monitor = monitor();
+
+        let cool = [sensor("x86_pkg_temp", 60.0), sensor("acpitz", 120.0)];
+        assert_eq!(monitor.evaluate(&cool), None);
+        assert_eq!(monitor.readings().len(), 1);
+
+        let warm = [sensor("x86_pkg_temp", 88.0), sensor("nvme/Composite", 50.0)];
+        assert_eq!(
+            monitor.evaluate(&warm),
+            Some((StallLevel::Warning, "nice".to_string()))
+        );
+
+        let hot = [
+            sensor("x86_pkg_temp", 90.0),
+            sensor("coretemp/Core 0", 96.0),
+        ];
+        assert_eq!(
+            monitor.evaluate(&hot),
+            Some((StallLevel::Critical, "throttle".to_string()))
+        );
+        assert_eq!(monitor.counts(), (1, 1));
+        assert_eq!(monitor.readings()[1].level, Some(StallLevel::Critical));
+
+        assert_eq!(monitor.evaluate(&cool), None);
+        assert_eq!(monitor.counts(), (0, 0));
+    }
+
+    #[test]
+    fn test_top_cpu_consumers() {
+        let monitor = monitor();
+        let processes = [
+            process(1, "systemd", 90.0),
+            process(10, "rustc", 95.0),
+            process(11, "Xorg", 80.0),
+            process(12, "brave", 40.0),
+            process(13, "node", 60.0),
+            process(14, "bash", 5.0),
+            process(15, "ffmpeg", 20.0),
+        ];
+
+        let pids: Vec<u32> = monitor
+            .top_cpu_consumers(&processes)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![10, 13, 12]);
+    }
+}
@@ -0,0 +1,773 @@
+//! Throttling through dynamic cgroups
+//!
+//! Instead of freezing or killing an offender, its process tree is moved into
+//! a dynamic cgroup `throttle-<pid>` with the default CPU/memory limits of
+//! [`DynamicCgroupSettings`](freezr_core::DynamicCgroupSettings). Once the
+//! tree has stayed below its limits for the release cooldown, the limits are
+//! lifted and the processes return to their original cgroups.
+//!
+//! Rules may add I/O caps (`io.max`) to the throttle cgroup, e.g. to keep a
+//! backup from saturating the disk, or pin it to a few cores (`cpuset.cpus`);
+//! runaway spawners get a task cap (`pids.max`, see [`Throttler::contain`]).
+//!
+//! In adaptive mode ([`AdaptiveSettings`]) the CPU and memory limits of
+//! throttled trees follow the system load and memory pressure on every
+//! check instead of staying fixed (see [`Throttler::adapt_limits`]).
+//!
+//! Without cgroup v2 (or when no dynamic cgroup can be created) the process
+//! is reniced instead.
+
+use freezr_core::{
+    error::Result, executor::ProcessExecutor, AdaptiveSettings, CgroupManager, CpuController,
+    CpuStats, IoController, IoMax, IoStat, MemoryController, MemoryPressure, PidsController,
+    ProcessHandle, ResourceLimits, ThrottledTree,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Nice level used when the process cannot be put into a cgroup
+const FALLBACK_NICE_LEVEL: i32 = 15;
+
+/// Share of an io.max bandwidth cap above which the tree counts as capped
+const IO_SATURATION: f64 = 0.9;
+
+/// Result of a throttle request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleOutcome {
+    /// Process tree moved into a throttle cgroup
+    Throttled,
+    /// Process is already throttled, its cooldown restarted
+    AlreadyThrottled,
+    /// No cgroup available, process reniced instead
+    Reniced,
+}
+
+/// Process tree held in a throttle cgroup
+struct ThrottledProcess {
+    handle: ProcessHandle,
+    name: String,
+    tree: ThrottledTree,
+    /// Start of the current period below the limits
+    calm_since: Instant,
+    /// Last seen `nr_throttled` from cpu.stat
+    nr_throttled: u64,
+    /// cpu.stat at the last adaptive adjustment
+    cpu_stats: CpuStats,
+    /// Last io.stat sample (only for trees with I/O caps)
+    io_stats: Vec<IoStat>,
+    io_seen: Instant,
+    /// Last seen `max` from pids.events (forks refused by pids.max)
+    pids_refused: u64,
+}
+
+impl ThrottledProcess {
+    /// Is `pid` held by this entry? (the process itself or a tree member)
+    fn covers(&self, pid: u32) -> bool {
+        // Live membership: includes children forked while throttled
+        self.tree
+            .cgroup
+            .get_processes()
+            .map(|pids| pids.contains(&pid))
+            .unwrap_or(false)
+    }
+
+    /// Record one observation of the cgroup
+    ///
+    /// The tree misbehaves while the kernel keeps throttling it (cpu.stat
+    /// `nr_throttled` grows) or it runs at another limit (memory.high,
+    /// io.max bandwidth). Returns true once it has behaved for `cooldown`.
+    fn observe(
+        &mut self,
+        nr_throttled: Option<u64>,
+        at_limit: bool,
+        cooldown: Duration,
+        now: Instant,
+    ) -> bool {
+        let cpu_throttled = nr_throttled.is_some_and(|n| n > self.nr_throttled);
+        if let Some(n) = nr_throttled {
+            self.nr_throttled = n;
+        }
+
+        if cpu_throttled || at_limit {
+            self.calm_since = now;
+            return false;
+        }
+        now.duration_since(self.calm_since) >= cooldown
+    }
+
+    /// Is the tree's I/O running at its io.max bandwidth caps?
+    fn at_io_max(&mut self, now: Instant) -> bool {
+        if self.tree.cgroup.limits.io_limits.is_empty() {
+            return false;
+        }
+
+        let path = &self.tree.cgroup.path;
+        let (Ok(limits), Ok(stats)) = (IoController::get_max(path), IoController::get_stats(path))
+        else {
+            return false;
+        };
+
+        let elapsed = now.duration_since(self.io_seen);
+        let saturated = io_saturated(&self.io_stats, &stats, &limits, elapsed);
+        self.io_stats = stats;
+        self.io_seen = now;
+        saturated
+    }
+
+    /// Adjust cpu.max and memory.high to the system state (adaptive mode)
+    ///
+    /// Returns true if a limit changed.
+    fn adapt(
+        &mut self,
+        settings: &AdaptiveSettings,
+        system_load: Option<f64>,
+        system_pressure: Option<f64>,
+    ) -> bool {
+        let path = self.tree.cgroup.path.clone();
+        let limits = &mut self.tree.cgroup.limits;
+        let mut adjusted = false;
+
+        if let (Some(load), Some(limit), Ok(stats)) = (
+            system_load,
+            limits.cpu_limit_percent,
+            CpuController::get_stats(&path),
+        ) {
+            let throttled = stats.since(&self.cpu_stats).throttle_percentage();
+            self.cpu_stats = stats;
+
+            if let Some(next) = settings.next_cpu_limit(limit, load, throttled) {
+                match CpuController::set_quota(&path, next) {
+                    Ok(()) => {
+                        info!(
+                            "Adaptive: CPU limit of {} ({}) {:.0}% -> {:.0}% (load {:.2}/CPU, {:.0}% throttled)",
+                            self.tree.root_pid, self.name, limit, next, load, throttled
+                        );
+                        limits.cpu_limit_percent = Some(next);
+                        adjusted = true;
+                    }
+                    Err(e) => warn!("Failed to adjust CPU limit of {:?}: {}", path, e),
+                }
+            }
+        }
+
+        // Start from the live memory.high: the cgroup event monitor raises it
+        // for trees stuck at the limit
+        if let Some(cached) = limits.memory_high {
+            limits.memory_high = MemoryController::get_high(&path).unwrap_or(Some(cached));
+        }
+
+        if let (Some(pressure), Some(high), Ok(usage), Ok(own)) = (
+            system_pressure,
+            limits.memory_high,
+            MemoryController::get_current(&path),
+            MemoryController::get_pressure(&path),
+        ) {
+            if let Some(next) = settings.next_memory_high(high, usage, pressure, own.some_avg10) {
+                match MemoryController::set_high(&path, next) {
+                    Ok(()) => {
+                        info!(
+                            "Adaptive: memory.high of {} ({}) {} MB -> {} MB (memory PSI {:.1}%, group {:.1}%)",
+                            self.tree.root_pid,
+                            self.name,
+                            high / 1024 / 1024,
+                            next / 1024 / 1024,
+                            pressure,
+                            own.some_avg10
+                        );
+                        limits.memory_high = Some(next);
+                        adjusted = true;
+                    }
+                    Err(e) => warn!("Failed to adjust memory.high of {:?}: {}", path, e),
+                }
+            }
+        }
+
+        adjusted
+    }
+
+    /// Did the tree try to fork past its pids.max since the last check?
+    fn at_pids_max(&mut self) -> bool {
+        if self.tree.cgroup.limits.pids_max.is_none() {
+            return false;
+        }
+
+        let Ok(events) = PidsController::get_events(&self.tree.cgroup.path) else {
+            return false;
+        };
+        let refused = events.max > self.pids_refused;
+        self.pids_refused = events.max;
+        refused
+    }
+}
+
+/// 1-minute load average per CPU from /proc/loadavg
+fn system_load() -> Option<f64> {
+    let content = std::fs::read_to_string("/proc/loadavg").ok()?;
+    let load: f64 = content.split_whitespace().next()?.parse().ok()?;
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    Some(load / cpus as f64)
+}
+
+/// Did any capped device run at (nearly) its byte rate cap between samples?
+fn io_saturated(
+    previous: &[IoStat],
+    current: &[IoStat],
+    limits: &[IoMax],
+    elapsed: Duration,
+) -> bool {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return false;
+    }
+
+    limits.iter().any(|max| {
+        let find = |stats: &[IoStat]| stats.iter().find(|s| s.device == max.device).copied();
+        let (Some(before), Some(after)) = (find(previous), find(current)) else {
+            return false;
+        };
+
+        let rate = |before: u64, after: u64| after.saturating_sub(before) as f64 / secs;
+        let capped =
+            |rate: f64, cap: Option<u64>| cap.is_some_and(|cap| rate >= cap as f64 * IO_SATURATION);
+        capped(rate(before.rbytes, after.rbytes), max.rbps)
+            || capped(rate(before.wbytes, after.wbytes), max.wbps)
+    })
+}
+
+#[derive(Default)]
+struct State {
+    throttled: HashMap<u32, ThrottledProcess>,
+    total_throttled: u64,
+    total_released: u64,
+    /// Limit changes made in adaptive mode
+    total_adjusted: u64,
+}
+
+/// Keeps offenders in throttle cgroups until they behave
+///
+/// Cheap to clone: all clones share the same set of throttled processes.
+#[derive(Clone, Default)]
+pub struct Throttler {
+    state: Arc<Mutex<State>>,
+    /// Cgroup manager creating the throttle cgroups (None = renice only)
+    cgroups: Option<Arc<Mutex<CgroupManager>>>,
+}
+
+impl Throttler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Throttle process trees through dynamic cgroups of `manager`
+    pub fn with_cgroups(mut self, manager: Arc<Mutex<CgroupManager>>) -> Self {
+        self.cgroups = Some(manager);
+        self
+    }
+
+    /// Are throttle cgroups available? (otherwise processes are reniced)
+    pub fn uses_cgroups(&self) -> bool {
+        self.cgroups.is_some()
+    }
+
+    /// Put a process tree under the default dynamic cgroup limits
+    ///
+    /// Throttling a process that is already throttled (or a member of a
+    /// throttled tree) restarts its release cooldown.
+    ///
+    /// # Arguments
+    /// * `handle` - Process to throttle
+    /// * `name` - Process name for logs
+    pub fn throttle(&self, handle: ProcessHandle, name: &str) -> Result<ThrottleOutcome> {
+        self.throttle_with(handle, name, ResourceLimits::default())
+    }
+
+    /// Like [`Throttler::throttle`], with extra limits of the rule
+    ///
+    /// Values set in `extra` (I/O caps, cpuset) override the default
+    /// dynamic limits. They apply only when the tree is newly throttled;
+    /// without cgroups the process is reniced and they are ignored.
+    pub fn throttle_with(
+        &self,
+        handle: ProcessHandle,
+        name: &str,
+        extra: ResourceLimits,
+    ) -> Result<ThrottleOutcome> {
+        let pid = handle.pid();
+        let mut state = self.lock();
+
+        let existing = state.throttled.iter_mut().find_map(|(&root, entry)| {
+            let same = root == pid && entry.handle.start_ticks() == handle.start_ticks();
+            (same || entry.covers(pid)).then_some(entry)
+        });
+        if let Some(entry) = existing {
+            debug!(
+                "Process {} violated again, restarting throttle cooldown",
+                pid
+            );
+            entry.calm_since = Instant::now();
+            return Ok(ThrottleOutcome::AlreadyThrottled);
+        }
+
+        let Some(tree) = self.throttle_tree(pid, extra) else {
+            ProcessExecutor::renice_process(&handle, FALLBACK_NICE_LEVEL)?;
+            return Ok(ThrottleOutcome::Reniced);
+        };
+
+        let cpu_stats = CpuController::get_stats(&tree.cgroup.path).unwrap_or_default();
+        let io_stats = IoController::get_stats(&tree.cgroup.path).unwrap_or_default();
+        state.throttled.insert(
+            pid,
+            ThrottledProcess {
+                handle,
+                name: name.to_string(),
+                tree,
+                calm_since: Instant::now(),
+                nr_throttled: cpu_stats.nr_throttled,
+                cpu_stats,
+                io_stats,
+                io_seen: Instant::now(),
+                pids_refused: 0,
+            },
+        );
+        state.total_throttled += 1;
+
+        Ok(ThrottleOutcome::Throttled)
+    }
+
+    /// Cap the number of tasks of a runaway process tree
+    ///
+    /// The tree is throttled like [`Throttler::throttle`], then its cgroup
+    /// gets `pids.max` = current tasks + `headroom`: further forks fail
+    /// instead of the processes being killed. The tree is released once no
+    /// fork has hit the cap for the cooldown. Without cgroups the process
+    /// is only reniced.
+    pub fn contain(
+        &self,
+        handle: ProcessHandle,
+        name: &str,
+        headroom: u64,
+    ) -> Result<ThrottleOutcome> {
+        let pid = handle.pid();
+        let outcome = self.throttle(handle, name)?;
+        if outcome == ThrottleOutcome::Reniced {
+            return Ok(outcome);
+        }
+
+        let mut state = self.lock();
+        let entry = state
+            .throttled
+            .values_mut()
+            .find(|entry| entry.tree.root_pid == pid || entry.covers(pid));
+        let Some(entry) = entry else {
+            return Ok(outcome); // tree exited meanwhile
+        };
+
+        let path = entry.tree.cgroup.path.clone();
+        let max = PidsController::get_current(&path)? + headroom;
+        PidsController::set_max(&path, max)?;
+        entry.tree.cgroup.limits.pids_max = Some(max);
+        entry.pids_refused = PidsController::get_events(&path).map_or(0, |e| e.max);
+
+        info!(
+            "Contained process tree of {} ({}) at {} tasks",
+            pid, name, max
+        );
+        Ok(outcome)
+    }
+
+    /// Release every tree that stayed below its limits for the cooldown
+    ///
+    /// Called on every monitoring cycle. Trees whose processes all exited
+    /// are released too. Returns the number of released trees.
+    pub fn release_calm(&self) -> usize {
+        let Some(cooldown) = self.cooldown() else {
+            return 0;
+        };
+
+        let now = Instant::now();
+        let mut state = self.lock();
+        let calm: Vec<u32> = state
+            .throttled
+            .iter_mut()
+            .filter_map(|(&pid, entry)| {
+                let path = &entry.tree.cgroup.path;
+                let empty = entry
+                    .tree
+                    .cgroup
+                    .get_processes()
+                    .map(|pids| pids.is_empty())
+                    .unwrap_or(true);
+                if empty {
+                    debug!("Throttled tree of process {} exited", pid);
+                    return Some(pid);
+                }
+
+                let nr_throttled = CpuController::get_stats(path).ok().map(|s| s.nr_throttled);
+                let at_memory_high = match entry.tree.cgroup.limits.memory_high {
+                    Some(high) => MemoryController::get_current(path).is_ok_and(|c| c >= high),
+                    None => false,
+                };
+                let at_io_max = entry.at_io_max(now);
+                let at_pids_max = entry.at_pids_max();
+                let at_limit = at_memory_high || at_io_max || at_pids_max;
+                entry
+                    .observe(nr_throttled, at_limit, cooldown, now)
+                    .then_some(pid)
+            })
+            .collect();
+
+        let mut released = 0;
+        for pid in calm {
+            let Some(entry) = state.throttled.get(&pid) else {
+                continue;
+            };
+            // A failed release is kept and retried on the next cycle
+            if self.release(entry) {
+                info!(
+                    "Released process {} ({}) from throttling after {:?} below limits",
+                    pid, entry.name, cooldown
+                );
+                state.throttled.remove(&pid);
+                released += 1;
+            }
+        }
+        state.total_released += released as u64;
+        released
+    }
+
+    /// Adjust the limits of throttled trees to the system (adaptive mode)
+    ///
+    /// Called on every monitoring cycle; does nothing unless adaptive mode
+    /// is enabled in the dynamic cgroup settings. Returns the number of
+    /// trees whose limits changed.
+    pub fn adapt_limits(&self) -> usize {
+        let Some(settings) = self.adaptive_settings() else {
+            return 0;
+        };
+
+        let system_load = system_load();
+        let system_pressure = MemoryPressure::read().ok().map(|p| p.some_avg10);
+
+        let mut state = self.lock();
+        let mut adjusted = 0;
+        for entry in state.throttled.values_mut() {
+            if entry.adapt(&settings, system_load, system_pressure) {
+                adjusted += 1;
+            }
+        }
+        state.total_adjusted += adjusted as u64;
+        adjusted
+    }
+
+    /// Lift the limits of every throttled tree (e.g. on shutdown)
+    ///
+    /// Trees that could not be released stay throttled.
+    pub fn release_all(&self) -> usize {
+        let mut state = self.lock();
+        let before = state.throttled.len();
+        state.throttled.retain(|_, p| !self.release(p));
+        let released = before - state.throttled.len();
+        state.total_released += released as u64;
+        released
+    }
+
+    /// Number of process trees currently throttled
+    pub fn throttled_count(&self) -> usize {
+        self.lock().throttled.len()
+    }
+
+    /// Is this PID throttled by the throttler? (root process of a tree)
+    pub fn is_throttled(&self, pid: u32) -> bool {
+        self.lock().throttled.contains_key(&pid)
+    }
+
+    /// Trees throttled and released since start
+    pub fn totals(&self) -> (u64, u64) {
+        let state = self.lock();
+        (state.total_throttled, state.total_released)
+    }
+
+    /// Limit changes made in adaptive mode since start
+    pub fn total_adjusted(&self) -> u64 {
+        self.lock().total_adjusted
+    }
+
+    /// Adaptive settings, None if adaptive mode is off or there are no cgroups
+    pub fn adaptive_settings(&self) -> Option<AdaptiveSettings> {
+        let manager = self.cgroups.as_ref()?;
+        let manager = manager.lock().unwrap_or_else(|p| p.into_inner());
+        let adaptive = &manager.dynamic_settings().adaptive;
+        adaptive.enabled.then(|| adaptive.clone())
+    }
+
+    /// Release cooldown from the dynamic cgroup settings
+    pub fn cooldown(&self) -> Option<Duration> {
+        let manager = self.cgroups.as_ref()?;
+        let manager = manager.lock().unwrap_or_else(|p| p.into_inner());
+        Some(Duration::from_secs(
+            manager.dynamic_settings().release_cooldown_secs,
+        ))
+    }
+
+    /// Move the tree of `pid` into a throttle cgroup
+    ///
+    /// None if cgroups are not configured or the cgroup could not be set up
+    /// (the caller falls back to renice).
+    fn throttle_tree(&self, pid: u32, extra: ResourceLimits) -> Option<ThrottledTree> {
+        let manager = self.cgroups.as_ref()?;
+        let mut manager = manager.lock().unwrap_or_else(|p| p.into_inner());
+        let limits = manager.dynamic_settings().default_limits().overlay(extra);
+
+        match manager.throttle_tree(pid, limits) {
+            Ok(tree) => {
+                info!(
+                    "Throttled tree of process {} via {} (CPU {:?}%, memory.high {:?} MB)",
+                    pid,
+                    tree.cgroup.path.display(),
+                    tree.cgroup.limits.cpu_limit_percent,
+                    tree.cgroup.limits.memory_high.map(|b| b / 1024 / 1024)
+                );
+                Some(tree)
+            }
+            Err(e) => {
+                warn!(
+                    "cgroup throttle of process {} failed, using renice: {}",
+                    pid, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Release one entry; false if the cgroup could not be removed
+    fn release(&self, process: &ThrottledProcess) -> bool {
+        let Some(manager) = &self.cgroups else {
+            return false;
+        };
+        let mut manager = manager.lock().unwrap_or_else(|p| p.into_inner());
+
+        match manager.release_tree(&process.tree) {
+            Ok(()) => true,
+            Err(e) => {
+                error!(
+                    "Failed to release throttled tree of {}: {}",
+                    process.tree.root_pid, e
+                );
+                false
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freezr_core::cgroups::cgroupfs;
+    use freezr_core::{Cgroup, CgroupConfig, CgroupType, DeviceNumber, FakeCgroupFs};
+    use std::path::PathBuf;
+    use std::process::{Command, Stdio};
+
+    fn throttled(pid: u32) -> ThrottledProcess {
+        ThrottledProcess {
+            handle: ProcessHandle::open(pid).unwrap(),
+            name: "test".to_string(),
+            tree: ThrottledTree {
+                root_pid: pid,
+                cgroup: Cgroup::new(
+                    format!("throttle-{}", pid),
+                    PathBuf::from("/nonexistent/throttle"),
+                    CgroupType::Dynamic,
+                ),
+                origins: HashMap::new(),
+            },
+            calm_since: Instant::now(),
+            nr_throttled: 10,
+            cpu_stats: CpuStats::default(),
+            io_stats: Vec::new(),
+            io_seen: Instant::now(),
+            pids_refused: 0,
+        }
+    }
+
+    fn io_stat(wbytes: u64) -> IoStat {
+        IoStat {
+            device: DeviceNumber::new(8, 0),
+            rbytes: 0,
+            wbytes,
+            rios: 0,
+            wios: 0,
+            dbytes: 0,
+            dios: 0,
+        }
+    }
+
+    #[test]
+    fn test_observe_releases_after_cooldown() {
+        let mut entry = throttled(std::process::id());
+        let cooldown = Duration::from_secs(60);
+        let start = entry.calm_since;
+
+        assert!(!entry.observe(Some(10), false, cooldown, start + Duration::from_secs(30)));
+        assert!(entry.observe(Some(10), false, cooldown, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_observe_cpu_throttling_restarts_cooldown() {
+        let mut entry = throttled(std::process::id());
+        let cooldown = Duration::from_secs(60);
+        let start = entry.calm_since;
+
+        // Kernel throttled the cgroup since the last check
+        let hit = start + Duration::from_secs(50);
+        assert!(!entry.observe(Some(15), false, cooldown, hit));
+        assert_eq!(entry.nr_throttled, 15);
+        assert_eq!(entry.calm_since, hit);
+
+        assert!(!entry.observe(Some(15), false, cooldown, start + Duration::from_secs(100)));
+        assert!(entry.observe(None, false, cooldown, start + Duration::from_secs(110)));
+    }
+
+    #[test]
+    fn test_observe_memory_high_restarts_cooldown() {
+        let mut entry = throttled(std::process::id());
+        let cooldown = Duration::from_secs(60);
+        let late = entry.calm_since + Duration::from_secs(120);
+
+        assert!(!entry.observe(Some(10), true, cooldown, late));
+        assert_eq!(entry.calm_since, late);
+    }
+
+    #[test]
+    fn test_io_saturation() {
+        let mb = 1024 * 1024;
+        let limits = [IoMax::new(DeviceNumber::new(8, 0)).with_wbps(20 * mb)];
+        let before = [io_stat(0)];
+        let second = Duration::from_secs(1);
+
+        // 19 MB/s against a 20 MB/s cap: held back by io.max
+        assert!(io_saturated(&before, &[io_stat(19 * mb)], &limits, second));
+        assert!(!io_saturated(&before, &[io_stat(5 * mb)], &limits, second));
+        assert!(!io_saturated(&[], &[io_stat(19 * mb)], &limits, second));
+        assert!(!io_saturated(
+            &before,
+            &[io_stat(19 * mb)],
+            &limits,
+            Duration::ZERO
+        ));
+
+        // Only reads are capped: writes never saturate
+        let read_cap = [IoMax::new(DeviceNumber::new(8, 0)).with_rbps(mb)];
+        assert!(!io_saturated(
+            &before,
+            &[io_stat(19 * mb)],
+            &read_cap,
+            second
+        ));
+    }
+
+    #[test]
+    fn test_without_cgroups_renices() {
+        let mut child = Command::new("sleep")
+            .arg("60")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let throttler = Throttler::new();
+        assert!(!throttler.uses_cgroups());
+
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        assert_eq!(
+            throttler.throttle(handle, "sleep").unwrap(),
+            ThrottleOutcome::Reniced
+        );
+        assert_eq!(throttler.throttled_count(), 0);
+        assert_eq!(throttler.release_calm(), 0);
+        assert_eq!(throttler.totals(), (0, 0));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_adapt_starts_from_live_memory_high() {
+        let fake = FakeCgroupFs::new("/fake-cgroupfs/throttle-adapt");
+        let _mount = fake.mount();
+        let path = fake.root().join("throttle-adapt");
+        cgroupfs::create_dir(&path).unwrap();
+
+        let mb = 1024 * 1024;
+        let mut entry = throttled(std::process::id());
+        entry.tree.cgroup.path = path.clone();
+        entry.tree.cgroup.limits = ResourceLimits::default().with_memory_high(1000 * mb);
+
+        // The cgroup event monitor relaxed the limit of the stuck tree
+        fake.set(&path.join("memory.high"), &(2000 * mb).to_string())
+            .unwrap();
+        fake.set(&path.join("memory.current"), &(2000 * mb).to_string())
+            .unwrap();
+
+        let settings = AdaptiveSettings {
+            enabled: true,
+            ..AdaptiveSettings::default()
+        };
+        assert!(entry.adapt(&settings, None, Some(50.0)));
+        assert_eq!(entry.tree.cgroup.limits.memory_high, Some(1600 * mb));
+        assert_eq!(MemoryController::get_high(&path).unwrap(), Some(1600 * mb));
+    }
+
+    #[test]
+    fn test_failed_release_is_retried() {
+        let fake = FakeCgroupFs::new("/fake-cgroupfs/throttle-retry");
+        let _mount = fake.mount();
+        let mut config = CgroupConfig {
+            enabled: true,
+            cgroupfs_root: fake.root().to_path_buf(),
+            root_path: fake.root().join("freezr.slice"),
+            journal_path: None,
+            ..CgroupConfig::default()
+        };
+        config.dynamic_settings.release_cooldown_secs = 0;
+        let mut manager = CgroupManager::new(config).unwrap();
+        manager.initialize().unwrap();
+        let throttler = Throttler::new().with_cgroups(Arc::new(Mutex::new(manager)));
+
+        let mut child = Command::new("sleep")
+            .arg("60")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let handle = ProcessHandle::open(child.id()).unwrap();
+        assert_eq!(
+            throttler.throttle(handle, "sleep").unwrap(),
+            ThrottleOutcome::Throttled
+        );
+
+        // A nested cgroup keeps the throttle cgroup busy: rmdir fails
+        let path = fake
+            .root()
+            .join(format!("freezr.slice/throttle-{}", child.id()));
+        let nested = path.join("nested");
+        cgroupfs::create_dir(&nested).unwrap();
+        assert_eq!(throttler.release_calm(), 0);
+        assert!(throttler.is_throttled(child.id()));
+
+        cgroupfs::remove_dir(&nested).unwrap();
+        assert_eq!(throttler.release_calm(), 1);
+        assert_eq!(throttler.throttled_count(), 0);
+        assert!(!cgroupfs::exists(&path));
+        assert_eq!(throttler.totals(), (1, 1));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
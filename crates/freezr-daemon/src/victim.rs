@@ -0,0 +1,262 @@
+//! OOM victim selection
+//!
+//! The "kill" pressure action used to kill every Brave, Telegram, large
+//! nvim and Firefox process in a fixed order. Instead, candidates are now
+//! ranked like the kernel OOM killer would, with user priorities on top:
+//!
+//! ```text
+//! score = (memory ‰ of MemTotal + oom_score) * (100 + priority) / 100
+//! ```
+//!
+//! Memory is PSS (RSS if unreadable), `oom_score` is the kernel badness
+//! including `oom_score_adj`. The monitor kills the top candidate, measures
+//! the stall again and only moves on to the next one if it is still needed.
+
+use crate::config::OomKillerConfig;
+use crate::rules::CompiledMatcher;
+use freezr_core::{error::Result, types::ProcessInfo, MemInfo, OomInfo};
+use std::cmp::Ordering;
+
+/// Ranked kill candidate
+#[derive(Debug, Clone)]
+pub struct Victim {
+    pub process: ProcessInfo,
+    /// PSS, or RSS when smaps_rollup is unreadable, in KB
+    pub memory_kb: u64,
+    pub oom_score: u32,
+    pub priority: i32,
+    pub score: f64,
+}
+
+/// Ranks processes for the "kill" pressure action
+pub struct VictimSelector {
+    config: OomKillerConfig,
+    priorities: Vec<(CompiledMatcher, i32)>,
+}
+
+impl Default for VictimSelector {
+    fn default() -> Self {
+        Self::new(OomKillerConfig::default()).expect("default OOM killer priorities are valid")
+    }
+}
+
+impl VictimSelector {
+    pub fn new(config: OomKillerConfig) -> Result<Self> {
+        let priorities = config
+            .priorities
+            .iter()
+            .map(|entry| Ok((CompiledMatcher::compile(&entry.matcher)?, entry.priority)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self { config, priorities })
+    }
+
+    pub fn config(&self) -> &OomKillerConfig {
+        &self.config
+    }
+
+    /// Priority of the first matching entry, 0 if none matches
+    pub fn priority_of(&self, process: &ProcessInfo) -> i32 {
+        self.priorities
+            .iter()
+            .find(|(matcher, _)| matcher.matches(process))
+            .map_or(0, |(_, priority)| *priority)
+    }
+
+    /// Candidates from highest to lowest score, OOM data read from /proc
+    pub fn rank(&self, processes: &[ProcessInfo]) -> Vec<Victim> {
+        let mem_total_kb = MemInfo::read().map_or(0, |m| m.mem_total_kb);
+        self.rank_with(processes, mem_total_kb, |pid| OomInfo::read(pid).ok())
+    }
+
+    /// Candidates from highest to lowest score
+    ///
+    /// Skipped: PID 1 and 2, the daemon itself, protected names, processes
+    /// below `min_rss_mb`, exempted by `oom_score_adj` = -1000 or with
+    /// priority -100, and processes without OOM data (already exited).
+    pub fn rank_with(
+        &self,
+        processes: &[ProcessInfo],
+        mem_total_kb: u64,
+        oom_info: impl Fn(u32) -> Option<OomInfo>,
+    ) -> Vec<Victim> {
+        let own_pid = std::process::id();
+
+        let mut victims: Vec<Victim> = processes
+            .iter()
+            .filter(|p| p.pid > 2 && p.pid != own_pid)
+            .filter(|p| p.memory_kb >= self.config.min_rss_mb * 1024)
+            .filter(|p| !self.config.protected.contains(&p.name))
+            .filter_map(|p| {
+                let priority = self.priority_of(p);
+                if priority <= -100 {
+                    return None;
+                }
+
+                let info = oom_info(p.pid)?;
+                if info.is_unkillable() {
+                    return None;
+                }
+
+                let memory_kb = info.pss_kb.unwrap_or(p.memory_kb);
+                Some(Victim {
+                    process: p.clone(),
+                    memory_kb,
+                    oom_score: info.oom_score,
+                    priority,
+                    score: score(memory_kb, mem_total_kb, info.oom_score, priority),
+                })
+            })
+            .collect();
+
+        victims.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        victims
+    }
+}
+
+/// Kill score of a process, see the module docs
+pub fn score(memory_kb: u64, mem_total_kb: u64, oom_score: u32, priority: i32) -> f64 {
+    let memory_permille = if mem_total_kb == 0 {
+        0.0
+    } else {
+        memory_kb as f64 * 1000.0 / mem_total_kb as f64
+    };
+    (memory_permille + oom_score as f64) * (100 + priority) as f64 / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RuleMatcher, VictimPriority};
+    use std::collections::HashMap;
+
+    const GB: u64 = 1024 * 1024;
+
+    fn process(pid: u32, name: &str, memory_kb: u64) -> ProcessInfo {
+        ProcessInfo::new(
+            pid,
+            name.to_string(),
+            format!("/usr/bin/{}", name),
+            0.0,
+            memory_kb,
+        )
+    }
+
+    fn selector(priorities: &[(&str, i32)]) -> VictimSelector {
+        VictimSelector::new(OomKillerConfig {
+            protected: vec!["Xorg".to_string()],
+            priorities: priorities
+                .iter()
+                .map(|(name, priority)| VictimPriority {
+                    matcher: RuleMatcher {
+                        name: Some(name.to_string()),
+                        ..Default::default()
+                    },
+                    priority: *priority,
+                })
+                .collect(),
+            ..OomKillerConfig::default()
+        })
+        .unwrap()
+    }
+
+    /// OOM data proportional to RSS on a 16GB machine
+    fn oom_table(processes: &[ProcessInfo]) -> HashMap<u32, OomInfo> {
+        processes
+            .iter()
+            .map(|p| {
+                let info = OomInfo {
+                    oom_score: (p.memory_kb * 1000 / (16 * GB)) as u32,
+                    oom_score_adj: 0,
+                    pss_kb: None,
+                };
+                (p.pid, info)
+            })
+            .collect()
+    }
+
+    fn ranked_pids(selector: &VictimSelector, processes: &[ProcessInfo]) -> Vec<u32> {
+        let table = oom_table(processes);
+        selector
+            .rank_with(processes, 16 * GB, |pid| table.get(&pid).copied())
+            .iter()
+            .map(|v| v.process.pid)
+            .collect()
+    }
+
+    #[test]
+    fn test_score() {
+        // 2GB of 16GB = 125‰, plus oom_score 75
+        assert_eq!(score(2 * GB, 16 * GB, 75, 0), 200.0);
+        assert_eq!(score(2 * GB, 16 * GB, 75, 50), 300.0);
+        assert_eq!(score(2 * GB, 16 * GB, 75, -50), 100.0);
+        assert_eq!(score(GB, 0, 10, 0), 10.0);
+    }
+
+    #[test]
+    fn test_ranks_by_memory_and_priority() {
+        let processes = [
+            process(10, "brave", 2 * GB),
+            process(11, "rustc", 3 * GB),
+            process(12, "telegram", GB),
+        ];
+
+        // Without priorities the biggest process goes first
+        assert_eq!(ranked_pids(&selector(&[]), &processes), vec![11, 10, 12]);
+
+        // Doubled score puts the browser in front of the compiler
+        let selector = selector(&[("brave", 100)]);
+        assert_eq!(ranked_pids(&selector, &processes), vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn test_skips_protected_small_and_exempt() {
+        let processes = [
+            process(1, "systemd", 4 * GB),
+            process(20, "Xorg", 4 * GB),
+            process(21, "nvim", 4 * GB),
+            process(22, "sleep", 1024),
+            process(23, "oomd", 4 * GB),
+            process(24, "firefox", GB),
+        ];
+        let mut table = oom_table(&processes);
+        table.get_mut(&23).unwrap().oom_score_adj = -1000;
+
+        let selector = selector(&[("nvim", -100)]);
+        let ranked: Vec<u32> = selector
+            .rank_with(&processes, 16 * GB, |pid| table.get(&pid).copied())
+            .iter()
+            .map(|v| v.process.pid)
+            .collect();
+        assert_eq!(ranked, vec![24]);
+    }
+
+    #[test]
+    fn test_prefers_pss_over_rss() {
+        // Renderers sharing 1.5GB: RSS says 2GB each, PSS 0.5GB
+        let processes = [process(30, "brave", 2 * GB), process(31, "java", GB)];
+        let table: HashMap<u32, OomInfo> = [
+            (
+                30,
+                OomInfo {
+                    oom_score: 0,
+                    oom_score_adj: 0,
+                    pss_kb: Some(GB / 2),
+                },
+            ),
+            (
+                31,
+                OomInfo {
+                    oom_score: 0,
+                    oom_score_adj: 0,
+                    pss_kb: None,
+                },
+            ),
+        ]
+        .into();
+
+        let victims = selector(&[]).rank_with(&processes, 16 * GB, |pid| table.get(&pid).copied());
+        assert_eq!(victims[0].process.pid, 31);
+        assert_eq!(victims[1].memory_kb, GB / 2);
+    }
+}